@@ -0,0 +1,51 @@
+#ifndef RUSTCROISSANT_H
+#define RUSTCROISSANT_H
+
+/* Generated with cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Generate Croissant metadata for a CSV file
+//
+// Returns the metadata as a JSON-LD string, or NULL on error. When
+// `output_path` is not NULL the metadata is also written to that file.
+//
+// # Safety
+//
+// `csv_path` must be a valid NUL-terminated string; `output_path` must be
+// NULL or a valid NUL-terminated string.
+char *rustcroissant_generate_from_csv(const char *csv_path, const char *output_path);
+
+// Validate a Croissant JSON-LD document
+//
+// Returns the issues as a JSON string of the form
+// `{"errors": n, "warnings": n, "issues": [...]}`, or NULL if `json` is not
+// a valid string.
+//
+// # Safety
+//
+// `json` must be a valid NUL-terminated string.
+char *rustcroissant_validate_json(const char *json);
+
+// Return the message of the last error on this thread, or NULL if none
+//
+// The returned string must be released with `rustcroissant_free_string`.
+char *rustcroissant_last_error(void);
+
+// Release a string returned by this library
+//
+// # Safety
+//
+// `value` must be NULL or a pointer returned by this library that has not
+// been freed yet.
+void rustcroissant_free_string(char *value);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RUSTCROISSANT_H */
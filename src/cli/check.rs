@@ -0,0 +1,150 @@
+//! Fast structural checks for pre-commit hooks
+//!
+//! Only the structure of each file is validated: nothing is hashed and
+//! nothing is fetched, so checking a handful of files takes milliseconds.
+//! Every issue is printed on its own line, prefixed with the file name.
+//! With `--staged`, the content staged in the git index is checked rather
+//! than that of the working tree, which may differ.
+//!
+//! JSON and YAML files are only checked when they hold a JSON-LD document,
+//! since other such files of a repository, such as package manifests or CI
+//! configuration, are not Croissant metadata.
+
+use rustcroissant::croissant::utils::get_file_extension;
+use rustcroissant::croissant::validate::{ValidationIssues, validate_content, validate_file};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Extensions of the files picked up by `--staged`
+const STAGED_EXTENSIONS: &[&str] = &["jsonld", "json", "yaml", "yml"];
+
+/// Extensions of the files only checked when they have a `@context`
+const SNIFFED_EXTENSIONS: &[&str] = &["json", "yaml", "yml"];
+
+/// Files to check: the given paths, the staged JSON-LD, JSON and YAML files
+/// with `--staged`, or else one path per line on stdin
+pub fn collect_paths(paths: Vec<String>, staged: bool) -> std::io::Result<Vec<PathBuf>> {
+    if staged {
+        return staged_files();
+    }
+    if !paths.is_empty() && paths != ["-"] {
+        return Ok(paths.into_iter().map(PathBuf::from).collect());
+    }
+
+    let mut from_stdin = Vec::new();
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() {
+            from_stdin.push(PathBuf::from(line));
+        }
+    }
+    Ok(from_stdin)
+}
+
+/// JSON-LD, JSON and YAML files added, copied or modified in the git index, by
+/// their path from the top of the repository
+fn staged_files() -> std::io::Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .args(["diff", "--cached", "--name-only", "--diff-filter=ACM", "-z"])
+        .output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(output
+        .stdout
+        .split(|b| *b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| PathBuf::from(String::from_utf8_lossy(name).into_owned()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| STAGED_EXTENSIONS.iter().any(|staged| ext == *staged))
+        })
+        .collect())
+}
+
+/// Content of a file as staged in the git index
+fn staged_content(path: &Path) -> std::io::Result<String> {
+    let output = Command::new("git")
+        .arg("show")
+        .arg(format!(":{}", path.display()))
+        .output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "git show failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(std::io::Error::other)
+}
+
+/// Check whether a file is only checked when it has a `@context`
+fn is_sniffed(path: &Path) -> bool {
+    get_file_extension(path).is_some_and(|ext| SNIFFED_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Check each file, or with `staged` its content in the git index, and
+/// print one line per issue; returns whether any file has errors
+///
+/// JSON and YAML files without a `@context` are skipped.
+pub fn run(paths: &[PathBuf], staged: bool) -> bool {
+    let mut failed = false;
+    for path in paths {
+        let issues = if staged || is_sniffed(path) {
+            let content = if staged {
+                staged_content(path)
+            } else {
+                std::fs::read_to_string(path)
+            };
+            match content {
+                Ok(content) if is_sniffed(path) && !content.contains("@context") => continue,
+                Ok(content) => validate_content(&content, path),
+                Err(e) => Err(e.into()),
+            }
+        } else {
+            validate_file(path)
+        };
+        let issues = issues.unwrap_or_else(|e| ValidationIssues::unparseable(&e));
+        failed |= issues.has_errors();
+        for issue in issues.issues() {
+            println!("{}: {issue}", path.display());
+        }
+    }
+    failed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_and_yaml_without_context_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("package.json");
+        std::fs::write(&manifest, r#"{"name": "app"}"#).unwrap();
+        let workflow = dir.path().join("ci.yml");
+        std::fs::write(&workflow, "on: push\n").unwrap();
+        assert!(!run(&[manifest, workflow], false));
+    }
+
+    #[test]
+    fn json_with_context_is_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata = dir.path().join("croissant.json");
+        std::fs::write(&metadata, r#"{"@context": {}, "@type": "sc:Dataset"}"#).unwrap();
+        assert!(run(&[metadata], false));
+    }
+
+    #[test]
+    fn jsonld_is_always_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata = dir.path().join("metadata.jsonld");
+        std::fs::write(&metadata, r#"{"name": "app"}"#).unwrap();
+        assert!(run(&[metadata], false));
+    }
+}
@@ -0,0 +1,153 @@
+//! Human-friendly summaries of Croissant metadata, for the inspect command
+//! and the MCP server's inspect_dataset tool
+//!
+//! The summary gives the dataset's name, version and licenses, each
+//! distribution with its format and size, and each record set with the
+//! name and type of its fields, subfields indented below their parent.
+
+use crate::cli::table::{Align, render};
+use rustcroissant::croissant::{Distribution, Field, Metadata};
+use serde_json::{Value, json};
+
+/// The summary as JSON, for scripts
+pub fn describe_metadata(metadata: &Metadata) -> Value {
+    json!({
+        "name": metadata.name,
+        "description": metadata.description,
+        "version": metadata.version,
+        "license": metadata.license,
+        "distributions": metadata.distribution.iter().map(|d| json!({
+            "name": d.name,
+            "type": distribution_kind(d),
+            "contentUrl": d.content_url,
+            "contentSize": d.content_size,
+            "encodingFormat": d.encoding_format,
+        })).collect::<Vec<_>>(),
+        "recordSets": metadata.record_set.iter().map(|rs| json!({
+            "name": rs.name,
+            "description": rs.description,
+            "fields": rs.field.iter().map(describe_field).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+fn describe_field(field: &Field) -> Value {
+    let mut json = json!({
+        "name": field.name,
+        "dataType": field.data_type,
+        "description": field.description,
+    });
+    if field.repeated {
+        json["repeated"] = true.into();
+    }
+    if let Some(reference) = &field.references {
+        json["references"] = reference.field.id.clone().into();
+    }
+    if !field.sub_field.is_empty() {
+        json["subFields"] = field.sub_field.iter().map(describe_field).collect();
+    }
+    json
+}
+
+/// The summary as text
+pub fn render_inspection(metadata: &Metadata) -> String {
+    let mut text = format!("{} (version {})\n", metadata.name, metadata.version);
+    if !metadata.description.is_empty() {
+        text.push_str(&format!("{}\n", metadata.description));
+    }
+    let license = if metadata.license.is_empty() {
+        "none given".to_string()
+    } else {
+        metadata.license.join(", ")
+    };
+    text.push_str(&format!("License: {license}\n"));
+
+    text.push_str(&format!(
+        "\n{} distribution(s)\n",
+        metadata.distribution.len()
+    ));
+    if !metadata.distribution.is_empty() {
+        let rows = metadata
+            .distribution
+            .iter()
+            .map(|d| {
+                vec![
+                    d.name.clone(),
+                    distribution_kind(d).to_string(),
+                    d.encoding_format.clone(),
+                    d.content_size.clone(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        text.push_str(&render(
+            &[["name", "type", "format", "size"]
+                .map(String::from)
+                .to_vec()],
+            &[Align::Left, Align::Left, Align::Left, Align::Right],
+            &rows,
+        ));
+    }
+
+    for record_set in &metadata.record_set {
+        text.push_str(&format!(
+            "\nRecord set {} ({} field(s))\n",
+            record_set.name,
+            record_set.field.len()
+        ));
+        if !record_set.description.is_empty() {
+            text.push_str(&format!("{}\n", record_set.description));
+        }
+        let mut rows = Vec::new();
+        for field in &record_set.field {
+            field_rows(field, 0, &mut rows);
+        }
+        text.push_str(&render(
+            &[["field", "type", "references"].map(String::from).to_vec()],
+            &[Align::Left, Align::Left, Align::Left],
+            &rows,
+        ));
+    }
+    text
+}
+
+/// Rows of a field and its subfields, indented by depth
+fn field_rows(field: &Field, depth: usize, rows: &mut Vec<Vec<String>>) {
+    rows.push(vec![
+        format!("{}{}", "  ".repeat(depth), field.name),
+        field_type(field),
+        field
+            .references
+            .as_ref()
+            .map(|reference| reference.field.id.clone())
+            .unwrap_or_default(),
+    ]);
+    for sub_field in &field.sub_field {
+        field_rows(sub_field, depth + 1, rows);
+    }
+}
+
+/// Short type of a field: its dataType without prefix, `Object` for fields
+/// with subfields and no dataType, in brackets when repeated
+fn field_type(field: &Field) -> String {
+    let name = if field.data_type.is_empty() && !field.sub_field.is_empty() {
+        "Object"
+    } else {
+        field
+            .data_type
+            .trim_start_matches("sc:")
+            .trim_start_matches("cr:")
+    };
+    if field.repeated {
+        format!("[{name}]")
+    } else {
+        name.to_string()
+    }
+}
+
+fn distribution_kind(distribution: &Distribution) -> &'static str {
+    if distribution.is_file_set() {
+        "FileSet"
+    } else {
+        "FileObject"
+    }
+}
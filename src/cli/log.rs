@@ -0,0 +1,153 @@
+//! Logging of the command-line interface
+//!
+//! Standard output only carries the results of commands: metadata,
+//! records, reports and the like. Everything else goes to standard error,
+//! as text or, with `--log-format json`, as one JSON object per line: the
+//! events of the library, filtered by `-v`, and the status messages of
+//! commands, logged under [`STATUS_TARGET`] and printed as plain lines in
+//! text format. `--quiet` leaves only errors.
+
+use std::fmt;
+use std::io::IsTerminal;
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::FmtContext;
+use tracing_subscriber::fmt::format::{self, FormatEvent, FormatFields};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Target of the status messages of commands
+pub const STATUS_TARGET: &str = "rustcroissant::status";
+
+/// Log a status message of a command, such as where it wrote its output
+macro_rules! status {
+    ($($arg:tt)+) => {
+        tracing::info!(target: $crate::cli::log::STATUS_TARGET, $($arg)+)
+    };
+}
+
+/// Log why a command failed
+macro_rules! failure {
+    ($($arg:tt)+) => {
+        tracing::error!(target: $crate::cli::log::STATUS_TARGET, $($arg)+)
+    };
+}
+
+pub(crate) use {failure, status};
+
+/// Format of what is logged to standard error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    /// One JSON object per line, for log aggregation
+    Json,
+}
+
+impl LogFormat {
+    /// All formats, in the order they are listed to users
+    pub const ALL: &'static [LogFormat] = &[LogFormat::Text, LogFormat::Json];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        }
+    }
+
+    /// Parse a command-line format name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+    }
+}
+
+/// Install the stderr subscriber: library events at the level of the -v
+/// count, status messages unless quiet, plus the timing layer when
+/// --timings is given
+pub fn init(verbosity: u8, quiet: bool, format: LogFormat, timings: bool) {
+    let level = match (quiet, verbosity) {
+        (true, _) => LevelFilter::ERROR,
+        (false, 0) => LevelFilter::WARN,
+        (false, 1) => LevelFilter::INFO,
+        (false, 2) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    };
+    let status_level = if quiet {
+        LevelFilter::ERROR
+    } else {
+        LevelFilter::INFO
+    };
+
+    let ansi = std::io::stderr().is_terminal();
+
+    let (text, status, json) = match format {
+        LogFormat::Text => (
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_ansi(ansi)
+                    .with_filter(
+                        Targets::new()
+                            .with_default(level)
+                            .with_target(STATUS_TARGET, LevelFilter::OFF),
+                    ),
+            ),
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .event_format(PlainMessage)
+                    .with_filter(
+                        Targets::new()
+                            .with_default(LevelFilter::OFF)
+                            .with_target(STATUS_TARGET, status_level),
+                    ),
+            ),
+            None,
+        ),
+        LogFormat::Json => (
+            None,
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_writer(std::io::stderr)
+                    .with_filter(
+                        Targets::new()
+                            .with_default(level)
+                            .with_target(STATUS_TARGET, status_level),
+                    ),
+            ),
+        ),
+    };
+
+    tracing_subscriber::registry()
+        .with(text)
+        .with(status)
+        .with(json)
+        .with(timings.then(super::timings::TimingsLayer::install))
+        .init();
+}
+
+/// Event format printing the message and fields of an event alone, as
+/// status messages are meant to be read
+struct PlainMessage;
+
+impl<S, N> FormatEvent<S, N> for PlainMessage
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: format::Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
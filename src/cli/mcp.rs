@@ -0,0 +1,347 @@
+//! Model Context Protocol server over stdio
+//!
+//! Messages are newline-delimited JSON-RPC 2.0 requests on stdin, answered
+//! on stdout. Logs go to stderr so they never corrupt the protocol stream.
+//!
+//! Tool arguments come from an agent that may have been prompted by the
+//! data it reads, so `generate_croissant` only writes new `.json` and
+//! `.jsonld` files, never replacing one that exists.
+
+use crate::cli::inspect::describe_metadata;
+use rustcroissant::croissant::generate::{
+    GenerateOptions, generate_metadata_from_csv, generate_metadata_from_csv_with_options,
+};
+use rustcroissant::croissant::utils::get_file_extension;
+use rustcroissant::croissant::validate::{
+    ValidationIssues, read_metadata, validate_file, validate_str_to_json,
+};
+use rustcroissant::version;
+use serde_json::{Value, json};
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// Protocol version the server implements, answered whichever version the
+/// client requests
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Extensions of the files `generate_croissant` may write
+const OUTPUT_EXTENSIONS: &[&str] = &["json", "jsonld"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve MCP requests from stdin until it is closed
+pub fn serve() -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(&message),
+            Err(e) => Some(error_response(
+                Value::Null,
+                PARSE_ERROR,
+                &format!("Parse error: {e}"),
+            )),
+        };
+
+        if let Some(response) = response {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle one JSON-RPC message, returning the response for requests and
+/// nothing for notifications
+fn handle_message(message: &Value) -> Option<Value> {
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        return Some(error_response(
+            message.get("id").cloned().unwrap_or(Value::Null),
+            INVALID_REQUEST,
+            "Request has no method",
+        ));
+    };
+    // Notifications carry no id and get no response
+    let id = message.get("id").cloned()?;
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    tracing::debug!(method, "MCP request");
+
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(&params),
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {method}"))),
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error_response(id, code, &message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Answer an `initialize` request with the protocol version the server
+/// implements; clients that cannot speak it disconnect
+fn initialize(params: &Value) -> Value {
+    if let Some(requested) = params.get("protocolVersion").and_then(Value::as_str)
+        && requested != PROTOCOL_VERSION
+    {
+        tracing::debug!(requested, "client requested another protocol version");
+    }
+
+    json!({
+        "protocolVersion": PROTOCOL_VERSION,
+        "capabilities": { "tools": {} },
+        "serverInfo": {
+            "name": version::get_version().app_name,
+            "version": version::get_version().version,
+        },
+    })
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "generate_croissant",
+            "description": "Generate Croissant JSON-LD metadata for a local CSV file, inferring column types and computing its SHA-256 hash.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "csv_path": { "type": "string", "description": "Path of the CSV file" },
+                    "output_path": { "type": "string", "description": "Optional path of a new .json or .jsonld file to also write the metadata to; existing files are not replaced" }
+                },
+                "required": ["csv_path"]
+            }
+        },
+        {
+            "name": "validate_croissant",
+            "description": "Validate Croissant metadata, given either as a file path or as an inline JSON-LD document, and list the errors and warnings found.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path of a Croissant JSON-LD file" },
+                    "json": { "type": "string", "description": "Inline Croissant JSON-LD document" }
+                }
+            }
+        },
+        {
+            "name": "inspect_dataset",
+            "description": "Summarize a dataset: for a Croissant file its distributions and record sets, for a CSV file its columns and inferred data types.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path of a Croissant JSON-LD file or a CSV file" }
+                },
+                "required": ["path"]
+            }
+        }
+    ])
+}
+
+/// Run a tool; tool failures are reported in the result with `isError`, as
+/// MCP expects, while malformed calls are JSON-RPC errors
+fn call_tool(params: &Value) -> Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "Tool call has no name".to_string()))?;
+    let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+    let argument = |key: &str| arguments.get(key).and_then(Value::as_str);
+
+    let outcome = match name {
+        "generate_croissant" => {
+            let csv_path =
+                argument("csv_path").ok_or((INVALID_PARAMS, "csv_path is required".to_string()))?;
+            generate_croissant(Path::new(csv_path), argument("output_path").map(Path::new))
+        }
+        "validate_croissant" => match (argument("path"), argument("json")) {
+            (Some(path), _) => Ok(validate_file(Path::new(path))
+                .unwrap_or_else(|e| ValidationIssues::unparseable(&e))
+                .to_json()
+                .to_string()),
+            (None, Some(content)) => Ok(validate_str_to_json(content).to_string()),
+            (None, None) => {
+                return Err((INVALID_PARAMS, "path or json is required".to_string()));
+            }
+        },
+        "inspect_dataset" => {
+            let path = argument("path").ok_or((INVALID_PARAMS, "path is required".to_string()))?;
+            inspect_dataset(Path::new(path)).map(|summary| summary.to_string())
+        }
+        _ => return Err((INVALID_PARAMS, format!("Unknown tool: {name}"))),
+    };
+
+    Ok(match outcome {
+        Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+        Err(message) => {
+            json!({ "content": [{ "type": "text", "text": message }], "isError": true })
+        }
+    })
+}
+
+/// Generate metadata for a CSV file, also writing it to `output_path` if
+/// that is a `.json` or `.jsonld` file that does not exist yet
+fn generate_croissant(csv_path: &Path, output_path: Option<&Path>) -> Result<String, String> {
+    if let Some(path) = output_path
+        && !get_file_extension(path).is_some_and(|ext| OUTPUT_EXTENSIONS.contains(&ext.as_str()))
+    {
+        return Err(format!(
+            "{} is not a .json or .jsonld file; only metadata files are written",
+            path.display()
+        ));
+    }
+    let options = GenerateOptions {
+        keep_existing: true,
+        ..Default::default()
+    };
+    generate_metadata_from_csv_with_options(csv_path, output_path, &options)
+        .and_then(|metadata| Ok(serde_json::to_string_pretty(&metadata)?))
+        .map_err(|e| e.to_string())
+}
+
+fn inspect_dataset(path: &Path) -> Result<Value, String> {
+    let metadata = if get_file_extension(path).as_deref() == Some("csv") {
+        generate_metadata_from_csv(path, None)
+    } else {
+        read_metadata(path)
+    }
+    .map_err(|e| e.to_string())?;
+
+    Ok(describe_metadata(&metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "id,value\n1,2.5\n";
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })
+    }
+
+    fn call(name: &str, arguments: Value) -> Value {
+        handle_message(&request(
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        ))
+        .expect("response")
+    }
+
+    #[test]
+    fn initialize_answers_the_supported_protocol_version() {
+        let response = handle_message(&request(
+            "initialize",
+            json!({ "protocolVersion": "1999-01-01" }),
+        ))
+        .expect("response");
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(response["result"]["capabilities"]["tools"].is_object());
+    }
+
+    #[test]
+    fn notifications_get_no_response() {
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert_eq!(handle_message(&notification), None);
+    }
+
+    #[test]
+    fn malformed_requests_are_json_rpc_errors() {
+        let response = handle_message(&json!({ "jsonrpc": "2.0", "id": 2 })).expect("response");
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let response = handle_message(&request("resources/list", json!({}))).expect("response");
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = call("generate_croissant", json!({}));
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = call("delete_everything", json!({}));
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn tools_list_names_every_tool() {
+        let response = handle_message(&request("tools/list", Value::Null)).expect("response");
+        let names = response["result"]["tools"]
+            .as_array()
+            .expect("tools")
+            .iter()
+            .map(|tool| tool["name"].as_str().expect("name"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "generate_croissant",
+                "validate_croissant",
+                "inspect_dataset"
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_croissant_writes_new_metadata_files_only() {
+        let dir = tempfile::tempdir().expect("temporary directory");
+        let csv_path = dir.path().join("data.csv");
+        std::fs::write(&csv_path, CSV).expect("write CSV");
+
+        let output = dir.path().join("data.jsonld");
+        let response = call(
+            "generate_croissant",
+            json!({ "csv_path": csv_path, "output_path": output }),
+        );
+        assert_eq!(response["result"]["isError"], false);
+        assert!(output.is_file());
+
+        // An existing file is not replaced
+        let existing = dir.path().join("existing.json");
+        std::fs::write(&existing, "keep").expect("write file");
+        let response = call(
+            "generate_croissant",
+            json!({ "csv_path": csv_path, "output_path": existing }),
+        );
+        assert_eq!(response["result"]["isError"], true);
+        assert_eq!(std::fs::read_to_string(&existing).expect("read"), "keep");
+
+        // Nor is anything but a metadata file written
+        let script = dir.path().join("profile.sh");
+        let response = call(
+            "generate_croissant",
+            json!({ "csv_path": csv_path, "output_path": script }),
+        );
+        assert_eq!(response["result"]["isError"], true);
+        assert!(!script.exists());
+    }
+
+    #[test]
+    fn validate_croissant_reports_unparseable_documents() {
+        let response = call("validate_croissant", json!({ "json": "{ not json" }));
+        assert_eq!(response["result"]["isError"], false);
+        let report: Value = serde_json::from_str(
+            response["result"]["content"][0]["text"]
+                .as_str()
+                .expect("text"),
+        )
+        .expect("JSON report");
+        assert!(report.to_string().contains("unparseable"), "{report}");
+    }
+}
@@ -0,0 +1,12 @@
+//! Helpers used only by the command-line interface
+
+pub mod check;
+pub mod inspect;
+pub mod log;
+pub mod mcp;
+pub mod preview;
+#[cfg(feature = "remote")]
+pub mod progress;
+pub mod prompt;
+pub mod table;
+pub mod timings;
@@ -0,0 +1,91 @@
+//! First records of a record set as a terminal table, for the preview
+//! command
+//!
+//! Each column is headed by its field's name and dataType. Numbers are
+//! right-aligned, nulls are shown as `null` and empty text as `""`, so they
+//! cannot be mistaken for each other, and line breaks and tabs are escaped
+//! to keep rows on one line.
+
+use crate::cli::table::{Align, render, truncate};
+use rustcroissant::croissant::DataType;
+use rustcroissant::croissant::Result;
+use rustcroissant::croissant::records::{RecordField, RecordReader, Value};
+
+/// Render up to `rows` records of `reader`, cutting cells longer than
+/// `max_width` characters
+///
+/// The reader should yield one record more than `rows`, so that a note can
+/// tell when there are further records.
+pub fn render_preview(reader: RecordReader, rows: usize, max_width: usize) -> Result<String> {
+    let fields = reader.fields().to_vec();
+    let mut records = Vec::with_capacity(rows);
+    let mut more = false;
+    for record in reader {
+        let record = record?;
+        if records.len() == rows {
+            more = true;
+            break;
+        }
+        records.push(
+            record
+                .iter()
+                .map(|value| truncate(&format_value(value), max_width))
+                .collect(),
+        );
+    }
+
+    let header = vec![
+        fields.iter().map(|field| field.name.clone()).collect(),
+        fields.iter().map(field_type).collect(),
+    ];
+    let align = fields
+        .iter()
+        .map(|field| match field.data_type {
+            DataType::Integer | DataType::Float if !field.repeated && field.fields.is_empty() => {
+                Align::Right
+            }
+            _ => Align::Left,
+        })
+        .collect::<Vec<_>>();
+    let mut table = render(&header, &align, &records);
+    if more {
+        table.push_str(&format!("(first {rows} records shown; there are more)\n"));
+    }
+    Ok(table)
+}
+
+/// Short type of a field: its dataType without prefix, `Object` for fields
+/// with subfields, in brackets when repeated
+fn field_type(field: &RecordField) -> String {
+    let name = if field.fields.is_empty() {
+        field.data_type.to_schema_org().trim_start_matches("sc:")
+    } else {
+        "Object"
+    };
+    if field.repeated {
+        format!("[{name}]")
+    } else {
+        name.to_string()
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Text(text) if text.is_empty() => "\"\"".to_string(),
+        Value::Text(text) => text
+            .chars()
+            .map(|c| {
+                if c.is_control() {
+                    c.escape_default().collect()
+                } else {
+                    c.to_string()
+                }
+            })
+            .collect(),
+        // Keeps a decimal point, so floats are told apart from integers
+        Value::Float(n) => format!("{n:?}"),
+        Value::List(_) | Value::Object(_) => value.to_json_string(),
+        value => value.to_string(),
+    }
+}
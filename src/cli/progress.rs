@@ -0,0 +1,91 @@
+//! Byte progress of remote transfers for `verify --remote` and `fetch`
+//!
+//! A reporter thread reads the shared [`Progress`] counters once a second
+//! and prints the bytes and files done so far, and the transfer rate, to
+//! stderr. On a terminal the line is redrawn in place; otherwise a line is
+//! printed every ten seconds, which keeps logs of long runs readable.
+
+use rustcroissant::croissant::remote::Progress;
+use rustcroissant::croissant::utils::format_file_size;
+use std::io::{IsTerminal, Write};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const TERMINAL_INTERVAL: Duration = Duration::from_secs(1);
+const LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Prints the progress of a transfer until finished
+pub struct ProgressReporter {
+    stop: mpsc::Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl ProgressReporter {
+    /// Start reporting on `progress`, out of its expected totals
+    pub fn start(progress: Progress) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let terminal = std::io::stderr().is_terminal();
+        let interval = if terminal {
+            TERMINAL_INTERVAL
+        } else {
+            LOG_INTERVAL
+        };
+        let thread = std::thread::spawn(move || {
+            let started = Instant::now();
+            loop {
+                let finished = !matches!(
+                    stopped.recv_timeout(interval),
+                    Err(RecvTimeoutError::Timeout)
+                );
+                // Nothing is reported until the files to fetch are known
+                if progress.total_files() == 0 {
+                    if finished {
+                        break;
+                    }
+                    continue;
+                }
+                let line = status_line(&progress, started.elapsed());
+                let mut stderr = std::io::stderr().lock();
+                let _ = match (terminal, finished) {
+                    (true, false) => write!(stderr, "\r\x1b[2K{line}"),
+                    (true, true) => writeln!(stderr, "\r\x1b[2K{line}"),
+                    (false, _) => writeln!(stderr, "{line}"),
+                };
+                let _ = stderr.flush();
+                if finished {
+                    break;
+                }
+            }
+        });
+        Self { stop, thread }
+    }
+
+    /// Print the final progress and stop reporting
+    pub fn finish(self) {
+        let _ = self.stop.send(());
+        let _ = self.thread.join();
+    }
+}
+
+/// Start a reporter if progress was asked for, or stderr is a terminal
+pub fn start_if(enabled: bool, progress: &Progress) -> Option<ProgressReporter> {
+    (enabled || std::io::stderr().is_terminal()).then(|| ProgressReporter::start(progress.clone()))
+}
+
+/// Bytes and files done, such as
+/// `1.2 GB of 200.0 GB (0%), 3/120 files, 45.0 MB/s`
+fn status_line(progress: &Progress, elapsed: Duration) -> String {
+    let bytes = progress.bytes();
+    let mut line = format_file_size(bytes);
+    if let Some(total) = progress.total_bytes() {
+        let percent = (bytes.min(total) as f64 / total as f64 * 100.0).floor();
+        line += &format!(" of {} ({percent}%)", format_file_size(total));
+    }
+    line += &format!(", {}/{} files", progress.files(), progress.total_files());
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        line += &format!(", {}/s", format_file_size((bytes as f64 / seconds) as u64));
+    }
+    line
+}
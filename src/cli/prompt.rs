@@ -0,0 +1,52 @@
+//! Prompts for dataset properties that `generate` was not given
+//!
+//! Asked on standard error and answered on standard input, and only when
+//! both are terminals, so scripts and CI never wait for an answer. An empty
+//! answer keeps the suggested default.
+
+use rustcroissant::croissant::generate::DatasetProperties;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Whether the user can be asked questions
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Ask for the name, description and license of the dataset generated from
+/// `file_name` where none was given
+pub fn complete_dataset_properties(
+    properties: &mut DatasetProperties,
+    file_name: &str,
+) -> io::Result<()> {
+    let stem = file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem);
+
+    if properties.name.is_none() {
+        properties.name = ask("Name", &format!("{stem}_dataset"))?;
+    }
+    if properties.description.is_none() {
+        properties.description = ask("Description", &format!("Dataset created from {file_name}"))?;
+    }
+    if properties.license.is_empty() {
+        properties.license = ask(
+            "License URL (e.g. https://creativecommons.org/licenses/by/4.0/)",
+            "none",
+        )?
+        .into_iter()
+        .collect();
+    }
+    Ok(())
+}
+
+/// The answer to a question, or `None` to keep the default
+fn ask(question: &str, default: &str) -> io::Result<Option<String>> {
+    let mut stderr = io::stderr().lock();
+    write!(stderr, "{question} [{default}]: ")?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok((!answer.is_empty()).then(|| answer.to_string()))
+}
@@ -0,0 +1,59 @@
+//! Plain-text tables for terminal output
+
+/// Alignment of a column's cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Render rows under one or more header rows, padding every column to its
+/// widest cell
+///
+/// Widths are counted in characters, so cells should not contain line
+/// breaks or wide characters.
+pub fn render(header: &[Vec<String>], align: &[Align], rows: &[Vec<String>]) -> String {
+    let mut widths = Vec::<usize>::new();
+    for row in header.iter().chain(rows) {
+        if widths.len() < row.len() {
+            widths.resize(row.len(), 0);
+        }
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    let rule = vec![widths.iter().map(|width| "-".repeat(*width)).collect()];
+    for row in header.iter().chain(&rule).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| {
+                match align.get(column).copied().unwrap_or(Align::Left) {
+                    Align::Left => format!("{cell:<width$}"),
+                    Align::Right => format!("{cell:>width$}"),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// Shorten text to at most `max_width` characters, ending it with `…` when
+/// cut
+pub fn truncate(text: &str, max_width: usize) -> String {
+    if text.chars().count() <= max_width {
+        return text.to_string();
+    }
+    let mut truncated = text
+        .chars()
+        .take(max_width.saturating_sub(1))
+        .collect::<String>();
+    truncated.push('…');
+    truncated
+}
@@ -0,0 +1,107 @@
+//! Per-phase timing summary for the --timings flag
+//!
+//! A tracing layer measures how long the library's instrumented spans stay
+//! open and sums them up per phase, so no timing code is needed in the
+//! library itself.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::Subscriber;
+use tracing::span::{Attributes, Id};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Phases reported in the summary, in display order
+const PHASES: &[&str] = &[
+    "reading",
+    "hashing",
+    "inference",
+    "validation",
+    "serialization",
+    "remote",
+];
+
+static TIMINGS: OnceLock<Timings> = OnceLock::new();
+
+struct Timings {
+    started: Instant,
+    totals: Mutex<BTreeMap<&'static str, Duration>>,
+}
+
+/// Map an instrumented span name to the phase it belongs to
+fn phase_of(span_name: &str) -> Option<&'static str> {
+    match span_name {
+        "read_metadata" => Some("reading"),
+        "calculate_sha256" | "calculate_sha256_async" => Some("hashing"),
+        "infer_csv_schema" => Some("inference"),
+        "validate_metadata" => Some("validation"),
+        "write_metadata" => Some("serialization"),
+        "fetch_sha256" => Some("remote"),
+        _ => None,
+    }
+}
+
+/// Tracing layer that accumulates span durations per phase
+pub struct TimingsLayer;
+
+impl TimingsLayer {
+    /// Create the layer and start the command's wall clock
+    pub fn install() -> Self {
+        let _ = TIMINGS.set(Timings {
+            started: Instant::now(),
+            totals: Mutex::new(BTreeMap::new()),
+        });
+        Self
+    }
+}
+
+impl<S> Layer<S> for TimingsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if phase_of(attrs.metadata().name()).is_some()
+            && let Some(span) = ctx.span(id)
+        {
+            span.extensions_mut().insert(Instant::now());
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let (Some(phase), Some(timings)) = (phase_of(span.name()), TIMINGS.get()) else {
+            return;
+        };
+        if let Some(started) = span.extensions().get::<Instant>() {
+            *timings
+                .totals
+                .lock()
+                .expect("timings poisoned")
+                .entry(phase)
+                .or_default() += started.elapsed();
+        }
+    }
+}
+
+/// Print the timing summary to stderr if --timings is active
+pub fn print_summary() {
+    let Some(timings) = TIMINGS.get() else {
+        return;
+    };
+    let totals = timings.totals.lock().expect("timings poisoned");
+
+    eprintln!("Timings:");
+    for phase in PHASES {
+        if let Some(elapsed) = totals.get(phase) {
+            eprintln!("  {phase:<14} {:>10.3}s", elapsed.as_secs_f64());
+        }
+    }
+    eprintln!(
+        "  {:<14} {:>10.3}s",
+        "total",
+        timings.started.elapsed().as_secs_f64()
+    );
+}
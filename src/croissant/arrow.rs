@@ -0,0 +1,371 @@
+//! Export of record sets as Arrow RecordBatches
+//!
+//! Records are materialized with [`read_records`], applying source
+//! transforms and null values, and grouped into batches whose schema
+//! follows the Croissant dataTypes with [`record_batches`]. Records that
+//! are filtered, sampled or joined can be grouped into batches the same way.
+
+use crate::croissant::core::{DataType, Metadata, RecordSet};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::records::{RecordField, RecordReader, Value, read_records};
+use arrow_array::builder::{
+    BooleanBuilder, Date32Builder, Float64Builder, Int64Builder, StringBuilder,
+    Time64MicrosecondBuilder, TimestampMicrosecondBuilder,
+};
+use arrow_array::{ArrayRef, ListArray, RecordBatch};
+use arrow_buffer::{NullBuffer, OffsetBuffer};
+use arrow_schema::{Field as ArrowField, Schema, SchemaRef, TimeUnit};
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Default number of rows per RecordBatch
+pub const DEFAULT_BATCH_SIZE: usize = 8192;
+
+/// Arrow type used for a Croissant data type
+///
+/// Dates become `Date32`; datetimes become microsecond timestamps in UTC,
+/// with naive datetimes taken to be UTC already, and times `Time64` in
+/// microseconds.
+pub fn arrow_data_type(data_type: &DataType) -> arrow_schema::DataType {
+    match data_type {
+        DataType::Integer => arrow_schema::DataType::Int64,
+        DataType::Float => arrow_schema::DataType::Float64,
+        DataType::Boolean => arrow_schema::DataType::Boolean,
+        DataType::Date => arrow_schema::DataType::Date32,
+        DataType::DateTime => {
+            arrow_schema::DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+        }
+        DataType::Time => arrow_schema::DataType::Time64(TimeUnit::Microsecond),
+        DataType::Text | DataType::Url => arrow_schema::DataType::Utf8,
+    }
+}
+
+/// Croissant data type of values of an Arrow type
+///
+/// Integers of any width become Integer, floating point and decimal numbers
+/// Float, and types without a Croissant counterpart, such as binary or
+/// nested ones, Text.
+pub fn croissant_data_type(data_type: &arrow_schema::DataType) -> DataType {
+    use arrow_schema::DataType as Arrow;
+
+    match data_type {
+        Arrow::Int8
+        | Arrow::Int16
+        | Arrow::Int32
+        | Arrow::Int64
+        | Arrow::UInt8
+        | Arrow::UInt16
+        | Arrow::UInt32
+        | Arrow::UInt64 => DataType::Integer,
+        Arrow::Float16
+        | Arrow::Float32
+        | Arrow::Float64
+        | Arrow::Decimal32(..)
+        | Arrow::Decimal64(..)
+        | Arrow::Decimal128(..)
+        | Arrow::Decimal256(..) => DataType::Float,
+        Arrow::Boolean => DataType::Boolean,
+        Arrow::Date32 | Arrow::Date64 => DataType::Date,
+        Arrow::Timestamp(..) => DataType::DateTime,
+        Arrow::Time32(_) | Arrow::Time64(_) => DataType::Time,
+        Arrow::Dictionary(_, values) => croissant_data_type(values),
+        _ => DataType::Text,
+    }
+}
+
+/// Arrow schema of a record set
+///
+/// Every column is nullable, and fields whose dataType is not a known
+/// schema.org type are exported as strings.
+pub fn record_set_schema(record_set: &RecordSet) -> Schema {
+    Schema::new(
+        record_set
+            .field
+            .iter()
+            .map(|field| {
+                ArrowField::new(
+                    &field.name,
+                    arrow_data_type(&field_data_type(&field.data_type)),
+                    true,
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Arrow type of a field of materialized records: a list of its dataType's
+/// type if repeated; objects are written as JSON text
+pub fn record_field_type(field: &RecordField) -> arrow_schema::DataType {
+    let data_type = arrow_data_type(&item_data_type(field));
+    if field.repeated {
+        arrow_schema::DataType::List(Arc::new(ArrowField::new_list_field(data_type, true)))
+    } else {
+        data_type
+    }
+}
+
+/// Arrow schema of materialized records, every column nullable
+pub fn records_schema(fields: &[RecordField]) -> Schema {
+    Schema::new(
+        fields
+            .iter()
+            .map(|field| ArrowField::new(&field.name, record_field_type(field), true))
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Type of the values, or of the items of lists, of a field
+fn item_data_type(field: &RecordField) -> DataType {
+    if field.fields.is_empty() {
+        field.data_type.clone()
+    } else {
+        DataType::Text
+    }
+}
+
+fn field_data_type(data_type: &str) -> DataType {
+    DataType::from_schema_org(data_type).unwrap_or(DataType::Text)
+}
+
+/// Read a record set, looked up by name or `@id`, as Arrow RecordBatches
+///
+/// Relative content URLs are resolved against `base_dir`, normally the
+/// directory of the metadata file.
+pub fn read_record_set(
+    metadata: &Metadata,
+    record_set: &str,
+    base_dir: &Path,
+) -> Result<RecordBatches> {
+    read_record_set_with_batch_size(metadata, record_set, base_dir, DEFAULT_BATCH_SIZE)
+}
+
+/// Read a record set as Arrow RecordBatches of at most `batch_size` rows
+#[tracing::instrument(level = "debug", skip(metadata, base_dir))]
+pub fn read_record_set_with_batch_size(
+    metadata: &Metadata,
+    record_set: &str,
+    base_dir: &Path,
+    batch_size: usize,
+) -> Result<RecordBatches> {
+    Ok(record_batches(
+        read_records(metadata, record_set, base_dir)?,
+        batch_size,
+    ))
+}
+
+/// Group materialized records into RecordBatches of at most `batch_size`
+/// rows
+pub fn record_batches(reader: RecordReader, batch_size: usize) -> RecordBatches {
+    RecordBatches {
+        schema: Arc::new(records_schema(reader.fields())),
+        fields: reader.fields().to_vec(),
+        reader,
+        batch_size: batch_size.max(1),
+        done: false,
+    }
+}
+
+/// Iterator over the RecordBatches of materialized records
+pub struct RecordBatches {
+    reader: RecordReader,
+    schema: SchemaRef,
+    fields: Vec<RecordField>,
+    batch_size: usize,
+    done: bool,
+}
+
+impl RecordBatches {
+    /// Schema shared by all batches
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let mut builders: Vec<ColumnBuilder> = self
+            .fields
+            .iter()
+            .map(|field| ColumnBuilder::for_field(field, self.batch_size))
+            .collect();
+
+        let mut rows = 0;
+        for record in self.reader.by_ref().take(self.batch_size) {
+            for (value, builder) in record?.iter().zip(&mut builders) {
+                builder.append_value(value)?;
+            }
+            rows += 1;
+        }
+
+        if rows == 0 {
+            return Ok(None);
+        }
+        let arrays = builders.into_iter().map(ColumnBuilder::finish).collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)
+            .map_err(|e| Error::new(format!("Could not build RecordBatch: {e}")))?;
+        Ok(Some(batch))
+    }
+}
+
+impl Iterator for RecordBatches {
+    type Item = Result<RecordBatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let batch = self.next_batch().transpose();
+        // Stop after the last record or the first error
+        if !matches!(batch, Some(Ok(_))) {
+            self.done = true;
+        }
+        batch
+    }
+}
+
+/// Typed builder for one column of a batch
+enum ColumnBuilder {
+    Integer(Int64Builder),
+    Float(Float64Builder),
+    Boolean(BooleanBuilder),
+    Date(Date32Builder),
+    DateTime(TimestampMicrosecondBuilder),
+    Time(Time64MicrosecondBuilder),
+    Text(StringBuilder),
+    /// Lists of values, built as the flat list of their items and the
+    /// offset of each list into it
+    Repeated {
+        items: Box<ColumnBuilder>,
+        item_type: arrow_schema::DataType,
+        offsets: Vec<i32>,
+        validity: Vec<bool>,
+    },
+}
+
+impl ColumnBuilder {
+    fn new(data_type: &DataType, capacity: usize) -> Self {
+        match data_type {
+            DataType::Integer => Self::Integer(Int64Builder::with_capacity(capacity)),
+            DataType::Float => Self::Float(Float64Builder::with_capacity(capacity)),
+            DataType::Boolean => Self::Boolean(BooleanBuilder::with_capacity(capacity)),
+            DataType::Date => Self::Date(Date32Builder::with_capacity(capacity)),
+            DataType::DateTime => Self::DateTime(
+                TimestampMicrosecondBuilder::with_capacity(capacity).with_timezone("UTC"),
+            ),
+            DataType::Time => Self::Time(Time64MicrosecondBuilder::with_capacity(capacity)),
+            DataType::Text | DataType::Url => {
+                Self::Text(StringBuilder::with_capacity(capacity, capacity * 16))
+            }
+        }
+    }
+
+    /// Builder for a field of materialized records
+    fn for_field(field: &RecordField, capacity: usize) -> Self {
+        let data_type = item_data_type(field);
+        if field.repeated {
+            let mut offsets = Vec::with_capacity(capacity + 1);
+            offsets.push(0);
+            Self::Repeated {
+                items: Box::new(Self::new(&data_type, capacity)),
+                item_type: arrow_data_type(&data_type),
+                offsets,
+                validity: Vec::with_capacity(capacity),
+            }
+        } else {
+            Self::new(&data_type, capacity)
+        }
+    }
+
+    fn append_null(&mut self) {
+        match self {
+            Self::Integer(b) => b.append_null(),
+            Self::Float(b) => b.append_null(),
+            Self::Boolean(b) => b.append_null(),
+            Self::Date(b) => b.append_null(),
+            Self::DateTime(b) => b.append_null(),
+            Self::Time(b) => b.append_null(),
+            Self::Text(b) => b.append_null(),
+            Self::Repeated {
+                offsets, validity, ..
+            } => {
+                offsets.push(offsets.last().copied().unwrap_or_default());
+                validity.push(false);
+            }
+        }
+    }
+
+    /// Append a materialized value, which has the column's type already
+    fn append_value(&mut self, value: &Value) -> Result<()> {
+        match (self, value) {
+            (builder, Value::Null) => builder.append_null(),
+            (
+                Self::Repeated {
+                    items,
+                    offsets,
+                    validity,
+                    ..
+                },
+                Value::List(values),
+            ) => {
+                for value in values {
+                    items.append_value(value)?;
+                }
+                let end = offsets.last().copied().unwrap_or_default() as usize + values.len();
+                offsets
+                    .push(i32::try_from(end).map_err(|_| Error::new("List column is too large"))?);
+                validity.push(true);
+            }
+            (Self::Integer(b), Value::Integer(n)) => b.append_value(*n),
+            (Self::Float(b), Value::Float(n)) => b.append_value(*n),
+            (Self::Boolean(b), Value::Boolean(v)) => b.append_value(*v),
+            (Self::Date(b), Value::Date(date)) => b.append_value(
+                days_since_epoch(*date)
+                    .ok_or_else(|| Error::invalid_data_type(value.to_string(), "sc:Date"))?,
+            ),
+            (Self::DateTime(b), Value::DateTime(datetime)) => {
+                b.append_value(datetime.timestamp_micros())
+            }
+            (Self::Time(b), Value::Time(time)) => b.append_value(micros_since_midnight(*time)),
+            (Self::Text(b), value) => b.append_value(value.to_string()),
+            (_, value) => {
+                return Err(Error::new(format!(
+                    "{value} does not match the type of its column"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> ArrayRef {
+        match self {
+            Self::Integer(mut b) => Arc::new(b.finish()),
+            Self::Float(mut b) => Arc::new(b.finish()),
+            Self::Boolean(mut b) => Arc::new(b.finish()),
+            Self::Date(mut b) => Arc::new(b.finish()),
+            Self::DateTime(mut b) => Arc::new(b.finish()),
+            Self::Time(mut b) => Arc::new(b.finish()),
+            Self::Text(mut b) => Arc::new(b.finish()),
+            Self::Repeated {
+                items,
+                item_type,
+                offsets,
+                validity,
+            } => Arc::new(ListArray::new(
+                Arc::new(ArrowField::new_list_field(item_type, true)),
+                OffsetBuffer::new(offsets.into()),
+                items.finish(),
+                Some(NullBuffer::from(validity)),
+            )),
+        }
+    }
+}
+
+/// Microseconds since midnight
+fn micros_since_midnight(time: NaiveTime) -> i64 {
+    i64::from(time.num_seconds_from_midnight()) * 1_000_000 + i64::from(time.nanosecond() / 1000)
+}
+
+/// Days since the Unix epoch
+fn days_since_epoch(date: NaiveDate) -> Option<i32> {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+    i32::try_from((date - epoch).num_days()).ok()
+}
@@ -0,0 +1,485 @@
+//! Builders for assembling Croissant metadata in code
+//!
+//! The builders fill in the JSON-LD context, `@type`s and `@id`s, so only
+//! the properties of the dataset need to be given. The metadata is
+//! validated when built:
+//!
+//! ```
+//! use rustcroissant::croissant::DataType;
+//! use rustcroissant::croissant::builder::{
+//!     DistributionBuilder, FieldBuilder, MetadataBuilder, RecordSetBuilder,
+//! };
+//!
+//! let metadata = MetadataBuilder::new("stations")
+//!     .description("Flow rates measured at river stations")
+//!     .license("https://creativecommons.org/licenses/by/4.0/")
+//!     .distribution(
+//!         DistributionBuilder::new("stations.csv")
+//!             .content_url("https://data.example.org/stations.csv")
+//!             .content_size(892)
+//!             .sha256("e90231ea70eb3317ffbcbcbfb18f10609ff20beb9db7cd9b136c8c0fcaffad6a"),
+//!     )
+//!     .record_set(
+//!         RecordSetBuilder::new("readings")
+//!             .field(FieldBuilder::new("station", DataType::Text).column("stations.csv", "station"))
+//!             .field(FieldBuilder::new("flow_rate", DataType::Float).column("stations.csv", "flow")),
+//!     )
+//!     .build()?;
+//!
+//! assert_eq!(metadata.record_set[0].field[1].id, "readings/flow_rate");
+//! # Ok::<(), rustcroissant::croissant::validate::ValidationIssues>(())
+//! ```
+
+use crate::croissant::core::{
+    Creator, DataType, Distribution, Extract, Field, FieldId, FieldReference, FieldSource,
+    FileObject, FileProperty, Metadata, Rai, RecordSet, create_default_context,
+};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::{calculate_sha256, media_type, publication_date};
+use crate::croissant::validate::ValidationIssues;
+use std::path::Path;
+
+/// Builder of a dataset's metadata
+///
+/// Unless set, the version is `1.0.0` and the publication date that of
+/// [`publication_date`].
+#[derive(Debug, Clone)]
+pub struct MetadataBuilder {
+    metadata: Metadata,
+}
+
+impl MetadataBuilder {
+    /// Metadata of a dataset with the given name and nothing else
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            metadata: Metadata {
+                context: create_default_context(),
+                type_: "sc:Dataset".to_string(),
+                name: name.into(),
+                description: String::new(),
+                conforms_to: "http://mlcommons.org/croissant/1.0".to_string(),
+                date_published: publication_date(),
+                version: "1.0.0".to_string(),
+                license: Vec::new(),
+                url: None,
+                creator: Vec::new(),
+                keywords: Vec::new(),
+                identifier: None,
+                cite_as: None,
+                distribution: Vec::new(),
+                record_set: Vec::new(),
+                rai: Rai::default(),
+            },
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.metadata.description = description.into();
+        self
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.metadata.version = version.into();
+        self
+    }
+
+    /// Publication date, as `YYYY-MM-DD`
+    pub fn date_published(mut self, date: impl Into<String>) -> Self {
+        self.metadata.date_published = date.into();
+        self
+    }
+
+    /// Add a license, by URL or name
+    pub fn license(mut self, license: impl Into<String>) -> Self {
+        self.metadata.license.push(license.into());
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.metadata.url = Some(url.into());
+        self
+    }
+
+    /// Add a creator; a name alone, as a `&str`, is taken to be a person's
+    pub fn creator(mut self, creator: impl Into<Creator>) -> Self {
+        self.metadata.creator.push(creator.into());
+        self
+    }
+
+    pub fn keyword(mut self, keyword: impl Into<String>) -> Self {
+        self.metadata.keywords.push(keyword.into());
+        self
+    }
+
+    /// Identifier such as a DOI
+    pub fn identifier(mut self, identifier: impl Into<String>) -> Self {
+        self.metadata.identifier = Some(identifier.into());
+        self
+    }
+
+    /// Citation of the dataset, such as a BibTeX entry
+    pub fn cite_as(mut self, citation: impl Into<String>) -> Self {
+        self.metadata.cite_as = Some(citation.into());
+        self
+    }
+
+    /// Responsible AI properties; setting any defines the `rai` prefix in
+    /// the context
+    pub fn rai(mut self, rai: Rai) -> Self {
+        if !rai.is_empty() {
+            self.metadata.context.add_rai_terms();
+        }
+        self.metadata.rai = rai;
+        self
+    }
+
+    /// Add a distribution; adding a file set defines the terms of file
+    /// sets in the context
+    pub fn distribution(mut self, distribution: DistributionBuilder) -> Self {
+        let distribution = distribution.build();
+        if distribution.is_file_set() {
+            self.metadata.context.add_file_set_terms();
+        }
+        self.metadata.distribution.push(distribution);
+        self
+    }
+
+    pub fn record_set(mut self, record_set: RecordSetBuilder) -> Self {
+        self.metadata.record_set.push(record_set.build());
+        self
+    }
+
+    /// The metadata, if it passes validation without errors, or the issues
+    /// found otherwise
+    pub fn build(self) -> std::result::Result<Metadata, ValidationIssues> {
+        self.metadata.into_validated()
+    }
+
+    /// The metadata as it stands, without validating it, such as to
+    /// complete it before it is validated
+    pub fn build_unchecked(self) -> Metadata {
+        self.metadata
+    }
+}
+
+/// Builder of a `cr:FileObject` or `cr:FileSet` distribution
+///
+/// Its name is its `@id`, which fields' sources refer to, and, for a file
+/// object, its content URL unless set. The encoding format of a file object
+/// is guessed from the name's extension.
+#[derive(Debug, Clone)]
+pub struct DistributionBuilder {
+    distribution: Distribution,
+}
+
+impl DistributionBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            distribution: Distribution {
+                id: name.clone(),
+                type_: "cr:FileObject".to_string(),
+                encoding_format: media_type(Path::new(&name))
+                    .unwrap_or("application/octet-stream")
+                    .to_string(),
+                content_url: name.clone(),
+                name,
+                description: None,
+                content_size: String::new(),
+                sha256: String::new(),
+                blake3: None,
+                delimiter: None,
+                contained_in: Vec::new(),
+                includes: Vec::new(),
+                excludes: Vec::new(),
+            },
+        }
+    }
+
+    /// File set of files of the given media type, such as `image/jpeg`,
+    /// which [`include`](Self::include) selects
+    pub fn file_set(name: impl Into<String>, encoding_format: impl Into<String>) -> Self {
+        let mut builder = Self::new(name);
+        builder.distribution.type_ = "cr:FileSet".to_string();
+        builder.distribution.content_url = String::new();
+        builder.distribution.encoding_format = encoding_format.into();
+        builder
+    }
+
+    /// Distribution of a local file, named after it, with its size and
+    /// SHA-256 digest
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| Error::invalid_format("Invalid file path"))?
+            .to_string_lossy()
+            .to_string();
+        let size = std::fs::metadata(path)
+            .map_err(|_| Error::file_not_found(path))?
+            .len();
+        Ok(Self::new(name)
+            .content_size(size)
+            .sha256(calculate_sha256(path)?))
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.distribution.description = Some(description.into());
+        self
+    }
+
+    pub fn content_url(mut self, url: impl Into<String>) -> Self {
+        self.distribution.content_url = url.into();
+        self
+    }
+
+    /// Size of the file in bytes
+    pub fn content_size(mut self, bytes: u64) -> Self {
+        self.distribution.content_size = format!("{bytes} B");
+        self
+    }
+
+    /// Media type, such as `text/csv`
+    pub fn encoding_format(mut self, format: impl Into<String>) -> Self {
+        self.distribution.encoding_format = format.into();
+        self
+    }
+
+    pub fn sha256(mut self, digest: impl Into<String>) -> Self {
+        self.distribution.sha256 = digest.into();
+        self
+    }
+
+    /// Field delimiter of delimited text, such as `;`
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.distribution.delimiter = Some(delimiter.to_string());
+        self
+    }
+
+    /// Add a glob pattern of the files of a file set, such as `images/*.jpg`
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.distribution.includes.push(pattern.into());
+        self
+    }
+
+    /// Add a glob pattern of files left out of a file set
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.distribution.excludes.push(pattern.into());
+        self
+    }
+
+    /// Add a distribution, by `@id`, that the files are part of
+    pub fn contained_in(mut self, file_object: impl Into<String>) -> Self {
+        self.distribution.contained_in.push(FileObject {
+            id: file_object.into(),
+        });
+        self
+    }
+
+    pub fn build(self) -> Distribution {
+        self.distribution
+    }
+}
+
+/// Builder of a record set
+///
+/// Its name is its `@id`, which the `@id`s of its fields start with.
+#[derive(Debug, Clone)]
+pub struct RecordSetBuilder {
+    record_set: RecordSet,
+    fields: Vec<FieldBuilder>,
+}
+
+impl RecordSetBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            record_set: RecordSet {
+                id: name.clone(),
+                type_: "cr:RecordSet".to_string(),
+                name,
+                description: String::new(),
+                data_type: None,
+                field: Vec::new(),
+                key: Vec::new(),
+                data: None,
+            },
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.record_set.description = description.into();
+        self
+    }
+
+    /// Class the records are instances of, such as `cr:Split`
+    pub fn data_type(mut self, data_type: impl Into<String>) -> Self {
+        self.record_set.data_type = Some(data_type.into());
+        self
+    }
+
+    pub fn field(mut self, field: FieldBuilder) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Add a field, by name, to the key identifying the records
+    pub fn key(mut self, field: impl AsRef<str>) -> Self {
+        self.record_set.key.push(FieldId {
+            id: format!("{}/{}", self.record_set.id, field.as_ref()),
+        });
+        self
+    }
+
+    /// Give the records inline rather than extracted from files, each
+    /// keyed by the `@id`s of the fields
+    pub fn data(mut self, records: Vec<serde_json::Map<String, serde_json::Value>>) -> Self {
+        self.record_set.data = Some(records);
+        self
+    }
+
+    pub fn build(mut self) -> RecordSet {
+        let id = self.record_set.id.clone();
+        self.record_set.field = self
+            .fields
+            .into_iter()
+            .map(|field| field.build_in(&id))
+            .collect();
+        self.record_set
+    }
+}
+
+/// Builder of a field
+///
+/// Its `@id` is its name prefixed with that of the record set or field it
+/// is added to, and its description defaults to `Field for <name>`.
+#[derive(Debug, Clone)]
+pub struct FieldBuilder {
+    field: Field,
+    sub_fields: Vec<FieldBuilder>,
+}
+
+impl FieldBuilder {
+    pub fn new(name: impl Into<String>, data_type: DataType) -> Self {
+        let name = name.into();
+        Self {
+            field: Field {
+                id: String::new(),
+                type_: "cr:Field".to_string(),
+                description: format!("Field for {name}"),
+                name,
+                data_type: data_type.to_schema_org().to_string(),
+                unit_text: None,
+                source: None,
+                references: None,
+                repeated: false,
+                sub_field: Vec::new(),
+                nullable: None,
+                null_values: Vec::new(),
+                statistics: None,
+            },
+            sub_fields: Vec::new(),
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.field.description = description.into();
+        self
+    }
+
+    /// Type of the values other than a [`DataType`], such as a semantic
+    /// type like `sc:GeoCoordinates`
+    pub fn data_type_iri(mut self, data_type: impl Into<String>) -> Self {
+        self.field.data_type = data_type.into();
+        self
+    }
+
+    pub fn unit(mut self, unit: impl Into<String>) -> Self {
+        self.field.unit_text = Some(unit.into());
+        self
+    }
+
+    /// Extract the values from a column of a distribution
+    pub fn column(mut self, file_object: impl Into<String>, column: impl Into<String>) -> Self {
+        self.field.source = Some(source(file_object, column.into(), None));
+        self
+    }
+
+    /// Extract the values from a property of the files of a distribution,
+    /// such as their names
+    pub fn file_property(mut self, file_object: impl Into<String>, property: FileProperty) -> Self {
+        self.field.source = Some(source(file_object, String::new(), Some(property)));
+        self
+    }
+
+    /// Extract the values from a property of each file of a file set, one
+    /// record per file
+    pub fn file_set_property(
+        mut self,
+        file_set: impl Into<String>,
+        property: FileProperty,
+    ) -> Self {
+        let mut source = source(String::new(), String::new(), Some(property));
+        source.file_set = Some(FileObject {
+            id: file_set.into(),
+        });
+        self.field.source = Some(source);
+        self
+    }
+
+    /// Refer to the values of another field, by its `@id`
+    pub fn references(mut self, field_id: impl Into<String>) -> Self {
+        self.field.references = Some(FieldReference {
+            field: FieldId {
+                id: field_id.into(),
+            },
+        });
+        self
+    }
+
+    /// Make each record hold a list of values
+    pub fn repeated(mut self) -> Self {
+        self.field.repeated = true;
+        self
+    }
+
+    pub fn nullable(mut self, nullable: bool) -> Self {
+        self.field.nullable = Some(nullable);
+        self
+    }
+
+    pub fn sub_field(mut self, field: FieldBuilder) -> Self {
+        self.sub_fields.push(field);
+        self
+    }
+
+    /// The field, with the `@id`s of it and its subfields under `parent`
+    fn build_in(mut self, parent: &str) -> Field {
+        self.field.id = format!("{parent}/{}", self.field.name);
+        let id = self.field.id.clone();
+        self.field.sub_field = self
+            .sub_fields
+            .into_iter()
+            .map(|field| field.build_in(&id))
+            .collect();
+        self.field
+    }
+}
+
+fn source(
+    file_object: impl Into<String>,
+    column: String,
+    property: Option<FileProperty>,
+) -> FieldSource {
+    FieldSource {
+        extract: Extract {
+            column,
+            file_property: property,
+        },
+        file_object: FileObject {
+            id: file_object.into(),
+        },
+        file_set: None,
+        transform: Vec::new(),
+    }
+}
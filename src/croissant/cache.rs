@@ -0,0 +1,151 @@
+//! On-disk cache of file hashes
+//!
+//! Entries are keyed on the canonical file path and invalidated whenever the
+//! file size or modification time changes, so repeated runs over unchanged
+//! files skip rehashing.
+
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::calculate_sha256;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// Environment variable overriding the cache directory
+pub const CACHE_DIR_ENV: &str = "CROISSANT_CACHE_DIR";
+
+const CACHE_FILE_NAME: &str = "hashes.json";
+
+/// A cached digest together with the file state it was computed from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct CacheEntry {
+    size: u64,
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    sha256: String,
+}
+
+/// Hash cache persisted as a JSON file
+#[derive(Debug)]
+pub struct HashCache {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl HashCache {
+    /// Open the cache stored at `path`
+    ///
+    /// A missing or unreadable cache file yields an empty cache rather than
+    /// an error, since the cache can always be rebuilt.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!(path = %path.display(), error = %e, "ignoring corrupt hash cache");
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Open the cache inside `dir`
+    pub fn in_dir(dir: &Path) -> Self {
+        Self::open(dir.join(CACHE_FILE_NAME))
+    }
+
+    /// Open the cache at its default location, if one can be determined
+    pub fn open_default() -> Option<Self> {
+        default_cache_dir().map(|dir| Self::in_dir(&dir))
+    }
+
+    /// Location of the cache file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of cached digests
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("hash cache poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the SHA-256 digest of a file, reusing the cached digest when
+    /// the file's size and modification time are unchanged
+    pub fn sha256(&self, file_path: &Path) -> Result<String> {
+        let canonical = file_path
+            .canonicalize()
+            .map_err(|_| Error::file_not_found(file_path))?;
+        let key = canonical.to_string_lossy().to_string();
+
+        let file_info = std::fs::metadata(&canonical)?;
+        let mtime = file_info
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let size = file_info.len();
+
+        if let Some(entry) = self.entries.lock().expect("hash cache poisoned").get(&key)
+            && entry.size == size
+            && entry.mtime_secs == mtime.as_secs()
+            && entry.mtime_nanos == mtime.subsec_nanos()
+        {
+            tracing::debug!(path = %file_path.display(), "hash cache hit");
+            return Ok(entry.sha256.clone());
+        }
+
+        let sha256 = calculate_sha256(&canonical)?;
+        self.entries.lock().expect("hash cache poisoned").insert(
+            key,
+            CacheEntry {
+                size,
+                mtime_secs: mtime.as_secs(),
+                mtime_nanos: mtime.subsec_nanos(),
+                sha256: sha256.clone(),
+            },
+        );
+
+        Ok(sha256)
+    }
+
+    /// Write the cache back to disk
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let content = {
+            let entries = self.entries.lock().expect("hash cache poisoned");
+            serde_json::to_string(&*entries)?
+        };
+
+        // Write to a temporary file first so an interrupted run never leaves
+        // a truncated cache behind
+        let temp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&temp_path, content)?;
+        std::fs::rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Default cache directory
+///
+/// Uses `CROISSANT_CACHE_DIR` if set, then `$XDG_CACHE_HOME/rustcroissant`,
+/// then `$HOME/.cache/rustcroissant`.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(CACHE_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+        return Some(PathBuf::from(dir).join("rustcroissant"));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache").join("rustcroissant"))
+}
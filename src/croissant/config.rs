@@ -0,0 +1,206 @@
+//! Project configuration files
+//!
+//! A `rustcroissant.toml` in the working directory or one of its parents,
+//! or the file given with `--config`, provides defaults shared by everyone
+//! generating, validating and verifying a project's metadata:
+//!
+//! ```toml
+//! [dataset]
+//! license = "https://creativecommons.org/licenses/by/4.0/"
+//! creator = ["Ada Lovelace", "Alan Turing"]
+//! keywords = ["sensors"]
+//! base-url = "https://data.example.org/v2/"
+//!
+//! [inference]
+//! sample-rows = 5000
+//! full-scan = false
+//! null-values = ["NA", "-"]
+//! number-locale = "de"
+//! enum-max-values = 20
+//! on-bad-row = "warn"
+//! delimiter = "|"
+//!
+//! [validate.rules]
+//! missing-checksum = "error"
+//! recommended-property = "off"
+//!
+//! [auth.hosts."data.example.org"]
+//! bearer = "${EXAMPLE_TOKEN}"
+//! ```
+//!
+//! Options given on the command line take precedence over the file. The
+//! `auth` section holds hosts as in the auth file of `remote::auth`, which
+//! takes precedence for the hosts it matches; it is only used from a file
+//! given with `--config`, as one found in the working directory may come
+//! with a cloned repository.
+
+use crate::croissant::core::{BadRowPolicy, InferenceOptions, NumberLocale, one_or_many};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::parse_delimiter;
+use crate::croissant::validate::{Rule, RuleLevel};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of project configuration files
+pub const CONFIG_FILE_NAME: &str = "rustcroissant.toml";
+
+/// Settings of a project configuration file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Dataset properties of generated metadata
+    pub dataset: DatasetConfig,
+    /// Data type inference settings of `generate`
+    pub inference: InferenceConfig,
+    /// Validation settings
+    pub validate: ValidateConfig,
+    /// Credentials for remote content
+    #[cfg(feature = "remote")]
+    pub auth: crate::croissant::remote::auth::AuthConfig,
+    /// Credentials, unused without the `remote` feature
+    #[cfg(not(feature = "remote"))]
+    auth: Option<toml::Table>,
+}
+
+/// Dataset properties set on generated metadata
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatasetConfig {
+    #[serde(with = "one_or_many")]
+    pub license: Vec<String>,
+    /// Names of the people who created the datasets
+    #[serde(with = "one_or_many")]
+    pub creator: Vec<String>,
+    #[serde(with = "one_or_many")]
+    pub keywords: Vec<String>,
+    /// URL the files are published under, which content URLs of local
+    /// files start with
+    #[serde(rename = "base-url")]
+    pub base_url: Option<String>,
+}
+
+/// Data type inference settings, each replacing the default when set
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct InferenceConfig {
+    pub sample_rows: Option<usize>,
+    pub full_scan: Option<bool>,
+    pub null_values: Option<Vec<String>>,
+    #[serde(deserialize_with = "number_locale")]
+    pub number_locale: Option<NumberLocale>,
+    /// Largest number of distinct values of enumeration columns; setting it
+    /// turns enumeration detection on
+    pub enum_max_values: Option<usize>,
+    #[serde(deserialize_with = "bad_row_policy")]
+    pub on_bad_row: Option<BadRowPolicy>,
+    /// Field delimiter of CSV files, `tab` or a single character
+    #[serde(deserialize_with = "delimiter")]
+    pub delimiter: Option<u8>,
+}
+
+impl InferenceConfig {
+    /// Set the configured settings on inference options
+    pub fn apply(&self, options: &mut InferenceOptions) {
+        if let Some(sample_rows) = self.sample_rows {
+            options.sample_rows = sample_rows;
+        }
+        if let Some(full_scan) = self.full_scan {
+            options.full_scan = full_scan;
+        }
+        if let Some(null_values) = &self.null_values {
+            options.null_values = null_values.clone();
+        }
+        if self.number_locale.is_some() {
+            options.number_locale = self.number_locale;
+        }
+        if self.enum_max_values.is_some() {
+            options.enum_max_values = self.enum_max_values;
+        }
+        if let Some(on_bad_row) = self.on_bad_row {
+            options.on_bad_row = on_bad_row;
+        }
+        if self.delimiter.is_some() {
+            options.delimiter = self.delimiter;
+        }
+    }
+}
+
+/// Validation settings
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValidateConfig {
+    /// Levels the issues of rules are reported at, instead of their own
+    #[serde(deserialize_with = "rule_levels")]
+    pub rules: BTreeMap<Rule, RuleLevel>,
+}
+
+impl ProjectConfig {
+    /// Parse a configuration file
+    pub fn parse(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| Error::invalid_format(e.to_string()))
+    }
+
+    /// Read a configuration file
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|_| Error::file_not_found(path))?;
+        Self::parse(&text).map_err(|e| match e {
+            Error::InvalidFormat { message } => {
+                Error::invalid_format(format!("{}: {message}", path.display()))
+            }
+            e => e,
+        })
+    }
+}
+
+/// The configuration file in `dir` or the closest of its parents, if any
+pub fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+fn number_locale<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<NumberLocale>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|name| NumberLocale::from_name(&name))
+        .transpose()
+        .map_err(de_error)
+}
+
+fn delimiter<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<u8>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|text| parse_delimiter(&text))
+        .transpose()
+        .map_err(de_error)
+}
+
+fn bad_row_policy<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<BadRowPolicy>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|name| BadRowPolicy::from_name(&name))
+        .transpose()
+        .map_err(de_error)
+}
+
+fn rule_levels<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<BTreeMap<Rule, RuleLevel>, D::Error> {
+    BTreeMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(rule, level)| Ok((Rule::from_name(&rule)?, RuleLevel::from_name(&level)?)))
+        .collect::<Result<_>>()
+        .map_err(de_error)
+}
+
+/// Deserialization error of a name that is not recognized
+fn de_error<E: serde::de::Error>(e: Error) -> E {
+    match e {
+        Error::InvalidFormat { message } => E::custom(message),
+        e => E::custom(e),
+    }
+}
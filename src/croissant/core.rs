@@ -1,6 +1,13 @@
-use chrono::DateTime;
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::{
+    DELIMITED_FORMATS, TSV_ENCODING_FORMAT, detect_delimiter, parse_delimiter,
+};
+use chrono::{DateTime, Datelike};
 use serde;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 // ============================================================================
 // Core Croissant Structures
 // ============================================================================
@@ -14,33 +21,271 @@ pub struct Field {
     pub type_: String,
     pub name: String,
     pub description: String,
-    #[serde(rename = "dataType")]
+    /// Type of the values; fields with subfields may leave it empty
+    ///
+    /// Of several types, as in `["sc:Text", "sc:name"]`, the first
+    /// standard data type is kept (see [`data_type`]).
+    #[serde(
+        rename = "dataType",
+        default,
+        deserialize_with = "data_type::deserialize"
+    )]
     pub data_type: String,
-    pub source: FieldSource,
+    #[serde(rename = "unitText", default, skip_serializing_if = "Option::is_none")]
+    pub unit_text: Option<String>,
+    /// Where the values are extracted from; fields of record sets whose
+    /// records are given inline have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<FieldSource>,
+    /// Field of another record set whose values this field's values refer to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<FieldReference>,
+    /// Whether each record holds a list of values
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub repeated: bool,
+    /// Fields making up the values of this one, which are then objects
+    #[serde(rename = "subField", default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_field: Vec<Field>,
+    /// Whether values may be missing; not part of the Croissant
+    /// specification, written in the rustcroissant namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<bool>,
+    /// Values other than blank ones that stand for missing values in the
+    /// source, such as `NA`; in the rustcroissant namespace
+    #[serde(rename = "nullValues", default, skip_serializing_if = "Vec::is_empty")]
+    pub null_values: Vec<String>,
+    /// Statistics of the values over the whole file, recorded with
+    /// `generate --statistics`; in the rustcroissant namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<ValueStatistics>,
+}
+
+/// Statistics of a field's values, as recorded in metadata
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueStatistics {
+    /// Number of values, null ones included
+    pub count: u64,
+    pub null_count: u64,
+    pub distinct_count: u64,
+    /// Whether `distinct_count` is an estimate
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub distinct_estimated: bool,
+    /// Smallest number, date, datetime or time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Value>,
+    /// Largest number, date, datetime or time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Value>,
+    /// Mean of numbers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean: Option<f64>,
+}
+
+impl Field {
+    /// The field's source, which reading its values requires
+    pub fn require_source(&self) -> Result<&FieldSource> {
+        self.source
+            .as_ref()
+            .ok_or_else(|| Error::missing_field(format!("source of field {}", self.name)))
+    }
+}
+
+/// FieldReference points at the field a field's values refer to, as in
+/// `{"field": {"@id": "movies/id"}}`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldReference {
+    pub field: FieldId,
+}
+
+/// FieldId identifies a field by its `@id`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldId {
+    #[serde(rename = "@id")]
+    pub id: String,
 }
 
 /// FieldSource represents the source information for a field
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FieldSource {
     pub extract: Extract,
-    #[serde(rename = "fileObject")]
+    /// File object the values are extracted from; empty when they come
+    /// from a file set
+    #[serde(
+        rename = "fileObject",
+        default,
+        skip_serializing_if = "FileObject::is_empty"
+    )]
     pub file_object: FileObject,
+    /// File set the values are extracted from, one record per file
+    #[serde(rename = "fileSet", default, skip_serializing_if = "Option::is_none")]
+    pub file_set: Option<FileObject>,
+    /// Transforms applied to the extracted value, in order; a single
+    /// transform may be given as an object rather than a list
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub transform: Vec<Transform>,
+}
+
+impl FieldSource {
+    /// `@id` of the file object or file set the values are extracted from
+    pub fn distribution_id(&self) -> &str {
+        self.file_set
+            .as_ref()
+            .unwrap_or(&self.file_object)
+            .id
+            .as_str()
+    }
+}
+
+/// Transform of an extracted value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Transform {
+    /// Regular expression the value must match; its first capture group,
+    /// or else the whole match, is kept
+    Regex(String),
+    /// Regular expression replacement written as `pattern/replacement`
+    Replace(String),
+    /// Separator splitting the value into a list
+    Separator(String),
+    /// JSONPath selecting part of a JSON value
+    JsonPath(String),
+    /// strftime format the value is parsed with, for dates and datetimes
+    Format(String),
+}
+
+/// Serialization of a list that may also be written as its single item
+pub(crate) mod one_or_many {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    pub fn serialize<T: Serialize, S: Serializer>(
+        items: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match items {
+            [item] => item.serialize(serializer),
+            items => items.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<T>, D::Error> {
+        Ok(match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(item) => vec![item],
+            OneOrMany::Many(items) => items,
+        })
+    }
+}
+
+/// Deserialization of a `dataType` that may list several types
+///
+/// Croissant lets a field give the type of its values along with semantic
+/// types, as in `["sc:URL", "wd:Q515"]`. The first of them that is a
+/// standard data type is kept, or else the first in the schema.org or
+/// Croissant vocabulary, or else the first; the others are dropped.
+pub(crate) mod data_type {
+    use super::{DataType, one_or_many};
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        Ok(first(one_or_many::deserialize(deserializer)?))
+    }
+
+    pub fn deserialize_option<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<String>, D::Error> {
+        let types = Option::<Many>::deserialize(deserializer)?;
+        Ok(types.map(|types| first(types.0)))
+    }
+
+    #[derive(Deserialize)]
+    struct Many(#[serde(with = "one_or_many")] Vec<String>);
+
+    fn first(types: Vec<String>) -> String {
+        let standard = |t: &&String| DataType::from_schema_org(t).is_some();
+        let vocabulary = |t: &&String| {
+            [
+                "sc:",
+                "cr:",
+                "https://schema.org/",
+                "http://mlcommons.org/croissant/",
+            ]
+            .iter()
+            .any(|prefix| t.starts_with(prefix))
+        };
+        let chosen = types
+            .iter()
+            .find(standard)
+            .or_else(|| types.iter().find(vocabulary))
+            .or(types.first())
+            .cloned()
+            .unwrap_or_default();
+        if types.len() > 1 {
+            tracing::debug!(?types, kept = %chosen, "keeping one of several data types");
+        }
+        chosen
+    }
 }
 
 /// Extract represents the extraction information for a field source
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Extract {
+    /// Column (or, for JSON Lines, key) the value is read from
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub column: String,
+    /// Property of the file itself, extracted instead of a column
+    #[serde(
+        rename = "fileProperty",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub file_property: Option<FileProperty>,
 }
 
-/// FileObject represents a file object reference
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Properties of a file that a field can be extracted from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FileProperty {
+    /// Path of the file as given by its content URL
+    Fullpath,
+    /// Name of the file, without its directory
+    Filename,
+    /// Whole content of the file, one record per file
+    Content,
+    /// Each line of the file, one record per line
+    Lines,
+    /// Number of each line, from 1, one record per line
+    LineNumbers,
+}
+
+/// FileObject represents a reference to a file object or file set
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct FileObject {
     #[serde(rename = "@id")]
     pub id: String,
 }
 
-/// Distribution represents a file in the Croissant metadata
+impl FileObject {
+    pub fn is_empty(&self) -> bool {
+        self.id.is_empty()
+    }
+}
+
+/// Distribution represents a file (`cr:FileObject`) or a set of files
+/// (`cr:FileSet`) in the Croissant metadata
+///
+/// File sets have no content URL, size or digest of their own; the files
+/// they hold are those matching one of their `includes` glob patterns and
+/// none of their `excludes`, in the distributions they are contained in or,
+/// when there are none, below the dataset's base location.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Distribution {
     #[serde(rename = "@id")]
@@ -48,13 +293,79 @@ pub struct Distribution {
     #[serde(rename = "@type")]
     pub type_: String,
     pub name: String,
-    #[serde(rename = "contentSize")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(
+        rename = "contentSize",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
     pub content_size: String,
-    #[serde(rename = "contentUrl")]
+    #[serde(
+        rename = "contentUrl",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
     pub content_url: String,
     #[serde(rename = "encodingFormat")]
     pub encoding_format: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub sha256: String,
+    /// BLAKE3 digest; not part of the Croissant specification, written in
+    /// the rustcroissant namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
+    /// Field delimiter of delimited text, `\t` for a tab; not part of the
+    /// Croissant specification, written in the rustcroissant namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+    /// Distributions, such as an archive, the file or files are part of
+    #[serde(
+        rename = "containedIn",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub contained_in: Vec<FileObject>,
+    /// Glob patterns of the files of a file set
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub includes: Vec<String>,
+    /// Glob patterns of files left out of a file set
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub excludes: Vec<String>,
+}
+
+impl Distribution {
+    pub fn is_file_set(&self) -> bool {
+        self.type_ == "cr:FileSet"
+    }
+
+    /// Field delimiter of this delimited text file, read from `path`: the
+    /// recorded delimiter, or else a tab for TSV and otherwise the one
+    /// found in its header line
+    pub fn field_delimiter(&self, path: &Path) -> Result<u8> {
+        match &self.delimiter {
+            Some(delimiter) => parse_delimiter(delimiter).map_err(|_| {
+                Error::invalid_format(format!(
+                    "Distribution {} has an invalid delimiter {delimiter:?}",
+                    self.id
+                ))
+            }),
+            None if self.encoding_format == TSV_ENCODING_FORMAT => Ok(b'\t'),
+            None => detect_delimiter(path),
+        }
+    }
+
+    /// Content size in bytes, when given as an exact byte count such as
+    /// `892 B`; rounded sizes such as `1.5 MB` are not exact
+    pub fn content_size_bytes(&self) -> Option<u64> {
+        self.content_size
+            .trim()
+            .trim_end_matches('B')
+            .trim_end()
+            .parse()
+            .ok()
+    }
 }
 
 /// RecordSet represents a record set in the Croissant metadata
@@ -66,7 +377,23 @@ pub struct RecordSet {
     pub type_: String,
     pub name: String,
     pub description: String,
+    /// Type of the records, such as `cr:Split` for the splits of a dataset
+    #[serde(
+        rename = "dataType",
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "data_type::deserialize_option"
+    )]
+    pub data_type: Option<String>,
     pub field: Vec<Field>,
+    /// Fields whose values identify each record, such as `{"@id": "genders/label"}`;
+    /// several make up a composite key
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub key: Vec<FieldId>,
+    /// Records given inline rather than extracted from files, as objects
+    /// keyed by field `@id`; used for enumerations such as splits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<serde_json::Map<String, Value>>>,
 }
 
 /// Context represents the JSON-LD context in the Croissant metadata
@@ -94,6 +421,90 @@ pub struct Context {
     pub file_property: String,
     pub sc: String,
     pub source: String,
+    /// Terms of file sets, only written when the dataset has any
+    #[serde(rename = "fileSet", default, skip_serializing_if = "Option::is_none")]
+    pub file_set: Option<String>,
+    #[serde(
+        rename = "containedIn",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub contained_in: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub includes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excludes: Option<String>,
+    /// Prefix of the Responsible AI vocabulary, only written when the
+    /// dataset has RAI properties
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rai: Option<String>,
+    /// Terms of the standard Croissant 1.0 context that contexts written
+    /// by other tools, or by earlier versions, may not define
+    #[serde(rename = "recordSet", default, skip_serializing_if = "Option::is_none")]
+    pub record_set: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+    #[serde(rename = "jsonPath", default, skip_serializing_if = "Option::is_none")]
+    pub json_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(rename = "subField", default, skip_serializing_if = "Option::is_none")]
+    pub sub_field: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeated: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub examples: Option<DataContext>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub md5: Option<String>,
+    #[serde(
+        rename = "isLiveDataset",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub is_live_dataset: Option<String>,
+    /// Prefix of rustcroissant's own vocabulary, and the terms of the
+    /// properties it adds to fields and distributions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<String>,
+    #[serde(
+        rename = "nullValues",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub null_values: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<DataContext>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+}
+
+impl Context {
+    /// Define the terms file sets are described with
+    pub fn add_file_set_terms(&mut self) {
+        self.file_set = Some("cr:fileSet".to_string());
+        self.contained_in = Some("cr:containedIn".to_string());
+        self.includes = Some("cr:includes".to_string());
+        self.excludes = Some("cr:excludes".to_string());
+    }
+
+    /// Define the prefix Responsible AI properties are written with
+    pub fn add_rai_terms(&mut self) {
+        self.rai = Some(RAI_NAMESPACE.to_string());
+    }
 }
 
 /// DataContext represents the data field in the context
@@ -122,15 +533,457 @@ pub struct Metadata {
     #[serde(rename = "@type")]
     pub type_: String,
     pub name: String,
+    #[serde(default)]
     pub description: String,
-    #[serde(rename = "conformsTo")]
+    #[serde(rename = "conformsTo", default)]
     pub conforms_to: String,
-    #[serde(rename = "datePublished")]
+    #[serde(
+        rename = "datePublished",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
     pub date_published: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub version: String,
+    /// Licenses of the dataset, such as SPDX license URLs
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub license: Vec<String>,
+    /// Web page of the dataset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// People and organizations that created the dataset
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub creator: Vec<Creator>,
+    /// Keywords describing the dataset
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub keywords: Vec<String>,
+    /// Persistent identifier of the dataset, such as a DOI URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// Citation of the dataset, such as a BibTeX entry
+    #[serde(rename = "citeAs", default, skip_serializing_if = "Option::is_none")]
+    pub cite_as: Option<String>,
     pub distribution: Vec<Distribution>,
     #[serde(rename = "recordSet")]
     pub record_set: Vec<RecordSet>,
+    /// Responsible AI properties
+    #[serde(flatten)]
+    pub rai: Rai,
+}
+
+/// Namespace of the Croissant Responsible AI (RAI) 1.0 vocabulary
+pub const RAI_NAMESPACE: &str = "http://mlcommons.org/croissant/RAI/";
+
+/// Namespace of the properties rustcroissant writes that are not part of
+/// the Croissant specification, such as `nullable` and `blake3`
+pub const RUSTCROISSANT_NAMESPACE: &str = "https://github.com/beyondcivic/rustcroissant/ns#";
+
+/// Dataset-level properties of the Croissant Responsible AI 1.0 extension,
+/// written with the `rai:` prefix
+///
+/// Every property is optional; those the specification allows several
+/// values of are lists.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Rai {
+    /// How the data was collected
+    #[serde(
+        rename = "rai:dataCollection",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_collection: Option<String>,
+    /// Kinds of collection, such as `Web Scraping` or `Surveys`
+    #[serde(
+        rename = "rai:dataCollectionType",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub data_collection_type: Vec<String>,
+    /// Data missing from the collection, and why
+    #[serde(
+        rename = "rai:dataCollectionMissingData",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_collection_missing_data: Option<String>,
+    /// Description of the raw data before any processing
+    #[serde(
+        rename = "rai:dataCollectionRawData",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_collection_raw_data: Option<String>,
+    /// Start and end of the collection, as ISO 8601 dates or datetimes
+    #[serde(
+        rename = "rai:dataCollectionTimeframe",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub data_collection_timeframe: Vec<String>,
+    /// How missing values were imputed
+    #[serde(
+        rename = "rai:dataImputationProtocol",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_imputation_protocol: Option<String>,
+    /// Preprocessing steps applied to the data
+    #[serde(
+        rename = "rai:dataPreprocessingProtocol",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub data_preprocessing_protocol: Vec<String>,
+    /// How the data was manipulated, such as cleaned or filtered
+    #[serde(
+        rename = "rai:dataManipulationProtocol",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_manipulation_protocol: Option<String>,
+    /// How the data was annotated
+    #[serde(
+        rename = "rai:dataAnnotationProtocol",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_annotation_protocol: Option<String>,
+    /// Platforms the annotations were made on
+    #[serde(
+        rename = "rai:dataAnnotationPlatform",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub data_annotation_platform: Vec<String>,
+    /// Analyses of the annotations, such as of annotator agreement
+    #[serde(
+        rename = "rai:dataAnnotationAnalysis",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub data_annotation_analysis: Vec<String>,
+    /// Number of annotations per item
+    #[serde(
+        rename = "rai:annotationsPerItem",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotations_per_item: Option<String>,
+    /// Demographics of the annotators
+    #[serde(
+        rename = "rai:annotatorDemographics",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub annotator_demographics: Vec<String>,
+    /// Tools that annotated the data automatically
+    #[serde(
+        rename = "rai:machineAnnotationTools",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub machine_annotation_tools: Vec<String>,
+    /// Known biases of the data
+    #[serde(
+        rename = "rai:dataBiases",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub data_biases: Vec<String>,
+    /// Known limitations of the data
+    #[serde(
+        rename = "rai:dataLimitations",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub data_limitations: Vec<String>,
+    /// Uses the data is or is not suited for
+    #[serde(
+        rename = "rai:dataUseCases",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub data_use_cases: Vec<String>,
+    /// Social impact of the data
+    #[serde(
+        rename = "rai:dataSocialImpact",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_social_impact: Option<String>,
+    /// Personal or sensitive information in the data, such as gender,
+    /// health or location
+    #[serde(
+        rename = "rai:personalSensitiveInformation",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "one_or_many"
+    )]
+    pub personal_sensitive_information: Vec<String>,
+    /// How the data is maintained and new versions are released
+    #[serde(
+        rename = "rai:dataReleaseMaintenancePlan",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data_release_maintenance_plan: Option<String>,
+}
+
+impl Rai {
+    /// Values of every property, by name without prefix, in declaration
+    /// order
+    pub fn properties(&self) -> Vec<(&'static str, Vec<&str>)> {
+        fn one(value: &Option<String>) -> Vec<&str> {
+            value.iter().map(String::as_str).collect()
+        }
+        fn many(values: &[String]) -> Vec<&str> {
+            values.iter().map(String::as_str).collect()
+        }
+        vec![
+            ("dataCollection", one(&self.data_collection)),
+            ("dataCollectionType", many(&self.data_collection_type)),
+            (
+                "dataCollectionMissingData",
+                one(&self.data_collection_missing_data),
+            ),
+            ("dataCollectionRawData", one(&self.data_collection_raw_data)),
+            (
+                "dataCollectionTimeframe",
+                many(&self.data_collection_timeframe),
+            ),
+            (
+                "dataImputationProtocol",
+                one(&self.data_imputation_protocol),
+            ),
+            (
+                "dataPreprocessingProtocol",
+                many(&self.data_preprocessing_protocol),
+            ),
+            (
+                "dataManipulationProtocol",
+                one(&self.data_manipulation_protocol),
+            ),
+            (
+                "dataAnnotationProtocol",
+                one(&self.data_annotation_protocol),
+            ),
+            (
+                "dataAnnotationPlatform",
+                many(&self.data_annotation_platform),
+            ),
+            (
+                "dataAnnotationAnalysis",
+                many(&self.data_annotation_analysis),
+            ),
+            ("annotationsPerItem", one(&self.annotations_per_item)),
+            ("annotatorDemographics", many(&self.annotator_demographics)),
+            (
+                "machineAnnotationTools",
+                many(&self.machine_annotation_tools),
+            ),
+            ("dataBiases", many(&self.data_biases)),
+            ("dataLimitations", many(&self.data_limitations)),
+            ("dataUseCases", many(&self.data_use_cases)),
+            ("dataSocialImpact", one(&self.data_social_impact)),
+            (
+                "personalSensitiveInformation",
+                many(&self.personal_sensitive_information),
+            ),
+            (
+                "dataReleaseMaintenancePlan",
+                one(&self.data_release_maintenance_plan),
+            ),
+        ]
+    }
+
+    /// Whether no property is set
+    pub fn is_empty(&self) -> bool {
+        self == &Rai::default()
+    }
+}
+
+/// Person or organization that created a dataset
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Creator {
+    /// `sc:Person` or `sc:Organization`
+    #[serde(rename = "@type", default = "Creator::person_type")]
+    pub type_: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl Creator {
+    /// Person with the given name
+    pub fn person(name: impl Into<String>) -> Self {
+        Self {
+            type_: Self::person_type(),
+            name: name.into(),
+            url: None,
+        }
+    }
+
+    fn person_type() -> String {
+        "sc:Person".to_string()
+    }
+}
+
+/// A person, by name
+impl From<&str> for Creator {
+    fn from(name: &str) -> Self {
+        Creator::person(name)
+    }
+}
+
+impl Metadata {
+    /// Find a record set by name or `@id`
+    pub fn find_record_set(&self, name_or_id: &str) -> Option<&RecordSet> {
+        self.record_set
+            .iter()
+            .find(|rs| rs.name == name_or_id || rs.id == name_or_id)
+    }
+
+    /// Find a field of a record set by `@id`, with its record set
+    pub fn find_field(&self, id: &str) -> Option<(&RecordSet, &Field)> {
+        self.record_set.iter().find_map(|record_set| {
+            record_set
+                .field
+                .iter()
+                .find(|field| field.id == id)
+                .map(|field| (record_set, field))
+        })
+    }
+
+    /// Locate the local CSV file a record set's fields are extracted from
+    ///
+    /// Relative content URLs are resolved against `base_dir`. Record sets
+    /// drawing from several files, or from remote distributions or others
+    /// than CSV and TSV files, are rejected.
+    pub fn record_set_csv_path(&self, record_set: &RecordSet, base_dir: &Path) -> Result<PathBuf> {
+        self.record_set_csv_file(record_set, base_dir)
+            .map(|(path, _)| path)
+    }
+
+    /// Like [`record_set_csv_path`](Self::record_set_csv_path), with the
+    /// distribution the file is described by
+    pub fn record_set_csv_file(
+        &self,
+        record_set: &RecordSet,
+        base_dir: &Path,
+    ) -> Result<(PathBuf, &Distribution)> {
+        let file_ids = record_set
+            .field
+            .iter()
+            .map(|f| Ok(f.require_source()?.distribution_id()))
+            .collect::<Result<Vec<_>>>()?;
+        let mut file_ids = file_ids.into_iter();
+        let file_id = file_ids.next().ok_or_else(|| {
+            Error::missing_field(format!("field in recordSet {}", record_set.name))
+        })?;
+        if file_ids.any(|id| id != file_id) {
+            return Err(Error::invalid_format(format!(
+                "RecordSet {} draws from several files, which is not supported",
+                record_set.name
+            )));
+        }
+
+        let distribution = self
+            .distribution
+            .iter()
+            .find(|d| d.id == file_id)
+            .ok_or_else(|| Error::missing_field(format!("distribution {file_id}")))?;
+        if !DELIMITED_FORMATS.contains(&distribution.encoding_format.as_str()) {
+            return Err(Error::invalid_format(format!(
+                "Distribution {file_id} has encoding format {}, only text/csv and text/tab-separated-values can be read",
+                distribution.encoding_format
+            )));
+        }
+        let url = &distribution.content_url;
+        if url.contains("://") {
+            return Err(Error::invalid_format(format!(
+                "Distribution {file_id} is not a local file: {url}"
+            )));
+        }
+
+        Ok((base_dir.join(url), distribution))
+    }
+}
+
+// ============================================================================
+// Semantic Comparison
+// ============================================================================
+
+/// Properties that change on every regeneration without changing meaning
+const VOLATILE_PROPERTIES: &[&str] = &["datePublished", "dateModified"];
+
+impl Metadata {
+    /// Check whether two metadata documents describe the same dataset,
+    /// ignoring volatile properties such as datePublished/dateModified and
+    /// the ordering of object keys
+    pub fn equivalent(&self, other: &Metadata) -> bool {
+        self.differences(other).is_empty()
+    }
+
+    /// List the JSON paths at which two metadata documents meaningfully
+    /// differ, ignoring the same volatile properties as [`Metadata::equivalent`]
+    pub fn differences(&self, other: &Metadata) -> Vec<String> {
+        let left = comparable_value(self);
+        let right = comparable_value(other);
+
+        let mut paths = Vec::new();
+        collect_differences("$", &left, &right, &mut paths);
+        paths
+    }
+}
+
+fn comparable_value(metadata: &Metadata) -> Value {
+    let mut value = serde_json::to_value(metadata).unwrap_or(Value::Null);
+    if let Value::Object(ref mut map) = value {
+        for key in VOLATILE_PROPERTIES {
+            map.remove(*key);
+        }
+    }
+    value
+}
+
+fn collect_differences(path: &str, left: &Value, right: &Value, paths: &mut Vec<String>) {
+    match (left, right) {
+        (Value::Object(l), Value::Object(r)) => {
+            let keys: BTreeSet<&String> = l.keys().chain(r.keys()).collect();
+            for key in keys {
+                let child = format!("{path}.{key}");
+                match (l.get(key), r.get(key)) {
+                    (Some(lv), Some(rv)) => collect_differences(&child, lv, rv, paths),
+                    _ => paths.push(child),
+                }
+            }
+        }
+        (Value::Array(l), Value::Array(r)) => {
+            for i in 0..l.len().max(r.len()) {
+                let child = format!("{path}[{i}]");
+                match (l.get(i), r.get(i)) {
+                    (Some(lv), Some(rv)) => collect_differences(&child, lv, rv, paths),
+                    _ => paths.push(child),
+                }
+            }
+        }
+        _ => {
+            if left != right {
+                paths.push(path.to_string());
+            }
+        }
+    }
 }
 
 // ============================================================================
@@ -144,7 +997,12 @@ pub enum DataType {
     Float,
     Text,
     Date,
+    DateTime,
+    /// Time of day, without a date
+    Time,
     Boolean,
+    /// Absolute URL; values are read as text
+    Url,
 }
 
 impl DataType {
@@ -155,9 +1013,477 @@ impl DataType {
             DataType::Float => "sc:Float",
             DataType::Text => "sc:Text",
             DataType::Date => "sc:Date",
+            DataType::DateTime => "sc:DateTime",
+            DataType::Time => "sc:Time",
             DataType::Boolean => "sc:Boolean",
+            DataType::Url => "sc:URL",
+        }
+    }
+
+    /// Parse a schema.org data type, accepting the `sc:` prefix or the full
+    /// `https://schema.org/` IRI
+    pub fn from_schema_org(value: &str) -> Option<DataType> {
+        let name = value
+            .strip_prefix("sc:")
+            .or_else(|| value.strip_prefix("https://schema.org/"))
+            .or_else(|| value.strip_prefix("http://schema.org/"))
+            .unwrap_or(value);
+        match name {
+            "Integer" => Some(DataType::Integer),
+            "Float" | "Number" => Some(DataType::Float),
+            "Text" => Some(DataType::Text),
+            "Date" => Some(DataType::Date),
+            "DateTime" => Some(DataType::DateTime),
+            "Time" => Some(DataType::Time),
+            "Boolean" => Some(DataType::Boolean),
+            "URL" => Some(DataType::Url),
+            _ => None,
+        }
+    }
+
+    /// Combine two observations of the same column into the narrowest type
+    /// consistent with both
+    ///
+    /// Types are promoted along Integer → Float → Text and
+    /// Date → DateTime → Text; any other mix (e.g. Boolean and Integer)
+    /// falls back to Text.
+    pub fn merge(&self, other: &DataType) -> DataType {
+        match (self, other) {
+            (a, b) if a == b => a.clone(),
+            (DataType::Integer, DataType::Float) | (DataType::Float, DataType::Integer) => {
+                DataType::Float
+            }
+            (DataType::Date, DataType::DateTime) | (DataType::DateTime, DataType::Date) => {
+                DataType::DateTime
+            }
+            _ => DataType::Text,
+        }
+    }
+}
+
+/// Default number of data rows sampled for type inference
+pub const DEFAULT_SAMPLE_ROWS: usize = 1000;
+
+/// Kind of text a column holds, recognized from its values during inference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticType {
+    /// Absolute `http`, `https` or `ftp` URLs, typed as `sc:URL`
+    Url,
+    /// Email addresses
+    Email,
+    /// UUIDs in their hyphenated form, usually identifiers
+    Uuid,
+}
+
+impl SemanticType {
+    /// All semantic types, in the order they are tried
+    pub const ALL: &'static [SemanticType] =
+        &[SemanticType::Url, SemanticType::Email, SemanticType::Uuid];
+
+    /// Whether a value is of this kind
+    pub fn matches(&self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            SemanticType::Url => is_url(value),
+            SemanticType::Email => is_email(value),
+            SemanticType::Uuid => is_uuid(value),
+        }
+    }
+
+    /// Data type of the values
+    pub fn data_type(&self) -> DataType {
+        match self {
+            SemanticType::Url => DataType::Url,
+            SemanticType::Email | SemanticType::Uuid => DataType::Text,
+        }
+    }
+
+    /// Description of a field of this kind read from the given column
+    pub fn describe(&self, column: &str) -> String {
+        match self {
+            SemanticType::Url => format!("URL given in {column}"),
+            SemanticType::Email => format!("Email address given in {column}"),
+            SemanticType::Uuid => format!("UUID identifier given in {column}"),
+        }
+    }
+}
+
+fn is_url(value: &str) -> bool {
+    let Some((scheme, rest)) = value.split_once("://") else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    ["http", "https", "ftp"]
+        .iter()
+        .any(|known| scheme.eq_ignore_ascii_case(known))
+        && !host.is_empty()
+        && !value.chars().any(char::is_whitespace)
+}
+
+fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+    let labels = domain.split('.').collect::<Vec<_>>();
+    !local.is_empty()
+        && !local.contains(|c: char| c.is_whitespace() || c == '@')
+        && labels.len() > 1
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+fn is_uuid(value: &str) -> bool {
+    let groups = value.split('-').collect::<Vec<_>>();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Currency symbols and codes recognized before or after numbers, with
+/// their ISO 4217 codes, longest first; `$` is taken for US dollars
+pub const CURRENCY_SYMBOLS: &[(&str, &str)] = &[
+    ("US$", "USD"),
+    ("USD", "USD"),
+    ("EUR", "EUR"),
+    ("GBP", "GBP"),
+    ("JPY", "JPY"),
+    ("CHF", "CHF"),
+    ("$", "USD"),
+    ("€", "EUR"),
+    ("£", "GBP"),
+    ("¥", "JPY"),
+    ("₹", "INR"),
+];
+
+/// Symbol written with a number, telling what it measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberAffix {
+    /// Amount of money, with the symbol written and its ISO 4217 code
+    Currency {
+        symbol: &'static str,
+        code: &'static str,
+    },
+    /// Percentage, written with a trailing `%`
+    Percent,
+}
+
+impl NumberAffix {
+    /// Split a value such as `$1,299.00`, `-€45` or `12 %` into its affix
+    /// and the number, still as written; the number is not checked
+    pub fn split(value: &str) -> Option<(NumberAffix, String)> {
+        let trimmed = value.trim();
+        let (sign, unsigned) = match trimmed.strip_prefix(['-', '+']) {
+            Some(rest) => (&trimmed[..1], rest),
+            None => ("", trimmed),
+        };
+        let (affix, number) = if let Some(number) = unsigned.strip_suffix('%') {
+            (NumberAffix::Percent, number)
+        } else {
+            CURRENCY_SYMBOLS.iter().find_map(|&(symbol, code)| {
+                let number = unsigned
+                    .strip_prefix(symbol)
+                    .or_else(|| unsigned.strip_suffix(symbol))?;
+                Some((NumberAffix::Currency { symbol, code }, number))
+            })?
+        };
+        let number = number.trim();
+        (!number.is_empty()).then(|| (affix, format!("{sign}{number}")))
+    }
+
+    /// Unit recorded for numbers with this affix: the currency code or `%`
+    pub fn unit(&self) -> &'static str {
+        match self {
+            NumberAffix::Currency { code, .. } => code,
+            NumberAffix::Percent => "%",
+        }
+    }
+
+    /// Transform removing the affix when records are read
+    pub fn transform(&self) -> Transform {
+        let symbol = match self {
+            NumberAffix::Currency { symbol, .. } => regex::escape(symbol),
+            NumberAffix::Percent => "%".to_string(),
+        };
+        Transform::Replace(format!("{symbol}/"))
+    }
+}
+
+/// What to do with CSV rows that do not have as many fields as the header,
+/// such as ragged rows or rows broken by unbalanced quotes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BadRowPolicy {
+    /// Fail on the first such row
+    #[default]
+    Error,
+    /// Skip such rows, logging a warning for each
+    Warn,
+    /// Skip such rows silently
+    Skip,
+}
+
+impl BadRowPolicy {
+    /// All policies, in the order they are listed to users
+    pub const ALL: &'static [BadRowPolicy] =
+        &[BadRowPolicy::Error, BadRowPolicy::Warn, BadRowPolicy::Skip];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            BadRowPolicy::Error => "error",
+            BadRowPolicy::Warn => "warn",
+            BadRowPolicy::Skip => "skip",
+        }
+    }
+
+    /// Parse a command-line policy name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|policy| policy.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown bad row policy: {name}")))
+    }
+}
+
+/// Default largest number of distinct values of an enumeration column
+pub const DEFAULT_ENUM_MAX_VALUES: usize = 20;
+
+/// Conventions for writing numbers with digit grouping or a decimal comma
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberLocale {
+    /// `1,234.56`
+    En,
+    /// `1.234,56`
+    De,
+    /// `1 234,56`, grouped with spaces or no-break spaces
+    Fr,
+}
+
+impl NumberLocale {
+    /// All locales, in the order they are tried when detecting one
+    pub const ALL: &'static [NumberLocale] =
+        &[NumberLocale::En, NumberLocale::De, NumberLocale::Fr];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            NumberLocale::En => "en",
+            NumberLocale::De => "de",
+            NumberLocale::Fr => "fr",
+        }
+    }
+
+    /// Parse a command-line locale name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|locale| locale.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown number locale: {name}")))
+    }
+
+    fn is_group_separator(&self, c: char) -> bool {
+        match self {
+            NumberLocale::En => c == ',',
+            NumberLocale::De => c == '.',
+            NumberLocale::Fr => c.is_whitespace(),
         }
     }
+
+    /// Character digits are grouped with, as written in CSVW number formats;
+    /// French numbers may also be grouped with other spaces
+    pub fn group_char(&self) -> char {
+        match self {
+            NumberLocale::En => ',',
+            NumberLocale::De => '.',
+            NumberLocale::Fr => ' ',
+        }
+    }
+
+    /// Character the fraction follows
+    pub fn decimal_separator(&self) -> char {
+        match self {
+            NumberLocale::En => '.',
+            NumberLocale::De | NumberLocale::Fr => ',',
+        }
+    }
+
+    /// Rewrite a number written in this locale as a plain one, such as
+    /// `1234.56`; digit groups must be of three digits
+    pub fn normalize(&self, value: &str) -> Option<String> {
+        let trimmed = value.trim();
+        let (sign, digits) = match trimmed.strip_prefix(['-', '+']) {
+            Some(rest) => (&trimmed[..1], rest),
+            None => ("", trimmed),
+        };
+        let (integer, fraction) = match digits.split_once(self.decimal_separator()) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits, None),
+        };
+
+        let groups = integer
+            .split(|c| self.is_group_separator(c))
+            .collect::<Vec<_>>();
+        let grouped = groups.len() > 1
+            && (1..=3).contains(&groups[0].len())
+            && groups[1..].iter().all(|group| group.len() == 3);
+        if !(grouped || groups.len() == 1 && !integer.is_empty())
+            || !groups
+                .iter()
+                .all(|group| group.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return None;
+        }
+        let mut normalized = format!("{sign}{}", groups.concat());
+        if let Some(fraction) = fraction {
+            if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            normalized.push('.');
+            normalized.push_str(fraction);
+        }
+        Some(normalized)
+    }
+
+    /// Transforms turning numbers of this locale into plain ones when
+    /// records are read
+    pub fn transforms(&self) -> Vec<Transform> {
+        let group = match self {
+            NumberLocale::En => ",",
+            NumberLocale::De => "\\.",
+            NumberLocale::Fr => "\\s",
+        };
+        let mut transforms = vec![Transform::Replace(format!("{group}/"))];
+        if self.decimal_separator() == ',' {
+            transforms.push(Transform::Replace(",/.".to_string()));
+        }
+        transforms
+    }
+}
+
+/// Values taken as missing during type inference by default, besides blank
+/// ones
+pub const DEFAULT_NULL_VALUES: &[&str] = &["NA", "N/A", "null", "-"];
+
+/// Options controlling data type inference
+#[derive(Debug, Clone, PartialEq)]
+pub struct InferenceOptions {
+    /// Maximum number of data rows to sample per file
+    pub sample_rows: usize,
+    /// Read every data row instead of `sample_rows`, so that no value
+    /// further down a file can contradict the inferred types; remote files
+    /// are then held in memory whole
+    pub full_scan: bool,
+    /// Values taken as missing besides blank ones, compared with values
+    /// trimmed of surrounding whitespace
+    pub null_values: Vec<String>,
+    /// Locale of numbers with digit grouping or a decimal comma; `None`
+    /// detects it, preferring plain numbers, then the locales in the order
+    /// of [`NumberLocale::ALL`]
+    pub number_locale: Option<NumberLocale>,
+    /// Largest number of distinct values of a Text column taken as an
+    /// enumeration, whose values then become a record set of their own;
+    /// `None` detects no enumerations
+    pub enum_max_values: Option<usize>,
+    /// What to do with sampled rows that do not have as many fields as the
+    /// header
+    pub on_bad_row: BadRowPolicy,
+    /// Field delimiter of CSV files; `None` detects it with
+    /// [`detect_delimiter`](crate::croissant::utils::detect_delimiter)
+    pub delimiter: Option<u8>,
+}
+
+impl Default for InferenceOptions {
+    fn default() -> Self {
+        Self {
+            sample_rows: DEFAULT_SAMPLE_ROWS,
+            full_scan: false,
+            null_values: DEFAULT_NULL_VALUES.iter().map(|v| v.to_string()).collect(),
+            number_locale: None,
+            enum_max_values: None,
+            on_bad_row: BadRowPolicy::Error,
+            delimiter: None,
+        }
+    }
+}
+
+impl InferenceOptions {
+    /// Number of data rows read per file: `sample_rows`, or all of them on
+    /// a full scan
+    pub fn row_limit(&self) -> usize {
+        if self.full_scan {
+            usize::MAX
+        } else {
+            self.sample_rows
+        }
+    }
+
+    /// Check whether a raw value is blank or one of the null values
+    pub fn is_null(&self, value: &str) -> bool {
+        is_null_value(value) || self.null_values.iter().any(|null| null == value.trim())
+    }
+}
+
+/// Check whether a raw value represents a missing value
+pub fn is_null_value(value: &str) -> bool {
+    value.trim().is_empty()
+}
+
+/// Non-ISO 8601 date and time formats recognized during type inference, as
+/// strftime patterns with the type of their values
+///
+/// When a column's values fit several, as `01/02/2024` does, the first one
+/// is taken: months come before days.
+pub const TEMPORAL_FORMATS: &[(&str, DataType)] = &[
+    ("%m/%d/%Y", DataType::Date),
+    ("%d/%m/%Y", DataType::Date),
+    ("%d.%m.%Y", DataType::Date),
+    ("%Y/%m/%d", DataType::Date),
+    ("%d-%m-%Y", DataType::Date),
+    ("%m/%d/%Y %H:%M", DataType::DateTime),
+    ("%m/%d/%Y %H:%M:%S", DataType::DateTime),
+    ("%m/%d/%Y %I:%M %p", DataType::DateTime),
+    ("%m/%d/%Y %I:%M:%S %p", DataType::DateTime),
+    ("%d/%m/%Y %H:%M", DataType::DateTime),
+    ("%d/%m/%Y %H:%M:%S", DataType::DateTime),
+    ("%d/%m/%Y %I:%M %p", DataType::DateTime),
+    ("%d/%m/%Y %I:%M:%S %p", DataType::DateTime),
+    ("%d.%m.%Y %H:%M", DataType::DateTime),
+    ("%d.%m.%Y %H:%M:%S", DataType::DateTime),
+    ("%d.%m.%Y %I:%M %p", DataType::DateTime),
+    ("%d.%m.%Y %I:%M:%S %p", DataType::DateTime),
+    ("%Y/%m/%d %H:%M", DataType::DateTime),
+    ("%Y/%m/%d %H:%M:%S", DataType::DateTime),
+    ("%Y/%m/%d %I:%M %p", DataType::DateTime),
+    ("%Y/%m/%d %I:%M:%S %p", DataType::DateTime),
+    ("%d-%m-%Y %H:%M", DataType::DateTime),
+    ("%d-%m-%Y %H:%M:%S", DataType::DateTime),
+    ("%d-%m-%Y %I:%M %p", DataType::DateTime),
+    ("%d-%m-%Y %I:%M:%S %p", DataType::DateTime),
+    ("%Y-%m-%d %I:%M %p", DataType::DateTime),
+    ("%Y-%m-%d %I:%M:%S %p", DataType::DateTime),
+    ("%I:%M %p", DataType::Time),
+    ("%I:%M:%S %p", DataType::Time),
+];
+
+/// Check whether a value is a date, datetime or time of `data_type` in a
+/// strftime format; years must have four digits
+pub fn matches_temporal_format(value: &str, format: &str, data_type: &DataType) -> bool {
+    let value = value.trim();
+    match data_type {
+        DataType::Date => {
+            chrono::NaiveDate::parse_from_str(value, format).is_ok_and(|date| date.year() >= 1000)
+        }
+        DataType::DateTime => chrono::NaiveDateTime::parse_from_str(value, format)
+            .is_ok_and(|datetime| datetime.year() >= 1000),
+        DataType::Time => chrono::NaiveTime::parse_from_str(value, format).is_ok(),
+        _ => false,
+    }
 }
 
 /// Infer the data type from a value string
@@ -184,9 +1510,19 @@ pub fn infer_data_type(value: &str) -> DataType {
         return DataType::Date;
     }
 
-    // Try to parse as ISO 8601 datetime
-    if DateTime::parse_from_rfc3339(trimmed).is_ok() {
-        return DataType::Date;
+    // Try to parse as ISO 8601 datetime, with or without a timezone
+    if DateTime::parse_from_rfc3339(trimmed).is_ok()
+        || chrono::NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+        || chrono::NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S%.f").is_ok()
+    {
+        return DataType::DateTime;
+    }
+
+    // Try to parse as ISO 8601 time of day (HH:MM or HH:MM:SS)
+    if chrono::NaiveTime::parse_from_str(trimmed, "%H:%M:%S%.f").is_ok()
+        || chrono::NaiveTime::parse_from_str(trimmed, "%H:%M").is_ok()
+    {
+        return DataType::Time;
     }
 
     // Default to Text
@@ -198,7 +1534,7 @@ pub fn infer_data_type(value: &str) -> DataType {
 // ============================================================================
 
 /// Create the default context for Croissant metadata
-pub fn create_default_context() -> Context {
+pub(crate) fn create_default_context() -> Context {
     Context {
         language: "en".to_string(),
         vocab: "https://schema.org/".to_string(),
@@ -221,5 +1557,36 @@ pub fn create_default_context() -> Context {
         file_property: "cr:fileProperty".to_string(),
         sc: "https://schema.org/".to_string(),
         source: "cr:source".to_string(),
+        file_set: None,
+        contained_in: None,
+        includes: None,
+        excludes: None,
+        rai: None,
+        record_set: Some("cr:recordSet".to_string()),
+        transform: Some("cr:transform".to_string()),
+        format: Some("cr:format".to_string()),
+        replace: Some("cr:replace".to_string()),
+        regex: Some("cr:regex".to_string()),
+        separator: Some("cr:separator".to_string()),
+        json_path: Some("cr:jsonPath".to_string()),
+        references: Some("cr:references".to_string()),
+        key: Some("cr:key".to_string()),
+        sub_field: Some("cr:subField".to_string()),
+        repeated: Some("cr:repeated".to_string()),
+        examples: Some(DataContext {
+            id: "cr:examples".to_string(),
+            type_: "@json".to_string(),
+        }),
+        md5: Some("cr:md5".to_string()),
+        is_live_dataset: Some("cr:isLiveDataset".to_string()),
+        rc: Some(RUSTCROISSANT_NAMESPACE.to_string()),
+        nullable: Some("rc:nullable".to_string()),
+        null_values: Some("rc:nullValues".to_string()),
+        statistics: Some(DataContext {
+            id: "rc:statistics".to_string(),
+            type_: "@json".to_string(),
+        }),
+        blake3: Some("rc:blake3".to_string()),
+        delimiter: Some("rc:delimiter".to_string()),
     }
 }
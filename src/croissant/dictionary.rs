@@ -0,0 +1,189 @@
+//! Data dictionary support for field descriptions
+//!
+//! A data dictionary maps CSV column names to human-written descriptions,
+//! units and semantic types. It is consulted during generation so fields get
+//! real descriptions instead of the generated placeholder.
+
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::get_file_extension;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+/// Descriptive information about a single column
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldDescription {
+    /// Human-readable description of the column
+    pub description: Option<String>,
+    /// Unit of measurement (e.g. "kg", "m^3/s")
+    pub unit: Option<String>,
+    /// Semantic type overriding the inferred dataType (e.g. "sc:URL")
+    pub semantic_type: Option<String>,
+}
+
+/// Source of field descriptions used during metadata generation
+///
+/// Providers must be `Sync` so they can be shared by parallel batch
+/// generation.
+pub trait FieldDescriptionProvider: Sync {
+    /// Return the description for a column, or `None` if it is unknown
+    fn describe(&self, column: &str) -> Option<FieldDescription>;
+}
+
+impl<F> FieldDescriptionProvider for F
+where
+    F: Fn(&str) -> Option<FieldDescription> + Sync,
+{
+    fn describe(&self, column: &str) -> Option<FieldDescription> {
+        self(column)
+    }
+}
+
+impl FieldDescriptionProvider for HashMap<String, FieldDescription> {
+    fn describe(&self, column: &str) -> Option<FieldDescription> {
+        self.get(column).cloned()
+    }
+}
+
+/// Data dictionary loaded from a CSV or JSON file
+#[derive(Debug, Clone, Default)]
+pub struct DataDictionary {
+    entries: HashMap<String, FieldDescription>,
+}
+
+impl DataDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the description of a column
+    pub fn insert(&mut self, column: impl Into<String>, description: FieldDescription) {
+        self.entries.insert(column.into(), description);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Load a data dictionary, choosing the format from the file extension
+    pub fn from_file(path: &Path) -> Result<Self> {
+        match get_file_extension(path).as_deref() {
+            Some("json") => Self::from_json_file(path),
+            Some("csv") => Self::from_csv_file(path),
+            _ => Err(Error::invalid_format(format!(
+                "Unsupported data dictionary format: {} (expected .csv or .json)",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Load a data dictionary from a CSV file
+    ///
+    /// The file must have a `column` header; `description`, `unit` and
+    /// `semantic_type` headers are optional.
+    pub fn from_csv_file(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|_| Error::file_not_found(path))?;
+        let mut reader = csv::Reader::from_reader(file);
+
+        let headers = reader.headers()?.clone();
+        let position = |name: &str| {
+            headers
+                .iter()
+                .position(|h| h.trim().eq_ignore_ascii_case(name))
+        };
+        let column_idx = position("column")
+            .ok_or_else(|| Error::invalid_format("Data dictionary has no \"column\" header"))?;
+        let description_idx = position("description");
+        let unit_idx = position("unit");
+        let semantic_type_idx = position("semantic_type").or_else(|| position("semanticType"));
+
+        let mut dictionary = Self::new();
+        for result in reader.records() {
+            let record = result?;
+            let cell = |idx: Option<usize>| {
+                idx.and_then(|i| record.get(i))
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+            };
+
+            let Some(column) = cell(Some(column_idx)) else {
+                continue;
+            };
+            dictionary.insert(
+                column,
+                FieldDescription {
+                    description: cell(description_idx),
+                    unit: cell(unit_idx),
+                    semantic_type: cell(semantic_type_idx),
+                },
+            );
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Load a data dictionary from a JSON file
+    ///
+    /// Accepts either an object keyed by column name, whose values are a
+    /// description string or an object, or an array of objects with a
+    /// `column` property.
+    pub fn from_json_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|_| Error::file_not_found(path))?;
+        let value: Value = serde_json::from_str(&content)?;
+
+        let mut dictionary = Self::new();
+        match value {
+            Value::Object(map) => {
+                for (column, entry) in map {
+                    dictionary.insert(column, parse_json_entry(&entry)?);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    let column = item.get("column").and_then(Value::as_str).ok_or_else(|| {
+                        Error::invalid_format("Data dictionary entry has no \"column\" property")
+                    })?;
+                    dictionary.insert(column, parse_json_entry(&item)?);
+                }
+            }
+            _ => {
+                return Err(Error::invalid_format(
+                    "Data dictionary must be a JSON object or array",
+                ));
+            }
+        }
+
+        Ok(dictionary)
+    }
+}
+
+impl FieldDescriptionProvider for DataDictionary {
+    fn describe(&self, column: &str) -> Option<FieldDescription> {
+        self.entries.get(column).cloned()
+    }
+}
+
+fn parse_json_entry(entry: &Value) -> Result<FieldDescription> {
+    let text = |key: &str| entry.get(key).and_then(Value::as_str).map(str::to_string);
+
+    match entry {
+        Value::String(description) => Ok(FieldDescription {
+            description: Some(description.clone()),
+            ..Default::default()
+        }),
+        Value::Object(_) => Ok(FieldDescription {
+            description: text("description"),
+            unit: text("unit"),
+            semantic_type: text("semanticType").or_else(|| text("semantic_type")),
+        }),
+        _ => Err(Error::invalid_format(
+            "Data dictionary entries must be strings or objects",
+        )),
+    }
+}
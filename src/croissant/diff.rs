@@ -0,0 +1,656 @@
+//! Changes between two versions of a dataset's metadata
+//!
+//! Distributions, record sets and fields are matched by `@id`; fields,
+//! subfields included, within their record set. A field that disappears
+//! while another appears in the same record set, extracted from the same
+//! source, with the same name, or in the same position with the same
+//! dataType, is taken to be renamed. Dataset properties that change on
+//! every regeneration, such as `datePublished`, are ignored, as in
+//! [`Metadata::equivalent`].
+//!
+//! ```
+//! use rustcroissant::croissant::DataType;
+//! use rustcroissant::croissant::builder::{FieldBuilder, MetadataBuilder, RecordSetBuilder};
+//! use rustcroissant::croissant::diff::{Change, diff_metadata};
+//!
+//! let old = MetadataBuilder::new("stations")
+//!     .record_set(RecordSetBuilder::new("readings").field(FieldBuilder::new("flow", DataType::Integer)))
+//!     .build_unchecked();
+//! let new = MetadataBuilder::new("stations")
+//!     .version("1.1.0")
+//!     .record_set(RecordSetBuilder::new("readings").field(FieldBuilder::new("flow", DataType::Float)))
+//!     .build_unchecked();
+//!
+//! let diff = diff_metadata(&old, &new);
+//! assert_eq!(diff.changes.len(), 2);
+//! assert!(matches!(&diff.changes[1], Change::DataTypeChanged { field, .. } if field == "readings/flow"));
+//! ```
+
+use crate::croissant::core::{Distribution, Field, Metadata, RecordSet};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// How a version number changed, comparing its dot-separated numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionBump {
+    Major,
+    Minor,
+    Patch,
+    /// The new version is lower
+    Downgrade,
+    /// Either version is not made of numbers
+    Other,
+}
+
+impl VersionBump {
+    /// Classify the change from `old` to `new`, versions that differ
+    pub fn between(old: &str, new: &str) -> Self {
+        let parse = |version: &str| {
+            version
+                .trim_start_matches('v')
+                .split('.')
+                .map(str::parse::<u64>)
+                .collect::<Result<Vec<_>, _>>()
+                .ok()
+        };
+        let (Some(mut old), Some(mut new)) = (parse(old), parse(new)) else {
+            return VersionBump::Other;
+        };
+        let len = old.len().max(new.len()).max(3);
+        old.resize(len, 0);
+        new.resize(len, 0);
+        match old.iter().zip(&new).position(|(a, b)| a != b) {
+            Some(index) if new[index] < old[index] => VersionBump::Downgrade,
+            Some(0) => VersionBump::Major,
+            Some(1) => VersionBump::Minor,
+            Some(_) => VersionBump::Patch,
+            None => VersionBump::Other,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VersionBump::Major => "major",
+            VersionBump::Minor => "minor",
+            VersionBump::Patch => "patch",
+            VersionBump::Downgrade => "downgrade",
+            VersionBump::Other => "other",
+        }
+    }
+}
+
+/// One change from the old metadata to the new
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(
+    tag = "change",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Change {
+    VersionChanged {
+        old: String,
+        new: String,
+        bump: VersionBump,
+    },
+    /// A dataset property other than the version, by its JSON name
+    PropertyChanged {
+        property: String,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+    DistributionAdded {
+        id: String,
+    },
+    DistributionRemoved {
+        id: String,
+    },
+    /// The SHA-256 digest or content size of a distribution changed
+    ContentChanged {
+        id: String,
+        old_sha256: String,
+        new_sha256: String,
+        old_size: String,
+        new_size: String,
+    },
+    /// Another property of a distribution, such as its contentUrl, changed
+    DistributionChanged {
+        id: String,
+        property: String,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+    RecordSetAdded {
+        id: String,
+    },
+    RecordSetRemoved {
+        id: String,
+    },
+    FieldAdded {
+        record_set: String,
+        field: String,
+        data_type: String,
+    },
+    FieldRemoved {
+        record_set: String,
+        field: String,
+    },
+    FieldRenamed {
+        record_set: String,
+        old: String,
+        new: String,
+    },
+    /// The dataType of a field, by its new `@id`, changed
+    DataTypeChanged {
+        record_set: String,
+        field: String,
+        old: String,
+        new: String,
+    },
+    /// Another property of a field, such as its source, changed
+    FieldChanged {
+        record_set: String,
+        field: String,
+        property: String,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::VersionChanged { old, new, bump } => {
+                write!(f, "~ version {old} -> {new} ({})", bump.name())
+            }
+            Change::PropertyChanged { property, old, new } => {
+                write!(f, "~ {property}: {} -> {}", show(old), show(new))
+            }
+            Change::DistributionAdded { id } => write!(f, "+ distribution {id}"),
+            Change::DistributionRemoved { id } => write!(f, "- distribution {id}"),
+            Change::ContentChanged {
+                id,
+                old_sha256,
+                new_sha256,
+                old_size,
+                new_size,
+            } => {
+                write!(f, "~ distribution {id}: content changed")?;
+                if old_sha256 != new_sha256 {
+                    write!(
+                        f,
+                        ", sha256 {} -> {}",
+                        short_digest(old_sha256),
+                        short_digest(new_sha256)
+                    )?;
+                }
+                if old_size != new_size {
+                    write!(f, ", size {old_size} -> {new_size}")?;
+                }
+                Ok(())
+            }
+            Change::DistributionChanged {
+                id,
+                property,
+                old,
+                new,
+            } => write!(
+                f,
+                "~ distribution {id}: {property} {} -> {}",
+                show(old),
+                show(new)
+            ),
+            Change::RecordSetAdded { id } => write!(f, "+ recordSet {id}"),
+            Change::RecordSetRemoved { id } => write!(f, "- recordSet {id}"),
+            Change::FieldAdded {
+                field, data_type, ..
+            } => write!(f, "+ field {field} ({data_type})"),
+            Change::FieldRemoved { field, .. } => write!(f, "- field {field}"),
+            Change::FieldRenamed { old, new, .. } => write!(f, "> field {old} renamed to {new}"),
+            Change::DataTypeChanged {
+                field, old, new, ..
+            } => write!(f, "~ field {field}: dataType {old} -> {new}"),
+            Change::FieldChanged {
+                field,
+                property,
+                old,
+                new,
+                ..
+            } => write!(
+                f,
+                "~ field {field}: {property} {} -> {}",
+                show(old),
+                show(new)
+            ),
+        }
+    }
+}
+
+fn show(value: &Option<Value>) -> String {
+    match value {
+        None => "(none)".to_string(),
+        Some(Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn short_digest(digest: &str) -> &str {
+    match digest {
+        "" => "(none)",
+        digest => &digest[..digest.len().min(12)],
+    }
+}
+
+/// Changes from one version of a dataset's metadata to another
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetadataDiff {
+    /// Changes to the dataset, then its distributions, then its record
+    /// sets and their fields
+    pub changes: Vec<Change>,
+}
+
+impl MetadataDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// How the version changed, if it did
+    pub fn version_bump(&self) -> Option<VersionBump> {
+        self.changes.iter().find_map(|change| match change {
+            Change::VersionChanged { bump, .. } => Some(*bump),
+            _ => None,
+        })
+    }
+
+    /// One line per change, prefixed with `+` for additions, `-` for
+    /// removals, `>` for renames and `~` for other changes
+    pub fn report(&self) -> String {
+        if self.is_empty() {
+            return "No changes.\n".to_string();
+        }
+        self.changes
+            .iter()
+            .map(|change| format!("{change}\n"))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).expect("changes serialize")
+    }
+}
+
+/// Dataset properties compared besides the version, by JSON name
+const DATASET_PROPERTIES: &[&str] = &[
+    "name",
+    "description",
+    "conformsTo",
+    "license",
+    "url",
+    "creator",
+    "keywords",
+    "identifier",
+    "citeAs",
+];
+
+/// Distribution properties compared besides the digest and size
+const DISTRIBUTION_PROPERTIES: &[&str] = &[
+    "@type",
+    "contentUrl",
+    "encodingFormat",
+    "containedIn",
+    "includes",
+    "excludes",
+];
+
+/// Field properties compared besides the dataType
+const FIELD_PROPERTIES: &[&str] = &[
+    "name",
+    "description",
+    "source",
+    "references",
+    "repeated",
+    "unitText",
+];
+
+/// Compare two versions of a dataset's metadata
+pub fn diff_metadata(old: &Metadata, new: &Metadata) -> MetadataDiff {
+    let mut changes = Vec::new();
+
+    if old.version != new.version {
+        changes.push(Change::VersionChanged {
+            old: old.version.clone(),
+            new: new.version.clone(),
+            bump: VersionBump::between(&old.version, &new.version),
+        });
+    }
+    let (old_json, new_json) = (to_json(old), to_json(new));
+    for (property, old, new) in changed_properties(&old_json, &new_json, DATASET_PROPERTIES) {
+        changes.push(Change::PropertyChanged { property, old, new });
+    }
+    if old.rai != new.rai {
+        changes.push(Change::PropertyChanged {
+            property: "rai".to_string(),
+            old: Some(to_json(&old.rai)),
+            new: Some(to_json(&new.rai)),
+        });
+    }
+
+    diff_distributions(&old.distribution, &new.distribution, &mut changes);
+
+    for record_set in &old.record_set {
+        match new.record_set.iter().find(|rs| rs.id == record_set.id) {
+            Some(new_record_set) => diff_fields(record_set, new_record_set, &mut changes),
+            None => changes.push(Change::RecordSetRemoved {
+                id: record_set.id.clone(),
+            }),
+        }
+    }
+    for record_set in &new.record_set {
+        if !old.record_set.iter().any(|rs| rs.id == record_set.id) {
+            changes.push(Change::RecordSetAdded {
+                id: record_set.id.clone(),
+            });
+        }
+    }
+
+    MetadataDiff { changes }
+}
+
+fn diff_distributions(old: &[Distribution], new: &[Distribution], changes: &mut Vec<Change>) {
+    for distribution in old {
+        let Some(new_distribution) = new.iter().find(|d| d.id == distribution.id) else {
+            changes.push(Change::DistributionRemoved {
+                id: distribution.id.clone(),
+            });
+            continue;
+        };
+        if !distribution
+            .sha256
+            .eq_ignore_ascii_case(&new_distribution.sha256)
+            || distribution.content_size != new_distribution.content_size
+        {
+            changes.push(Change::ContentChanged {
+                id: distribution.id.clone(),
+                old_sha256: distribution.sha256.clone(),
+                new_sha256: new_distribution.sha256.clone(),
+                old_size: distribution.content_size.clone(),
+                new_size: new_distribution.content_size.clone(),
+            });
+        }
+        let (old_json, new_json) = (to_json(distribution), to_json(new_distribution));
+        for (property, old, new) in
+            changed_properties(&old_json, &new_json, DISTRIBUTION_PROPERTIES)
+        {
+            changes.push(Change::DistributionChanged {
+                id: distribution.id.clone(),
+                property,
+                old,
+                new,
+            });
+        }
+    }
+    for distribution in new {
+        if !old.iter().any(|d| d.id == distribution.id) {
+            changes.push(Change::DistributionAdded {
+                id: distribution.id.clone(),
+            });
+        }
+    }
+}
+
+/// Compare the fields of two versions of a record set, subfields included
+fn diff_fields(old: &RecordSet, new: &RecordSet, changes: &mut Vec<Change>) {
+    let old_fields = flatten_fields(&old.field);
+    let new_fields = flatten_fields(&new.field);
+    let record_set = || new.id.clone();
+
+    let mut removed = old_fields
+        .keys()
+        .filter(|id| !new_fields.contains_key(*id))
+        .copied()
+        .collect::<Vec<_>>();
+    let mut added = new_fields
+        .keys()
+        .filter(|id| !old_fields.contains_key(*id))
+        .copied()
+        .collect::<Vec<_>>();
+
+    // Pair removed fields with added ones extracted from the same source,
+    // then with ones of the same name, then with top-level ones of the same
+    // dataType in the same position, as when a column is renamed
+    let mut pairs = Vec::new();
+    let same_source = |old: &Field, new: &Field| old.source.is_some() && old.source == new.source;
+    let same_name = |old: &Field, new: &Field| old.name == new.name;
+    let same_place = |old_field: &Field, new_field: &Field| {
+        let position = |record_set: &RecordSet, field: &Field| {
+            record_set.field.iter().position(|f| f.id == field.id)
+        };
+        old_field.data_type == new_field.data_type
+            && position(old, old_field).is_some()
+            && position(old, old_field) == position(new, new_field)
+    };
+    for matches in [
+        &same_source as &dyn Fn(&Field, &Field) -> bool,
+        &same_name,
+        &same_place,
+    ] {
+        let mut paired = HashSet::new();
+        for old_id in &removed {
+            if let Some(position) = added
+                .iter()
+                .position(|new_id| matches(old_fields[old_id], new_fields[new_id]))
+            {
+                pairs.push((*old_id, added.remove(position)));
+                paired.insert(*old_id);
+            }
+        }
+        removed.retain(|id| !paired.contains(id));
+    }
+
+    for id in &removed {
+        changes.push(Change::FieldRemoved {
+            record_set: record_set(),
+            field: id.to_string(),
+        });
+    }
+    for id in &added {
+        changes.push(Change::FieldAdded {
+            record_set: record_set(),
+            field: id.to_string(),
+            data_type: new_fields[id].data_type.clone(),
+        });
+    }
+    for (old_id, new_id) in &pairs {
+        changes.push(Change::FieldRenamed {
+            record_set: record_set(),
+            old: old_id.to_string(),
+            new: new_id.to_string(),
+        });
+    }
+
+    let kept = old_fields
+        .keys()
+        .filter(|id| new_fields.contains_key(*id))
+        .map(|id| (*id, *id));
+    for (old_id, new_id) in kept.chain(pairs.iter().copied()) {
+        let (old_field, new_field) = (old_fields[old_id], new_fields[new_id]);
+        if old_field.data_type != new_field.data_type {
+            changes.push(Change::DataTypeChanged {
+                record_set: record_set(),
+                field: new_id.to_string(),
+                old: old_field.data_type.clone(),
+                new: new_field.data_type.clone(),
+            });
+        }
+        let (old_json, new_json) = (to_json(old_field), to_json(new_field));
+        for (property, old, new) in changed_properties(&old_json, &new_json, FIELD_PROPERTIES) {
+            changes.push(Change::FieldChanged {
+                record_set: record_set(),
+                field: new_id.to_string(),
+                property,
+                old,
+                new,
+            });
+        }
+    }
+}
+
+/// Fields and their subfields by `@id`
+fn flatten_fields(fields: &[Field]) -> BTreeMap<&str, &Field> {
+    let mut by_id = BTreeMap::new();
+    let mut stack = fields.iter().collect::<Vec<_>>();
+    while let Some(field) = stack.pop() {
+        by_id.insert(field.id.as_str(), field);
+        stack.extend(&field.sub_field);
+    }
+    by_id
+}
+
+fn to_json(value: &impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// The listed properties whose values differ, with their old and new
+/// values
+fn changed_properties(
+    old: &Value,
+    new: &Value,
+    properties: &[&str],
+) -> Vec<(String, Option<Value>, Option<Value>)> {
+    properties
+        .iter()
+        .filter_map(|property| {
+            let (old, new) = (old.get(property), new.get(property));
+            (old != new).then(|| (property.to_string(), old.cloned(), new.cloned()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::croissant::DataType;
+    use crate::croissant::builder::{FieldBuilder, MetadataBuilder, RecordSetBuilder};
+
+    fn metadata(fields: Vec<FieldBuilder>) -> Metadata {
+        let record_set = fields
+            .into_iter()
+            .fold(RecordSetBuilder::new("readings"), RecordSetBuilder::field);
+        MetadataBuilder::new("stations")
+            .record_set(record_set)
+            .build_unchecked()
+    }
+
+    /// Field additions, removals, renames and dataType changes, leaving
+    /// out other field properties such as names and descriptions
+    fn field_changes(old: &Metadata, new: &Metadata) -> Vec<Change> {
+        diff_metadata(old, new)
+            .changes
+            .into_iter()
+            .filter(|change| !matches!(change, Change::FieldChanged { .. }))
+            .collect()
+    }
+
+    fn renamed(old: &str, new: &str) -> Change {
+        Change::FieldRenamed {
+            record_set: "readings".to_string(),
+            old: format!("readings/{old}"),
+            new: format!("readings/{new}"),
+        }
+    }
+
+    #[test]
+    fn field_extracted_from_the_same_column_is_renamed() {
+        let old = metadata(vec![
+            FieldBuilder::new("id", DataType::Integer).column("data.csv", "id"),
+            FieldBuilder::new("flow", DataType::Float).column("data.csv", "flow"),
+        ]);
+        let new = metadata(vec![
+            FieldBuilder::new("discharge", DataType::Float).column("data.csv", "flow"),
+            FieldBuilder::new("id", DataType::Integer).column("data.csv", "id"),
+        ]);
+        assert_eq!(field_changes(&old, &new), [renamed("flow", "discharge")]);
+    }
+
+    #[test]
+    fn field_in_the_same_place_with_the_same_data_type_is_renamed() {
+        let old = metadata(vec![
+            FieldBuilder::new("id", DataType::Integer).column("data.csv", "id"),
+            FieldBuilder::new("flow", DataType::Float).column("data.csv", "flow"),
+        ]);
+        let new = metadata(vec![
+            FieldBuilder::new("id", DataType::Integer).column("data.csv", "id"),
+            FieldBuilder::new("discharge", DataType::Float).column("data.csv", "discharge"),
+        ]);
+        assert_eq!(field_changes(&old, &new), [renamed("flow", "discharge")]);
+
+        // Another dataType in the same place is another field
+        let new = metadata(vec![
+            FieldBuilder::new("id", DataType::Integer).column("data.csv", "id"),
+            FieldBuilder::new("station", DataType::Text).column("data.csv", "station"),
+        ]);
+        assert_eq!(
+            field_changes(&old, &new),
+            [
+                Change::FieldRemoved {
+                    record_set: "readings".to_string(),
+                    field: "readings/flow".to_string(),
+                },
+                Change::FieldAdded {
+                    record_set: "readings".to_string(),
+                    field: "readings/station".to_string(),
+                    data_type: "sc:Text".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn data_type_change_is_reported_for_kept_and_renamed_fields() {
+        let old = metadata(vec![
+            FieldBuilder::new("id", DataType::Integer).column("data.csv", "id"),
+            FieldBuilder::new("flow", DataType::Integer).column("data.csv", "flow"),
+        ]);
+        let new = metadata(vec![
+            FieldBuilder::new("id", DataType::Text).column("data.csv", "id"),
+            FieldBuilder::new("discharge", DataType::Float).column("data.csv", "flow"),
+        ]);
+        let changes = field_changes(&old, &new);
+        assert_eq!(changes[0], renamed("flow", "discharge"));
+        assert_eq!(
+            changes[1..],
+            [
+                Change::DataTypeChanged {
+                    record_set: "readings".to_string(),
+                    field: "readings/id".to_string(),
+                    old: "sc:Integer".to_string(),
+                    new: "sc:Text".to_string(),
+                },
+                Change::DataTypeChanged {
+                    record_set: "readings".to_string(),
+                    field: "readings/discharge".to_string(),
+                    old: "sc:Integer".to_string(),
+                    new: "sc:Float".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            changes[2].to_string(),
+            "~ field readings/discharge: dataType sc:Integer -> sc:Float"
+        );
+    }
+
+    #[test]
+    fn version_bumps() {
+        assert_eq!(VersionBump::between("1.0.0", "2.0.0"), VersionBump::Major);
+        assert_eq!(VersionBump::between("1.0", "1.1"), VersionBump::Minor);
+        assert_eq!(VersionBump::between("v1.0.0", "1.0.1"), VersionBump::Patch);
+        assert_eq!(
+            VersionBump::between("1.2.0", "1.1.9"),
+            VersionBump::Downgrade
+        );
+        assert_eq!(VersionBump::between("1.0.0", "draft"), VersionBump::Other);
+    }
+}
@@ -0,0 +1,199 @@
+//! Character encodings of CSV files
+//!
+//! CSV files are read as UTF-8. Files starting with a byte order mark are
+//! taken to be in the encoding it names; others are UTF-16 when most code
+//! units of their start have a zero byte on the same side, UTF-8 when their
+//! start is valid UTF-8 and Latin-1 otherwise. [`DecodingReader`] transcodes them to UTF-8 as they
+//! are read, so headers, type inference and records see the same text
+//! whatever the encoding.
+
+use std::io::{self, Read};
+
+/// Number of leading bytes an encoding is detected from
+pub const DETECTION_BYTES: usize = 64 * 1024;
+
+/// Size of the chunks transcoded at a time
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Character encoding of a text file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    /// UTF-8 starting with a byte order mark, which is skipped
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// ISO-8859-1, each byte being the code point of its character
+    Latin1,
+}
+
+impl TextEncoding {
+    /// IANA name of the encoding
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => "UTF-8",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+            TextEncoding::Latin1 => "ISO-8859-1",
+        }
+    }
+
+    /// Whether the text has to be transcoded to be read as UTF-8
+    pub fn needs_transcoding(&self) -> bool {
+        !matches!(self, TextEncoding::Utf8 | TextEncoding::Utf8Bom)
+    }
+
+    /// Detect the encoding of text from its leading bytes
+    pub fn detect(start: &[u8]) -> TextEncoding {
+        if start.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            return TextEncoding::Utf8Bom;
+        }
+        if start.starts_with(&[0xFF, 0xFE]) {
+            return TextEncoding::Utf16Le;
+        }
+        if start.starts_with(&[0xFE, 0xFF]) {
+            return TextEncoding::Utf16Be;
+        }
+
+        // ASCII characters in UTF-16 have a zero high byte, and text other
+        // than UTF-16 hardly has zero bytes
+        let units = start.len() / 2;
+        if units > 0 {
+            let zeros = |offset: usize| {
+                start
+                    .chunks_exact(2)
+                    .filter(|unit| unit[offset] == 0)
+                    .count()
+            };
+            let (low, high) = (zeros(0), zeros(1));
+            if high * 2 > units && low * 10 < units {
+                return TextEncoding::Utf16Le;
+            }
+            if low * 2 > units && high * 10 < units {
+                return TextEncoding::Utf16Be;
+            }
+        }
+
+        match std::str::from_utf8(start) {
+            Ok(_) => TextEncoding::Utf8,
+            // A character cut at the end of the sample is still UTF-8
+            Err(e) if e.error_len().is_none() => TextEncoding::Utf8,
+            Err(_) => TextEncoding::Latin1,
+        }
+    }
+
+    /// Length of the byte order mark text in this encoding starts with
+    fn bom_len(&self, start: &[u8]) -> usize {
+        match self {
+            TextEncoding::Utf8Bom => 3,
+            TextEncoding::Utf16Le if start.starts_with(&[0xFF, 0xFE]) => 2,
+            TextEncoding::Utf16Be if start.starts_with(&[0xFE, 0xFF]) => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// Reader detecting the encoding of the text it reads and yielding it as
+/// UTF-8
+///
+/// Bytes that are not valid in the encoding become U+FFFD replacement
+/// characters, except in UTF-8 text, which is passed through unchanged.
+pub struct DecodingReader<R> {
+    inner: io::Chain<io::Cursor<Vec<u8>>, R>,
+    encoding: TextEncoding,
+    /// Bytes read but not decoded yet, such as half a UTF-16 code unit
+    pending: Vec<u8>,
+    /// Decoded bytes not returned yet
+    decoded: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> DecodingReader<R> {
+    /// Detect the encoding of `inner` from its leading bytes and read it
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut start = Vec::with_capacity(DETECTION_BYTES);
+        (&mut inner)
+            .take(DETECTION_BYTES as u64)
+            .read_to_end(&mut start)?;
+        let encoding = TextEncoding::detect(&start);
+        let bom_len = encoding.bom_len(&start);
+        let mut head = io::Cursor::new(start);
+        head.set_position(bom_len as u64);
+        Ok(Self {
+            inner: head.chain(inner),
+            encoding,
+            pending: Vec::new(),
+            decoded: Vec::new(),
+            position: 0,
+        })
+    }
+
+    /// Encoding the text was detected to be in
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    /// Transcode the next chunk, returning false at the end of the text
+    fn fill(&mut self) -> io::Result<bool> {
+        let mut chunk = [0u8; CHUNK_SIZE];
+        let read = self.inner.read(&mut chunk)?;
+        self.pending.extend_from_slice(&chunk[..read]);
+        let at_end = read == 0;
+        self.decoded.clear();
+        self.position = 0;
+
+        let mut text = String::new();
+        let consumed = match self.encoding {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => unreachable!("UTF-8 is read as is"),
+            TextEncoding::Latin1 => {
+                text.extend(self.pending.iter().map(|&byte| char::from(byte)));
+                self.pending.len()
+            }
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+                let unit = |pair: &[u8]| match self.encoding {
+                    TextEncoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                };
+                let mut units = self.pending.chunks_exact(2).map(unit).collect::<Vec<_>>();
+                // Keep a high surrogate for the chunk holding its pair
+                let kept_surrogate = !at_end
+                    && units
+                        .last()
+                        .is_some_and(|unit| (0xD800..0xDC00).contains(unit));
+                if kept_surrogate {
+                    units.pop();
+                }
+                text.extend(
+                    char::decode_utf16(units.iter().copied())
+                        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)),
+                );
+                let mut consumed = units.len() * 2;
+                if at_end && self.pending.len() % 2 == 1 {
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    consumed += 1;
+                }
+                consumed
+            }
+        };
+        self.pending.drain(..consumed);
+        self.decoded.extend_from_slice(text.as_bytes());
+        Ok(!self.decoded.is_empty() || !at_end)
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.encoding.needs_transcoding() {
+            return self.inner.read(buf);
+        }
+        while self.position == self.decoded.len() {
+            if !self.fill()? {
+                return Ok(0);
+            }
+        }
+        let count = buf.len().min(self.decoded.len() - self.position);
+        buf[..count].copy_from_slice(&self.decoded[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
@@ -21,6 +21,10 @@ pub enum Error {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// YAML serialization/deserialization failed
+    #[error("YAML error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
     /// File not found
     #[error("File not found: {path}")]
     FileNotFound { path: PathBuf },
@@ -45,6 +49,10 @@ pub enum Error {
     #[error("Invalid data type: {value} cannot be parsed as {data_type}")]
     InvalidDataType { value: String, data_type: String },
 
+    /// Remote resource could not be fetched
+    #[error("Remote error: {url} - {message}")]
+    Remote { url: String, message: String },
+
     /// Generic error
     #[error("Error: {0}")]
     Generic(String),
@@ -90,6 +98,14 @@ impl Error {
             data_type: data_type.into(),
         }
     }
+
+    /// Create a new remote error
+    pub fn remote(url: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Remote {
+            url: url.into(),
+            message: message.into(),
+        }
+    }
 }
 
 /// Convert anyhow::Error to our Error type
@@ -0,0 +1,265 @@
+//! W3C CSV on the Web (CSVW) table group descriptions
+//!
+//! Each record set becomes a table whose URL is the distribution its fields
+//! are extracted from, with one column per field. Record set keys become
+//! primary keys and field references foreign keys, and a recorded
+//! delimiter other than a comma the table's dialect. Record sets holding
+//! their records inline have no file to describe, so they are left out.
+//!
+//! The transforms of a field that CSVW can express become the `format` of
+//! its column's datatype: the digit grouping and decimal comma of localized
+//! numbers as `groupChar` and `decimalChar`, and the strftime format of
+//! dates and times as a date pattern. Other transforms, such as those
+//! removing currency symbols or reading Unix timestamps, are left out.
+
+use crate::croissant::core::{
+    DataType, Distribution, Field, Metadata, NumberLocale, RecordSet, Transform,
+};
+use serde_json::{Value, json};
+
+/// CSVW namespace, used as the document's `@context`
+pub const CSVW_CONTEXT: &str = "http://www.w3.org/ns/csvw";
+
+/// CSVW datatype name for a Croissant data type
+pub fn csvw_datatype(data_type: &DataType) -> &'static str {
+    match data_type {
+        DataType::Integer => "integer",
+        DataType::Float => "double",
+        DataType::Boolean => "boolean",
+        DataType::Date => "date",
+        DataType::DateTime => "datetime",
+        DataType::Time => "time",
+        DataType::Text => "string",
+        DataType::Url => "anyURI",
+    }
+}
+
+/// CSVW date pattern for a strftime format, if every part of it has one
+///
+/// Letters other than fields are quoted, as the `T` of ISO 8601.
+pub fn csvw_date_pattern(format: &str) -> Option<String> {
+    let mut pattern = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            if c.is_ascii_alphabetic() {
+                pattern.push_str(&format!("'{c}'"));
+            } else {
+                pattern.push(c);
+            }
+            continue;
+        }
+        let field = match chars.next()? {
+            'Y' => "yyyy",
+            'y' => "yy",
+            'm' => "MM",
+            'd' => "dd",
+            'H' => "HH",
+            'I' => "hh",
+            'M' => "mm",
+            'S' => "ss",
+            'p' => "a",
+            'z' => "xx",
+            ':' if chars.next()? == 'z' => "xxx",
+            '.' => match (chars.next()?, chars.next()?) {
+                ('3', 'f') => ".SSS",
+                ('6', 'f') => ".SSSSSS",
+                ('9', 'f') => ".SSSSSSSSS",
+                _ => return None,
+            },
+            '%' => "%",
+            _ => return None,
+        };
+        pattern.push_str(field);
+    }
+    Some(pattern)
+}
+
+/// CSVW datatype of a field's column: the datatype name, or the name and
+/// the format its transforms read values in
+fn column_datatype(field: &Field, data_type: &DataType) -> Value {
+    let name = csvw_datatype(data_type);
+    let transforms = field
+        .source
+        .as_ref()
+        .map(|source| source.transform.as_slice())
+        .unwrap_or_default();
+    let (format, expressed) = match data_type {
+        DataType::Integer | DataType::Float => NumberLocale::ALL
+            .iter()
+            .map(|locale| (locale, locale.transforms()))
+            .find(|(_, locale_transforms)| {
+                locale_transforms
+                    .iter()
+                    .all(|transform| transforms.contains(transform))
+            })
+            .map(|(locale, locale_transforms)| {
+                let format = json!({
+                    "groupChar": locale.group_char().to_string(),
+                    "decimalChar": locale.decimal_separator().to_string(),
+                });
+                (Some(format), locale_transforms)
+            })
+            .unwrap_or_default(),
+        DataType::Date | DataType::DateTime | DataType::Time => transforms
+            .iter()
+            .find_map(|transform| match transform {
+                Transform::Format(format) => Some((
+                    Some(Value::from(csvw_date_pattern(format)?)),
+                    vec![transform.clone()],
+                )),
+                _ => None,
+            })
+            .unwrap_or_default(),
+        _ => (None, Vec::new()),
+    };
+    if transforms
+        .iter()
+        .any(|transform| !expressed.contains(transform))
+    {
+        tracing::info!(
+            field = %field.name,
+            "field transforms without a CSVW equivalent are left out of its column"
+        );
+    }
+    match format {
+        Some(format) => json!({ "base": name, "format": format }),
+        None => json!(name),
+    }
+}
+
+/// Build the CSVW metadata document (`-metadata.json`) for the metadata
+pub fn table_group(metadata: &Metadata) -> Value {
+    let tables = metadata
+        .record_set
+        .iter()
+        .filter_map(|record_set| {
+            let Some(distribution) = table_distribution(metadata, record_set) else {
+                tracing::info!(
+                    record_set = %record_set.name,
+                    "record set has no file of its own; left out of the CSVW table group"
+                );
+                return None;
+            };
+
+            let columns = record_set
+                .field
+                .iter()
+                .map(|field| {
+                    let data_type =
+                        DataType::from_schema_org(&field.data_type).unwrap_or(DataType::Text);
+                    let mut column = json!({
+                        "name": field.name,
+                        "titles": field
+                            .source
+                            .as_ref()
+                            .map_or(field.name.as_str(), |source| &source.extract.column),
+                        "datatype": column_datatype(field, &data_type),
+                        "dc:description": field.description,
+                    });
+                    if let Some(unit) = &field.unit_text {
+                        column["schema:unitText"] = json!(unit);
+                    }
+                    if field.nullable == Some(false) {
+                        column["required"] = json!(true);
+                    }
+                    if !field.null_values.is_empty() {
+                        // Blank values stay null, as CSVW's default
+                        let mut nulls = vec![String::new()];
+                        nulls.extend(field.null_values.iter().cloned());
+                        column["null"] = json!(nulls);
+                    }
+                    column
+                })
+                .collect::<Vec<_>>();
+
+            let mut table_schema = json!({ "columns": columns });
+            let primary_key = record_set
+                .key
+                .iter()
+                .filter_map(|key| record_set.field.iter().find(|field| field.id == key.id))
+                .map(|field| field.name.clone())
+                .collect::<Vec<_>>();
+            if !primary_key.is_empty() {
+                table_schema["primaryKey"] = json!(primary_key);
+            }
+            let foreign_keys = foreign_keys(metadata, record_set);
+            if !foreign_keys.is_empty() {
+                table_schema["foreignKeys"] = json!(foreign_keys);
+            }
+
+            let mut table = json!({
+                "url": distribution.content_url,
+                "dc:title": record_set.name,
+                "dc:description": record_set.description,
+                "tableSchema": table_schema,
+            });
+            if let Some(delimiter) = distribution.delimiter.as_deref().filter(|d| *d != ",") {
+                table["dialect"] = json!({ "delimiter": delimiter });
+            }
+            Some(table)
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "@context": CSVW_CONTEXT,
+        "dc:title": metadata.name,
+        "dc:description": metadata.description,
+        "tables": tables,
+    })
+}
+
+/// The file a record set's fields are extracted from
+fn table_distribution<'a>(
+    metadata: &'a Metadata,
+    record_set: &RecordSet,
+) -> Option<&'a Distribution> {
+    if record_set.data.is_some() {
+        return None;
+    }
+    record_set
+        .field
+        .first()
+        .and_then(|field| field.source.as_ref())
+        .and_then(|source| {
+            metadata
+                .distribution
+                .iter()
+                .find(|d| d.id == source.distribution_id())
+        })
+}
+
+/// Foreign keys of a record set's table, one per field referencing a field
+/// of a table in the group
+fn foreign_keys(metadata: &Metadata, record_set: &RecordSet) -> Vec<Value> {
+    record_set
+        .field
+        .iter()
+        .filter_map(|field| {
+            let reference = field.references.as_ref()?;
+            let target = metadata.find_field(&reference.field.id).and_then(
+                |(referenced_set, referenced)| {
+                    Some((
+                        &table_distribution(metadata, referenced_set)?.content_url,
+                        referenced,
+                    ))
+                },
+            );
+            let Some((resource, referenced)) = target else {
+                tracing::warn!(
+                    field = %field.name,
+                    reference = %reference.field.id,
+                    "referenced field is not a column of a CSVW table; no foreign key written"
+                );
+                return None;
+            };
+            Some(json!({
+                "columnReference": field.name,
+                "reference": {
+                    "resource": resource,
+                    "columnReference": referenced.name,
+                },
+            }))
+        })
+        .collect()
+}
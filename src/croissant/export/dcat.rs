@@ -0,0 +1,108 @@
+//! DCAT-AP dataset descriptions
+//!
+//! The dataset is described as a `dcat:Dataset` in JSON-LD, with its
+//! license, keywords, creators and landing page, and one
+//! `dcat:Distribution` per file carrying its download URL, media type, byte
+//! size and SPDX SHA-256 checksum.
+
+use crate::croissant::core::Metadata;
+use crate::croissant::utils::parse_file_size;
+use serde_json::{Value, json};
+
+/// Prefix of IANA media type IRIs, as used by DCAT-AP
+pub const MEDIA_TYPE_PREFIX: &str = "http://www.iana.org/assignments/media-types/";
+
+/// Build the DCAT-AP JSON-LD description of the metadata
+pub fn dcat_dataset(metadata: &Metadata) -> Value {
+    let language = &metadata.context.language;
+    let distributions = metadata
+        .distribution
+        .iter()
+        .map(|d| {
+            let mut distribution = json!({
+                "@type": "dcat:Distribution",
+                "dct:title": { "@value": d.name, "@language": language },
+                "dcat:accessURL": { "@id": d.content_url },
+                "dcat:downloadURL": { "@id": d.content_url },
+                "dcat:mediaType": { "@id": format!("{MEDIA_TYPE_PREFIX}{}", d.encoding_format) },
+            });
+            if let Some(size) = parse_file_size(&d.content_size) {
+                distribution["dcat:byteSize"] =
+                    json!({ "@value": size.to_string(), "@type": "xsd:nonNegativeInteger" });
+            }
+            if !d.sha256.is_empty() {
+                distribution["spdx:checksum"] = json!({
+                    "@type": "spdx:Checksum",
+                    "spdx:algorithm": { "@id": "spdx:checksumAlgorithm_sha256" },
+                    "spdx:checksumValue": { "@value": d.sha256, "@type": "xsd:hexBinary" },
+                });
+            }
+            distribution
+        })
+        .collect::<Vec<_>>();
+
+    let mut dataset = json!({
+        "@context": {
+            "dcat": "http://www.w3.org/ns/dcat#",
+            "dct": "http://purl.org/dc/terms/",
+            "foaf": "http://xmlns.com/foaf/0.1/",
+            "spdx": "http://spdx.org/rdf/terms#",
+            "xsd": "http://www.w3.org/2001/XMLSchema#",
+        },
+        "@type": "dcat:Dataset",
+        "dct:title": { "@value": metadata.name, "@language": language },
+        "dct:description": { "@value": metadata.description, "@language": language },
+        "dct:issued": { "@value": metadata.date_published, "@type": "xsd:date" },
+        "dct:conformsTo": { "@id": metadata.conforms_to },
+        "dcat:version": metadata.version,
+        "dcat:distribution": distributions,
+    });
+    if let Some(identifier) = &metadata.identifier {
+        dataset["dct:identifier"] = json!(identifier);
+    }
+    if !metadata.license.is_empty() {
+        let licenses = metadata
+            .license
+            .iter()
+            .map(|license| {
+                if license.contains("://") {
+                    json!({ "@id": license })
+                } else {
+                    json!(license)
+                }
+            })
+            .collect::<Vec<_>>();
+        dataset["dct:license"] = json!(licenses);
+    }
+    if !metadata.keywords.is_empty() {
+        let keywords = metadata
+            .keywords
+            .iter()
+            .map(|keyword| json!({ "@value": keyword, "@language": language }))
+            .collect::<Vec<_>>();
+        dataset["dcat:keyword"] = json!(keywords);
+    }
+    if !metadata.creator.is_empty() {
+        let creators = metadata
+            .creator
+            .iter()
+            .map(|creator| {
+                let agent_type = if creator.type_.ends_with("Organization") {
+                    "foaf:Organization"
+                } else {
+                    "foaf:Person"
+                };
+                let mut agent = json!({ "@type": agent_type, "foaf:name": creator.name });
+                if let Some(url) = &creator.url {
+                    agent["foaf:homepage"] = json!({ "@id": url });
+                }
+                agent
+            })
+            .collect::<Vec<_>>();
+        dataset["dct:creator"] = json!(creators);
+    }
+    if let Some(url) = &metadata.url {
+        dataset["dcat:landingPage"] = json!({ "@id": url });
+    }
+    dataset
+}
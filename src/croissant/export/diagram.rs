@@ -0,0 +1,136 @@
+//! Diagrams of a dataset's structure, as Mermaid flowcharts or Graphviz DOT
+//!
+//! Distributions are drawn as files and record sets as boxes listing their
+//! fields, with an edge from each distribution to the record sets whose
+//! fields are extracted from it. Record sets do not reference each other in
+//! this model yet, so no join edges are drawn.
+
+use crate::croissant::core::{Metadata, RecordSet};
+use std::fmt::Write;
+
+/// Mermaid flowchart of the dataset
+pub fn mermaid(metadata: &Metadata) -> String {
+    let mut diagram = String::new();
+    write_mermaid(&mut diagram, metadata).expect("writing to a String cannot fail");
+    diagram
+}
+
+/// Graphviz DOT graph of the dataset
+pub fn dot(metadata: &Metadata) -> String {
+    let mut diagram = String::new();
+    write_dot(&mut diagram, metadata).expect("writing to a String cannot fail");
+    diagram
+}
+
+fn write_mermaid(diagram: &mut String, metadata: &Metadata) -> std::fmt::Result {
+    writeln!(diagram, "flowchart LR")?;
+    for (index, distribution) in metadata.distribution.iter().enumerate() {
+        writeln!(
+            diagram,
+            "    d{index}[(\"{}<br/>{}\")]",
+            mermaid_text(&distribution.name),
+            mermaid_text(&distribution.encoding_format)
+        )?;
+    }
+    for (index, record_set) in metadata.record_set.iter().enumerate() {
+        writeln!(
+            diagram,
+            "    subgraph r{index}[\"{}\"]",
+            mermaid_text(&record_set.name)
+        )?;
+        for (field_index, field) in record_set.field.iter().enumerate() {
+            writeln!(
+                diagram,
+                "        r{index}f{field_index}[\"{}: {}\"]",
+                mermaid_text(&field.name),
+                mermaid_text(&field.data_type)
+            )?;
+        }
+        writeln!(diagram, "    end")?;
+    }
+    for (from, to) in source_edges(metadata) {
+        writeln!(diagram, "    d{from} -->|source| r{to}")?;
+    }
+    Ok(())
+}
+
+fn write_dot(diagram: &mut String, metadata: &Metadata) -> std::fmt::Result {
+    writeln!(diagram, "digraph {} {{", dot_text(&metadata.name))?;
+    writeln!(diagram, "    rankdir=LR;")?;
+    writeln!(diagram, "    node [fontname=\"Helvetica\"];")?;
+    for (index, distribution) in metadata.distribution.iter().enumerate() {
+        writeln!(
+            diagram,
+            "    d{index} [shape=cylinder, label={}];",
+            dot_text(&format!(
+                "{}\n{}",
+                distribution.name, distribution.encoding_format
+            ))
+        )?;
+    }
+    for (index, record_set) in metadata.record_set.iter().enumerate() {
+        writeln!(
+            diagram,
+            "    r{index} [shape=record, label={}];",
+            record_label(record_set)
+        )?;
+    }
+    for (from, to) in source_edges(metadata) {
+        writeln!(diagram, "    d{from} -> r{to} [label=\"source\"];")?;
+    }
+    writeln!(diagram, "}}")
+}
+
+/// Quoted record label listing the fields, left-aligned, below the record
+/// set's name
+fn record_label(record_set: &RecordSet) -> String {
+    let escape = |text: &str| {
+        text.chars().fold(String::new(), |mut escaped, c| {
+            if "{}|<>\\\"".contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(if c == '\n' { ' ' } else { c });
+            escaped
+        })
+    };
+    let fields = record_set
+        .field
+        .iter()
+        .map(|field| format!("{}: {}\\l", escape(&field.name), escape(&field.data_type)))
+        .collect::<String>();
+    format!("\"{{{}|{fields}}}\"", escape(&record_set.name))
+}
+
+/// (distribution index, record set index) for each distribution a record
+/// set's fields are extracted from
+fn source_edges(metadata: &Metadata) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    for (to, record_set) in metadata.record_set.iter().enumerate() {
+        for (from, distribution) in metadata.distribution.iter().enumerate() {
+            if record_set.field.iter().any(|field| {
+                field
+                    .source
+                    .as_ref()
+                    .is_some_and(|source| source.distribution_id() == distribution.id)
+            }) {
+                edges.push((from, to));
+            }
+        }
+    }
+    edges
+}
+
+/// Text for a quoted Mermaid label, with quotes as entity codes
+fn mermaid_text(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// Quoted DOT string
+fn dot_text(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
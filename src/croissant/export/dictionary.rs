@@ -0,0 +1,98 @@
+//! Data dictionary tables for review of a dataset's fields
+//!
+//! One row per field, with its record set, source column, data type, unit
+//! and description. The CSV table has `column`, `unit` and `description`
+//! headers, so it can be edited and passed back to `generate --dictionary`.
+
+use crate::croissant::core::Metadata;
+use crate::croissant::errors::{Error, Result};
+
+/// Column headers of the data dictionary table
+pub const DICTIONARY_HEADERS: [&str; 6] = [
+    "record_set",
+    "field",
+    "column",
+    "data_type",
+    "unit",
+    "description",
+];
+
+/// Rows of the data dictionary, in record set and field order
+pub fn dictionary_rows(metadata: &Metadata) -> Vec<[String; 6]> {
+    metadata
+        .record_set
+        .iter()
+        .flat_map(|record_set| {
+            record_set.field.iter().map(|field| {
+                [
+                    record_set.name.clone(),
+                    field.name.clone(),
+                    field
+                        .source
+                        .as_ref()
+                        .map(|source| source.extract.column.clone())
+                        .unwrap_or_default(),
+                    field.data_type.clone(),
+                    field.unit_text.clone().unwrap_or_default(),
+                    field.description.clone(),
+                ]
+            })
+        })
+        .collect()
+}
+
+/// Data dictionary as CSV
+pub fn dictionary_csv(metadata: &Metadata) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(DICTIONARY_HEADERS)?;
+    for row in dictionary_rows(metadata) {
+        writer.write_record(&row)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| Error::new(format!("Error writing data dictionary: {e}")))?;
+    String::from_utf8(bytes).map_err(|e| Error::new(format!("Invalid data dictionary: {e}")))
+}
+
+/// Data dictionary as an Excel workbook, with a bold, frozen and filterable
+/// header row
+#[cfg(feature = "xlsx")]
+pub fn dictionary_xlsx(metadata: &Metadata) -> Result<Vec<u8>> {
+    use rust_xlsxwriter::{Format, Workbook, XlsxError};
+
+    let xlsx_error = |e: XlsxError| Error::new(format!("Error writing xlsx workbook: {e}"));
+    let rows = dictionary_rows(metadata);
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("Data dictionary").map_err(xlsx_error)?;
+    let bold = Format::new().set_bold();
+    for (col, header) in DICTIONARY_HEADERS.iter().enumerate() {
+        worksheet
+            .write_string_with_format(0, col as u16, *header, &bold)
+            .map_err(xlsx_error)?;
+    }
+    for (row, cells) in rows.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            worksheet
+                .write_string(row as u32 + 1, col as u16, cell)
+                .map_err(xlsx_error)?;
+        }
+    }
+    worksheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+    worksheet
+        .autofilter(0, 0, rows.len() as u32, DICTIONARY_HEADERS.len() as u16 - 1)
+        .map_err(xlsx_error)?;
+    worksheet.autofit();
+
+    workbook.save_to_buffer().map_err(xlsx_error)
+}
+
+/// Data dictionary as an Excel workbook; always fails, since rustcroissant
+/// was built without the `xlsx` feature
+#[cfg(not(feature = "xlsx"))]
+pub fn dictionary_xlsx(_metadata: &Metadata) -> Result<Vec<u8>> {
+    Err(Error::new(
+        "xlsx export requires rustcroissant to be built with the xlsx feature",
+    ))
+}
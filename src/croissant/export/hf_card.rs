@@ -0,0 +1,270 @@
+//! Hugging Face dataset cards
+//!
+//! The card is a README.md whose YAML front matter carries the properties
+//! the Hub indexes (license, language, size category, configs and their
+//! data files), followed by a Markdown description of the record sets and
+//! files.
+
+use crate::croissant::core::{Distribution, Metadata, RecordSet};
+use crate::croissant::errors::Result;
+use crate::croissant::records::split_field;
+use crate::croissant::utils::open_csv_reader_with_delimiter;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Hub size categories, by upper bound on the number of rows
+const SIZE_CATEGORIES: &[(u64, &str)] = &[
+    (1_000, "n<1K"),
+    (10_000, "1K<n<10K"),
+    (100_000, "10K<n<100K"),
+    (1_000_000, "100K<n<1M"),
+    (10_000_000, "1M<n<10M"),
+    (100_000_000, "10M<n<100M"),
+    (1_000_000_000, "100M<n<1B"),
+    (10_000_000_000, "1B<n<10B"),
+    (100_000_000_000, "10B<n<100B"),
+    (1_000_000_000_000, "100B<n<1T"),
+];
+
+/// Hub size category for a number of rows
+pub fn size_category(rows: u64) -> &'static str {
+    SIZE_CATEGORIES
+        .iter()
+        .find(|(bound, _)| rows < *bound)
+        .map_or("n>1T", |(_, category)| category)
+}
+
+/// Hub license identifier for a license URL or name
+///
+/// Creative Commons and SPDX license URLs map to their identifiers, other
+/// URLs to `other`, and names are taken to be SPDX identifiers.
+pub fn hub_license(license: &str) -> String {
+    let license = license.trim().trim_end_matches('/');
+    if let Some(path) = license
+        .split_once("creativecommons.org/")
+        .map(|(_, path)| path)
+    {
+        return match path.split('/').collect::<Vec<_>>().as_slice() {
+            ["publicdomain", "zero", version, ..] => format!("cc0-{version}"),
+            ["licenses", kind, version, ..] => format!("cc-{kind}-{version}"),
+            _ => "other".to_string(),
+        };
+    }
+    if let Some(id) = license.split_once("spdx.org/licenses/").map(|(_, id)| id) {
+        return id.trim_end_matches(".html").to_lowercase();
+    }
+    if license.contains("://") {
+        "other".to_string()
+    } else {
+        license.to_lowercase()
+    }
+}
+
+/// Render a dataset card for the metadata
+///
+/// Each record set becomes a config whose data files are those of the
+/// distribution its fields are extracted from. When the record set has a
+/// split field referencing a record set that lists the splits, each split
+/// gets the files whose path names it; otherwise all files are the `train`
+/// split. When `base_dir` is given, the rows of local CSV files are counted
+/// to fill in the size category; otherwise, or when a file cannot be read,
+/// the category is left out.
+pub fn dataset_card(metadata: &Metadata, base_dir: Option<&Path>) -> String {
+    let rows = base_dir.and_then(|dir| count_rows(metadata, dir));
+    let mut card = String::new();
+    write_card(&mut card, metadata, rows).expect("writing to a String cannot fail");
+    card
+}
+
+fn write_card(card: &mut String, metadata: &Metadata, rows: Option<u64>) -> std::fmt::Result {
+    // Front matter; JSON strings are valid YAML scalars, which saves
+    // reimplementing YAML quoting rules
+    card.push_str("---\n");
+    writeln!(card, "pretty_name: {}", yaml_string(&metadata.name))?;
+    if !metadata.license.is_empty() {
+        card.push_str("license:\n");
+        for license in &metadata.license {
+            writeln!(card, "- {}", yaml_string(&hub_license(license)))?;
+        }
+    }
+    writeln!(
+        card,
+        "language:\n- {}",
+        yaml_string(&metadata.context.language)
+    )?;
+    if let Some(rows) = rows {
+        writeln!(
+            card,
+            "size_categories:\n- {}",
+            yaml_string(size_category(rows))
+        )?;
+    }
+
+    let configs = metadata
+        .record_set
+        .iter()
+        .filter_map(|rs| {
+            let file_id = rs.field.first()?.source.as_ref()?.distribution_id();
+            let distribution = metadata.distribution.iter().find(|d| d.id == file_id)?;
+            Some((rs, distribution))
+        })
+        .collect::<Vec<_>>();
+    if !configs.is_empty() {
+        card.push_str("configs:\n");
+        for (index, (record_set, distribution)) in configs.iter().enumerate() {
+            writeln!(card, "- config_name: {}", yaml_string(&record_set.name))?;
+            if index == 0 {
+                card.push_str("  default: true\n");
+            }
+            card.push_str("  data_files:\n");
+            for (split, paths) in data_files(metadata, record_set, distribution) {
+                writeln!(card, "  - split: {}", yaml_string(&split))?;
+                if let [path] = paths.as_slice() {
+                    writeln!(card, "    path: {}", yaml_string(path))?;
+                } else {
+                    card.push_str("    path:\n");
+                    for path in paths {
+                        writeln!(card, "    - {}", yaml_string(&path))?;
+                    }
+                }
+            }
+        }
+    }
+    card.push_str("tags:\n- croissant\n");
+    card.push_str("---\n\n");
+
+    // Body
+    writeln!(card, "# {}\n", metadata.name)?;
+    writeln!(card, "{}\n", metadata.description)?;
+    writeln!(card, "- Version: {}", metadata.version)?;
+    writeln!(card, "- Published: {}", metadata.date_published)?;
+    writeln!(card, "- Conforms to: {}\n", metadata.conforms_to)?;
+
+    if !metadata.distribution.is_empty() {
+        card.push_str("## Files\n\n");
+        card.push_str("| File | Format | Size | SHA-256 |\n");
+        card.push_str("|---|---|---|---|\n");
+        for d in &metadata.distribution {
+            writeln!(
+                card,
+                "| {} | {} | {} | `{}` |",
+                table_cell(&d.content_url),
+                table_cell(&d.encoding_format),
+                table_cell(&d.content_size),
+                d.sha256
+            )?;
+        }
+        card.push('\n');
+    }
+
+    for record_set in &metadata.record_set {
+        writeln!(card, "## Record set `{}`\n", record_set.name)?;
+        writeln!(card, "{}\n", record_set.description)?;
+        card.push_str("| Field | Type | Description |\n");
+        card.push_str("|---|---|---|\n");
+        for field in &record_set.field {
+            let data_type = match &field.unit_text {
+                Some(unit) => format!("{} ({unit})", field.data_type),
+                None => field.data_type.clone(),
+            };
+            writeln!(
+                card,
+                "| {} | {} | {} |",
+                table_cell(&field.name),
+                table_cell(&data_type),
+                table_cell(&field.description)
+            )?;
+        }
+        card.push('\n');
+    }
+
+    card.push_str(
+        "This card was generated from the dataset's [Croissant](https://mlcommons.org/croissant/) metadata.\n",
+    );
+    Ok(())
+}
+
+fn yaml_string(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+fn table_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Data files of a config by split
+fn data_files(
+    metadata: &Metadata,
+    record_set: &RecordSet,
+    distribution: &Distribution,
+) -> Vec<(String, Vec<String>)> {
+    let paths = if distribution.is_file_set() {
+        distribution.includes.clone()
+    } else {
+        vec![distribution.content_url.clone()]
+    };
+    let splits = split_field(metadata, record_set)
+        .and_then(|(_, splits)| splits?.data.as_ref())
+        .map(|data| data.iter().filter_map(split_name).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let names = |path: &str, split: &str| {
+        path.split(['/', '.', '-', '_'])
+            .any(|part| part.eq_ignore_ascii_case(split))
+    };
+    let by_split = splits
+        .into_iter()
+        .map(|split| {
+            let paths = paths
+                .iter()
+                .filter(|path| names(path, &split))
+                .cloned()
+                .collect::<Vec<_>>();
+            (split, paths)
+        })
+        .filter(|(_, paths)| !paths.is_empty())
+        .collect::<Vec<_>>();
+    if by_split.is_empty() {
+        vec![("train".to_string(), paths)]
+    } else {
+        by_split
+    }
+}
+
+/// Name of a split listed inline: the value of its name field, or its first
+/// text value
+fn split_name(split: &serde_json::Map<String, serde_json::Value>) -> Option<String> {
+    split
+        .iter()
+        .find(|(key, _)| key.rsplit('/').next() == Some("name"))
+        .and_then(|(_, value)| value.as_str())
+        .or_else(|| split.values().find_map(serde_json::Value::as_str))
+        .map(str::to_string)
+}
+
+/// Total number of data rows in the local CSV files of all record sets,
+/// but those holding their records inline
+fn count_rows(metadata: &Metadata, base_dir: &Path) -> Option<u64> {
+    let paths = metadata
+        .record_set
+        .iter()
+        .filter(|rs| rs.data.is_none())
+        .map(|rs| {
+            let (path, distribution) = metadata.record_set_csv_file(rs, base_dir)?;
+            let delimiter = distribution.field_delimiter(&path)?;
+            Ok((path, delimiter))
+        })
+        .collect::<Result<BTreeMap<PathBuf, u8>>>()
+        .ok()?;
+
+    let mut total = 0;
+    for (path, delimiter) in paths {
+        let mut reader = open_csv_reader_with_delimiter(&path, delimiter).ok()?;
+        let mut record = csv::ByteRecord::new();
+        while reader.read_byte_record(&mut record).ok()? {
+            total += 1;
+        }
+    }
+    Some(total)
+}
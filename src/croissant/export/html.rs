@@ -0,0 +1,102 @@
+//! schema.org Dataset snippets for landing pages
+//!
+//! The snippet is an HTML `<script type="application/ld+json">` element
+//! holding the plain schema.org projection of the metadata, the form read
+//! by search engines such as Google Dataset Search. Croissant-specific
+//! properties (record sets, extraction sources) are left out, and fields
+//! are listed as `variableMeasured`.
+
+use crate::croissant::core::Metadata;
+use serde_json::{Value, json};
+
+/// Build the schema.org `Dataset` projection of the metadata
+pub fn schema_org_dataset(metadata: &Metadata) -> Value {
+    let distribution = metadata
+        .distribution
+        .iter()
+        .map(|d| {
+            let mut download = json!({
+                "@type": "DataDownload",
+                "name": d.name,
+                "contentUrl": d.content_url,
+                "encodingFormat": d.encoding_format,
+                "contentSize": d.content_size,
+            });
+            if !d.sha256.is_empty() {
+                download["sha256"] = json!(d.sha256);
+            }
+            download
+        })
+        .collect::<Vec<_>>();
+
+    let variables = metadata
+        .record_set
+        .iter()
+        .flat_map(|rs| &rs.field)
+        .map(|field| {
+            let mut variable = json!({
+                "@type": "PropertyValue",
+                "name": field.name,
+                "description": field.description,
+            });
+            if let Some(unit) = &field.unit_text {
+                variable["unitText"] = json!(unit);
+            }
+            variable
+        })
+        .collect::<Vec<_>>();
+
+    let mut dataset = json!({
+        "@context": "https://schema.org/",
+        "@type": "Dataset",
+        "name": metadata.name,
+        "description": metadata.description,
+        "version": metadata.version,
+        "datePublished": metadata.date_published,
+        "inLanguage": metadata.context.language,
+        "distribution": distribution,
+    });
+    match metadata.license.as_slice() {
+        [] => {}
+        [license] => dataset["license"] = json!(license),
+        licenses => dataset["license"] = json!(licenses),
+    }
+    if !metadata.keywords.is_empty() {
+        dataset["keywords"] = json!(metadata.keywords);
+    }
+    if !metadata.creator.is_empty() {
+        let creators = metadata
+            .creator
+            .iter()
+            .map(|creator| {
+                let mut object = json!({
+                    "@type": creator.type_.strip_prefix("sc:").unwrap_or(&creator.type_),
+                    "name": creator.name,
+                });
+                if let Some(url) = &creator.url {
+                    object["url"] = json!(url);
+                }
+                object
+            })
+            .collect::<Vec<_>>();
+        dataset["creator"] = json!(creators);
+    }
+    if let Some(url) = &metadata.url {
+        dataset["url"] = json!(url);
+    }
+    if !variables.is_empty() {
+        dataset["variableMeasured"] = json!(variables);
+    }
+    dataset
+}
+
+/// Render the HTML `<script>` element embedding the schema.org projection
+pub fn html_snippet(metadata: &Metadata) -> serde_json::Result<String> {
+    let json = serde_json::to_string_pretty(&schema_org_dataset(metadata))?;
+    // A literal "</script>" or "<!--" inside a string would end the script
+    // element early; "<\/" is an equivalent JSON escape
+    let json = json.replace("</", "<\\/").replace("<!--", "<\\u0021--");
+    Ok(format!(
+        "<script type=\"application/ld+json\">\n{json}\n</script>\n"
+    ))
+}
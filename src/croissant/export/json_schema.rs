@@ -0,0 +1,83 @@
+//! JSON Schema describing the records of each record set
+//!
+//! A record is an object with one property per field. Every field is
+//! required, since each record has a value for every column, and nullable
+//! unless the field says otherwise.
+
+use crate::croissant::core::{DataType, Metadata, RecordSet};
+use serde_json::{Map, Value, json};
+
+/// JSON Schema dialect of the generated schemas
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Schema of a single value of the given data type, without `null`
+pub fn value_schema(data_type: &DataType) -> Value {
+    match data_type {
+        DataType::Integer => json!({ "type": "integer" }),
+        DataType::Float => json!({ "type": "number" }),
+        DataType::Boolean => json!({ "type": "boolean" }),
+        DataType::Date => json!({ "type": "string", "format": "date" }),
+        DataType::DateTime => json!({ "type": "string", "format": "date-time" }),
+        DataType::Time => json!({ "type": "string", "format": "time" }),
+        DataType::Text => json!({ "type": "string" }),
+        DataType::Url => json!({ "type": "string", "format": "uri" }),
+    }
+}
+
+/// Schema of a single record of a record set
+pub fn record_schema(record_set: &RecordSet) -> Value {
+    let mut properties = Map::new();
+    for field in &record_set.field {
+        let data_type = DataType::from_schema_org(&field.data_type).unwrap_or(DataType::Text);
+        let mut schema = value_schema(&data_type);
+        // Nullable: widen the type to a ["type", "null"] union
+        if field.nullable != Some(false) {
+            let type_ = schema["type"].take();
+            schema["type"] = json!([type_, "null"]);
+        }
+        schema["description"] = json!(field.description);
+        if let Some(unit) = &field.unit_text {
+            schema["x-unitText"] = json!(unit);
+        }
+        properties.insert(field.name.clone(), schema);
+    }
+
+    json!({
+        "title": record_set.name,
+        "description": record_set.description,
+        "type": "object",
+        "properties": properties,
+        "required": record_set.field.iter().map(|f| &f.name).collect::<Vec<_>>(),
+        "additionalProperties": false,
+    })
+}
+
+/// Schema document with the record schema of every record set under
+/// `$defs`, keyed by record set name
+///
+/// The document itself accepts a record of any of the record sets; refer to
+/// `#/$defs/<name>` to validate records of a single one.
+pub fn json_schema(metadata: &Metadata) -> Value {
+    let defs = metadata
+        .record_set
+        .iter()
+        .map(|record_set| (record_set.name.clone(), record_schema(record_set)))
+        .collect::<Map<_, _>>();
+    let refs = metadata
+        .record_set
+        .iter()
+        .map(|record_set| {
+            // JSON Pointer escaping of the name
+            let name = record_set.name.replace('~', "~0").replace('/', "~1");
+            json!({ "$ref": format!("#/$defs/{name}") })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "$schema": JSON_SCHEMA_DIALECT,
+        "title": metadata.name,
+        "description": metadata.description,
+        "anyOf": refs,
+        "$defs": defs,
+    })
+}
@@ -0,0 +1,51 @@
+//! File manifests for training pipelines
+//!
+//! A manifest lists the dataset's files as CSV, one row per file with its
+//! shard, path, size and digest, so data loaders can pick up their share of
+//! the files without parsing JSON-LD. Files are assigned to shards in
+//! document order, starting a new shard whenever the current one would
+//! exceed the shard size.
+
+use crate::croissant::core::Metadata;
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::parse_file_size;
+
+/// Default target size of a shard
+pub const DEFAULT_SHARD_SIZE: u64 = 1 << 30;
+
+/// Header of the manifest
+const COLUMNS: &[&str] = &["shard", "path", "size", "sha256", "encoding_format"];
+
+/// Render the CSV manifest of the metadata's files
+///
+/// A file whose size cannot be parsed counts as empty when filling shards,
+/// and its size column is left blank.
+pub fn file_manifest(metadata: &Metadata, shard_size: u64) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(COLUMNS)?;
+
+    let mut shard = 0u64;
+    let mut shard_bytes = 0u64;
+    for distribution in &metadata.distribution {
+        let size = parse_file_size(&distribution.content_size);
+        let bytes = size.unwrap_or(0);
+        if shard_bytes > 0 && shard_bytes + bytes > shard_size {
+            shard += 1;
+            shard_bytes = 0;
+        }
+        shard_bytes += bytes;
+
+        writer.write_record([
+            shard.to_string().as_str(),
+            &distribution.content_url,
+            &size.map(|s| s.to_string()).unwrap_or_default(),
+            &distribution.sha256,
+            &distribution.encoding_format,
+        ])?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| Error::new(format!("Could not write manifest: {e}")))?;
+    String::from_utf8(bytes).map_err(|e| Error::new(format!("Could not write manifest: {e}")))
+}
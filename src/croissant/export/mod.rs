@@ -0,0 +1,299 @@
+//! Export of Croissant metadata to other formats
+
+use crate::croissant::core::Metadata;
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::jsonld::{JsonLdForm, to_form};
+use std::path::Path;
+
+pub mod csvw;
+pub mod dcat;
+pub mod diagram;
+pub mod dictionary;
+pub mod hf_card;
+pub mod html;
+pub mod json_schema;
+pub mod manifest;
+pub mod sql;
+
+/// Formats metadata can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Data dictionary of the fields as CSV
+    Csv,
+    /// W3C CSV on the Web table group metadata
+    Csvw,
+    /// DCAT-AP dataset description in JSON-LD
+    Dcat,
+    /// DCAT-AP dataset description in Turtle (`rdf` feature)
+    DcatTurtle,
+    /// Graphviz DOT diagram of the dataset's structure
+    Dot,
+    /// Hugging Face dataset card (README.md with YAML front matter)
+    HfCard,
+    /// HTML snippet embedding the schema.org Dataset for landing pages
+    Html,
+    /// The Croissant metadata itself, as JSON-LD
+    JsonLd,
+    /// JSON Schema of the records of each record set
+    JsonSchema,
+    /// CSV manifest of the dataset's files with shard assignments
+    Manifest,
+    /// Mermaid flowchart of the dataset's structure
+    Mermaid,
+    /// RDF N-Quads (`rdf` feature)
+    NQuads,
+    /// SQL `CREATE TABLE` statements for the record sets
+    Sql,
+    /// RDF Turtle (`rdf` feature)
+    Turtle,
+    /// Data dictionary of the fields as an Excel workbook (`xlsx` feature)
+    Xlsx,
+}
+
+impl ExportFormat {
+    /// All formats, in the order they are listed to users
+    pub const ALL: &'static [ExportFormat] = &[
+        ExportFormat::Csv,
+        ExportFormat::Csvw,
+        ExportFormat::Dcat,
+        ExportFormat::DcatTurtle,
+        ExportFormat::Dot,
+        ExportFormat::HfCard,
+        ExportFormat::Html,
+        ExportFormat::JsonLd,
+        ExportFormat::JsonSchema,
+        ExportFormat::Manifest,
+        ExportFormat::Mermaid,
+        ExportFormat::NQuads,
+        ExportFormat::Sql,
+        ExportFormat::Turtle,
+        ExportFormat::Xlsx,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Csvw => "csvw",
+            ExportFormat::Dcat => "dcat",
+            ExportFormat::DcatTurtle => "dcat-turtle",
+            ExportFormat::Dot => "dot",
+            ExportFormat::HfCard => "hf-card",
+            ExportFormat::Html => "html",
+            ExportFormat::JsonLd => "jsonld",
+            ExportFormat::JsonSchema => "json-schema",
+            ExportFormat::Manifest => "manifest",
+            ExportFormat::Mermaid => "mermaid",
+            ExportFormat::NQuads => "nquads",
+            ExportFormat::Sql => "sql",
+            ExportFormat::Turtle => "turtle",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+
+    /// Parse a command-line format name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown export format: {name}")))
+    }
+
+    /// Whether the format is JSON-LD, and so can be written in any
+    /// [`JsonLdForm`]
+    pub fn is_json_ld(&self) -> bool {
+        matches!(self, ExportFormat::Dcat | ExportFormat::JsonLd)
+    }
+
+    /// Whether the format is JSON, and so can be minified
+    pub fn is_json(&self) -> bool {
+        self.is_json_ld() || matches!(self, ExportFormat::Csvw | ExportFormat::JsonSchema)
+    }
+
+    /// Whether the format is a data dictionary table
+    pub fn is_dictionary(&self) -> bool {
+        matches!(self, ExportFormat::Csv | ExportFormat::Xlsx)
+    }
+
+    /// Whether the format is binary, and so only available through
+    /// [`export_metadata_bytes`]
+    pub fn is_binary(&self) -> bool {
+        matches!(self, ExportFormat::Xlsx)
+    }
+}
+
+/// Options for formats that can be written more than one way
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Form JSON-LD formats are written in; forms other than compact are
+    /// rejected for other formats
+    pub jsonld_form: JsonLdForm,
+    /// Dialect of SQL DDL
+    pub sql_dialect: sql::SqlDialect,
+    /// Write JSON formats minified rather than pretty-printed; rejected for
+    /// other formats
+    pub minified: bool,
+}
+
+/// Export metadata in the given format
+///
+/// `base_dir` is the directory relative content URLs are resolved against;
+/// formats that summarize the data itself (such as row counts) read local
+/// files from there when it is given.
+pub fn export_metadata(
+    metadata: &Metadata,
+    format: ExportFormat,
+    base_dir: Option<&Path>,
+) -> Result<String> {
+    export_metadata_with_options(metadata, format, base_dir, &ExportOptions::default())
+}
+
+/// Export metadata in the given format with the given options
+pub fn export_metadata_with_options(
+    metadata: &Metadata,
+    format: ExportFormat,
+    base_dir: Option<&Path>,
+    options: &ExportOptions,
+) -> Result<String> {
+    let form = options.jsonld_form;
+    if form != JsonLdForm::Compact && !format.is_json_ld() {
+        return Err(Error::invalid_format(format!(
+            "The {} form only applies to JSON-LD formats, not {}",
+            form.name(),
+            format.name()
+        )));
+    }
+    if options.minified && !format.is_json() {
+        return Err(Error::invalid_format(format!(
+            "Minified output only applies to JSON formats, not {}",
+            format.name()
+        )));
+    }
+    let json = |value: &serde_json::Value| -> Result<String> {
+        Ok(if options.minified {
+            serde_json::to_string(value)?
+        } else {
+            serde_json::to_string_pretty(value)?
+        })
+    };
+
+    match format {
+        ExportFormat::Csv => dictionary::dictionary_csv(metadata),
+        ExportFormat::Csvw => json(&csvw::table_group(metadata)),
+        ExportFormat::Dcat => json(&to_form(&dcat::dcat_dataset(metadata), form, None)?),
+        ExportFormat::DcatTurtle => rdf_export(
+            &serde_json::to_string(&dcat::dcat_dataset(metadata))?,
+            base_dir,
+            RdfExport::Turtle,
+        ),
+        ExportFormat::Dot => Ok(diagram::dot(metadata)),
+        ExportFormat::HfCard => Ok(hf_card::dataset_card(metadata, base_dir)),
+        ExportFormat::Html => Ok(html::html_snippet(metadata)?),
+        ExportFormat::JsonLd => json(&to_form(&serde_json::to_value(metadata)?, form, None)?),
+        ExportFormat::JsonSchema => json(&json_schema::json_schema(metadata)),
+        ExportFormat::Manifest => manifest::file_manifest(metadata, manifest::DEFAULT_SHARD_SIZE),
+        ExportFormat::Mermaid => Ok(diagram::mermaid(metadata)),
+        ExportFormat::NQuads => rdf_export(
+            &serde_json::to_string(metadata)?,
+            base_dir,
+            RdfExport::NQuads,
+        ),
+        ExportFormat::Sql => Ok(sql::create_tables(metadata, options.sql_dialect)),
+        ExportFormat::Turtle => rdf_export(
+            &serde_json::to_string(metadata)?,
+            base_dir,
+            RdfExport::Turtle,
+        ),
+        ExportFormat::Xlsx => Err(Error::invalid_format(
+            "xlsx is a binary format; use export_metadata_bytes",
+        )),
+    }
+}
+
+/// Export metadata in the given format with the given options, as bytes
+///
+/// Unlike [`export_metadata_with_options`], this supports binary formats.
+pub fn export_metadata_bytes(
+    metadata: &Metadata,
+    format: ExportFormat,
+    base_dir: Option<&Path>,
+    options: &ExportOptions,
+) -> Result<Vec<u8>> {
+    match format {
+        ExportFormat::Xlsx => dictionary::dictionary_xlsx(metadata),
+        _ => export_metadata_with_options(metadata, format, base_dir, options)
+            .map(String::into_bytes),
+    }
+}
+
+/// Canonical N-Quads (RDFC-1.0) of the metadata graph
+///
+/// The output is identical for metadata describing the same graph, and does
+/// not depend on where the metadata is stored, so it can be hashed or signed.
+/// Requires the `rdf` feature.
+#[cfg(feature = "rdf")]
+pub fn canonical_nquads(metadata: &Metadata) -> Result<String> {
+    crate::croissant::rdf::canonicalize(&serde_json::to_string(metadata)?)
+}
+
+/// Canonical N-Quads (RDFC-1.0) of the metadata graph; always fails, since
+/// rustcroissant was built without the `rdf` feature
+#[cfg(not(feature = "rdf"))]
+pub fn canonical_nquads(_metadata: &Metadata) -> Result<String> {
+    Err(Error::new(
+        "Canonical output requires rustcroissant to be built with the rdf feature",
+    ))
+}
+
+/// RDF serialization requested from [`rdf_export`]; mirrors
+/// `croissant::rdf::RdfFormat`, which only exists with the `rdf` feature
+enum RdfExport {
+    Turtle,
+    NQuads,
+}
+
+#[cfg(feature = "rdf")]
+fn rdf_export(json_ld: &str, base_dir: Option<&Path>, format: RdfExport) -> Result<String> {
+    use crate::croissant::rdf::{RdfFormat, json_ld_to_rdf};
+
+    let format = match format {
+        RdfExport::Turtle => RdfFormat::Turtle,
+        RdfExport::NQuads => RdfFormat::NQuads,
+    };
+    json_ld_to_rdf(json_ld, &base_iri(base_dir), format)
+}
+
+#[cfg(not(feature = "rdf"))]
+fn rdf_export(_json_ld: &str, _base_dir: Option<&Path>, _format: RdfExport) -> Result<String> {
+    Err(Error::new(
+        "RDF export requires rustcroissant to be built with the rdf feature",
+    ))
+}
+
+/// `file:` IRI of the metadata directory, against which relative IRIs in
+/// the document are resolved
+#[cfg(feature = "rdf")]
+fn base_iri(base_dir: Option<&Path>) -> String {
+    let dir = base_dir
+        .and_then(|dir| {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            dir.canonicalize().ok()
+        })
+        .unwrap_or_default();
+
+    let mut iri = String::from("file://");
+    for byte in dir.to_string_lossy().trim_end_matches('/').bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            iri.push(byte as char);
+        } else {
+            iri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    iri.push('/');
+    iri
+}
@@ -0,0 +1,222 @@
+//! SQL DDL for loading record sets into a database
+//!
+//! Each record set becomes a `CREATE TABLE` statement with one column per
+//! field, typed from the field's data type. The fields of a record set's
+//! `key` make up its primary key, and fields that reference a field of
+//! another record set get a foreign key constraint; tables are created
+//! after those they reference, so the constraints can be checked.
+
+use crate::croissant::core::{DataType, Metadata, RecordSet};
+use crate::croissant::errors::{Error, Result};
+use std::fmt::Write;
+
+/// SQL dialects DDL can be written for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SqlDialect {
+    #[default]
+    Postgres,
+    Sqlite,
+    DuckDb,
+}
+
+impl SqlDialect {
+    /// All dialects, in the order they are listed to users
+    pub const ALL: &'static [SqlDialect] =
+        &[SqlDialect::Postgres, SqlDialect::Sqlite, SqlDialect::DuckDb];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            SqlDialect::Postgres => "postgres",
+            SqlDialect::Sqlite => "sqlite",
+            SqlDialect::DuckDb => "duckdb",
+        }
+    }
+
+    /// Parse a command-line dialect name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|dialect| dialect.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown SQL dialect: {name}")))
+    }
+
+    /// Column type for a Croissant data type
+    ///
+    /// SQLite has no boolean or date types; booleans are stored as 0/1
+    /// integers and dates and times as ISO 8601 text.
+    pub fn column_type(&self, data_type: &DataType) -> &'static str {
+        match (self, data_type) {
+            (SqlDialect::Postgres, DataType::Integer) => "BIGINT",
+            (SqlDialect::Postgres, DataType::Float) => "DOUBLE PRECISION",
+            (SqlDialect::Postgres, DataType::Boolean) => "BOOLEAN",
+            (SqlDialect::Postgres, DataType::Date) => "DATE",
+            (SqlDialect::Postgres, DataType::DateTime) => "TIMESTAMP",
+            (SqlDialect::Postgres, DataType::Time) => "TIME",
+            (SqlDialect::Postgres, DataType::Text | DataType::Url) => "TEXT",
+            (SqlDialect::Sqlite, DataType::Integer | DataType::Boolean) => "INTEGER",
+            (SqlDialect::Sqlite, DataType::Float) => "REAL",
+            (
+                SqlDialect::Sqlite,
+                DataType::Date
+                | DataType::DateTime
+                | DataType::Time
+                | DataType::Text
+                | DataType::Url,
+            ) => "TEXT",
+            (SqlDialect::DuckDb, DataType::Integer) => "BIGINT",
+            (SqlDialect::DuckDb, DataType::Float) => "DOUBLE",
+            (SqlDialect::DuckDb, DataType::Boolean) => "BOOLEAN",
+            (SqlDialect::DuckDb, DataType::Date) => "DATE",
+            (SqlDialect::DuckDb, DataType::DateTime) => "TIMESTAMP",
+            (SqlDialect::DuckDb, DataType::Time) => "TIME",
+            (SqlDialect::DuckDb, DataType::Text | DataType::Url) => "VARCHAR",
+        }
+    }
+}
+
+/// `CREATE TABLE` statements for every record set of the metadata
+///
+/// Descriptions are kept as SQL comments; fields with an unknown data type
+/// are typed as text.
+pub fn create_tables(metadata: &Metadata, dialect: SqlDialect) -> String {
+    let mut ddl = String::new();
+    write_ddl(&mut ddl, metadata, dialect).expect("writing to a String cannot fail");
+    ddl
+}
+
+fn write_ddl(ddl: &mut String, metadata: &Metadata, dialect: SqlDialect) -> std::fmt::Result {
+    writeln!(
+        ddl,
+        "-- {} ({} DDL generated from Croissant metadata)",
+        comment(&metadata.name),
+        dialect.name()
+    )?;
+
+    for record_set in creation_order(metadata) {
+        writeln!(ddl)?;
+        writeln!(ddl, "-- {}", comment(&record_set.description))?;
+        writeln!(ddl, "CREATE TABLE {} (", quote_identifier(&record_set.name))?;
+        let constraints = constraints(metadata, record_set);
+        let width = record_set
+            .field
+            .iter()
+            .map(|field| quote_identifier(&field.name).chars().count())
+            .max()
+            .unwrap_or(0);
+        for (index, field) in record_set.field.iter().enumerate() {
+            let data_type = DataType::from_schema_org(&field.data_type).unwrap_or(DataType::Text);
+            let separator = if index + 1 < record_set.field.len() || !constraints.is_empty() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(
+                ddl,
+                "    {:width$} {}{separator} -- {}",
+                quote_identifier(&field.name),
+                dialect.column_type(&data_type),
+                comment(&field.description)
+            )?;
+        }
+        if !constraints.is_empty() {
+            writeln!(ddl, "    {}", constraints.join(",\n    "))?;
+        }
+        writeln!(ddl, ");")?;
+    }
+    Ok(())
+}
+
+/// Record sets in the order their tables are created: each after those it
+/// references, and otherwise in document order
+fn creation_order(metadata: &Metadata) -> Vec<&RecordSet> {
+    fn visit<'a>(
+        metadata: &'a Metadata,
+        record_set: &'a RecordSet,
+        order: &mut Vec<&'a RecordSet>,
+    ) {
+        if order.iter().any(|visited| visited.id == record_set.id) {
+            return;
+        }
+        order.push(record_set);
+        for field in &record_set.field {
+            if let Some((referenced, _)) = field
+                .references
+                .as_ref()
+                .and_then(|reference| metadata.find_field(&reference.field.id))
+            {
+                visit(metadata, referenced, order);
+            }
+        }
+        // Referenced record sets were visited after this one, so move it
+        // behind them
+        let position = order
+            .iter()
+            .position(|visited| visited.id == record_set.id)
+            .expect("record set was visited");
+        let record_set = order.remove(position);
+        order.push(record_set);
+    }
+
+    let mut order = Vec::new();
+    for record_set in &metadata.record_set {
+        visit(metadata, record_set, &mut order);
+    }
+    order
+}
+
+/// Primary and foreign key constraints of a record set's table
+fn constraints(metadata: &Metadata, record_set: &RecordSet) -> Vec<String> {
+    let mut constraints = Vec::new();
+    let key = record_set
+        .key
+        .iter()
+        .map(|key| {
+            record_set
+                .field
+                .iter()
+                .find(|field| field.id == key.id)
+                .map(|field| quote_identifier(&field.name))
+        })
+        .collect::<Option<Vec<_>>>();
+    match key {
+        Some(key) if !key.is_empty() => {
+            constraints.push(format!("PRIMARY KEY ({})", key.join(", ")))
+        }
+        Some(_) => {}
+        None => tracing::warn!(
+            record_set = %record_set.name,
+            "key names a field the record set does not have; no primary key written"
+        ),
+    }
+
+    for field in &record_set.field {
+        let Some(reference) = &field.references else {
+            continue;
+        };
+        match metadata.find_field(&reference.field.id) {
+            Some((referenced_set, referenced)) => constraints.push(format!(
+                "FOREIGN KEY ({}) REFERENCES {} ({})",
+                quote_identifier(&field.name),
+                quote_identifier(&referenced_set.name),
+                quote_identifier(&referenced.name)
+            )),
+            None => tracing::warn!(
+                field = %field.name,
+                reference = %reference.field.id,
+                "referenced field not found; no foreign key written"
+            ),
+        }
+    }
+    constraints
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Text safe to put in a `--` comment
+fn comment(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
@@ -0,0 +1,167 @@
+//! Files of file sets
+//!
+//! A `cr:FileSet` holds the files matching one of its `includes` glob
+//! patterns and none of its `excludes`. Patterns are matched against paths
+//! with `/` separators, relative to the directory the file set is contained
+//! in: `*` and `?` match within a path segment, `**/` matches any number of
+//! directories, `[...]` a character class and `{a,b}` either alternative.
+//!
+//! Only local directories are listed; file sets contained in archives or
+//! remote locations are not.
+
+use crate::croissant::core::{Distribution, Metadata};
+use crate::croissant::errors::{Error, Result};
+use regex::Regex;
+use std::path::Path;
+
+/// Translate a glob pattern into a regular expression matching whole paths
+pub fn glob_regex(pattern: &str) -> Result<Regex> {
+    let invalid = |reason: &str| Error::invalid_format(format!("Invalid glob {pattern}: {reason}"));
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    let mut in_alternatives = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:[^/]*/)*");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                let mut class = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') if !class.is_empty() => break,
+                        Some(c) => class.push(c),
+                        None => return Err(invalid("unclosed [")),
+                    }
+                }
+                let class = match class.strip_prefix('!') {
+                    Some(rest) => format!("^{rest}"),
+                    None => class,
+                };
+                regex.push('[');
+                regex.push_str(&class.replace('\\', "\\\\"));
+                regex.push(']');
+            }
+            '{' if !in_alternatives => {
+                in_alternatives = true;
+                regex.push_str("(?:");
+            }
+            ',' if in_alternatives => regex.push('|'),
+            '}' if in_alternatives => {
+                in_alternatives = false;
+                regex.push(')');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    if in_alternatives {
+        return Err(invalid("unclosed {"));
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|e| invalid(&e.to_string()))
+}
+
+/// Files of a file set, as paths relative to `base_dir` with `/`
+/// separators, sorted
+///
+/// A file set contained in other distributions is listed in each of them
+/// that is a local directory; one that is not is listed below `base_dir`.
+pub fn list_files(
+    metadata: &Metadata,
+    file_set: &Distribution,
+    base_dir: &Path,
+) -> Result<Vec<String>> {
+    let includes = file_set
+        .includes
+        .iter()
+        .map(|pattern| glob_regex(pattern))
+        .collect::<Result<Vec<_>>>()?;
+    let excludes = file_set
+        .excludes
+        .iter()
+        .map(|pattern| glob_regex(pattern))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut roots = Vec::new();
+    for container in &file_set.contained_in {
+        let distribution = metadata
+            .distribution
+            .iter()
+            .find(|d| d.id == container.id)
+            .ok_or_else(|| Error::missing_field(format!("distribution {}", container.id)))?;
+        let dir = base_dir.join(&distribution.content_url);
+        if distribution.content_url.contains("://") || !dir.is_dir() {
+            return Err(Error::invalid_format(format!(
+                "FileSet {} is contained in {}, which is not a local directory",
+                file_set.id, distribution.id
+            )));
+        }
+        roots.push(distribution.content_url.trim_end_matches('/').to_string());
+    }
+    if roots.is_empty() {
+        roots.push(String::new());
+    }
+
+    let mut files = Vec::new();
+    for root in roots {
+        let dir = base_dir.join(&root);
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir.as_path()
+        };
+        files.extend(
+            list_dir(dir)?
+                .into_iter()
+                .filter(|path| {
+                    includes.iter().any(|glob| glob.is_match(path))
+                        && !excludes.iter().any(|glob| glob.is_match(path))
+                })
+                .map(|path| match root.as_str() {
+                    "" => path,
+                    root => format!("{root}/{path}"),
+                }),
+        );
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Files in `dir` and its subdirectories, as paths relative to it with `/`
+/// separators, sorted; hidden files and directories are skipped
+pub fn list_dir(dir: &Path) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    walk(dir, String::new(), &mut paths)?;
+    paths.sort();
+    Ok(paths)
+}
+
+/// Collect the paths of the files below `dir`, prefixed with `prefix`;
+/// hidden files and directories are skipped
+fn walk(dir: &Path, prefix: String, paths: &mut Vec<String>) -> Result<()> {
+    let entries = std::fs::read_dir(dir).map_err(|_| Error::file_not_found(dir))?;
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let relative = format!("{prefix}{name}");
+        if path.is_dir() {
+            walk(&path, format!("{relative}/"), paths)?;
+        } else {
+            paths.push(relative);
+        }
+    }
+    Ok(())
+}
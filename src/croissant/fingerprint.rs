@@ -0,0 +1,152 @@
+//! Content fingerprints of record sets
+//!
+//! A fingerprint is a SHA-256 digest of the materialized records of a record
+//! set rather than of its files. Records are normalized before hashing:
+//! fields are taken in name order, values in their typed form (so `1.50` and
+//! `1.5` in a Float column are the same, and dates are ISO 8601 whatever
+//! their format in the file), and the order of the records does not matter.
+//! Re-encoding, re-compressing, re-sorting or converting the files of a
+//! dataset therefore keeps its fingerprints, while any change to a value, a
+//! field's name or its dataType changes them.
+
+use crate::croissant::errors::Result;
+use crate::croissant::records::{RecordField, RecordReader, Value};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Version of the normalization, hashed into every fingerprint so that
+/// fingerprints computed differently are never confused
+const FINGERPRINT_VERSION: &str = "croissant-records-v1";
+
+/// Fingerprint of the records of a record set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fingerprint {
+    pub records: u64,
+    /// Hex-encoded SHA-256 digest
+    pub sha256: String,
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.sha256)
+    }
+}
+
+/// Compute the fingerprint of every record of `reader`
+///
+/// Each record is hashed on its own and the digests are added up modulo
+/// 2^256, which makes the result independent of record order while still
+/// counting duplicate records.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn record_set_fingerprint(reader: RecordReader) -> Result<Fingerprint> {
+    let mut order = (0..reader.fields().len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| reader.fields()[a].name.cmp(&reader.fields()[b].name));
+    let mut schema = String::new();
+    for &index in &order {
+        write_field(&mut schema, &reader.fields()[index]);
+    }
+
+    let mut records = 0u64;
+    let mut sum = [0u8; 32];
+    let mut buffer = String::new();
+    for record in reader {
+        let record = record?;
+        buffer.clear();
+        for &index in &order {
+            write_value(&mut buffer, &record[index]);
+            buffer.push('\n');
+        }
+        add_digest(&mut sum, &Sha256::digest(buffer.as_bytes()).into());
+        records += 1;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(FINGERPRINT_VERSION.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(schema.as_bytes());
+    hasher.update(format!("\n{records}\n").as_bytes());
+    hasher.update(sum);
+    Ok(Fingerprint {
+        records,
+        sha256: hex::encode(hasher.finalize()),
+    })
+}
+
+/// Add a digest to a running sum, as big-endian integers modulo 2^256
+fn add_digest(sum: &mut [u8; 32], digest: &[u8; 32]) {
+    let mut carry = 0u16;
+    for (total, byte) in sum.iter_mut().zip(digest).rev() {
+        let added = u16::from(*total) + u16::from(*byte) + carry;
+        *total = added as u8;
+        carry = added >> 8;
+    }
+}
+
+/// Normalized description of a field: its name, dataType and subfields
+fn write_field(out: &mut String, field: &RecordField) {
+    out.push_str(&serde_json::Value::from(field.name.as_str()).to_string());
+    out.push(':');
+    if field.repeated {
+        out.push('[');
+    }
+    if field.fields.is_empty() {
+        out.push_str(field.data_type.to_schema_org());
+    } else {
+        let mut fields = field.fields.iter().collect::<Vec<_>>();
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        out.push('{');
+        for (index, field) in fields.into_iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+            write_field(out, field);
+        }
+        out.push('}');
+    }
+    if field.repeated {
+        out.push(']');
+    }
+    out.push(';');
+}
+
+/// Normalized text of a value: JSON, with object members in name order,
+/// floats always written with a decimal point and negative zero as zero
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Float(n) if n.is_finite() => {
+            let n = if *n == 0.0 { 0.0 } else { *n };
+            out.push_str(&format!("{n:?}"));
+        }
+        Value::Float(n) => out.push_str(&format!("\"{n}\"")),
+        Value::Boolean(_) | Value::Integer(_) => out.push_str(&value.to_string()),
+        Value::Date(_) | Value::DateTime(_) | Value::Time(_) | Value::Text(_) => {
+            out.push_str(&serde_json::Value::from(value.to_string()).to_string());
+        }
+        Value::List(values) => {
+            out.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_value(out, value);
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            let mut members = members.iter().collect::<Vec<_>>();
+            members.sort_by(|(a, _), (b, _)| a.cmp(b));
+            out.push('{');
+            for (index, (name, value)) in members.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::from(name.as_str()).to_string());
+                out.push(':');
+                write_value(out, value);
+            }
+            out.push('}');
+        }
+    }
+}
@@ -1,104 +1,1539 @@
-use chrono::Utc;
-
+use crate::croissant::builder::{
+    DistributionBuilder, FieldBuilder, MetadataBuilder, RecordSetBuilder,
+};
+use crate::croissant::cache::HashCache;
 use crate::croissant::core::{
-    DataType, Distribution, Extract, Field, FieldSource, FileObject, Metadata, RecordSet,
-    create_default_context, infer_data_type,
+    Creator, DataType, Distribution, Extract, Field, FieldId, FieldReference, FieldSource,
+    FileObject, FileProperty, InferenceOptions, Metadata, Rai, RecordSet, create_default_context,
 };
+use crate::croissant::dictionary::FieldDescriptionProvider;
+use crate::croissant::encoding::TextEncoding;
 use crate::croissant::errors::{Error, Result};
-use crate::croissant::utils::{calculate_sha256, get_csv_columns};
-use std::path::Path;
+use crate::croissant::fileset::list_dir;
+use crate::croissant::jsonld::{JsonLdForm, to_form};
+use crate::croissant::records::{RecordOptions, read_records_with_options};
+use crate::croissant::stats::record_statistics;
+use crate::croissant::units::{HeaderUnit, unit_from_header};
+use crate::croissant::utils::{
+    CsvSchema, DELIMITED_FORMATS, calculate_sha256, delimited_encoding_format, detect_delimiter,
+    detect_file_encoding, get_file_extension, infer_csv_schema, is_parquet_path, is_xlsx_path,
+    is_yaml_path, media_type, parallel_map, publication_date,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Options controlling metadata generation
+#[derive(Clone, Default)]
+pub struct GenerateOptions<'a> {
+    /// Source of field descriptions, units and semantic types
+    pub descriptions: Option<&'a dyn FieldDescriptionProvider>,
+    /// Data type inference settings
+    pub inference: InferenceOptions,
+    /// Cache used to skip rehashing unchanged files
+    pub hash_cache: Option<&'a HashCache>,
+    /// Also record a (non-standard) BLAKE3 digest of each distribution;
+    /// requires the `blake3` feature
+    pub blake3: bool,
+    /// JSON-LD form the metadata file is written in
+    pub jsonld_form: JsonLdForm,
+    /// Write minified rather than pretty-printed JSON
+    pub minified: bool,
+    /// Name fields after their column without the unit found in its header,
+    /// such as `weight` for `weight (kg)`; the unit is recorded as
+    /// `unitText` either way
+    pub strip_unit_names: bool,
+    /// How field names become the `@id`s of fields
+    pub id_strategy: IdStrategy,
+    /// Record the character encoding of the CSV file in the description of
+    /// its distribution
+    pub record_encoding: bool,
+    /// Read every record of local CSV files once more, streaming, to
+    /// record the statistics of each field's values in its `statistics`
+    pub statistics: bool,
+    /// How the content URLs of local files are written
+    pub content_urls: ContentUrlStyle,
+    /// Dataset properties set instead of the generated ones
+    pub dataset: DatasetProperties,
+    /// Fail rather than replace output files that already exist
+    pub keep_existing: bool,
+    /// Generate metadata without writing output files or creating output
+    /// directories
+    pub dry_run: bool,
+}
+
+/// How the content URLs of local files are written
+///
+/// Files read from URLs keep their URL whatever the style.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ContentUrlStyle {
+    /// The file name, for metadata kept next to its file
+    #[default]
+    FileName,
+    /// The path of the file relative to the directory of the metadata file,
+    /// or to the working directory when no metadata file is written
+    RelativeToOutput,
+    /// The percent-encoded file name appended to a base URL, such as
+    /// `https://data.example.org/v2/`, where the files are published
+    BaseUrl(String),
+}
+
+impl ContentUrlStyle {
+    /// Content URL of the local file `path` described by metadata written
+    /// to `output_path`
+    pub fn content_url(&self, path: &Path, output_path: Option<&Path>) -> Result<String> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| Error::invalid_format("Invalid file path"))?
+            .to_string_lossy();
+        Ok(match self {
+            ContentUrlStyle::FileName => file_name.to_string(),
+            ContentUrlStyle::BaseUrl(base) => {
+                let separator = if base.ends_with('/') { "" } else { "/" };
+                format!("{base}{separator}{}", percent_encode(&file_name))
+            }
+            ContentUrlStyle::RelativeToOutput => {
+                let current_dir = std::env::current_dir()?;
+                let metadata_dir = match output_path.and_then(Path::parent) {
+                    Some(dir) => current_dir.join(dir),
+                    None => current_dir.clone(),
+                };
+                relative_path(&current_dir.join(path), &metadata_dir)
+            }
+        })
+    }
+}
+
+/// Path from `dir` to `path`, both absolute, with `/` separators
+fn relative_path(path: &Path, dir: &Path) -> String {
+    use std::path::Component;
+
+    let path = path_clean::clean(path);
+    let dir = path_clean::clean(dir);
+    let path_components = path.components().collect::<Vec<_>>();
+    let dir_components = dir.components().collect::<Vec<_>>();
+    let common = path_components
+        .iter()
+        .zip(&dir_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+    std::iter::repeat_n("..".to_string(), dir_components.len() - common)
+        .chain(
+            path_components[common..]
+                .iter()
+                .filter_map(|component| match component {
+                    Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                    _ => None,
+                }),
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Dataset properties given at generation time
+///
+/// Each property that is set replaces the one generated from the CSV file;
+/// license, creators, URL, keywords and citation are otherwise left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DatasetProperties {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    /// Publication date as `YYYY-MM-DD`, instead of that of
+    /// [`publication_date`](crate::croissant::utils::publication_date)
+    pub date_published: Option<String>,
+    pub license: Vec<String>,
+    pub url: Option<String>,
+    pub creator: Vec<Creator>,
+    pub keywords: Vec<String>,
+    /// Citation of the dataset, such as a BibTeX entry
+    pub cite_as: Option<String>,
+}
+
+impl DatasetProperties {
+    /// Set the given properties on the metadata
+    pub fn apply(&self, metadata: &mut Metadata) {
+        if let Some(name) = &self.name {
+            metadata.name = name.clone();
+        }
+        if let Some(description) = &self.description {
+            metadata.description = description.clone();
+        }
+        if let Some(version) = &self.version {
+            metadata.version = version.clone();
+        }
+        if let Some(date_published) = &self.date_published {
+            metadata.date_published = date_published.clone();
+        }
+        if !self.license.is_empty() {
+            metadata.license = self.license.clone();
+        }
+        if self.url.is_some() {
+            metadata.url = self.url.clone();
+        }
+        if !self.creator.is_empty() {
+            metadata.creator = self.creator.clone();
+        }
+        if !self.keywords.is_empty() {
+            metadata.keywords = self.keywords.clone();
+        }
+        if self.cite_as.is_some() {
+            metadata.cite_as = self.cite_as.clone();
+        }
+    }
+}
+
+/// How field names, taken from column headers, become `@id`s
+///
+/// Whatever the strategy, fields keep the header as their `name` (unless
+/// units are stripped from it) and as the column they are extracted from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// Use names as they are, spaces and slashes included
+    #[default]
+    Keep,
+    /// Lowercase names, drop accents from Latin letters and replace each run
+    /// of other characters than ASCII letters and digits with `_`,
+    /// numbering names that become the same
+    Slugify,
+    /// Percent-encode the UTF-8 bytes of characters other than ASCII
+    /// letters, digits, `-`, `.`, `_` and `~`
+    PercentEncode,
+    /// Fail on names holding such characters
+    Reject,
+}
+
+impl IdStrategy {
+    /// All strategies, in the order they are listed to users
+    pub const ALL: &'static [IdStrategy] = &[
+        IdStrategy::Keep,
+        IdStrategy::Slugify,
+        IdStrategy::PercentEncode,
+        IdStrategy::Reject,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            IdStrategy::Keep => "keep",
+            IdStrategy::Slugify => "slugify",
+            IdStrategy::PercentEncode => "percent-encode",
+            IdStrategy::Reject => "reject",
+        }
+    }
+
+    /// Parse a command-line strategy name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|strategy| strategy.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown id strategy: {name}")))
+    }
+
+    /// `@id` segments for the given field names, in the same order
+    pub fn id_segments(&self, names: &[String]) -> Result<Vec<String>> {
+        match self {
+            IdStrategy::Keep => Ok(names.to_vec()),
+            IdStrategy::Slugify => {
+                let mut seen = HashSet::new();
+                Ok(names
+                    .iter()
+                    .map(|name| {
+                        let slug = slugify(name);
+                        let mut segment = slug.clone();
+                        let mut number = 1;
+                        while !seen.insert(segment.clone()) {
+                            number += 1;
+                            segment = format!("{slug}_{number}");
+                        }
+                        segment
+                    })
+                    .collect())
+            }
+            IdStrategy::PercentEncode => {
+                Ok(names.iter().map(|name| percent_encode(name)).collect())
+            }
+            IdStrategy::Reject => names
+                .iter()
+                .map(|name| {
+                    if !name.is_empty() && name.chars().all(is_id_char) {
+                        Ok(name.clone())
+                    } else {
+                        Err(Error::invalid_format(format!(
+                            "Column name {name:?} cannot be used in an @id as is; \
+                             slugify or percent-encode it instead"
+                        )))
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// ASCII letters for a lowercase accented Latin letter, so that slugs of
+/// names such as `température` stay readable
+fn fold_accent(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => "a",
+        'æ' => "ae",
+        'ç' => "c",
+        'è' | 'é' | 'ê' | 'ë' => "e",
+        'ì' | 'í' | 'î' | 'ï' => "i",
+        'ñ' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => "o",
+        'œ' => "oe",
+        'ß' => "ss",
+        'ù' | 'ú' | 'û' | 'ü' => "u",
+        'ý' | 'ÿ' => "y",
+        _ => return None,
+    })
+}
+
+/// Percent-encode the UTF-8 bytes of characters other than ASCII letters,
+/// digits, `-`, `.`, `_` and `~`
+fn percent_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| {
+            if is_id_char(byte.into()) {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+/// Characters left as they are in `@id` segments
+fn is_id_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~')
+}
+
+fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if let Some(folded) = fold_accent(c) {
+            slug.push_str(folded);
+        } else if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    let slug = slug.trim_end_matches('_');
+    if slug.is_empty() {
+        "field".to_string()
+    } else {
+        slug.to_string()
+    }
+}
 
 /// Generate Croissant metadata from a CSV file
 pub fn generate_metadata_from_csv(csv_path: &Path, output_path: Option<&Path>) -> Result<Metadata> {
-    // Get file information
-    let file_name = csv_path
+    generate_metadata_from_csv_with_options(csv_path, output_path, &GenerateOptions::default())
+}
+
+/// Generate Croissant metadata from a CSV file, taking field descriptions,
+/// units and semantic types from the given provider when available
+pub fn generate_metadata_from_csv_with_descriptions(
+    csv_path: &Path,
+    output_path: Option<&Path>,
+    descriptions: Option<&dyn FieldDescriptionProvider>,
+) -> Result<Metadata> {
+    let options = GenerateOptions {
+        descriptions,
+        ..Default::default()
+    };
+    generate_metadata_from_csv_with_options(csv_path, output_path, &options)
+}
+
+/// Generate Croissant metadata from a CSV file with the given options
+#[tracing::instrument(skip_all, fields(path = %csv_path.display()))]
+pub fn generate_metadata_from_csv_with_options(
+    csv_path: &Path,
+    output_path: Option<&Path>,
+    options: &GenerateOptions,
+) -> Result<Metadata> {
+    check_output(output_path, options)?;
+
+    // Get column information and infer data types from sampled rows
+    let delimiter = match options.inference.delimiter {
+        Some(delimiter) => delimiter,
+        None => detect_delimiter(csv_path)?,
+    };
+    let schema = infer_csv_schema(csv_path, delimiter, &options.inference)?;
+
+    tracing::debug!(columns = schema.headers.len(), "read CSV header");
+
+    let source = SourceFile {
+        delimiter: Some(delimiter),
+        ..local_source_file(
+            csv_path,
+            output_path,
+            options,
+            delimited_encoding_format(delimiter),
+            detect_file_encoding(csv_path)?,
+        )?
+    };
+    let mut metadata = build_metadata(source, &schema, options)?;
+    if options.statistics {
+        add_statistics(&mut metadata, csv_path, options)?;
+    }
+
+    // Write metadata to file if output path is provided, unless on a dry run
+    write_output(&metadata, output_path, options)?;
+
+    Ok(metadata)
+}
+
+/// Generate Croissant metadata from a CSV, Parquet or Excel file with the
+/// given options
+///
+/// Parquet files, told apart by their `.parquet` extension, are described
+/// from the schema in their footer instead of sampled rows; reading them
+/// requires the `parquet` feature. Excel workbooks (`.xlsx`, with the
+/// `xlsx` feature) get a record set per sheet.
+pub fn generate_metadata_from_file(
+    path: &Path,
+    output_path: Option<&Path>,
+    options: &GenerateOptions,
+) -> Result<Metadata> {
+    if options.statistics && (is_parquet_path(path) || is_xlsx_path(path)) {
+        tracing::warn!(path = %path.display(), "statistics are only recorded for CSV files");
+    }
+    if is_parquet_path(path) {
+        generate_metadata_from_parquet(path, output_path, options)
+    } else if is_xlsx_path(path) {
+        generate_metadata_from_xlsx(path, output_path, options)
+    } else {
+        generate_metadata_from_csv_with_options(path, output_path, options)
+    }
+}
+
+/// Record the statistics of the fields of the main record set, streaming
+/// every record of the CSV file it was generated from once
+///
+/// Memory stays bounded whatever the size of the file, as distinct values
+/// are only counted exactly up to
+/// [`EXACT_DISTINCT_LIMIT`](crate::croissant::stats::EXACT_DISTINCT_LIMIT).
+#[tracing::instrument(level = "debug", skip_all)]
+fn add_statistics(
+    metadata: &mut Metadata,
+    csv_path: &Path,
+    options: &GenerateOptions,
+) -> Result<()> {
+    // The content URL may be relative to the output or a published URL;
+    // records are read from the file itself
+    let mut local = metadata.clone();
+    local.distribution[0].content_url = csv_path.to_string_lossy().into_owned();
+    let record_options = RecordOptions {
+        on_bad_row: options.inference.on_bad_row,
+        ..RecordOptions::default()
+    };
+    let reader = read_records_with_options(&local, "main", Path::new(""), &record_options)?;
+    let statistics = record_statistics(reader)?;
+    tracing::debug!(records = statistics.records, "computed statistics");
+
+    let record_set = metadata
+        .record_set
+        .iter_mut()
+        .find(|record_set| record_set.id == "main")
+        .expect("generated metadata has a main record set");
+    for (field, statistics) in record_set.field.iter_mut().zip(&statistics.fields) {
+        field.statistics = Some(statistics.into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+#[tracing::instrument(skip_all, fields(path = %parquet_path.display()))]
+fn generate_metadata_from_parquet(
+    parquet_path: &Path,
+    output_path: Option<&Path>,
+    options: &GenerateOptions,
+) -> Result<Metadata> {
+    use crate::croissant::parquet::{PARQUET_ENCODING_FORMAT, read_parquet_schema};
+
+    check_output(output_path, options)?;
+
+    let schema = read_parquet_schema(parquet_path)?;
+
+    tracing::debug!(columns = schema.headers.len(), "read Parquet schema");
+
+    let source = local_source_file(
+        parquet_path,
+        output_path,
+        options,
+        PARQUET_ENCODING_FORMAT,
+        TextEncoding::Utf8,
+    )?;
+    let metadata = build_metadata(source, &schema, options)?;
+
+    write_output(&metadata, output_path, options)?;
+
+    Ok(metadata)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn generate_metadata_from_parquet(
+    _parquet_path: &Path,
+    _output_path: Option<&Path>,
+    _options: &GenerateOptions,
+) -> Result<Metadata> {
+    Err(Error::new(
+        "Parquet input requires rustcroissant to be built with the parquet feature",
+    ))
+}
+
+/// Metadata for an Excel workbook: one distribution, and a record set per
+/// sheet named after it, with the sheet's enumerations
+#[cfg(feature = "xlsx")]
+#[tracing::instrument(skip_all, fields(path = %xlsx_path.display()))]
+fn generate_metadata_from_xlsx(
+    xlsx_path: &Path,
+    output_path: Option<&Path>,
+    options: &GenerateOptions,
+) -> Result<Metadata> {
+    use crate::croissant::xlsx::{XLSX_ENCODING_FORMAT, read_xlsx_schemas};
+
+    check_output(output_path, options)?;
+
+    let sheets = read_xlsx_schemas(xlsx_path, &options.inference)?;
+
+    tracing::debug!(sheets = sheets.len(), "read workbook");
+
+    let source = local_source_file(
+        xlsx_path,
+        output_path,
+        options,
+        XLSX_ENCODING_FORMAT,
+        TextEncoding::Utf8,
+    )?;
+    let file_name = source.name.clone();
+    let mut merged: Option<Metadata> = None;
+    let mut record_set_ids = HashSet::new();
+    for (sheet, schema) in &sheets {
+        let mut part = build_metadata(source.clone(), schema, options)?;
+        let renamed = record_set_renames(&part.record_set, &slugify(sheet), &mut record_set_ids);
+        for record_set in &mut part.record_set {
+            let is_sheet = record_set.id == "main";
+            rename_record_set(record_set, &renamed, &HashMap::new());
+            if is_sheet {
+                record_set.name = sheet.clone();
+                record_set.description = format!("Records from sheet {sheet} of {file_name}");
+            }
+        }
+        match &mut merged {
+            Some(metadata) => metadata.record_set.append(&mut part.record_set),
+            None => merged = Some(part),
+        }
+    }
+    let metadata = merged.expect("workbooks read have a sheet");
+
+    write_output(&metadata, output_path, options)?;
+
+    Ok(metadata)
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn generate_metadata_from_xlsx(
+    _xlsx_path: &Path,
+    _output_path: Option<&Path>,
+    _options: &GenerateOptions,
+) -> Result<Metadata> {
+    Err(Error::new(
+        "Excel input requires rustcroissant to be built with the xlsx feature",
+    ))
+}
+
+/// Name, size, digests and content URL of a local data file
+fn local_source_file(
+    path: &Path,
+    output_path: Option<&Path>,
+    options: &GenerateOptions,
+    encoding_format: &str,
+    encoding: TextEncoding,
+) -> Result<SourceFile> {
+    let name = path
         .file_name()
         .ok_or_else(|| Error::invalid_format("Invalid file path"))?
         .to_string_lossy()
         .to_string();
 
-    let file_info = std::fs::metadata(csv_path).map_err(|_| Error::file_not_found(csv_path))?;
-    let file_size = file_info.len();
+    let size = std::fs::metadata(path)
+        .map_err(|_| Error::file_not_found(path))?
+        .len();
+
+    let sha256 = match options.hash_cache {
+        Some(cache) => cache.sha256(path)?,
+        None => calculate_sha256(path)?,
+    };
+
+    let blake3 = if options.blake3 {
+        Some(blake3_digest(path)?)
+    } else {
+        None
+    };
+
+    Ok(SourceFile {
+        content_url: options.content_urls.content_url(path, output_path)?,
+        name,
+        size,
+        sha256,
+        blake3,
+        encoding_format: encoding_format.to_string(),
+        delimiter: None,
+        encoding,
+    })
+}
+
+/// Generate Croissant metadata from a CSV object in object storage
+///
+/// The object is streamed once: it is hashed in full while its leading
+/// rows are kept for type inference. Its URL becomes the distribution's
+/// content URL.
+#[cfg(feature = "object-store")]
+#[tracing::instrument(skip_all, fields(url = %url))]
+pub async fn generate_metadata_from_object(
+    url: &str,
+    output_path: Option<&Path>,
+    options: &GenerateOptions<'_>,
+) -> Result<Metadata> {
+    use crate::croissant::remote::cloud;
+
+    reject_remote_blake3(options)?;
+    check_output(output_path, options)?;
+    let sample = cloud::fetch_sample(url, options.inference.row_limit().saturating_add(1)).await?;
+    metadata_from_sample(sample, output_path, options)
+}
+
+/// Generate Croissant metadata from a CSV file on an SFTP or FTP server
+///
+/// Like [`generate_metadata_from_object`], the file is streamed once for
+/// hashing and type inference. The transfer blocks the calling thread.
+#[cfg(feature = "sftp")]
+#[tracing::instrument(skip_all, fields(url = %url))]
+pub fn generate_metadata_from_ftp(
+    url: &str,
+    output_path: Option<&Path>,
+    options: &GenerateOptions<'_>,
+) -> Result<Metadata> {
+    use crate::croissant::remote::ftp;
+
+    reject_remote_blake3(options)?;
+    check_output(output_path, options)?;
+    let sample = ftp::fetch_sample(url, options.inference.row_limit().saturating_add(1))?;
+    metadata_from_sample(sample, output_path, options)
+}
 
-    // Calculate SHA-256 hash
-    let file_sha256 = calculate_sha256(csv_path)?;
+#[cfg(any(feature = "object-store", feature = "sftp"))]
+fn reject_remote_blake3(options: &GenerateOptions) -> Result<()> {
+    if options.blake3 {
+        return Err(Error::new(
+            "BLAKE3 digests are only computed for local and downloaded files",
+        ));
+    }
+    Ok(())
+}
+
+/// Metadata for a remote CSV file from its sample, written to `output_path`
+/// if given
+#[cfg(any(feature = "object-store", feature = "sftp"))]
+fn metadata_from_sample(
+    sample: crate::croissant::remote::RemoteSample,
+    output_path: Option<&Path>,
+    options: &GenerateOptions,
+) -> Result<Metadata> {
+    use crate::croissant::utils::{
+        csv_reader_builder, infer_csv_schema_from_reader, is_tsv_path, sniff_delimiter,
+    };
+    use std::io::Read;
+
+    use crate::croissant::encoding::DecodingReader;
+
+    let url = sample.digest.url;
+    let delimiter = match options.inference.delimiter {
+        Some(delimiter) => delimiter,
+        None if is_tsv_path(Path::new(url_file_name(&url))) => b'\t',
+        None => {
+            let mut head = Vec::new();
+            DecodingReader::new(sample.head.as_slice())?.read_to_end(&mut head)?;
+            sniff_delimiter(&head)
+        }
+    };
+    let reader =
+        csv_reader_builder(delimiter).from_reader(DecodingReader::new(sample.head.as_slice())?);
+    let schema = infer_csv_schema_from_reader(reader, &url, &options.inference)?;
+
+    tracing::debug!(columns = schema.headers.len(), "read CSV header");
+
+    let metadata = build_metadata(
+        SourceFile {
+            name: url_file_name(&url).to_string(),
+            content_url: url.clone(),
+            size: sample.digest.content_size,
+            sha256: sample.digest.sha256,
+            blake3: None,
+            encoding_format: delimited_encoding_format(delimiter).to_string(),
+            delimiter: Some(delimiter),
+            encoding: TextEncoding::detect(&sample.head),
+        },
+        &schema,
+        options,
+    )?;
+
+    write_output(&metadata, output_path, options)?;
+
+    Ok(metadata)
+}
+
+/// The last path segment of a URL, without query or fragment
+#[cfg(feature = "remote")]
+fn url_file_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path)
+}
 
-    // Get column information
-    let (headers, first_row) = get_csv_columns(csv_path)?;
+/// Generate Croissant metadata from a CSV file at an HTTP(S) URL
+///
+/// The file is downloaded through the download cache, so generating again
+/// for an unchanged file does not transfer it again. The URL becomes the
+/// distribution's content URL.
+#[cfg(feature = "remote")]
+#[tracing::instrument(skip_all, fields(url = %url))]
+pub async fn generate_metadata_from_url(
+    url: &str,
+    output_path: Option<&Path>,
+    options: &GenerateOptions<'_>,
+    cache: &crate::croissant::remote::download::DownloadCache,
+) -> Result<Metadata> {
+    use crate::croissant::utils::is_tsv_path;
+
+    check_output(output_path, options)?;
+    let download = cache
+        .fetch(&crate::croissant::remote::auth::client(), url)
+        .await?;
+    let file_blake3 = if options.blake3 {
+        Some(blake3_digest(&download.path)?)
+    } else {
+        None
+    };
+    let delimiter = match options.inference.delimiter {
+        Some(delimiter) => delimiter,
+        None if is_tsv_path(Path::new(url_file_name(url))) => b'\t',
+        None => detect_delimiter(&download.path)?,
+    };
+    let schema = infer_csv_schema(&download.path, delimiter, &options.inference)?;
+
+    tracing::debug!(columns = schema.headers.len(), "read CSV header");
+
+    let metadata = build_metadata(
+        SourceFile {
+            name: url_file_name(url).to_string(),
+            content_url: url.to_string(),
+            size: download.content_size,
+            sha256: download.sha256,
+            blake3: file_blake3,
+            encoding_format: delimited_encoding_format(delimiter).to_string(),
+            delimiter: Some(delimiter),
+            encoding: detect_file_encoding(&download.path)?,
+        },
+        &schema,
+        options,
+    )?;
+
+    write_output(&metadata, output_path, options)?;
+
+    Ok(metadata)
+}
+
+/// A CSV file metadata is generated for
+#[derive(Clone)]
+struct SourceFile {
+    /// File name, used as the distribution's `@id` and name
+    name: String,
+    /// Where the file is published; its name for local files
+    content_url: String,
+    size: u64,
+    sha256: String,
+    blake3: Option<String>,
+    encoding_format: String,
+    /// Field delimiter of CSV files
+    delimiter: Option<u8>,
+    /// Character encoding of CSV files
+    encoding: TextEncoding,
+}
+
+/// Metadata for a CSV file with the given columns, with one record set
+/// named `main` and one for the values of each enumeration column
+fn build_metadata(
+    source: SourceFile,
+    schema: &CsvSchema,
+    options: &GenerateOptions,
+) -> Result<Metadata> {
+    let CsvSchema {
+        headers, columns, ..
+    } = schema;
+    let file_name = source.name;
+    if source.encoding.needs_transcoding() {
+        tracing::warn!(
+            file = %file_name,
+            encoding = source.encoding.label(),
+            "CSV file is not UTF-8; reading it transcoded"
+        );
+    }
+    if schema.bad_rows > 0 {
+        tracing::warn!(
+            file = %file_name,
+            rows = schema.bad_rows,
+            "skipped sampled rows without as many fields as the header"
+        );
+    }
 
     // Create fields based on CSV columns
     let mut fields = Vec::new();
-    for (i, header) in headers.iter().enumerate() {
-        let field_id = format!("main/{header}");
-        let mut data_type = DataType::Text; // Default
-
-        // Try to infer data type from first row if available
-        if let Some(ref row) = first_row {
-            if i < row.len() {
-                data_type = infer_data_type(&row[i]);
-            }
-        }
+    let mut enumerations = Vec::new();
+    let units = headers
+        .iter()
+        .map(|header| unit_from_header(header))
+        .collect::<Vec<_>>();
+    let names = field_names(headers, &units, options.strip_unit_names);
+    let segments = options.id_strategy.id_segments(&names)?;
+    for ((((header, column), unit), name), segment) in headers
+        .iter()
+        .zip(columns)
+        .zip(units)
+        .zip(names)
+        .zip(segments)
+    {
+        let field_id = format!("main/{segment}");
+
+        let described = options
+            .descriptions
+            .and_then(|provider| provider.describe(header))
+            .unwrap_or_default();
 
         let field = Field {
             id: field_id,
             type_: "cr:Field".to_string(),
-            name: header.clone(),
-            description: format!("Field for {header}"),
-            data_type: data_type.to_schema_org().to_string(),
-            source: FieldSource {
+            name,
+            description: described
+                .description
+                .or_else(|| column.semantic_type.map(|kind| kind.describe(header)))
+                .unwrap_or_else(|| format!("Field for {header}")),
+            data_type: described
+                .semantic_type
+                .unwrap_or_else(|| column.data_type.to_schema_org().to_string()),
+            unit_text: described
+                .unit
+                .or_else(|| column.unit.clone())
+                .or(unit.map(|unit| unit.unit)),
+            source: Some(FieldSource {
                 extract: Extract {
                     column: header.clone(),
+                    file_property: None,
                 },
                 file_object: FileObject {
                     id: file_name.clone(),
                 },
-            },
+                file_set: None,
+                transform: column.transform.clone(),
+            }),
+            references: column.enum_values.as_ref().map(|values| {
+                let enumeration = enumeration_record_set(header, &segment, values);
+                let reference = FieldReference {
+                    field: FieldId {
+                        id: enumeration.field[0].id.clone(),
+                    },
+                };
+                enumerations.push(enumeration);
+                reference
+            }),
+            repeated: false,
+            sub_field: Vec::new(),
+            nullable: Some(column.nullable),
+            null_values: column.null_values.clone(),
+            statistics: None,
         };
 
         fields.push(field);
     }
 
     // Create metadata structure
-    let dataset_name = csv_path
-        .file_stem()
-        .unwrap_or_default()
-        .to_string_lossy()
+    let dataset_name = file_name
+        .rsplit_once('.')
+        .map_or(file_name.as_str(), |(stem, _)| stem)
         .to_string();
 
-    let metadata = Metadata {
+    let mut metadata = Metadata {
         context: create_default_context(),
         type_: "sc:Dataset".to_string(),
         name: format!("{dataset_name}_dataset"),
         description: format!("Dataset created from {file_name}"),
         conforms_to: "http://mlcommons.org/croissant/1.0".to_string(),
-        date_published: Utc::now().format("%Y-%m-%d").to_string(),
+        date_published: publication_date(),
         version: "1.0.0".to_string(),
+        license: Vec::new(),
+        url: None,
+        creator: Vec::new(),
+        keywords: Vec::new(),
+        identifier: None,
+        cite_as: None,
         distribution: vec![Distribution {
             id: file_name.clone(),
             type_: "cr:FileObject".to_string(),
             name: file_name.clone(),
-            content_size: format!("{file_size} B"),
-            content_url: file_name,
-            encoding_format: "text/csv".to_string(),
-            sha256: file_sha256,
+            description: (options.record_encoding
+                && DELIMITED_FORMATS.contains(&source.encoding_format.as_str()))
+            .then(|| format!("CSV file encoded in {}", source.encoding.label())),
+            content_size: format!("{} B", source.size),
+            content_url: source.content_url,
+            encoding_format: source.encoding_format,
+            sha256: source.sha256,
+            blake3: source.blake3,
+            delimiter: source
+                .delimiter
+                .map(|delimiter| char::from(delimiter).to_string()),
+            contained_in: Vec::new(),
+            includes: Vec::new(),
+            excludes: Vec::new(),
         }],
-        record_set: vec![RecordSet {
+        record_set: std::iter::once(RecordSet {
             id: "main".to_string(),
             type_: "cr:RecordSet".to_string(),
             name: "main".to_string(),
-            description: format!(
-                "Records from {}",
-                csv_path.file_name().unwrap().to_string_lossy()
-            ),
+            description: format!("Records from {file_name}"),
+            data_type: None,
             field: fields,
+            key: Vec::new(),
+            data: None,
+        })
+        .chain(enumerations)
+        .collect(),
+        rai: Rai::default(),
+    };
+    options.dataset.apply(&mut metadata);
+    Ok(metadata)
+}
+
+/// Names of the fields read from the given columns: the headers, or with
+/// `strip_units` the headers without their unit where that keeps names
+/// unique
+fn field_names(headers: &[String], units: &[Option<HeaderUnit>], strip_units: bool) -> Vec<String> {
+    let stripped = headers
+        .iter()
+        .zip(units)
+        .map(|(header, unit)| match unit {
+            Some(unit) if strip_units => &unit.name,
+            _ => header,
+        })
+        .collect::<Vec<_>>();
+    stripped
+        .iter()
+        .zip(headers)
+        .map(|(name, header)| {
+            if stripped.iter().filter(|other| *other == name).count() == 1 {
+                name.to_string()
+            } else {
+                header.clone()
+            }
+        })
+        .collect()
+}
+
+/// Record set named `<segment>_values` listing the values of an
+/// enumeration column inline, for the column's field to reference
+fn enumeration_record_set(header: &str, segment: &str, values: &[String]) -> RecordSet {
+    let id = format!("{segment}_values");
+    let field_id = format!("{id}/name");
+    RecordSet {
+        id: id.clone(),
+        type_: "cr:RecordSet".to_string(),
+        name: id,
+        description: format!("Possible values of {header}"),
+        data_type: Some("sc:Enumeration".to_string()),
+        field: vec![Field {
+            id: field_id.clone(),
+            type_: "cr:Field".to_string(),
+            name: "name".to_string(),
+            description: format!("Value of {header}"),
+            data_type: DataType::Text.to_schema_org().to_string(),
+            unit_text: None,
+            source: None,
+            references: None,
+            repeated: false,
+            sub_field: Vec::new(),
+            nullable: None,
+            null_values: Vec::new(),
+            statistics: None,
         }],
+        key: vec![FieldId {
+            id: field_id.clone(),
+        }],
+        data: Some(
+            values
+                .iter()
+                .map(|value| {
+                    let mut record = serde_json::Map::new();
+                    record.insert(field_id.clone(), value.clone().into());
+                    record
+                })
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(feature = "blake3")]
+fn blake3_digest(path: &Path) -> Result<String> {
+    crate::croissant::utils::calculate_blake3(path)
+}
+
+#[cfg(not(feature = "blake3"))]
+fn blake3_digest(_path: &Path) -> Result<String> {
+    Err(Error::new(
+        "BLAKE3 digests require rustcroissant to be built with the blake3 feature",
+    ))
+}
+
+/// Serialize metadata and write it to a file, as YAML for a `.yaml` or
+/// `.yml` path and as pretty-printed JSON-LD otherwise
+#[tracing::instrument(level = "debug", skip_all, fields(output = %output_path.display()))]
+pub fn write_metadata(metadata: &Metadata, output_path: &Path) -> Result<()> {
+    write_metadata_with_options(metadata, output_path, &GenerateOptions::default())
+}
+
+/// Serialize metadata in the JSON-LD form and style of the options and
+/// write it to a file, as YAML for a `.yaml` or `.yml` path and as JSON
+/// otherwise
+///
+/// Metadata written in any form can be read back, expanded and flattened
+/// documents through [`metadata_from_json_ld`](crate::croissant::validate::metadata_from_json_ld).
+#[tracing::instrument(level = "debug", skip_all, fields(output = %output_path.display()))]
+pub fn write_metadata_with_options(
+    metadata: &Metadata,
+    output_path: &Path,
+    options: &GenerateOptions,
+) -> Result<()> {
+    check_output(Some(output_path), options)?;
+    let serialized = serialize_metadata(metadata, output_path, options)?;
+    std::fs::write(output_path, serialized)?;
+    tracing::info!(output = %output_path.display(), "wrote metadata");
+    Ok(())
+}
+
+/// Serialize metadata as it is written to `output_path`: as YAML for a
+/// `.yaml` or `.yml` path and as JSON-LD otherwise
+pub fn serialize_metadata(
+    metadata: &Metadata,
+    output_path: &Path,
+    options: &GenerateOptions,
+) -> Result<String> {
+    if is_yaml_path(output_path) {
+        let document = to_form(&serde_json::to_value(metadata)?, options.jsonld_form, None)?;
+        Ok(serde_yaml::to_string(&document)?)
+    } else {
+        metadata_to_json(metadata, options)
+    }
+}
+
+/// Fail if the output file exists and the options keep existing files
+fn check_output(output_path: Option<&Path>, options: &GenerateOptions) -> Result<()> {
+    match output_path {
+        Some(path) if options.keep_existing && path.exists() => {
+            Err(Error::new(format!("{} already exists", path.display())))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Write metadata to the output file, if any, unless on a dry run
+fn write_output(
+    metadata: &Metadata,
+    output_path: Option<&Path>,
+    options: &GenerateOptions,
+) -> Result<()> {
+    match output_path {
+        Some(path) if !options.dry_run => write_metadata_with_options(metadata, path, options),
+        _ => Ok(()),
+    }
+}
+
+/// Serialize metadata as JSON-LD in the form and style of the options,
+/// ending with a newline
+///
+/// The output only depends on the metadata: object keys are sorted, and
+/// fields keep the column order of the source file.
+pub fn metadata_to_json(metadata: &Metadata, options: &GenerateOptions) -> Result<String> {
+    let document = to_form(&serde_json::to_value(metadata)?, options.jsonld_form, None)?;
+    let mut json = if options.minified {
+        serde_json::to_string(&document)?
+    } else {
+        serde_json::to_string_pretty(&document)?
     };
+    json.push('\n');
+    Ok(json)
+}
+
+/// Outcome of generating metadata for every CSV file in a directory
+#[derive(Debug, Default)]
+pub struct BatchGeneration {
+    /// Files whose metadata was generated, with the output path if written
+    /// (or, on a dry run, to be written)
+    pub generated: Vec<(PathBuf, Option<PathBuf>, Metadata)>,
+    /// Files that could not be processed
+    pub failed: Vec<(PathBuf, Error)>,
+}
 
-    // Write metadata to file if output path is provided
-    if let Some(output_path) = output_path {
-        let metadata_json = serde_json::to_string_pretty(&metadata)?;
-        std::fs::write(output_path, metadata_json)?;
+impl BatchGeneration {
+    pub fn has_failures(&self) -> bool {
+        !self.failed.is_empty()
     }
+}
+
+/// Generate Croissant metadata for every CSV file in a directory, and every
+/// Parquet file with the `parquet` feature
+///
+/// Files are processed in parallel, at most `max_threads` at a time (`None`
+/// uses the available parallelism). When `output_dir` is given, each file's
+/// metadata is written there as `<file stem>.jsonld`, or, when several files
+/// share a stem (such as `a.csv` and `a.parquet`), as `<file name>.jsonld`.
+/// A failing file is recorded in the result instead of aborting the batch.
+#[tracing::instrument(skip_all, fields(dir = %input_dir.display()))]
+pub fn generate_metadata_for_directory(
+    input_dir: &Path,
+    output_dir: Option<&Path>,
+    options: &GenerateOptions,
+    max_threads: Option<usize>,
+) -> Result<BatchGeneration> {
+    let mut csv_files = Vec::new();
+    for entry in std::fs::read_dir(input_dir).map_err(|_| Error::file_not_found(input_dir))? {
+        let path = entry?.path();
+        let extension = get_file_extension(&path);
+        if path.is_file()
+            && (matches!(extension.as_deref(), Some("csv" | "tsv" | "tab"))
+                || (cfg!(feature = "parquet") && extension.as_deref() == Some("parquet"))
+                || (cfg!(feature = "xlsx") && extension.as_deref() == Some("xlsx")))
+        {
+            csv_files.push(path);
+        }
+    }
+    csv_files.sort();
+    tracing::info!(files = csv_files.len(), "generating metadata for directory");
+
+    if let Some(dir) = output_dir
+        && !options.dry_run
+    {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut stems = HashMap::new();
+    for csv_path in &csv_files {
+        *stems.entry(csv_path.file_stem()).or_insert(0) += 1;
+    }
+    let results = parallel_map(&csv_files, max_threads, |csv_path| {
+        let output_path = output_dir.map(|dir| {
+            let name = if stems[&csv_path.file_stem()] > 1 {
+                csv_path.file_name()
+            } else {
+                csv_path.file_stem()
+            };
+            let mut name = name.unwrap_or_default().to_os_string();
+            name.push(".jsonld");
+            dir.join(name)
+        });
+        generate_metadata_from_file(csv_path, output_path.as_deref(), options)
+            .map(|metadata| (output_path, metadata))
+    });
+
+    let mut batch = BatchGeneration::default();
+    for (csv_path, result) in csv_files.into_iter().zip(results) {
+        match result {
+            Ok((output_path, metadata)) => batch.generated.push((csv_path, output_path, metadata)),
+            Err(e) => {
+                tracing::debug!(path = %csv_path.display(), error = %e, "generation failed");
+                batch.failed.push((csv_path, e));
+            }
+        }
+    }
+
+    Ok(batch)
+}
+
+/// Check whether a directory holds files of one type, none of them CSV,
+/// Parquet or Excel files, making it a file set rather than a batch of
+/// tables
+pub fn is_file_set_directory(dir: &Path) -> bool {
+    let Ok(files) = list_dir(dir) else {
+        return false;
+    };
+    let extensions = files
+        .iter()
+        .map(|file| get_file_extension(Path::new(file)))
+        .collect::<HashSet<_>>();
+    match extensions.iter().next() {
+        Some(Some(extension)) if extensions.len() == 1 => !matches!(
+            extension.as_str(),
+            "csv" | "tsv" | "tab" | "parquet" | "xlsx"
+        ),
+        _ => false,
+    }
+}
+
+/// Generate Croissant metadata describing the files of a directory, such as
+/// a folder of images, as one `cr:FileSet`
+///
+/// Every file in the directory and its subdirectories must have the same
+/// extension, which gives the file set's glob pattern and encoding format.
+/// The record set holds a record per file with its path, its name and,
+/// for images, audio, video and text, its content.
+#[tracing::instrument(skip_all, fields(dir = %dir.display()))]
+pub fn generate_metadata_for_file_set(
+    dir: &Path,
+    output_path: Option<&Path>,
+    options: &GenerateOptions,
+) -> Result<Metadata> {
+    check_output(output_path, options)?;
+
+    let mut files = list_dir(dir)?;
+    if let Some(output_path) = output_path
+        && let Ok(output_path) = output_path.canonicalize()
+    {
+        files.retain(|file| dir.join(file).canonicalize().ok().as_ref() != Some(&output_path));
+    }
+    if files.is_empty() {
+        return Err(Error::invalid_format(format!(
+            "{} holds no files",
+            dir.display()
+        )));
+    }
+    if files
+        .iter()
+        .any(|file| Path::new(file).extension().is_none())
+    {
+        return Err(Error::invalid_format(format!(
+            "{} holds files without an extension",
+            dir.display()
+        )));
+    }
+    let mut extensions = files
+        .iter()
+        .filter_map(|file| Path::new(file).extension())
+        .map(|extension| extension.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    extensions.sort();
+    extensions.dedup();
+    let lowercase = extensions
+        .iter()
+        .map(|extension| extension.to_lowercase())
+        .collect::<HashSet<_>>();
+    if lowercase.len() != 1 {
+        return Err(Error::invalid_format(format!(
+            "{} holds files of several types ({}); a file set holds files of one type",
+            dir.display(),
+            extensions.join(", ")
+        )));
+    }
+
+    let dir_name = dir
+        .canonicalize()
+        .map_err(|_| Error::file_not_found(dir))?
+        .file_name()
+        .map_or("files".to_string(), |name| {
+            name.to_string_lossy().to_string()
+        });
+    let location = match options.content_urls {
+        ContentUrlStyle::BaseUrl(_) => dir_name.clone(),
+        ref style => style.content_url(dir, output_path)?,
+    };
+    let prefix = match location.trim_end_matches('/') {
+        "" | "." => String::new(),
+        location => format!("{location}/"),
+    };
+    let nested = if files.iter().any(|file| file.contains('/')) {
+        "**/"
+    } else {
+        ""
+    };
+    let encoding_format = media_type(Path::new(&files[0])).unwrap_or("application/octet-stream");
+
+    let mut file_set = DistributionBuilder::file_set(dir_name.clone(), encoding_format)
+        .description(format!("{} files in {dir_name}", files.len()));
+    for extension in &extensions {
+        file_set = file_set.include(format!("{prefix}{nested}*.{extension}"));
+    }
+
+    let mut record_set = RecordSetBuilder::new("main")
+        .description(format!("Files in {dir_name}"))
+        .field(
+            FieldBuilder::new("path", DataType::Text)
+                .description("Path of the file")
+                .file_set_property(&dir_name, FileProperty::Fullpath),
+        )
+        .field(
+            FieldBuilder::new("filename", DataType::Text)
+                .description("Name of the file")
+                .file_set_property(&dir_name, FileProperty::Filename),
+        );
+    let content_type = match encoding_format.split('/').next() {
+        Some("image") => Some("sc:ImageObject"),
+        Some("audio") => Some("sc:AudioObject"),
+        Some("video") => Some("sc:VideoObject"),
+        Some("text") => Some("sc:Text"),
+        _ if encoding_format.contains("json") || encoding_format.ends_with("xml") => {
+            Some("sc:Text")
+        }
+        _ => None,
+    };
+    if let Some(content_type) = content_type {
+        record_set = record_set.field(
+            FieldBuilder::new("content", DataType::Text)
+                .data_type_iri(content_type)
+                .description("Content of the file")
+                .file_set_property(&dir_name, FileProperty::Content),
+        );
+    }
+
+    let mut metadata = MetadataBuilder::new(format!("{dir_name}_dataset"))
+        .description(format!("Dataset created from the files in {dir_name}"))
+        .distribution(file_set)
+        .record_set(record_set)
+        .build_unchecked();
+    options.dataset.apply(&mut metadata);
+
+    write_output(&metadata, output_path, options)?;
+
+    Ok(metadata)
+}
+
+/// Generate one Croissant document for several CSV or Parquet files, with
+/// a distribution and a record set for each
+///
+/// Files are read in parallel, at most `max_threads` at a time. Record sets
+/// are named after the files' stems, and enumeration record sets after
+/// those; `@id`s shared by files get a numeric suffix, as in `data_2.csv`,
+/// and the `@id`s of fields and the sources and references pointing at them
+/// are renamed along. Files of the same name in different directories would
+/// share a content URL, so they are then written as paths relative to the
+/// metadata file, which also name their distributions; with a base URL,
+/// such files are an error. Unless given, the dataset is named after the
+/// output file.
+#[tracing::instrument(skip_all, fields(files = paths.len()))]
+pub fn generate_metadata_from_files<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    output_path: Option<&Path>,
+    options: &GenerateOptions,
+    max_threads: Option<usize>,
+) -> Result<Metadata> {
+    check_output(output_path, options)?;
+
+    // Files are only described here; the merged document is written below
+    let mut file_options = GenerateOptions {
+        keep_existing: false,
+        dry_run: true,
+        ..options.clone()
+    };
+    let clashing = clashing_content_url(paths, &options.content_urls, output_path)?;
+    let relative_names = match (&options.content_urls, clashing) {
+        (_, None) => false,
+        (ContentUrlStyle::FileName, Some(_)) => {
+            file_options.content_urls = ContentUrlStyle::RelativeToOutput;
+            true
+        }
+        (_, Some(url)) => {
+            return Err(Error::new(format!(
+                "Several input files would have the content URL {url}"
+            )));
+        }
+    };
+    if let Some(url) = clashing_content_url(paths, &file_options.content_urls, output_path)? {
+        return Err(Error::new(format!(
+            "Several input files would have the content URL {url}; is a file given twice?"
+        )));
+    }
+    let parts = parallel_map(paths, max_threads, |path| {
+        generate_metadata_from_file(path.as_ref(), output_path, &file_options)
+    });
+
+    let mut merged: Option<Metadata> = None;
+    let mut file_names = Vec::new();
+    let mut distribution_ids = HashSet::new();
+    let mut record_set_ids = HashSet::new();
+    for (path, part) in paths.iter().zip(parts) {
+        let mut part = part?;
+        let stem = path
+            .as_ref()
+            .file_stem()
+            .map_or("main".into(), |stem| stem.to_string_lossy());
+        let renamed = record_set_renames(&part.record_set, &slugify(&stem), &mut record_set_ids);
+        let files = part
+            .distribution
+            .iter_mut()
+            .map(|distribution| {
+                if relative_names {
+                    distribution.name = distribution.content_url.clone();
+                }
+                file_names.push(distribution.name.clone());
+                let id = unique_id(&mut distribution_ids, distribution.id.clone());
+                (std::mem::replace(&mut distribution.id, id.clone()), id)
+            })
+            .collect::<HashMap<_, _>>();
+        for record_set in &mut part.record_set {
+            rename_record_set(record_set, &renamed, &files);
+        }
+
+        match &mut merged {
+            Some(metadata) => {
+                metadata.distribution.append(&mut part.distribution);
+                metadata.record_set.append(&mut part.record_set);
+            }
+            None => merged = Some(part),
+        }
+    }
+    let mut metadata = merged.ok_or_else(|| Error::new("No input files given"))?;
+
+    metadata.name = output_path
+        .and_then(Path::file_stem)
+        .map_or("dataset".to_string(), |stem| {
+            stem.to_string_lossy().to_string()
+        });
+    metadata.description = format!("Dataset created from {}", file_names.join(", "));
+    options.dataset.apply(&mut metadata);
+
+    write_output(&metadata, output_path, options)?;
 
     Ok(metadata)
 }
+
+/// The first content URL that `style` would give more than one of `paths`
+fn clashing_content_url<P: AsRef<Path>>(
+    paths: &[P],
+    style: &ContentUrlStyle,
+    output_path: Option<&Path>,
+) -> Result<Option<String>> {
+    let mut urls = HashSet::new();
+    for path in paths {
+        let url = style.content_url(path.as_ref(), output_path)?;
+        if !urls.insert(url.clone()) {
+            return Ok(Some(url));
+        }
+    }
+    Ok(None)
+}
+
+/// New `@id`s of the record sets generated for one table, keyed by old
+/// `@id`: `main` becomes `name`, and the others are prefixed with it, all
+/// made unique among `used`
+fn record_set_renames(
+    record_sets: &[RecordSet],
+    name: &str,
+    used: &mut HashSet<String>,
+) -> HashMap<String, String> {
+    let main = unique_id(used, name.to_string());
+    record_sets
+        .iter()
+        .map(|record_set| {
+            let id = if record_set.id == "main" {
+                main.clone()
+            } else {
+                unique_id(used, format!("{main}_{}", record_set.id))
+            };
+            (record_set.id.clone(), id)
+        })
+        .collect()
+}
+
+/// `id`, or if already used the first of `id_2`, `id_3`... that is not,
+/// keeping a file extension last
+fn unique_id(used: &mut HashSet<String>, id: String) -> String {
+    if used.insert(id.clone()) {
+        return id;
+    }
+    let (stem, extension) = match id.rsplit_once('.') {
+        Some((stem, extension)) => (stem, format!(".{extension}")),
+        None => (id.as_str(), String::new()),
+    };
+    (2..)
+        .map(|n| format!("{stem}_{n}{extension}"))
+        .find(|candidate| used.insert(candidate.clone()))
+        .expect("unused suffix")
+}
+
+/// Rename a record set and its fields after `record_sets`, and the file
+/// objects they are extracted from after `files`, both keyed by old `@id`
+fn rename_record_set(
+    record_set: &mut RecordSet,
+    record_sets: &HashMap<String, String>,
+    files: &HashMap<String, String>,
+) {
+    if let Some(id) = record_sets.get(&record_set.id) {
+        record_set.name = id.clone();
+        record_set.id = id.clone();
+    }
+    for field in &mut record_set.field {
+        rename_field(field, record_sets, files);
+    }
+    for record in record_set.data.iter_mut().flatten() {
+        *record = std::mem::take(record)
+            .into_iter()
+            .map(|(key, value)| (renamed_field_id(&key, record_sets), value))
+            .collect();
+    }
+}
+
+fn rename_field(
+    field: &mut Field,
+    record_sets: &HashMap<String, String>,
+    files: &HashMap<String, String>,
+) {
+    field.id = renamed_field_id(&field.id, record_sets);
+    if let Some(reference) = &mut field.references {
+        reference.field.id = renamed_field_id(&reference.field.id, record_sets);
+    }
+    if let Some(source) = &mut field.source
+        && let Some(id) = files.get(&source.file_object.id)
+    {
+        source.file_object.id = id.clone();
+    }
+    for sub_field in &mut field.sub_field {
+        rename_field(sub_field, record_sets, files);
+    }
+}
+
+/// Field `@id` of the form `record_set/field` with its record set renamed
+fn renamed_field_id(id: &str, record_sets: &HashMap<String, String>) -> String {
+    match id.split_once('/') {
+        Some((record_set, rest)) if record_sets.contains_key(record_set) => {
+            format!("{}/{rest}", record_sets[record_set])
+        }
+        _ => id.to_string(),
+    }
+}
+
+/// Generate Croissant metadata from a CSV file on tokio's blocking thread
+/// pool, so callers on the async runtime are not stalled by CSV parsing and
+/// hashing
+///
+/// The options move to the blocking thread, so a description provider or
+/// hash cache they hold must be borrowed for `'static`.
+#[cfg(feature = "tokio")]
+#[tracing::instrument(skip_all, fields(path = %csv_path.display()))]
+pub async fn generate_metadata_from_csv_async(
+    csv_path: &Path,
+    output_path: Option<&Path>,
+    options: GenerateOptions<'static>,
+) -> Result<Metadata> {
+    let csv_path = csv_path.to_path_buf();
+    let output_path = output_path.map(Path::to_path_buf);
+    let span = tracing::Span::current();
+
+    tokio::task::spawn_blocking(move || {
+        span.in_scope(|| {
+            generate_metadata_from_csv_with_options(&csv_path, output_path.as_deref(), &options)
+        })
+    })
+    .await
+    .map_err(|e| Error::new(format!("Generation task failed: {e}")))?
+}
@@ -0,0 +1,422 @@
+//! Import of W3C CSV on the Web (CSVW) metadata
+//!
+//! Both table group documents (with `tables`) and single-table documents
+//! (with a top-level `url`) are accepted. Each table becomes a CSV
+//! distribution and a record set with one field per non-virtual column.
+//! Primary keys become record set keys, single-column foreign keys into
+//! tables of the same group field references, and the delimiter of a
+//! table's dialect (or the group's) the distribution's delimiter. The
+//! `groupChar` and `decimalChar` of a numeric datatype's format become
+//! replace transforms, and the date pattern of a temporal one a format
+//! transform.
+
+use crate::croissant::core::{
+    DataType, Distribution, Extract, Field, FieldId, FieldReference, FieldSource, FileObject,
+    Metadata, Rai, RecordSet, Transform, create_default_context,
+};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::{
+    calculate_sha256, delimited_encoding_format, parse_delimiter, publication_date,
+};
+use serde_json::Value;
+use std::path::Path;
+
+/// Croissant data type for a CSVW datatype name
+///
+/// Derived XSD types map to the Croissant type of their base, and unknown
+/// datatypes fall back to Text.
+pub fn data_type_from_csvw(datatype: &str) -> DataType {
+    let name = datatype
+        .strip_prefix("xsd:")
+        .or_else(|| datatype.strip_prefix("http://www.w3.org/2001/XMLSchema#"))
+        .unwrap_or(datatype);
+    match name {
+        "integer" | "int" | "long" | "short" | "byte" | "nonNegativeInteger"
+        | "positiveInteger" | "nonPositiveInteger" | "negativeInteger" | "unsignedLong"
+        | "unsignedInt" | "unsignedShort" | "unsignedByte" => DataType::Integer,
+        "number" | "decimal" | "double" | "float" => DataType::Float,
+        "boolean" => DataType::Boolean,
+        "date" => DataType::Date,
+        "datetime" | "dateTime" | "dateTimeStamp" => DataType::DateTime,
+        "time" => DataType::Time,
+        "anyURI" => DataType::Url,
+        _ => DataType::Text,
+    }
+}
+
+/// strftime format for a CSVW date pattern, if every part of it has one
+pub fn strftime_from_csvw(pattern: &str) -> Option<String> {
+    let mut format = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\'' {
+            // Quoted literal text, with '' standing for a quote
+            if chars.next_if_eq(&'\'').is_some() {
+                format.push('\'');
+                continue;
+            }
+            loop {
+                match chars.next()? {
+                    '\'' if chars.next_if_eq(&'\'').is_none() => break,
+                    '%' => format.push_str("%%"),
+                    c => format.push(c),
+                }
+            }
+            continue;
+        }
+        if !c.is_ascii_alphabetic() {
+            if c == '%' {
+                format.push_str("%%");
+            } else {
+                format.push(c);
+            }
+            continue;
+        }
+        let mut run = 1;
+        while chars.next_if_eq(&c).is_some() {
+            run += 1;
+        }
+        let field = match (c, run) {
+            ('y', 4) => "%Y",
+            ('y', 2) => "%y",
+            ('M', 1 | 2) => "%m",
+            ('d', 1 | 2) => "%d",
+            ('H', 1 | 2) => "%H",
+            ('h', 1 | 2) => "%I",
+            ('m', 1 | 2) => "%M",
+            ('s', 1 | 2) => "%S",
+            ('a', 1) => "%p",
+            ('x' | 'X', 2) => "%z",
+            ('x' | 'X', 3) => "%:z",
+            ('S', _) if format.ends_with('.') => {
+                format.pop();
+                match run {
+                    3 => "%.3f",
+                    6 => "%.6f",
+                    9 => "%.9f",
+                    _ => "%.f",
+                }
+            }
+            _ => return None,
+        };
+        format.push_str(field);
+    }
+    Some(format)
+}
+
+/// Transforms reading values in the `format` of a CSVW datatype
+fn format_transforms(format: &Value, data_type: &DataType, column: &str) -> Vec<Transform> {
+    let transforms = match (data_type, format) {
+        (DataType::Integer | DataType::Float, Value::Object(format)) => {
+            let mut transforms = Vec::new();
+            if let Some(group) = format.get("groupChar").and_then(Value::as_str) {
+                // Spaces group digits with any kind of space
+                let pattern = match group {
+                    " " => "\\s".to_string(),
+                    group => regex::escape(group),
+                };
+                transforms.push(Transform::Replace(format!("{pattern}/")));
+            }
+            if let Some(decimal) = format
+                .get("decimalChar")
+                .and_then(Value::as_str)
+                .filter(|decimal| *decimal != ".")
+            {
+                transforms.push(Transform::Replace(format!("{}/.", regex::escape(decimal))));
+            }
+            Some(transforms)
+        }
+        (DataType::Date | DataType::DateTime | DataType::Time, Value::String(pattern)) => {
+            strftime_from_csvw(pattern).map(|format| vec![Transform::Format(format)])
+        }
+        _ => None,
+    };
+    transforms.unwrap_or_else(|| {
+        tracing::warn!(column, %format, "CSVW datatype format not supported; not imported");
+        Vec::new()
+    })
+}
+
+/// Convert a CSVW metadata document to Croissant metadata
+pub fn import_csvw(content: &str, base_dir: &Path) -> Result<Metadata> {
+    let document: Value = serde_json::from_str(content)?;
+    let tables = match document.get("tables") {
+        Some(Value::Array(tables)) => tables.iter().collect::<Vec<_>>(),
+        Some(_) => return Err(Error::invalid_format("CSVW \"tables\" must be an array")),
+        None if document.get("url").is_some() => vec![&document],
+        None => return Err(Error::missing_field("CSVW tables or url")),
+    };
+
+    let mut distribution = Vec::new();
+    let mut record_set = Vec::new();
+    for table in &tables {
+        let url = table
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::missing_field("CSVW table url"))?;
+        let file_name = url.rsplit('/').next().unwrap_or(url).to_string();
+        let table_name = text(table.get("dc:title")).unwrap_or_else(|| {
+            file_name
+                .rsplit_once('.')
+                .map_or(&*file_name, |(stem, _)| stem)
+                .to_string()
+        });
+
+        let fields = table
+            .pointer("/tableSchema/columns")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter(|column| column.get("virtual").and_then(Value::as_bool) != Some(true))
+            .map(|column| import_column(column, &table_name, url))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Hash and size the table when it is available locally
+        let local_path = base_dir.join(url);
+        let (sha256, content_size) = if !url.contains("://") && local_path.is_file() {
+            (
+                calculate_sha256(&local_path)?,
+                format!("{} B", std::fs::metadata(&local_path)?.len()),
+            )
+        } else {
+            (String::new(), String::new())
+        };
+        tracing::debug!(url, size = %content_size, "imported CSVW table");
+
+        let delimiter = table
+            .pointer("/dialect/delimiter")
+            .or_else(|| document.pointer("/dialect/delimiter"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        distribution.push(Distribution {
+            id: url.to_string(),
+            type_: "cr:FileObject".to_string(),
+            name: file_name,
+            description: None,
+            content_size,
+            content_url: url.to_string(),
+            encoding_format: delimited_encoding_format(
+                delimiter
+                    .as_deref()
+                    .and_then(|d| parse_delimiter(d).ok())
+                    .unwrap_or(b','),
+            )
+            .to_string(),
+            sha256,
+            blake3: None,
+            delimiter,
+            contained_in: Vec::new(),
+            includes: Vec::new(),
+            excludes: Vec::new(),
+        });
+        record_set.push(RecordSet {
+            id: table_name.clone(),
+            type_: "cr:RecordSet".to_string(),
+            name: table_name.clone(),
+            description: text(table.get("dc:description"))
+                .unwrap_or_else(|| format!("Records from {url}")),
+            data_type: None,
+            field: fields,
+            key: Vec::new(),
+            data: None,
+        });
+    }
+
+    // Keys name columns, and foreign keys other tables, so they are read
+    // once every table is
+    for (index, table) in tables.iter().enumerate() {
+        import_keys(table, index, &tables, &mut record_set);
+    }
+
+    let first_table = record_set
+        .first()
+        .map(|rs| rs.name.clone())
+        .unwrap_or_default();
+    Ok(Metadata {
+        context: create_default_context(),
+        type_: "sc:Dataset".to_string(),
+        name: text(document.get("dc:title")).unwrap_or_else(|| format!("{first_table}_dataset")),
+        description: text(document.get("dc:description"))
+            .unwrap_or_else(|| "Dataset imported from CSVW metadata".to_string()),
+        conforms_to: "http://mlcommons.org/croissant/1.0".to_string(),
+        date_published: publication_date(),
+        version: "1.0.0".to_string(),
+        license: Vec::new(),
+        url: None,
+        creator: Vec::new(),
+        keywords: Vec::new(),
+        identifier: None,
+        cite_as: None,
+        distribution,
+        record_set,
+        rai: Rai::default(),
+    })
+}
+
+/// Set the key of the record set at `index` from its table's primary key,
+/// and references of its fields from the table's foreign keys
+fn import_keys(table: &Value, index: usize, tables: &[&Value], record_set: &mut [RecordSet]) {
+    let field_id = |record_set: &RecordSet, column: &str| {
+        record_set
+            .field
+            .iter()
+            .find(|field| field.name == column)
+            .map(|field| FieldId {
+                id: field.id.clone(),
+            })
+    };
+
+    if let Some(primary_key) = table.pointer("/tableSchema/primaryKey") {
+        let key = column_names(primary_key)
+            .iter()
+            .map(|column| field_id(&record_set[index], column))
+            .collect::<Option<Vec<_>>>();
+        match key {
+            Some(key) => record_set[index].key = key,
+            None => tracing::warn!(
+                table = %record_set[index].name,
+                "CSVW primary key names an unknown column; not imported"
+            ),
+        }
+    }
+
+    let foreign_keys = table
+        .pointer("/tableSchema/foreignKeys")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for foreign_key in foreign_keys {
+        let columns = foreign_key
+            .get("columnReference")
+            .map(column_names)
+            .unwrap_or_default();
+        let referenced_columns = foreign_key
+            .pointer("/reference/columnReference")
+            .map(column_names)
+            .unwrap_or_default();
+        let referenced_set = foreign_key
+            .pointer("/reference/resource")
+            .and_then(Value::as_str)
+            .and_then(|resource| {
+                tables
+                    .iter()
+                    .position(|table| table.get("url").and_then(Value::as_str) == Some(resource))
+            });
+
+        let reference = match (
+            columns.as_slice(),
+            referenced_columns.as_slice(),
+            referenced_set,
+        ) {
+            ([column], [referenced_column], Some(referenced_set)) => {
+                field_id(&record_set[referenced_set], referenced_column).zip(
+                    record_set[index]
+                        .field
+                        .iter()
+                        .position(|field| field.name == *column),
+                )
+            }
+            _ => None,
+        };
+        match reference {
+            Some((referenced, position)) => {
+                record_set[index].field[position].references =
+                    Some(FieldReference { field: referenced })
+            }
+            None => tracing::warn!(
+                table = %record_set[index].name,
+                "only single-column CSVW foreign keys into tables of the group are imported"
+            ),
+        }
+    }
+}
+
+/// Column names of a CSVW column reference, a name or an array of names
+fn column_names(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(name) => vec![name.clone()],
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn import_column(column: &Value, table_name: &str, url: &str) -> Result<Field> {
+    let titles = text(column.get("titles"));
+    let name = text(column.get("name"))
+        .or_else(|| titles.clone())
+        .ok_or_else(|| Error::missing_field(format!("CSVW column name in table {table_name}")))?;
+
+    let (datatype, format) = match column.get("datatype") {
+        Some(Value::String(name)) => (name.as_str(), None),
+        Some(Value::Object(datatype)) => (
+            datatype
+                .get("base")
+                .and_then(Value::as_str)
+                .unwrap_or("string"),
+            datatype.get("format"),
+        ),
+        _ => ("string", None),
+    };
+    let data_type = data_type_from_csvw(datatype);
+    let transform = format
+        .map(|format| format_transforms(format, &data_type, &name))
+        .unwrap_or_default();
+
+    Ok(Field {
+        id: format!("{table_name}/{name}"),
+        type_: "cr:Field".to_string(),
+        description: text(column.get("dc:description"))
+            .unwrap_or_else(|| format!("Field for {name}")),
+        data_type: data_type.to_schema_org().to_string(),
+        unit_text: text(column.get("schema:unitText")),
+        source: Some(FieldSource {
+            extract: Extract {
+                column: titles.unwrap_or_else(|| name.clone()),
+                file_property: None,
+            },
+            file_object: FileObject {
+                id: url.to_string(),
+            },
+            file_set: None,
+            transform,
+        }),
+        references: None,
+        repeated: false,
+        sub_field: Vec::new(),
+        nullable: column
+            .get("required")
+            .and_then(Value::as_bool)
+            .map(|required| !required),
+        null_values: match column.get("null") {
+            Some(Value::String(null)) => vec![null.clone()],
+            Some(Value::Array(nulls)) => nulls
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        }
+        .into_iter()
+        .filter(|null| !null.trim().is_empty())
+        .collect(),
+        statistics: None,
+        name,
+    })
+}
+
+/// Plain text of a CSVW natural language property, which may be a string,
+/// a value object, or an array of either
+fn text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(values) => values.iter().find_map(|v| text(Some(v))),
+        Value::Object(object) => text(object.get("@value")),
+        _ => None,
+    }
+}
@@ -0,0 +1,239 @@
+//! Import of DCAT dataset records
+//!
+//! A DCAT record in JSON-LD seeds a Croissant document: the dataset's title,
+//! description, issue date, version, identifier, licenses, keywords,
+//! creators (or publisher) and landing page are carried over, and each
+//! distribution becomes a FileObject. DCAT does not describe the contents
+//! of files, so no record sets are created. Properties are matched on their
+//! local name, so compact (`dct:title`), full-IRI and unprefixed keys are
+//! all understood.
+
+use crate::croissant::core::{Creator, Distribution, Metadata, Rai, create_default_context};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::export::dcat::MEDIA_TYPE_PREFIX;
+use crate::croissant::utils::publication_date;
+use serde_json::Value;
+
+/// Convert a DCAT JSON-LD record to Croissant metadata
+pub fn import_dcat(content: &str) -> Result<Metadata> {
+    let document: Value = serde_json::from_str(content)?;
+    let graph = graph_nodes(&document);
+    let dataset = graph
+        .iter()
+        .copied()
+        .find(|node| has_type(node, "Dataset"))
+        .or_else(|| {
+            // A catalog embedding its datasets
+            graph
+                .iter()
+                .find_map(|node| property(node, "dataset"))
+                .and_then(|datasets| values(datasets).into_iter().next())
+                .map(|dataset| resolve(dataset, &graph))
+        })
+        .ok_or_else(|| Error::missing_field("dcat:Dataset"))?;
+
+    let name = property(dataset, "title")
+        .or_else(|| property(dataset, "identifier"))
+        .and_then(literal)
+        .ok_or_else(|| Error::missing_field("dct:title"))?;
+
+    let distribution = property(dataset, "distribution")
+        .map(values)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|node| import_distribution(resolve(node, &graph), &graph))
+        .collect::<Result<Vec<_>>>()?;
+
+    let literals = |name: &str| {
+        property(dataset, name)
+            .map(values)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|value| literal(resolve(value, &graph)))
+            .collect::<Vec<_>>()
+    };
+    // The publisher stands in for creators when none are named
+    let creator = property(dataset, "creator")
+        .or_else(|| property(dataset, "publisher"))
+        .map(values)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|node| import_agent(resolve(node, &graph)))
+        .collect();
+
+    Ok(Metadata {
+        context: create_default_context(),
+        type_: "sc:Dataset".to_string(),
+        description: property(dataset, "description")
+            .and_then(literal)
+            .unwrap_or_default(),
+        conforms_to: "http://mlcommons.org/croissant/1.0".to_string(),
+        date_published: property(dataset, "issued")
+            .and_then(literal)
+            .unwrap_or_else(publication_date),
+        version: property(dataset, "version")
+            .or_else(|| property(dataset, "versionInfo"))
+            .and_then(literal)
+            .unwrap_or_else(|| "1.0.0".to_string()),
+        license: literals("license"),
+        url: property(dataset, "landingPage").and_then(literal),
+        creator,
+        keywords: literals("keyword"),
+        identifier: property(dataset, "identifier").and_then(literal),
+        cite_as: None,
+        distribution,
+        record_set: Vec::new(),
+        rai: Rai::default(),
+        name,
+    })
+}
+
+/// Creator for a `foaf:Agent` (or schema.org) node, or a plain name
+fn import_agent(node: &Value) -> Option<Creator> {
+    if !node.is_object() {
+        return literal(node).map(Creator::person);
+    }
+    let name = property(node, "name")
+        .or_else(|| property(node, "label"))
+        .and_then(literal)?;
+    Some(Creator {
+        type_: if has_type(node, "Organization") {
+            "sc:Organization".to_string()
+        } else {
+            "sc:Person".to_string()
+        },
+        name,
+        url: property(node, "homepage")
+            .or_else(|| property(node, "url"))
+            .and_then(literal),
+    })
+}
+
+fn import_distribution(node: &Value, graph: &[&Value]) -> Result<Distribution> {
+    let url = property(node, "downloadURL")
+        .or_else(|| property(node, "accessURL"))
+        .and_then(literal)
+        .ok_or_else(|| Error::missing_field("dcat:downloadURL or dcat:accessURL"))?;
+    let file_name = url
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(&url)
+        .to_string();
+
+    let encoding_format = property(node, "mediaType")
+        .or_else(|| property(node, "format"))
+        .and_then(literal)
+        .map(|media_type| {
+            media_type
+                .strip_prefix(MEDIA_TYPE_PREFIX)
+                .or_else(|| {
+                    media_type.strip_prefix("https://www.iana.org/assignments/media-types/")
+                })
+                .map(str::to_string)
+                .unwrap_or(media_type)
+        })
+        .unwrap_or_default();
+
+    // Checksums are nodes of their own, listed in expanded documents and
+    // referenced in flattened ones
+    let sha256 = property(node, "checksum")
+        .map(values)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|checksum| resolve(checksum, graph))
+        .filter(|checksum| {
+            property(checksum, "algorithm")
+                .and_then(literal)
+                .is_some_and(|algorithm| algorithm.ends_with("sha256"))
+        })
+        .find_map(|checksum| property(checksum, "checksumValue").and_then(literal))
+        .unwrap_or_default();
+
+    Ok(Distribution {
+        id: file_name.clone(),
+        type_: "cr:FileObject".to_string(),
+        name: property(node, "title")
+            .and_then(literal)
+            .unwrap_or_else(|| file_name.clone()),
+        description: property(node, "description").and_then(literal),
+        content_size: property(node, "byteSize")
+            .and_then(literal)
+            .map(|size| format!("{size} B"))
+            .unwrap_or_default(),
+        content_url: url,
+        encoding_format,
+        sha256,
+        blake3: None,
+        delimiter: None,
+        contained_in: Vec::new(),
+        includes: Vec::new(),
+        excludes: Vec::new(),
+    })
+}
+
+/// Top-level nodes of a JSON-LD document
+fn graph_nodes(document: &Value) -> Vec<&Value> {
+    match document.get("@graph") {
+        Some(graph) => values(graph),
+        None => values(document),
+    }
+}
+
+/// Follow a node reference (`{"@id": ...}`) to the node in the graph
+fn resolve<'a>(node: &'a Value, graph: &[&'a Value]) -> &'a Value {
+    let is_reference = node.as_object().is_some_and(|object| object.len() == 1);
+    match node.get("@id").and_then(Value::as_str) {
+        Some(id) if is_reference => graph
+            .iter()
+            .copied()
+            .find(|candidate| {
+                candidate.get("@id").and_then(Value::as_str) == Some(id) && *candidate != node
+            })
+            .unwrap_or(node),
+        _ => node,
+    }
+}
+
+fn values(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    }
+}
+
+/// Local name of a compact or full IRI, e.g. `title` for `dct:title` or
+/// `http://purl.org/dc/terms/title`
+fn local_name(key: &str) -> &str {
+    key.rsplit(['#', '/', ':']).next().unwrap_or(key)
+}
+
+fn property<'a>(node: &'a Value, name: &str) -> Option<&'a Value> {
+    node.as_object()?
+        .iter()
+        .find(|(key, _)| !key.starts_with('@') && local_name(key) == name)
+        .map(|(_, value)| value)
+}
+
+fn has_type(node: &Value, name: &str) -> bool {
+    node.get("@type")
+        .map(values)
+        .unwrap_or_default()
+        .iter()
+        .any(|t| t.as_str().is_some_and(|t| local_name(t) == name))
+}
+
+/// String form of a literal or IRI value: a plain string, `@value`, `@id`,
+/// or the first such value of an array
+fn literal(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Array(items) => items.iter().find_map(literal),
+        Value::Object(object) => object
+            .get("@value")
+            .or_else(|| object.get("@id"))
+            .and_then(literal),
+        _ => None,
+    }
+}
@@ -0,0 +1,50 @@
+//! Import of metadata from other formats into Croissant
+
+use crate::croissant::core::Metadata;
+use crate::croissant::errors::{Error, Result};
+use std::path::Path;
+
+pub mod csvw;
+pub mod dcat;
+
+/// Formats metadata can be imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// W3C CSV on the Web metadata (`-metadata.json`)
+    Csvw,
+    /// DCAT dataset record in JSON-LD
+    Dcat,
+}
+
+impl ImportFormat {
+    /// All formats, in the order they are listed to users
+    pub const ALL: &'static [ImportFormat] = &[ImportFormat::Csvw, ImportFormat::Dcat];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportFormat::Csvw => "csvw",
+            ImportFormat::Dcat => "dcat",
+        }
+    }
+
+    /// Parse a command-line format name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown import format: {name}")))
+    }
+}
+
+/// Convert a document in the given format to Croissant metadata
+///
+/// `base_dir` is the directory relative URLs in the document are resolved
+/// against; local files found there are hashed and sized.
+pub fn import_metadata(content: &str, format: ImportFormat, base_dir: &Path) -> Result<Metadata> {
+    match format {
+        ImportFormat::Csvw => csvw::import_csvw(content, base_dir),
+        ImportFormat::Dcat => dcat::import_dcat(content),
+    }
+}
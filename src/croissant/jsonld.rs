@@ -0,0 +1,836 @@
+//! JSON-LD document forms
+//!
+//! Croissant metadata is written in compacted form, with the Croissant
+//! `@context`. Consumers that work on IRIs rather than terms can ask for the
+//! expanded form, where every key and type is a full IRI and every value is
+//! a value or node object, or the flattened form, where nested nodes are
+//! lifted into a single list of node objects referencing each other by
+//! `@id`.
+//!
+//! Going the other way, [`compact`] rewrites any document with a given
+//! context, and [`frame`] gathers a graph of nodes back into a tree, which
+//! is how metadata written with a context other than rustcroissant's is
+//! read.
+//!
+//! Only inline contexts are processed; remote contexts are never fetched.
+//! Relative IRIs in `@id` are left relative unless a base IRI is given.
+
+use crate::croissant::errors::{Error, Result};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, HashMap};
+
+/// Forms a JSON-LD document can be written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonLdForm {
+    /// Terms and compact IRIs, with the document's `@context` (as written)
+    #[default]
+    Compact,
+    /// Full IRIs and explicit value objects, without a context
+    Expanded,
+    /// Expanded node objects, one per node, referencing each other by `@id`
+    Flattened,
+}
+
+impl JsonLdForm {
+    /// All forms, in the order they are listed to users
+    pub const ALL: &'static [JsonLdForm] = &[
+        JsonLdForm::Compact,
+        JsonLdForm::Expanded,
+        JsonLdForm::Flattened,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            JsonLdForm::Compact => "compact",
+            JsonLdForm::Expanded => "expanded",
+            JsonLdForm::Flattened => "flattened",
+        }
+    }
+
+    /// Parse a command-line form name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|form| form.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown JSON-LD form: {name}")))
+    }
+}
+
+/// Rewrite a compacted JSON-LD document in the given form
+///
+/// The compact form returns the document unchanged.
+pub fn to_form(document: &Value, form: JsonLdForm, base_iri: Option<&str>) -> Result<Value> {
+    match form {
+        JsonLdForm::Compact => Ok(document.clone()),
+        JsonLdForm::Expanded => expand(document, base_iri),
+        JsonLdForm::Flattened => flatten(document, base_iri),
+    }
+}
+
+/// Expand a JSON-LD document
+///
+/// The result is always an array of node objects. Keys that do not expand
+/// to an IRI are dropped, as are null values.
+pub fn expand(document: &Value, base_iri: Option<&str>) -> Result<Value> {
+    let context = ActiveContext {
+        base: base_iri.map(str::to_string),
+        ..Default::default()
+    };
+    let mut nodes = expand_element(&context, None, document)?;
+
+    // A lone top-level graph object stands for its nodes
+    if let [Value::Object(node)] = nodes.as_slice()
+        && node.len() == 1
+        && let Some(Value::Array(graph)) = node.get("@graph")
+    {
+        nodes = graph.clone();
+    }
+    // Free-floating values and bare references carry no statements
+    nodes.retain(|node| {
+        node.as_object()
+            .is_some_and(|node| !node.contains_key("@value") && !is_reference(node))
+    });
+    Ok(Value::Array(nodes))
+}
+
+/// Flatten a JSON-LD document
+///
+/// Every node becomes a top-level expanded node object, sorted by `@id`.
+/// Nodes without an `@id` get blank node identifiers (`_:b0`, `_:b1`, ...)
+/// in document order, and existing blank node identifiers are relabelled the
+/// same way.
+pub fn flatten(document: &Value, base_iri: Option<&str>) -> Result<Value> {
+    let expanded = expand(document, base_iri)?;
+    let mut flattener = Flattener::default();
+    for node in expanded.as_array().into_iter().flatten() {
+        if let Value::Object(node) = node {
+            flattener.add_node(node)?;
+        }
+    }
+    let nodes = flattener
+        .nodes
+        .into_values()
+        .filter(|node| !is_reference(node))
+        .map(Value::Object)
+        .collect();
+    Ok(Value::Array(nodes))
+}
+
+/// Compact a JSON-LD document with a context
+///
+/// The document is expanded first, so the context it was written with does
+/// not matter. Keys become the terms `context` defines for their IRI, IRIs
+/// relative to its `@vocab` or compact IRIs; types and `@vocab`-typed values
+/// become compact IRIs. Value objects become plain values, and single values
+/// are taken out of their array unless their term has a `@set` container.
+/// Several top-level nodes are kept in a `@graph`.
+pub fn compact(document: &Value, context: &Value) -> Result<Value> {
+    let expanded = expand(document, None)?;
+    let compactor = Compactor::new(ActiveContext::default().with_local(context)?);
+    let mut nodes = expanded
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+        .map(|node| compactor.node(node))
+        .collect::<Vec<_>>();
+    let mut compacted = match nodes.len() {
+        1 => nodes.remove(0),
+        _ => Map::from_iter([(
+            "@graph".to_string(),
+            Value::Array(nodes.into_iter().map(Value::Object).collect()),
+        )]),
+    };
+    compacted.insert("@context".to_string(), context.clone());
+    Ok(Value::Object(compacted))
+}
+
+/// The first node of a type in a JSON-LD document, expanded, with the
+/// top-level nodes it references embedded in place of the references
+///
+/// This gathers a document written as a graph of nodes referencing each
+/// other by `@id`, such as a flattened one, back into a single tree. A node
+/// is never embedded within itself, so cycles stay references. Blank node
+/// identifiers, which only served to link nodes, are dropped.
+pub fn frame(document: &Value, type_iri: &str) -> Result<Value> {
+    let expanded = expand(document, None)?;
+    let nodes = expanded
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+        .collect::<Vec<_>>();
+    let by_id = nodes
+        .iter()
+        .filter_map(|node| Some((node.get("@id")?.as_str()?, *node)))
+        .collect::<HashMap<_, _>>();
+    let root = nodes
+        .iter()
+        .find(|node| {
+            node.get("@type")
+                .and_then(Value::as_array)
+                .is_some_and(|types| types.iter().any(|type_| type_ == type_iri))
+        })
+        .ok_or_else(|| {
+            Error::invalid_format(format!(
+                "No node of type {type_iri} in the JSON-LD document"
+            ))
+        })?;
+    Ok(Value::Object(embed(root, &by_id, &mut Vec::new())))
+}
+
+/// A node with the references among its values replaced by the nodes they
+/// reference, except for nodes on `path`, which it is nested in
+fn embed(
+    node: &Map<String, Value>,
+    nodes: &HashMap<&str, &Map<String, Value>>,
+    path: &mut Vec<String>,
+) -> Map<String, Value> {
+    let id = node.get("@id").and_then(Value::as_str);
+    if let Some(id) = id {
+        path.push(id.to_string());
+    }
+    let embedded = node
+        .iter()
+        .filter(|(key, value)| {
+            *key != "@id" || !value.as_str().is_some_and(|id| id.starts_with("_:"))
+        })
+        .map(|(key, value)| {
+            let value = match value {
+                Value::Array(values) if !key.starts_with('@') => Value::Array(
+                    values
+                        .iter()
+                        .map(|value| embed_value(value, nodes, path))
+                        .collect(),
+                ),
+                value => value.clone(),
+            };
+            (key.clone(), value)
+        })
+        .collect();
+    if id.is_some() {
+        path.pop();
+    }
+    embedded
+}
+
+fn embed_value(
+    value: &Value,
+    nodes: &HashMap<&str, &Map<String, Value>>,
+    path: &mut Vec<String>,
+) -> Value {
+    let Value::Object(object) = value else {
+        return value.clone();
+    };
+    if object.contains_key("@value") {
+        return value.clone();
+    }
+    if let Some(items) = object.get("@list") {
+        let items = items
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|item| embed_value(item, nodes, path))
+            .collect::<Vec<_>>();
+        return json!({ "@list": items });
+    }
+    let referenced = object
+        .get("@id")
+        .and_then(Value::as_str)
+        .filter(|id| is_reference(object) && !path.iter().any(|p| p == id))
+        .and_then(|id| nodes.get(id).copied());
+    Value::Object(embed(referenced.unwrap_or(object), nodes, path))
+}
+
+/// Whether a node object only references a node, making no statements
+fn is_reference(node: &Map<String, Value>) -> bool {
+    node.len() == 1 && node.contains_key("@id")
+}
+
+// ============================================================================
+// Context processing
+// ============================================================================
+
+/// Definition of a term in the active context
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TermDefinition {
+    iri: String,
+    /// Type coercion: `@id`, `@vocab`, `@json` or a datatype IRI
+    type_: Option<String>,
+    /// Language of string values; `Some(None)` removes the default language
+    language: Option<Option<String>>,
+    list: bool,
+    /// Values are kept in an array when compacting, even a single one
+    set: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ActiveContext {
+    terms: HashMap<String, TermDefinition>,
+    vocab: Option<String>,
+    language: Option<String>,
+    base: Option<String>,
+}
+
+impl ActiveContext {
+    /// Apply a local `@context` value to this context
+    fn with_local(&self, local: &Value) -> Result<ActiveContext> {
+        let mut result = self.clone();
+        let locals = match local {
+            Value::Array(locals) => locals.iter().collect::<Vec<_>>(),
+            local => vec![local],
+        };
+        for local in locals {
+            match local {
+                Value::Null => {
+                    result = ActiveContext {
+                        base: self.base.clone(),
+                        ..Default::default()
+                    }
+                }
+                Value::String(iri) => {
+                    return Err(Error::invalid_format(format!(
+                        "Remote JSON-LD context {iri} is not supported"
+                    )));
+                }
+                Value::Object(definitions) => result.define_all(definitions)?,
+                _ => return Err(Error::invalid_format("Invalid JSON-LD @context")),
+            }
+        }
+        Ok(result)
+    }
+
+    fn define_all(&mut self, definitions: &Map<String, Value>) -> Result<()> {
+        match definitions.get("@vocab") {
+            Some(Value::String(vocab)) => self.vocab = Some(self.expand_iri(vocab, true)),
+            Some(Value::Null) => self.vocab = None,
+            _ => {}
+        }
+        match definitions.get("@language") {
+            Some(Value::String(language)) => self.language = Some(language.clone()),
+            Some(Value::Null) => self.language = None,
+            _ => {}
+        }
+        if let Some(Value::String(base)) = definitions.get("@base") {
+            self.base = Some(base.clone());
+        }
+
+        // Terms may be defined with prefixes declared later in the same
+        // context, so definitions are resolved in passes until none changes
+        let terms = definitions
+            .iter()
+            .filter(|(term, _)| !term.starts_with('@'))
+            .collect::<Vec<_>>();
+        for _ in 0..=terms.len() {
+            let mut changed = false;
+            for (term, definition) in &terms {
+                let definition = match definition {
+                    Value::Null => {
+                        changed |= self.terms.remove(term.as_str()).is_some();
+                        continue;
+                    }
+                    Value::String(iri) => TermDefinition {
+                        iri: self.expand_term_iri(term, iri),
+                        ..Default::default()
+                    },
+                    Value::Object(definition) => {
+                        let iri = match definition.get("@id") {
+                            Some(Value::String(iri)) => self.expand_term_iri(term, iri),
+                            _ => self.expand_term_iri(term, term),
+                        };
+                        let type_ = match definition.get("@type") {
+                            Some(Value::String(type_)) if type_.starts_with('@') => {
+                                Some(type_.clone())
+                            }
+                            Some(Value::String(type_)) => Some(self.expand_iri(type_, true)),
+                            _ => None,
+                        };
+                        let language = match definition.get("@language") {
+                            Some(Value::String(language)) => Some(Some(language.clone())),
+                            Some(Value::Null) => Some(None),
+                            _ => None,
+                        };
+                        let container = definition.get("@container");
+                        TermDefinition {
+                            iri,
+                            type_,
+                            language,
+                            list: container == Some(&json!("@list")),
+                            set: container == Some(&json!("@set")),
+                        }
+                    }
+                    _ => {
+                        return Err(Error::invalid_format(format!(
+                            "Invalid JSON-LD definition of term {term}"
+                        )));
+                    }
+                };
+                let previous = self.terms.insert(term.to_string(), definition.clone());
+                changed |= previous.as_ref() != Some(&definition);
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn expand_term_iri(&self, term: &str, iri: &str) -> String {
+        // A term defined as itself is expanded against the vocabulary rather
+        // than looked up, which would never terminate
+        if iri == term && !iri.contains(':') {
+            return self
+                .vocab
+                .as_ref()
+                .map_or_else(|| iri.to_string(), |vocab| format!("{vocab}{iri}"));
+        }
+        self.expand_iri(iri, true)
+    }
+
+    /// Expand a term, compact IRI or relative IRI
+    ///
+    /// `vocab` is set for keys, types and `@vocab` values, which are looked
+    /// up as terms and resolved against `@vocab`; other values are resolved
+    /// against the base IRI.
+    fn expand_iri(&self, value: &str, vocab: bool) -> String {
+        if value.starts_with('@') {
+            return value.to_string();
+        }
+        if vocab && let Some(definition) = self.terms.get(value) {
+            return definition.iri.clone();
+        }
+        if let Some((prefix, suffix)) = value.split_once(':') {
+            if prefix == "_" || suffix.starts_with("//") {
+                return value.to_string();
+            }
+            return match self.terms.get(prefix) {
+                Some(definition) => format!("{}{suffix}", definition.iri),
+                None => value.to_string(),
+            };
+        }
+        if vocab && let Some(vocab) = &self.vocab {
+            return format!("{vocab}{value}");
+        }
+        match &self.base {
+            Some(base) if !value.is_empty() => resolve_relative(base, value),
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// Resolve a relative reference against a base IRI; covers the plain path
+/// references found in metadata, not the full RFC 3986 algorithm
+fn resolve_relative(base: &str, reference: &str) -> String {
+    if reference.starts_with('#') || reference.starts_with('?') {
+        let base = base.split(['#', '?']).next().unwrap_or(base);
+        return format!("{base}{reference}");
+    }
+    let base = base.split(['#', '?']).next().unwrap_or(base);
+    if let Some(path) = reference.strip_prefix('/') {
+        let authority_end = base
+            .find("://")
+            .and_then(|start| base[start + 3..].find('/').map(|end| start + 3 + end))
+            .unwrap_or(base.len());
+        return format!("{}/{path}", &base[..authority_end]);
+    }
+    let directory = base.rfind('/').map_or(base, |end| &base[..=end]);
+    format!("{directory}{reference}")
+}
+
+// ============================================================================
+// Expansion
+// ============================================================================
+
+fn expand_element(
+    context: &ActiveContext,
+    property: Option<&TermDefinition>,
+    element: &Value,
+) -> Result<Vec<Value>> {
+    match element {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(items) => {
+            let mut expanded = Vec::new();
+            for item in items {
+                expanded.extend(expand_element(context, property, item)?);
+            }
+            Ok(expanded)
+        }
+        Value::Object(object) if object.contains_key("@value") => {
+            Ok(vec![expand_value_object(context, object)])
+        }
+        Value::Object(object) if object.contains_key("@list") => {
+            let items = expand_element(context, property, &object["@list"])?;
+            Ok(vec![json!({ "@list": items })])
+        }
+        Value::Object(object) => Ok(vec![Value::Object(expand_node(context, object)?)]),
+        scalar => Ok(expand_scalar(context, property, scalar)
+            .into_iter()
+            .collect()),
+    }
+}
+
+fn expand_value_object(context: &ActiveContext, object: &Map<String, Value>) -> Value {
+    let mut expanded = Map::new();
+    for (key, value) in object {
+        let value = match (key.as_str(), value) {
+            ("@type", Value::String(type_)) => Value::from(context.expand_iri(type_, true)),
+            _ => value.clone(),
+        };
+        expanded.insert(key.clone(), value);
+    }
+    Value::Object(expanded)
+}
+
+fn expand_scalar(
+    context: &ActiveContext,
+    property: Option<&TermDefinition>,
+    scalar: &Value,
+) -> Option<Value> {
+    let type_ = property.and_then(|p| p.type_.as_deref());
+    match (type_, scalar) {
+        (_, Value::Null) => None,
+        (Some("@id"), Value::String(iri)) => Some(json!({ "@id": context.expand_iri(iri, false) })),
+        (Some("@vocab"), Value::String(iri)) => {
+            Some(json!({ "@id": context.expand_iri(iri, true) }))
+        }
+        (Some(type_), scalar) if !type_.starts_with('@') => {
+            Some(json!({ "@value": scalar, "@type": type_ }))
+        }
+        (_, Value::String(text)) => {
+            let language = match property.and_then(|p| p.language.clone()) {
+                Some(language) => language,
+                None => context.language.clone(),
+            };
+            Some(match language {
+                Some(language) => json!({ "@value": text, "@language": language }),
+                None => json!({ "@value": text }),
+            })
+        }
+        (_, scalar) => Some(json!({ "@value": scalar })),
+    }
+}
+
+fn expand_node(parent: &ActiveContext, object: &Map<String, Value>) -> Result<Map<String, Value>> {
+    let context = match object.get("@context") {
+        Some(local) => parent.with_local(local)?,
+        None => parent.clone(),
+    };
+
+    let mut expanded = Map::new();
+    for (key, value) in object {
+        match key.as_str() {
+            "@context" => {}
+            "@id" => {
+                let id = value
+                    .as_str()
+                    .ok_or_else(|| Error::invalid_format("JSON-LD @id must be a string"))?;
+                expanded.insert(key.clone(), Value::from(context.expand_iri(id, false)));
+            }
+            "@type" => {
+                let types = match value {
+                    Value::Array(types) => types.iter().collect::<Vec<_>>(),
+                    type_ => vec![type_],
+                };
+                let types = types
+                    .into_iter()
+                    .map(|type_| {
+                        type_
+                            .as_str()
+                            .map(|type_| Value::from(context.expand_iri(type_, true)))
+                            .ok_or_else(|| Error::invalid_format("JSON-LD @type must be a string"))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                expanded.insert(key.clone(), Value::Array(types));
+            }
+            "@graph" => {
+                let graph = expand_element(&context, None, value)?;
+                expanded.insert(key.clone(), Value::Array(graph));
+            }
+            keyword if keyword.starts_with('@') => {
+                expanded.insert(key.clone(), value.clone());
+            }
+            term => {
+                let definition = context.terms.get(term);
+                let iri = context.expand_iri(term, true);
+                if !iri.contains(':') {
+                    tracing::debug!(term, "dropping key that does not expand to an IRI");
+                    continue;
+                }
+
+                let mut values = if definition.and_then(|d| d.type_.as_deref()) == Some("@json") {
+                    vec![json!({ "@value": value, "@type": "@json" })]
+                } else {
+                    expand_element(&context, definition, value)?
+                };
+                if definition.is_some_and(|d| d.list) {
+                    values = vec![json!({ "@list": values })];
+                }
+                if values.is_empty() {
+                    continue;
+                }
+                match expanded.get_mut(&iri) {
+                    Some(Value::Array(existing)) => existing.extend(values),
+                    _ => {
+                        expanded.insert(iri, Value::Array(values));
+                    }
+                }
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+// ============================================================================
+// Flattening
+// ============================================================================
+
+#[derive(Default)]
+struct Flattener {
+    nodes: BTreeMap<String, Map<String, Value>>,
+    blank_nodes: HashMap<String, String>,
+    next_blank_node: usize,
+}
+
+impl Flattener {
+    fn blank_node(&mut self, label: Option<&str>) -> String {
+        if let Some(label) = label
+            && let Some(relabelled) = self.blank_nodes.get(label)
+        {
+            return relabelled.clone();
+        }
+        let relabelled = format!("_:b{}", self.next_blank_node);
+        self.next_blank_node += 1;
+        if let Some(label) = label {
+            self.blank_nodes
+                .insert(label.to_string(), relabelled.clone());
+        }
+        relabelled
+    }
+
+    fn node_id(&mut self, node: &Map<String, Value>) -> String {
+        match node.get("@id").and_then(Value::as_str) {
+            Some(id) if id.starts_with("_:") => self.blank_node(Some(id)),
+            Some(id) => id.to_string(),
+            None => self.blank_node(None),
+        }
+    }
+
+    /// Add a node and the nodes nested in it, returning its identifier
+    fn add_node(&mut self, node: &Map<String, Value>) -> Result<String> {
+        let id = self.node_id(node);
+        self.nodes
+            .entry(id.clone())
+            .or_insert_with(|| Map::from_iter([("@id".to_string(), Value::from(id.clone()))]));
+
+        for (key, value) in node {
+            let values = match key.as_str() {
+                "@id" => continue,
+                "@graph" => {
+                    return Err(Error::invalid_format(
+                        "Flattening named graphs is not supported",
+                    ));
+                }
+                "@type" => value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|type_| match type_.as_str() {
+                        Some(label) if label.starts_with("_:") => {
+                            Value::from(self.blank_node(Some(label)))
+                        }
+                        _ => type_.clone(),
+                    })
+                    .collect::<Vec<_>>(),
+                keyword if keyword.starts_with('@') => {
+                    self.node_entry(&id).insert(key.clone(), value.clone());
+                    continue;
+                }
+                _ => {
+                    let mut values = Vec::new();
+                    for value in value.as_array().into_iter().flatten() {
+                        values.push(self.flatten_value(value)?);
+                    }
+                    values
+                }
+            };
+
+            let existing = self
+                .node_entry(&id)
+                .entry(key.clone())
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(existing) = existing {
+                for value in values {
+                    if !existing.contains(&value) {
+                        existing.push(value);
+                    }
+                }
+            }
+        }
+        Ok(id)
+    }
+
+    fn flatten_value(&mut self, value: &Value) -> Result<Value> {
+        let Value::Object(object) = value else {
+            return Ok(value.clone());
+        };
+        if object.contains_key("@value") {
+            return Ok(value.clone());
+        }
+        if let Some(items) = object.get("@list") {
+            let mut flattened = Vec::new();
+            for item in items.as_array().into_iter().flatten() {
+                flattened.push(self.flatten_value(item)?);
+            }
+            return Ok(json!({ "@list": flattened }));
+        }
+        Ok(json!({ "@id": self.add_node(object)? }))
+    }
+
+    fn node_entry(&mut self, id: &str) -> &mut Map<String, Value> {
+        self.nodes
+            .get_mut(id)
+            .expect("node added before its entries")
+    }
+}
+
+// ============================================================================
+// Compaction
+// ============================================================================
+
+struct Compactor {
+    context: ActiveContext,
+    /// Term chosen for each IRI that terms are defined for
+    terms: HashMap<String, String>,
+}
+
+impl Compactor {
+    fn new(context: ActiveContext) -> Self {
+        let mut terms = HashMap::<String, String>::new();
+        for (term, definition) in &context.terms {
+            let chosen = terms.entry(definition.iri.clone()).or_insert(term.clone());
+            // The shortest term wins, and the first alphabetically among
+            // equally short ones, so the choice does not depend on hashing
+            if (term.len(), term) < (chosen.len(), chosen) {
+                *chosen = term.clone();
+            }
+        }
+        Self { context, terms }
+    }
+
+    /// Compact an IRI; `vocab` is set for keys, which may become terms or
+    /// be written relative to `@vocab`
+    fn compact_iri(&self, iri: &str, vocab: bool) -> String {
+        if vocab {
+            if let Some(term) = self.terms.get(iri) {
+                return term.clone();
+            }
+            if let Some(suffix) = self
+                .context
+                .vocab
+                .as_deref()
+                .and_then(|vocab| iri.strip_prefix(vocab))
+                && !suffix.is_empty()
+                && !suffix.contains(':')
+                && !self.context.terms.contains_key(suffix)
+            {
+                return suffix.to_string();
+            }
+        }
+        self.context
+            .terms
+            .iter()
+            .filter(|(term, definition)| {
+                !term.contains(':')
+                    && definition.iri.ends_with(['/', '#'])
+                    && iri.len() > definition.iri.len()
+                    && iri.starts_with(&definition.iri)
+            })
+            .max_by(|(a, a_def), (b, b_def)| {
+                (a_def.iri.len(), b.as_str()).cmp(&(b_def.iri.len(), a.as_str()))
+            })
+            .map_or_else(
+                || iri.to_string(),
+                |(prefix, definition)| format!("{prefix}:{}", &iri[definition.iri.len()..]),
+            )
+    }
+
+    fn node(&self, node: &Map<String, Value>) -> Map<String, Value> {
+        let mut compacted = Map::new();
+        for (key, value) in node {
+            let (key, value) = match key.as_str() {
+                "@type" => {
+                    let mut types = value
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .map(|type_| Value::from(self.compact_iri(type_, false)))
+                        .collect::<Vec<_>>();
+                    let types = match types.len() {
+                        1 => types.remove(0),
+                        _ => Value::Array(types),
+                    };
+                    (key.clone(), types)
+                }
+                "@graph" => {
+                    let graph = value
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_object)
+                        .map(|node| Value::Object(self.node(node)))
+                        .collect();
+                    (key.clone(), Value::Array(graph))
+                }
+                keyword if keyword.starts_with('@') => (key.clone(), value.clone()),
+                iri => {
+                    let term = self.compact_iri(iri, true);
+                    let definition = self.context.terms.get(&term);
+                    let mut values = value
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|value| self.value(definition, value))
+                        .collect::<Vec<_>>();
+                    let values = match values.len() {
+                        1 if !definition.is_some_and(|d| d.set) => values.remove(0),
+                        _ => Value::Array(values),
+                    };
+                    (term, values)
+                }
+            };
+            compacted.insert(key, value);
+        }
+        compacted
+    }
+
+    fn value(&self, property: Option<&TermDefinition>, value: &Value) -> Value {
+        let Value::Object(object) = value else {
+            return value.clone();
+        };
+        if let Some(value) = object.get("@value") {
+            return value.clone();
+        }
+        if let Some(items) = object.get("@list") {
+            let items = items
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|item| self.value(property, item))
+                .collect();
+            return Value::Array(items);
+        }
+        if is_reference(object)
+            && let Some(id) = object.get("@id").and_then(Value::as_str)
+        {
+            match property.and_then(|p| p.type_.as_deref()) {
+                Some("@vocab") => return Value::from(self.compact_iri(id, false)),
+                Some("@id") => return Value::from(id),
+                _ => {}
+            }
+        }
+        Value::Object(self.node(object))
+    }
+}
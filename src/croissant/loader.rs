@@ -0,0 +1,301 @@
+//! Loading the records of a dataset from its metadata
+//!
+//! A [`Loader`] holds a dataset's metadata and the location its relative
+//! content URLs are resolved against, either a local directory or, for
+//! datasets published on the web, a base URL. Its records are read with
+//! [`records`](crate::croissant::records), each field's `extract.column`
+//! naming the column or key its values come from, and handed out as JSON
+//! objects keyed by field name.
+//!
+//! With the `remote` feature, files at HTTP(S) URLs are downloaded through
+//! the [download cache](crate::croissant::remote::download) and checked
+//! against their recorded SHA-256 digest before being read.
+//!
+//! ```no_run
+//! use rustcroissant::croissant::loader::Loader;
+//!
+//! let loader = Loader::open("stations/metadata.json".as_ref())?;
+//! for record in loader.records("readings")? {
+//!     let record = record?;
+//!     println!("{} {}", record["station"], record["flow_rate"]);
+//! }
+//! # Ok::<(), rustcroissant::croissant::Error>(())
+//! ```
+
+use crate::croissant::core::{Field, Metadata};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::records::{
+    RecordField, RecordOptions, RecordReader, read_records_with_options,
+};
+use crate::croissant::validate::read_metadata;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A record, as a JSON object keyed by field name
+pub type JsonRecord = serde_json::Map<String, serde_json::Value>;
+
+/// Where relative content URLs are resolved
+#[derive(Debug, Clone, PartialEq)]
+enum Base {
+    Dir(PathBuf),
+    Url(String),
+}
+
+/// Reader of the records of a dataset
+#[derive(Debug, Clone)]
+pub struct Loader {
+    metadata: Metadata,
+    base: Base,
+    options: RecordOptions,
+    #[cfg(feature = "remote")]
+    download_cache: Option<crate::croissant::remote::download::DownloadCache>,
+}
+
+impl Loader {
+    /// Loader of a dataset whose relative content URLs are relative to
+    /// `base_dir`
+    pub fn new(metadata: Metadata, base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            metadata,
+            base: Base::Dir(base_dir.into()),
+            options: RecordOptions::default(),
+            #[cfg(feature = "remote")]
+            download_cache: None,
+        }
+    }
+
+    /// Loader of the dataset described by a metadata file, with relative
+    /// content URLs resolved against the file's directory
+    pub fn open(metadata_path: &Path) -> Result<Self> {
+        let metadata = read_metadata(metadata_path)?;
+        let base_dir = metadata_path.parent().unwrap_or(Path::new(""));
+        Ok(Self::new(metadata, base_dir))
+    }
+
+    /// Resolve relative content URLs against a URL, such as that the
+    /// metadata was published at, rather than a directory
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base = Base::Url(url.into());
+        self
+    }
+
+    /// Options the records are read with, such as a split or a limit
+    pub fn with_options(mut self, options: RecordOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Cache remote files are downloaded into; without one, the default
+    /// cache directory is used
+    #[cfg(feature = "remote")]
+    pub fn with_download_cache(
+        mut self,
+        cache: crate::croissant::remote::download::DownloadCache,
+    ) -> Self {
+        self.download_cache = Some(cache);
+        self
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Names of the record sets of the dataset
+    pub fn record_sets(&self) -> impl Iterator<Item = &str> {
+        self.metadata
+            .record_set
+            .iter()
+            .map(|record_set| record_set.name.as_str())
+    }
+
+    /// Read the records of a record set, looked up by name or `@id`
+    ///
+    /// Remote files the record set draws from are downloaded first; local
+    /// files are read lazily.
+    pub fn records(&self, record_set: &str) -> Result<JsonRecords> {
+        let record_set_id = self
+            .metadata
+            .find_record_set(record_set)
+            .ok_or_else(|| Error::missing_field(format!("recordSet {record_set}")))?
+            .id
+            .clone();
+
+        let mut metadata = self.metadata.clone();
+        let used = self.distributions_used(&record_set_id);
+        let mut remote = Vec::new();
+        for distribution in &mut metadata.distribution {
+            if !used.contains(distribution.id.as_str()) {
+                continue;
+            }
+            let url = &mut distribution.content_url;
+            if let Base::Url(base) = &self.base
+                && !url.contains("://")
+            {
+                *url = join_url(base, url);
+            }
+            if url.contains("://") {
+                remote.push((distribution.id.clone(), url.clone()));
+            }
+        }
+        for (id, path) in self.download(&metadata, &remote)? {
+            if let Some(distribution) = metadata.distribution.iter_mut().find(|d| d.id == id) {
+                distribution.content_url = path.to_string_lossy().to_string();
+            }
+        }
+
+        let base_dir = match &self.base {
+            Base::Dir(dir) => dir.as_path(),
+            Base::Url(_) => Path::new(""),
+        };
+        let reader = read_records_with_options(&metadata, &record_set_id, base_dir, &self.options)?;
+        Ok(JsonRecords::new(reader))
+    }
+
+    /// `@id`s of the distributions reading a record set may open: those its
+    /// fields extract from, and, when references are resolved, every other
+    fn distributions_used(&self, record_set_id: &str) -> HashSet<&str> {
+        fn sources<'a>(fields: &'a [Field], used: &mut HashSet<&'a str>) {
+            for field in fields {
+                if let Some(source) = &field.source {
+                    used.insert(source.distribution_id());
+                }
+                sources(&field.sub_field, used);
+            }
+        }
+
+        let mut used = HashSet::new();
+        for record_set in &self.metadata.record_set {
+            if record_set.id == record_set_id || self.options.resolve_references {
+                sources(&record_set.field, &mut used);
+            }
+        }
+        used
+    }
+
+    /// Download remote distributions, given by `@id` and URL, checking
+    /// each against its recorded digest; returns their local paths
+    #[cfg(feature = "remote")]
+    fn download(
+        &self,
+        metadata: &Metadata,
+        remote: &[(String, String)],
+    ) -> Result<Vec<(String, PathBuf)>> {
+        use crate::croissant::remote::download::{CacheMode, DownloadCache};
+        use crate::croissant::remote::{DEFAULT_MAX_CONCURRENT_FETCHES, Progress};
+
+        if remote.is_empty() {
+            return Ok(Vec::new());
+        }
+        let cache = match &self.download_cache {
+            Some(cache) => cache.clone(),
+            None => DownloadCache::open_default(CacheMode::Revalidate)
+                .ok_or_else(|| Error::new("No cache directory could be determined"))?,
+        };
+        let urls = remote
+            .iter()
+            .map(|(_, url)| url.clone())
+            .collect::<Vec<_>>();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let downloads = runtime.block_on(cache.fetch_many(
+            &urls,
+            DEFAULT_MAX_CONCURRENT_FETCHES,
+            &Progress::new(),
+        ));
+
+        remote
+            .iter()
+            .zip(downloads)
+            .map(|((id, url), download)| {
+                let download = download?;
+                let recorded = metadata
+                    .distribution
+                    .iter()
+                    .find(|d| &d.id == id)
+                    .map_or("", |d| d.sha256.as_str());
+                if !recorded.is_empty() && !recorded.eq_ignore_ascii_case(&download.sha256) {
+                    return Err(Error::remote(
+                        url,
+                        format!(
+                            "downloaded content has SHA-256 {}, but {} is recorded",
+                            download.sha256, recorded
+                        ),
+                    ));
+                }
+                Ok((id.clone(), download.path))
+            })
+            .collect()
+    }
+
+    /// Download remote distributions; always fails when there are any,
+    /// since rustcroissant was built without the `remote` feature
+    #[cfg(not(feature = "remote"))]
+    fn download(
+        &self,
+        _metadata: &Metadata,
+        remote: &[(String, String)],
+    ) -> Result<Vec<(String, PathBuf)>> {
+        match remote.first() {
+            Some((_, url)) => Err(Error::new(format!(
+                "Loading {url} requires rustcroissant to be built with the remote feature"
+            ))),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Resolve a relative URL against a base URL, the base's last path segment
+/// being replaced unless it ends with `/`
+fn join_url(base: &str, relative: &str) -> String {
+    if let Some(rest) = relative.strip_prefix('/') {
+        let origin_end = base
+            .find("://")
+            .and_then(|scheme| base[scheme + 3..].find('/').map(|i| scheme + 3 + i))
+            .unwrap_or(base.len());
+        return format!("{}/{rest}", &base[..origin_end]);
+    }
+    let dir = match base.rfind('/') {
+        Some(i) if !base[..i].ends_with('/') => &base[..=i],
+        _ => return format!("{base}/{relative}"),
+    };
+    format!("{dir}{}", relative.trim_start_matches("./"))
+}
+
+/// Records of a record set as JSON objects, read lazily
+pub struct JsonRecords {
+    reader: RecordReader,
+    names: Vec<String>,
+}
+
+impl JsonRecords {
+    fn new(reader: RecordReader) -> Self {
+        let names = reader.fields().iter().map(|f| f.name.clone()).collect();
+        Self { reader, names }
+    }
+
+    /// Names and types of the fields, in record order
+    pub fn fields(&self) -> &[RecordField] {
+        self.reader.fields()
+    }
+
+    /// The underlying reader, yielding typed values
+    pub fn into_reader(self) -> RecordReader {
+        self.reader
+    }
+}
+
+impl Iterator for JsonRecords {
+    type Item = Result<JsonRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.reader.next()?;
+        Some(record.map(|values| {
+            self.names
+                .iter()
+                .cloned()
+                .zip(values.iter().map(|value| value.to_json()))
+                .collect()
+        }))
+    }
+}
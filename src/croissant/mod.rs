@@ -1,5 +1,46 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod builder;
+pub mod cache;
+pub mod config;
 mod core;
+pub mod dictionary;
+pub mod diff;
+pub mod encoding;
 mod errors;
+pub mod export;
+pub mod fileset;
+pub mod fingerprint;
 pub mod generate;
+pub mod import;
+pub mod jsonld;
+pub mod loader;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "publish")]
+pub mod publish;
+#[cfg(feature = "duckdb")]
+pub mod query;
+#[cfg(feature = "rdf")]
+pub mod rdf;
+pub mod records;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod spec;
+pub mod stats;
+pub mod summary;
+pub mod units;
 pub mod utils;
 pub mod validate;
+pub mod verify;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+
+pub use core::{
+    BadRowPolicy, Context, Creator, DEFAULT_ENUM_MAX_VALUES, DEFAULT_NULL_VALUES, DataContext,
+    DataType, DataTypeContext, Distribution, Extract, Field, FieldId, FieldReference, FieldSource,
+    FileObject, FileProperty, InferenceOptions, Metadata, NumberAffix, NumberLocale, RAI_NAMESPACE,
+    RUSTCROISSANT_NAMESPACE, Rai, RecordSet, SemanticType, ValueStatistics, infer_data_type,
+    is_null_value,
+};
+pub use errors::{Error, Result};
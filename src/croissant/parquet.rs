@@ -0,0 +1,78 @@
+//! Parquet files: schemas of files to describe and export of materialized
+//! records
+//!
+//! Metadata is generated for Parquet files from the Arrow schema stored in
+//! their footer, which gives every column's type without reading any rows.
+//! Records are grouped into Arrow batches typed after the fields' dataTypes
+//! and written with Snappy compression, one row group per
+//! [`DEFAULT_ROW_GROUP_SIZE`] records, so the typed, transformed and joined
+//! records of a record set can be loaded by any Parquet reader.
+
+use crate::croissant::arrow::{croissant_data_type, record_batches};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::records::RecordReader;
+use crate::croissant::utils::{ColumnSchema, CsvSchema};
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use ::parquet::basic::Compression;
+use ::parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::path::Path;
+
+/// Default number of records per row group
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 128 * 1024;
+
+/// encodingFormat of Parquet distributions
+pub const PARQUET_ENCODING_FORMAT: &str = "application/x-parquet";
+
+/// Columns of a Parquet file, read from the schema in its footer
+///
+/// Columns are nullable as declared in the schema.
+pub(crate) fn read_parquet_schema(path: &Path) -> Result<CsvSchema> {
+    let file = std::fs::File::open(path).map_err(|_| Error::file_not_found(path))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).map_err(|e| {
+        Error::invalid_format(format!("{} is not a Parquet file: {e}", path.display()))
+    })?;
+    let fields = builder.schema().fields();
+    Ok(CsvSchema {
+        headers: fields.iter().map(|field| field.name().clone()).collect(),
+        columns: fields
+            .iter()
+            .map(|field| ColumnSchema {
+                data_type: croissant_data_type(field.data_type()),
+                nullable: field.is_nullable(),
+                null_values: Vec::new(),
+                transform: Vec::new(),
+                semantic_type: None,
+                enum_values: None,
+                unit: None,
+            })
+            .collect(),
+        bad_rows: 0,
+    })
+}
+
+/// Write every record of `reader` as a Parquet file, returning how many were
+/// written
+#[tracing::instrument(level = "debug", skip(reader, output))]
+pub fn write_parquet(reader: RecordReader, output: impl Write + Send) -> Result<usize> {
+    let batches = record_batches(reader, crate::croissant::arrow::DEFAULT_BATCH_SIZE);
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_max_row_group_size(DEFAULT_ROW_GROUP_SIZE)
+        .build();
+    let mut writer =
+        ArrowWriter::try_new(output, batches.schema(), Some(properties)).map_err(parquet_error)?;
+    let mut written = 0;
+    for batch in batches {
+        let batch = batch?;
+        written += batch.num_rows();
+        writer.write(&batch).map_err(parquet_error)?;
+    }
+    writer.close().map_err(parquet_error)?;
+    Ok(written)
+}
+
+fn parquet_error(error: ::parquet::errors::ParquetError) -> Error {
+    Error::new(format!("Could not write Parquet: {error}"))
+}
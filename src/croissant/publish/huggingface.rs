@@ -0,0 +1,462 @@
+//! Hugging Face Hub (`hf://user/dataset`) publishing
+//!
+//! Everything is uploaded in a single commit through the Hub's commit API:
+//! the metadata as `croissant.json`, the dataset card with its front matter
+//! filled from the metadata and, when asked for, the local files of the
+//! distributions. Data files go through Git LFS and keep their relative
+//! content URL as their path, so the published metadata still resolves.
+//!
+//! The access token is read from `HF_TOKEN` or the token file written by
+//! `huggingface-cli login`; `HF_ENDPOINT` points at another Hub instance.
+
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::publish::{
+    DataFile, PublishOptions, Published, check_response, local_data_files,
+};
+use crate::croissant::utils::is_yaml_path;
+use crate::croissant::validate::read_metadata;
+use crate::croissant::{Metadata, RecordSet};
+use base64::Engine;
+use reqwest::header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE};
+use reqwest::{RequestBuilder, StatusCode};
+use serde_json::{Value, json};
+use serde_yaml::{Mapping, Value as YamlValue};
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the Hub access token
+pub const TOKEN_ENV: &str = "HF_TOKEN";
+
+/// Environment variable overriding the Hub endpoint
+pub const ENDPOINT_ENV: &str = "HF_ENDPOINT";
+
+const DEFAULT_ENDPOINT: &str = "https://huggingface.co";
+
+/// Path of the metadata in the repository
+pub const METADATA_FILE_NAME: &str = "croissant.json";
+
+/// Path of the dataset card in the repository
+const CARD_FILE_NAME: &str = "README.md";
+
+/// Branch commits are made to
+const REVISION: &str = "main";
+
+/// Repository id (`user/dataset`) of an `hf://user/dataset` or
+/// `hf://datasets/user/dataset` target
+pub fn parse_repo_id(target: &str) -> Result<String> {
+    let invalid = || {
+        Error::new(format!(
+            "Invalid Hugging Face Hub target {target}: expected hf://user/dataset"
+        ))
+    };
+    let rest = target.strip_prefix("hf://").ok_or_else(invalid)?;
+    let repo_id = rest
+        .strip_prefix("datasets/")
+        .unwrap_or(rest)
+        .trim_end_matches('/');
+    match repo_id.split('/').collect::<Vec<_>>()[..] {
+        [owner, name] if !owner.is_empty() && !name.is_empty() => Ok(repo_id.to_string()),
+        _ => Err(invalid()),
+    }
+}
+
+/// Publish a metadata file to the Hub repository named by `target`, with
+/// the endpoint and token from the environment
+pub async fn publish(
+    metadata_path: &Path,
+    target: &str,
+    options: &PublishOptions,
+) -> Result<Published> {
+    let repo_id = parse_repo_id(target)?;
+    HubClient::from_env()?
+        .publish(&repo_id, metadata_path, options)
+        .await
+}
+
+/// Hub API client authenticated with a user access token
+#[derive(Debug, Clone)]
+pub struct HubClient {
+    client: reqwest::Client,
+    endpoint: String,
+    token: String,
+}
+
+impl HubClient {
+    pub fn new(endpoint: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+        }
+    }
+
+    /// Client for `HF_ENDPOINT` (default `https://huggingface.co`), with the
+    /// token from `HF_TOKEN` or the `huggingface-cli login` token file
+    pub fn from_env() -> Result<Self> {
+        let endpoint = std::env::var(ENDPOINT_ENV).unwrap_or_else(|_| DEFAULT_ENDPOINT.to_string());
+        let token = std::env::var(TOKEN_ENV)
+            .ok()
+            .or_else(token_from_file)
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| {
+                Error::new(format!(
+                    "No Hugging Face Hub token found: set {TOKEN_ENV} or run huggingface-cli login"
+                ))
+            })?;
+        Ok(Self::new(endpoint, token))
+    }
+
+    /// Upload a metadata file, its dataset card and, if asked for, the
+    /// local files of its distributions in a single commit, creating the
+    /// repository if it does not exist yet
+    #[tracing::instrument(level = "debug", skip(self, options))]
+    pub async fn publish(
+        &self,
+        repo_id: &str,
+        metadata_path: &Path,
+        options: &PublishOptions,
+    ) -> Result<Published> {
+        let metadata = read_metadata(metadata_path)?;
+        // Upload the file as written; YAML metadata is converted to JSON-LD
+        let metadata_json = if is_yaml_path(metadata_path) {
+            serde_json::to_string_pretty(&metadata)?
+        } else {
+            std::fs::read_to_string(metadata_path)?
+        };
+        let data_files = if options.include_data {
+            let base_dir = metadata_path.parent().unwrap_or(Path::new("."));
+            local_data_files(&metadata, base_dir, &[METADATA_FILE_NAME, CARD_FILE_NAME]).await?
+        } else {
+            Vec::new()
+        };
+
+        self.create_repo(repo_id, options.private).await?;
+        let existing_card = self.read_file(repo_id, CARD_FILE_NAME).await?;
+        let card = dataset_card(
+            &metadata,
+            existing_card.as_deref(),
+            &data_files_by_id(&data_files),
+        )?;
+        for file in &data_files {
+            self.upload_lfs(repo_id, file).await?;
+        }
+
+        let message = options
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("Publish Croissant metadata for {}", metadata.name));
+        let mut operations = vec![
+            json!({"key": "header", "value": {"summary": message, "description": ""}}),
+            regular_file(METADATA_FILE_NAME, metadata_json.as_bytes()),
+            regular_file(CARD_FILE_NAME, card.as_bytes()),
+        ];
+        operations.extend(data_files.iter().map(|file| {
+            json!({"key": "lfsFile", "value": {
+                "path": file.path,
+                "algo": "sha256",
+                "oid": file.sha256,
+                "size": file.size,
+            }})
+        }));
+        self.commit(repo_id, &operations).await?;
+
+        let mut files = vec![METADATA_FILE_NAME.to_string(), CARD_FILE_NAME.to_string()];
+        files.extend(data_files.into_iter().map(|file| file.path));
+        Ok(Published {
+            url: format!("{}/datasets/{repo_id}", self.endpoint),
+            files,
+            doi: None,
+        })
+    }
+
+    /// Create a dataset repository, doing nothing if it already exists
+    async fn create_repo(&self, repo_id: &str, private: bool) -> Result<()> {
+        let url = format!("{}/api/repos/create", self.endpoint);
+        let (organization, name) = repo_id.split_once('/').unwrap_or(("", repo_id));
+        let body = json!({
+            "type": "dataset",
+            "name": name,
+            "organization": organization,
+            "private": private,
+        });
+        let response = self
+            .authorized(self.client.post(&url))
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| Error::remote(&url, e.to_string()))?;
+        if response.status() == StatusCode::CONFLICT {
+            tracing::debug!(repo_id, "repository already exists");
+            return Ok(());
+        }
+        check_response(response, &url).await?;
+        tracing::info!(repo_id, private, "created repository");
+        Ok(())
+    }
+
+    /// Content of a file in the repository, if it exists
+    async fn read_file(&self, repo_id: &str, path: &str) -> Result<Option<String>> {
+        let url = format!(
+            "{}/datasets/{repo_id}/resolve/{REVISION}/{path}",
+            self.endpoint
+        );
+        let response = self
+            .authorized(self.client.get(&url))
+            .send()
+            .await
+            .map_err(|e| Error::remote(&url, e.to_string()))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let text = check_response(response, &url)
+            .await?
+            .text()
+            .await
+            .map_err(|e| Error::remote(&url, e.to_string()))?;
+        Ok(Some(text))
+    }
+
+    /// Upload a data file to the repository's LFS storage, unless the Hub
+    /// already holds content with its digest
+    async fn upload_lfs(&self, repo_id: &str, file: &DataFile) -> Result<()> {
+        let url = format!(
+            "{}/datasets/{repo_id}.git/info/lfs/objects/batch",
+            self.endpoint
+        );
+        let body = json!({
+            "operation": "upload",
+            "transfers": ["basic"],
+            "hash_algo": "sha256",
+            "ref": {"name": REVISION},
+            "objects": [{"oid": file.sha256, "size": file.size}],
+        });
+        let batch = self
+            .send_json(
+                self.authorized(self.client.post(&url))
+                    .header(ACCEPT, "application/vnd.git-lfs+json")
+                    .header(CONTENT_TYPE, "application/vnd.git-lfs+json")
+                    .body(body.to_string()),
+                &url,
+            )
+            .await?;
+        let object = &batch["objects"][0];
+        if let Some(error) = object.get("error") {
+            return Err(Error::remote(&url, format!("LFS batch failed: {error}")));
+        }
+        let Some(upload) = object["actions"].get("upload") else {
+            tracing::debug!(path = file.path, "content already on the Hub");
+            return Ok(());
+        };
+
+        let href = action_href(upload, &url)?;
+        let content = tokio::fs::File::open(&file.local_path).await?;
+        // Storage backends reject chunked uploads, so send the length
+        let request = with_action_headers(self.client.put(href), upload)
+            .header(CONTENT_LENGTH, file.size)
+            .body(content);
+        check_response(
+            request
+                .send()
+                .await
+                .map_err(|e| Error::remote(href, e.to_string()))?,
+            href,
+        )
+        .await?;
+        tracing::info!(path = file.path, size = file.size, "uploaded data file");
+
+        if let Some(verify) = object["actions"].get("verify") {
+            let href = action_href(verify, &url)?;
+            let body = json!({"oid": file.sha256, "size": file.size});
+            let request = with_action_headers(self.client.post(href), verify)
+                .header(CONTENT_TYPE, "application/vnd.git-lfs+json")
+                .body(body.to_string());
+            check_response(
+                request
+                    .send()
+                    .await
+                    .map_err(|e| Error::remote(href, e.to_string()))?,
+                href,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Make a commit with the given header and file operations
+    async fn commit(&self, repo_id: &str, operations: &[Value]) -> Result<()> {
+        let url = format!("{}/api/datasets/{repo_id}/commit/{REVISION}", self.endpoint);
+        let body = operations
+            .iter()
+            .map(|operation| operation.to_string() + "\n")
+            .collect::<String>();
+        let commit = self
+            .send_json(
+                self.authorized(self.client.post(&url))
+                    .header(CONTENT_TYPE, "application/x-ndjson")
+                    .body(body),
+                &url,
+            )
+            .await?;
+        tracing::info!(commit = %commit["commitOid"], "committed to the Hub");
+        Ok(())
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        request.bearer_auth(&self.token)
+    }
+
+    /// Send a request and parse its JSON response
+    async fn send_json(&self, request: RequestBuilder, url: &str) -> Result<Value> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::remote(url, e.to_string()))?;
+        let bytes = check_response(response, url)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| Error::remote(url, e.to_string()))?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+/// URL of an LFS action
+fn action_href<'a>(action: &'a Value, url: &str) -> Result<&'a str> {
+    action["href"]
+        .as_str()
+        .ok_or_else(|| Error::remote(url, "LFS action without href"))
+}
+
+/// Add the headers an LFS action asks for
+fn with_action_headers(mut request: RequestBuilder, action: &Value) -> RequestBuilder {
+    if let Some(headers) = action["header"].as_object() {
+        for (name, value) in headers {
+            if let Some(value) = value.as_str() {
+                request = request.header(name, value);
+            }
+        }
+    }
+    request
+}
+
+/// Commit operation adding a small file inline
+fn regular_file(path: &str, content: &[u8]) -> Value {
+    json!({"key": "file", "value": {
+        "path": path,
+        "content": base64::engine::general_purpose::STANDARD.encode(content),
+        "encoding": "base64",
+    }})
+}
+
+/// Token saved by `huggingface-cli login`: `$HF_TOKEN_PATH`, or `token`
+/// in `$HF_HOME` (default `~/.cache/huggingface`)
+fn token_from_file() -> Option<String> {
+    let path = match std::env::var_os("HF_TOKEN_PATH") {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let home = match std::env::var_os("HF_HOME") {
+                Some(home) => PathBuf::from(home),
+                None => match std::env::var_os("XDG_CACHE_HOME") {
+                    Some(cache) => PathBuf::from(cache).join("huggingface"),
+                    None => PathBuf::from(std::env::var_os("HOME")?)
+                        .join(".cache")
+                        .join("huggingface"),
+                },
+            };
+            home.join("token")
+        }
+    };
+    let token = std::fs::read_to_string(path).ok()?;
+    Some(token.trim().to_string())
+}
+
+/// Repository paths of the uploaded files, by distribution `@id`
+fn data_files_by_id(files: &[DataFile]) -> Vec<(&str, &str)> {
+    files
+        .iter()
+        .map(|file| (file.distribution_id.as_str(), file.path.as_str()))
+        .collect()
+}
+
+/// Dataset card for the metadata
+///
+/// An existing card keeps its text and front matter, with `pretty_name`
+/// set to the dataset name and, unless it already has them, a `configs`
+/// entry for each record set whose file was uploaded. Without a card, one
+/// is written from the name, description and version.
+fn dataset_card(
+    metadata: &Metadata,
+    existing: Option<&str>,
+    data_files: &[(&str, &str)],
+) -> Result<String> {
+    let (mut front_matter, body) = match existing {
+        Some(card) => split_front_matter(card)?,
+        None => (Mapping::new(), default_card_body(metadata)),
+    };
+
+    front_matter.insert("pretty_name".into(), metadata.name.clone().into());
+    let configs = metadata
+        .record_set
+        .iter()
+        .filter_map(|record_set| {
+            let path = record_set_file(record_set, data_files)?;
+            let mut config = Mapping::new();
+            config.insert("config_name".into(), record_set.name.clone().into());
+            config.insert("data_files".into(), path.into());
+            Some(YamlValue::Mapping(config))
+        })
+        .collect::<Vec<_>>();
+    if !configs.is_empty() && !front_matter.contains_key("configs") {
+        front_matter.insert("configs".into(), YamlValue::Sequence(configs));
+    }
+
+    Ok(format!(
+        "---\n{}---\n{body}",
+        serde_yaml::to_string(&front_matter)?
+    ))
+}
+
+/// Uploaded path of the file a record set's fields are extracted from
+fn record_set_file<'a>(record_set: &RecordSet, data_files: &[(&str, &'a str)]) -> Option<&'a str> {
+    let file_id = record_set.field.first()?.source.as_ref()?.distribution_id();
+    data_files
+        .iter()
+        .find(|(id, _)| *id == file_id)
+        .map(|(_, path)| *path)
+}
+
+/// Front matter and text of a dataset card
+fn split_front_matter(card: &str) -> Result<(Mapping, String)> {
+    let Some(rest) = card.strip_prefix("---\n") else {
+        return Ok((Mapping::new(), card.to_string()));
+    };
+    let (yaml, body) = match rest.find("\n---\n") {
+        Some(end) => (&rest[..end], &rest[end + "\n---\n".len()..]),
+        None if rest.starts_with("---\n") => ("", &rest["---\n".len()..]),
+        None => return Ok((Mapping::new(), card.to_string())),
+    };
+    let front_matter = match serde_yaml::from_str::<Option<Mapping>>(yaml)? {
+        Some(mapping) => mapping,
+        None => Mapping::new(),
+    };
+    Ok((front_matter, body.to_string()))
+}
+
+/// Text of a new dataset card
+fn default_card_body(metadata: &Metadata) -> String {
+    let mut body = format!("# {}\n\n", metadata.name);
+    if !metadata.description.is_empty() {
+        body.push_str(&format!("{}\n\n", metadata.description));
+    }
+    if !metadata.version.is_empty() {
+        body.push_str(&format!("Version {}", metadata.version));
+        if !metadata.date_published.is_empty() {
+            body.push_str(&format!(", published {}", metadata.date_published));
+        }
+        body.push_str(".\n\n");
+    }
+    body.push_str(&format!(
+        "The dataset is described by its [Croissant](https://mlcommons.org/croissant/) metadata in [{METADATA_FILE_NAME}]({METADATA_FILE_NAME}).\n"
+    ));
+    body
+}
@@ -0,0 +1,172 @@
+//! Publishing of Croissant metadata, and optionally the files it
+//! describes, to dataset repositories
+//!
+//! The Hugging Face Hub (`hf://user/dataset`) is handled by `huggingface`,
+//! and Zenodo depositions by `zenodo`.
+
+use crate::croissant::Metadata;
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::fileset::list_files;
+use crate::croissant::utils::calculate_sha256_many;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+pub mod huggingface;
+pub mod zenodo;
+
+/// Options controlling publishing
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions {
+    /// Also upload the local files of the distributions
+    pub include_data: bool,
+    /// Create the repository as private if it does not exist yet (on
+    /// Zenodo: make the files closed access)
+    pub private: bool,
+    /// Commit message (default: derived from the dataset name); not used
+    /// by Zenodo
+    pub message: Option<String>,
+}
+
+/// Result of a successful publish
+#[derive(Debug, Clone, PartialEq)]
+pub struct Published {
+    /// Web page of the published dataset
+    pub url: String,
+    /// Paths of the uploaded files in the repository
+    pub files: Vec<String>,
+    /// DOI reserved or minted for the dataset
+    pub doi: Option<String>,
+}
+
+/// Local file of a distribution to upload, and its path in the repository
+#[derive(Debug, Clone)]
+pub(crate) struct DataFile {
+    pub(crate) distribution_id: String,
+    pub(crate) local_path: PathBuf,
+    /// Path of the file in the repository or deposition
+    pub(crate) path: String,
+    /// Digest recorded in the metadata, if any
+    pub(crate) expected_sha256: String,
+    pub(crate) sha256: String,
+    pub(crate) size: u64,
+}
+
+/// The local files of the distributions, hashed and checked against the
+/// digests recorded in the metadata
+///
+/// Remote distributions are left out, and file sets stand for the files
+/// they hold. Local files must lie below
+/// `base_dir`, so their content URLs also resolve in the repository, and
+/// must not be stored at one of the `reserved` paths.
+pub(crate) async fn local_data_files(
+    metadata: &Metadata,
+    base_dir: &Path,
+    reserved: &[&str],
+) -> Result<Vec<DataFile>> {
+    let mut files = Vec::new();
+    for distribution in &metadata.distribution {
+        // The files of a file set are uploaded one by one
+        let content_urls = if distribution.is_file_set() {
+            list_files(metadata, distribution, base_dir)?
+        } else {
+            vec![distribution.content_url.clone()]
+        };
+        for content_url in &content_urls {
+            if content_url.contains("://") {
+                tracing::info!(content_url, "not uploading remote distribution");
+                continue;
+            }
+            let path = relative_path(content_url)
+                .filter(|path| !reserved.contains(&path.as_str()))
+                .ok_or_else(|| {
+                    Error::invalid_format(format!(
+                        "Cannot upload {content_url}: only relative content URLs below the metadata's directory can be published"
+                    ))
+                })?;
+            let local_path = base_dir.join(content_url);
+            let size = std::fs::metadata(&local_path)
+                .map_err(|_| Error::file_not_found(&local_path))?
+                .len();
+            files.push(DataFile {
+                distribution_id: distribution.id.clone(),
+                local_path,
+                path,
+                expected_sha256: distribution.sha256.clone(),
+                sha256: String::new(),
+                size,
+            });
+        }
+    }
+
+    let paths = files
+        .iter()
+        .map(|file| file.local_path.clone())
+        .collect::<Vec<_>>();
+    let digests = tokio::task::spawn_blocking(move || calculate_sha256_many(&paths, None))
+        .await
+        .map_err(|e| Error::new(format!("Hashing task failed: {e}")))?;
+    for (file, digest) in files.iter_mut().zip(digests) {
+        file.sha256 = digest?;
+        if !file.expected_sha256.is_empty()
+            && !file.expected_sha256.eq_ignore_ascii_case(&file.sha256)
+        {
+            return Err(Error::invalid_format(format!(
+                "{} has sha256 {}, but the metadata records {}; regenerate the metadata before publishing",
+                file.local_path.display(),
+                file.sha256,
+                file.expected_sha256
+            )));
+        }
+    }
+    Ok(files)
+}
+
+/// Normalized form of a relative content URL, if it stays below the
+/// metadata's directory
+fn relative_path(content_url: &str) -> Option<String> {
+    if content_url.starts_with('/') {
+        return None;
+    }
+    let mut segments = Vec::new();
+    for segment in content_url.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            segment => segments.push(segment),
+        }
+    }
+    (!segments.is_empty()).then(|| segments.join("/"))
+}
+
+/// Fail on an unsuccessful response, with the error message the server
+/// sent
+pub(crate) async fn check_response(
+    response: reqwest::Response,
+    url: &str,
+) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let text = response.text().await.unwrap_or_default();
+    let message = match serde_json::from_str::<Value>(&text) {
+        Ok(body) => error_message(&body).unwrap_or(text),
+        Err(_) => text,
+    };
+    Err(Error::remote(url, format!("{status}: {message}")))
+}
+
+/// Message of a JSON error response: its `error` or `message`, followed by
+/// any per-field `errors`
+fn error_message(body: &Value) -> Option<String> {
+    let mut message = body["error"]
+        .as_str()
+        .or(body["message"].as_str())?
+        .to_string();
+    for error in body["errors"].as_array().into_iter().flatten() {
+        if let (Some(field), Some(detail)) = (error["field"].as_str(), error["message"].as_str()) {
+            message.push_str(&format!("; {field}: {detail}"));
+        }
+    }
+    Some(message)
+}
@@ -0,0 +1,346 @@
+//! Zenodo depositions (`publish --target zenodo`)
+//!
+//! A deposition is created, or a new version of an existing one opened,
+//! with its metadata mapped from the Croissant file and a reserved DOI.
+//! The DOI is written into the Croissant `identifier` and `citeAs` before
+//! the metadata and the local files of the distributions are uploaded, so
+//! the deposited Croissant file already cites itself. Depositions stay
+//! drafts unless submitted, since publishing on Zenodo cannot be undone.
+//!
+//! The access token is read from `ZENODO_TOKEN`; `ZENODO_URL` points at
+//! another instance, such as `https://sandbox.zenodo.org`.
+
+use crate::croissant::Metadata;
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::generate::write_metadata;
+use crate::croissant::publish::{
+    DataFile, PublishOptions, Published, check_response, local_data_files,
+};
+use crate::croissant::validate::read_metadata;
+use chrono::{Datelike, NaiveDate, Utc};
+use reqwest::RequestBuilder;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use serde_json::{Value, json};
+use std::path::Path;
+
+/// Environment variable holding the Zenodo access token
+pub const TOKEN_ENV: &str = "ZENODO_TOKEN";
+
+/// Environment variable overriding the Zenodo endpoint
+pub const ENDPOINT_ENV: &str = "ZENODO_URL";
+
+const DEFAULT_ENDPOINT: &str = "https://zenodo.org";
+
+/// Name of the Croissant file in the deposition
+pub const METADATA_FILE_NAME: &str = "croissant.json";
+
+/// Options specific to Zenodo depositions
+#[derive(Debug, Clone, Default)]
+pub struct ZenodoOptions {
+    /// Deposition to add a new version to, instead of creating one
+    pub deposition: Option<u64>,
+    /// Creators of the dataset, as `Family, Given` names; Zenodo requires
+    /// at least one
+    pub creators: Vec<String>,
+    /// Publish the deposition, minting its DOI; cannot be undone
+    pub submit: bool,
+}
+
+/// Deposit a metadata file and the local files of its distributions on
+/// Zenodo, with the endpoint and token from the environment
+///
+/// The reserved DOI is also written back into the metadata file.
+pub async fn publish(
+    metadata_path: &Path,
+    options: &PublishOptions,
+    zenodo: &ZenodoOptions,
+) -> Result<Published> {
+    ZenodoClient::from_env()?
+        .publish(metadata_path, options, zenodo)
+        .await
+}
+
+/// Zenodo REST API client authenticated with a personal access token
+#[derive(Debug, Clone)]
+pub struct ZenodoClient {
+    client: reqwest::Client,
+    endpoint: String,
+    token: String,
+}
+
+impl ZenodoClient {
+    pub fn new(endpoint: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+        }
+    }
+
+    /// Client for `ZENODO_URL` (default `https://zenodo.org`), with the
+    /// token from `ZENODO_TOKEN`
+    pub fn from_env() -> Result<Self> {
+        let endpoint = std::env::var(ENDPOINT_ENV).unwrap_or_else(|_| DEFAULT_ENDPOINT.to_string());
+        let token = std::env::var(TOKEN_ENV)
+            .ok()
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| Error::new(format!("No Zenodo token found: set {TOKEN_ENV}")))?;
+        Ok(Self::new(endpoint, token))
+    }
+
+    /// Create or update a deposition for a metadata file, write its DOI
+    /// into the file and upload the file and the local files of its
+    /// distributions
+    #[tracing::instrument(level = "debug", skip(self, options, zenodo))]
+    pub async fn publish(
+        &self,
+        metadata_path: &Path,
+        options: &PublishOptions,
+        zenodo: &ZenodoOptions,
+    ) -> Result<Published> {
+        if zenodo.creators.is_empty() {
+            return Err(Error::new("Zenodo depositions need at least one creator"));
+        }
+        let mut metadata = read_metadata(metadata_path)?;
+        let base_dir = metadata_path.parent().unwrap_or(Path::new("."));
+        let data_files = local_data_files(&metadata, base_dir, &[METADATA_FILE_NAME]).await?;
+
+        let deposition = match zenodo.deposition {
+            Some(id) => self.open_draft(id).await?,
+            None => self.create().await?,
+        };
+        let id = deposition["id"]
+            .as_u64()
+            .ok_or_else(|| Error::new("Zenodo returned a deposition without id"))?;
+        let url = self.deposition_url(id);
+        let body = json!({"metadata": deposition_metadata(&metadata, options, zenodo)});
+        let deposition = self
+            .send_json(
+                self.client
+                    .put(&url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(body.to_string()),
+                &url,
+            )
+            .await?;
+        let doi = deposition["metadata"]["prereserve_doi"]["doi"]
+            .as_str()
+            .ok_or_else(|| Error::new("Zenodo did not reserve a DOI for the deposition"))?
+            .to_string();
+        tracing::info!(id, doi, "reserved DOI");
+
+        metadata.identifier = Some(format!("https://doi.org/{doi}"));
+        metadata.cite_as = Some(bibtex_citation(&metadata, &zenodo.creators, id, &doi));
+        write_metadata(&metadata, metadata_path)?;
+
+        self.remove_replaced_files(id, &deposition, &data_files)
+            .await?;
+        let bucket = deposition["links"]["bucket"]
+            .as_str()
+            .ok_or_else(|| Error::new("Zenodo returned a deposition without file bucket"))?;
+        let metadata_json = serde_json::to_string_pretty(&metadata)?;
+        self.upload(
+            bucket,
+            METADATA_FILE_NAME,
+            metadata_json.len() as u64,
+            metadata_json,
+        )
+        .await?;
+        for file in &data_files {
+            let content = tokio::fs::File::open(&file.local_path).await?;
+            self.upload(bucket, &file.path, file.size, content).await?;
+        }
+
+        let deposition = if zenodo.submit {
+            let url = format!("{}/actions/publish", self.deposition_url(id));
+            let published = self.send_json(self.client.post(&url), &url).await?;
+            tracing::info!(id, doi, "published deposition");
+            published
+        } else {
+            deposition
+        };
+
+        let mut files = vec![METADATA_FILE_NAME.to_string()];
+        files.extend(data_files.into_iter().map(|file| file.path));
+        Ok(Published {
+            url: deposition["links"]["html"]
+                .as_str()
+                .map_or_else(|| format!("{}/deposit/{id}", self.endpoint), str::to_string),
+            files,
+            doi: Some(doi),
+        })
+    }
+
+    fn deposition_url(&self, id: u64) -> String {
+        format!("{}/api/deposit/depositions/{id}", self.endpoint)
+    }
+
+    /// Create an empty deposition
+    async fn create(&self) -> Result<Value> {
+        let url = format!("{}/api/deposit/depositions", self.endpoint);
+        let deposition = self
+            .send_json(
+                self.client
+                    .post(&url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body("{}"),
+                &url,
+            )
+            .await?;
+        tracing::info!(id = %deposition["id"], "created deposition");
+        Ok(deposition)
+    }
+
+    /// Draft of a deposition: the deposition itself while it is unpublished,
+    /// otherwise a new version of it
+    async fn open_draft(&self, id: u64) -> Result<Value> {
+        let url = self.deposition_url(id);
+        let deposition = self.send_json(self.client.get(&url), &url).await?;
+        if !deposition["submitted"].as_bool().unwrap_or(false) {
+            return Ok(deposition);
+        }
+
+        let url = format!("{url}/actions/newversion");
+        let versioned = self.send_json(self.client.post(&url), &url).await?;
+        let draft_url = versioned["links"]["latest_draft"]
+            .as_str()
+            .ok_or_else(|| Error::remote(&url, "no draft for the new version"))?;
+        let draft = self
+            .send_json(self.client.get(draft_url), draft_url)
+            .await?;
+        tracing::info!(id, draft = %draft["id"], "opened new version");
+        Ok(draft)
+    }
+
+    /// Delete files of a draft that are about to be uploaded again, such as
+    /// those carried over from the previous version
+    async fn remove_replaced_files(
+        &self,
+        id: u64,
+        deposition: &Value,
+        data_files: &[DataFile],
+    ) -> Result<()> {
+        let uploaded = |name: &str| {
+            name == METADATA_FILE_NAME || data_files.iter().any(|file| file.path == name)
+        };
+        for file in deposition["files"].as_array().into_iter().flatten() {
+            let (Some(name), Some(file_id)) = (file["filename"].as_str(), file["id"].as_str())
+            else {
+                continue;
+            };
+            if uploaded(name) {
+                let url = format!("{}/files/{file_id}", self.deposition_url(id));
+                let response = self
+                    .authorized(self.client.delete(&url))
+                    .send()
+                    .await
+                    .map_err(|e| Error::remote(&url, e.to_string()))?;
+                check_response(response, &url).await?;
+                tracing::debug!(name, "removed previous file");
+            }
+        }
+        Ok(())
+    }
+
+    /// Upload a file into the deposition's bucket
+    async fn upload(
+        &self,
+        bucket: &str,
+        name: &str,
+        size: u64,
+        content: impl Into<reqwest::Body>,
+    ) -> Result<()> {
+        let url = format!("{bucket}/{name}");
+        let response = self
+            .authorized(self.client.put(&url))
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .header(CONTENT_LENGTH, size)
+            .body(content)
+            .send()
+            .await
+            .map_err(|e| Error::remote(&url, e.to_string()))?;
+        check_response(response, &url).await?;
+        tracing::info!(name, size, "uploaded file");
+        Ok(())
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        request.bearer_auth(&self.token)
+    }
+
+    /// Send an authorized request and parse its JSON response
+    async fn send_json(&self, request: RequestBuilder, url: &str) -> Result<Value> {
+        let response = self
+            .authorized(request)
+            .send()
+            .await
+            .map_err(|e| Error::remote(url, e.to_string()))?;
+        let bytes = check_response(response, url)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| Error::remote(url, e.to_string()))?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+/// Zenodo deposition metadata for a Croissant dataset
+fn deposition_metadata(
+    metadata: &Metadata,
+    options: &PublishOptions,
+    zenodo: &ZenodoOptions,
+) -> Value {
+    let description = if metadata.description.is_empty() {
+        &metadata.name
+    } else {
+        &metadata.description
+    };
+    let mut deposition = json!({
+        "upload_type": "dataset",
+        "title": metadata.name,
+        "description": description,
+        "creators": zenodo
+            .creators
+            .iter()
+            .map(|name| json!({"name": name}))
+            .collect::<Vec<_>>(),
+        "access_right": if options.private { "closed" } else { "open" },
+        "prereserve_doi": true,
+    });
+    if !metadata.version.is_empty() {
+        deposition["version"] = json!(metadata.version);
+    }
+    if let Some(date) = publication_date(metadata) {
+        deposition["publication_date"] = json!(date.format("%Y-%m-%d").to_string());
+    }
+    deposition
+}
+
+/// The metadata's `datePublished`, if it starts with an ISO 8601 date
+fn publication_date(metadata: &Metadata) -> Option<NaiveDate> {
+    let date = metadata.date_published.get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// BibTeX entry citing the deposition
+fn bibtex_citation(metadata: &Metadata, creators: &[String], id: u64, doi: &str) -> String {
+    let year = publication_date(metadata).map_or_else(|| Utc::now().year(), |date| date.year());
+    let mut fields = vec![
+        ("author", creators.join(" and ")),
+        ("title", metadata.name.clone()),
+        ("year", year.to_string()),
+        ("publisher", "Zenodo".to_string()),
+    ];
+    if !metadata.version.is_empty() {
+        fields.push(("version", metadata.version.clone()));
+    }
+    fields.push(("doi", doi.to_string()));
+    fields.push(("url", format!("https://doi.org/{doi}")));
+
+    let fields = fields
+        .iter()
+        .map(|(name, value)| format!("  {name} = {{{value}}}"))
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!("@dataset{{zenodo_{id},\n{fields}\n}}")
+}
@@ -0,0 +1,179 @@
+//! SQL queries over described datasets with an embedded DuckDB
+//!
+//! Each record set becomes a table named after it, with a column per field
+//! under the field's name and of its Croissant dataType. The records are
+//! read with [`read_records`], so source transforms, null values, the
+//! file's delimiter and encoding, and records given inline all apply, and
+//! queries see the dataset as the metadata describes it rather than as raw
+//! CSV. Lists and objects, the values of repeated fields and of fields
+//! with subfields, are stored as JSON text.
+
+use crate::croissant::core::{DataType, Metadata, RecordSet};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::records::{self, RecordField, read_records};
+use chrono::{NaiveDate, Timelike};
+use duckdb::types::{TimeUnit, Value};
+use duckdb::{Connection, appender_params_from_iter};
+use std::path::Path;
+
+/// Column names and rows returned by a query, with values rendered as text
+/// and SQL NULLs as `None`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+/// SQL type used for a Croissant data type
+pub fn sql_type(data_type: &DataType) -> &'static str {
+    match data_type {
+        DataType::Integer => "BIGINT",
+        DataType::Float => "DOUBLE",
+        DataType::Boolean => "BOOLEAN",
+        DataType::Date => "DATE",
+        DataType::DateTime => "TIMESTAMP",
+        DataType::Time => "TIME",
+        DataType::Text | DataType::Url => "VARCHAR",
+    }
+}
+
+/// Open an in-memory DuckDB database with a table for every record set
+///
+/// Relative content URLs are resolved against `base_dir`, normally the
+/// directory of the metadata file. Every record is loaded when the
+/// database is opened.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn open_connection(metadata: &Metadata, base_dir: &Path) -> Result<Connection> {
+    let connection = Connection::open_in_memory().map_err(query_error)?;
+    for record_set in &metadata.record_set {
+        load_record_set(&connection, metadata, record_set, base_dir)?;
+    }
+    Ok(connection)
+}
+
+/// Run a SQL query over the record sets of a metadata document
+#[tracing::instrument(level = "debug", skip(metadata, base_dir))]
+pub fn query_metadata(metadata: &Metadata, base_dir: &Path, sql: &str) -> Result<QueryResult> {
+    let connection = open_connection(metadata, base_dir)?;
+    let mut statement = connection.prepare(sql).map_err(query_error)?;
+    let mut rows = statement.query([]).map_err(query_error)?;
+
+    let mut result = QueryResult::default();
+    while let Some(row) = rows.next().map_err(query_error)? {
+        let statement = row.as_ref();
+        if result.columns.is_empty() {
+            result.columns = statement.column_names();
+        }
+        let values = (0..statement.column_count())
+            .map(|i| row.get::<_, Value>(i).map(render_value))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(query_error)?;
+        result.rows.push(values);
+    }
+    if result.columns.is_empty()
+        && let Some(statement) = rows.as_ref()
+    {
+        result.columns = statement.column_names();
+    }
+
+    Ok(result)
+}
+
+/// Create the table of a record set and append its records
+fn load_record_set(
+    connection: &Connection,
+    metadata: &Metadata,
+    record_set: &RecordSet,
+    base_dir: &Path,
+) -> Result<()> {
+    let records = read_records(metadata, &record_set.id, base_dir)?;
+    let columns = records
+        .fields()
+        .iter()
+        .map(|field| format!("{} {}", quote_identifier(&field.name), column_type(field)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let table = quote_identifier(&record_set.name);
+    let sql = format!("CREATE TABLE {table} ({columns});");
+    tracing::debug!(record_set = %record_set.name, %sql, "creating table");
+    connection.execute_batch(&sql).map_err(query_error)?;
+
+    let mut appender = connection.appender(&record_set.name).map_err(query_error)?;
+    for record in records {
+        let values = record?.iter().map(sql_value).collect::<Vec<_>>();
+        appender
+            .append_row(appender_params_from_iter(values))
+            .map_err(query_error)?;
+    }
+    appender.flush().map_err(query_error)
+}
+
+/// SQL type of the column of a field of materialized records; lists and
+/// objects are JSON text
+fn column_type(field: &RecordField) -> &'static str {
+    if field.repeated || !field.fields.is_empty() {
+        "VARCHAR"
+    } else {
+        sql_type(&field.data_type)
+    }
+}
+
+/// DuckDB value of a materialized value, of its column's type
+fn sql_value(value: &records::Value) -> Value {
+    match value {
+        records::Value::Null => Value::Null,
+        records::Value::Boolean(b) => Value::Boolean(*b),
+        records::Value::Integer(n) => Value::BigInt(*n),
+        records::Value::Float(n) => Value::Double(*n),
+        records::Value::Date(date) => Value::Date32(days_since_epoch(*date)),
+        records::Value::DateTime(datetime) => {
+            Value::Timestamp(TimeUnit::Microsecond, datetime.timestamp_micros())
+        }
+        records::Value::Time(time) => Value::Time64(
+            TimeUnit::Microsecond,
+            i64::from(time.num_seconds_from_midnight()) * 1_000_000
+                + i64::from(time.nanosecond() / 1000),
+        ),
+        records::Value::Text(text) => Value::Text(text.clone()),
+        records::Value::List(_) | records::Value::Object(_) => Value::Text(value.to_json_string()),
+    }
+}
+
+fn days_since_epoch(date: NaiveDate) -> i32 {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date");
+    (date - epoch).num_days() as i32
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn render_value(value: Value) -> Option<String> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Boolean(b) => b.to_string(),
+        Value::TinyInt(n) => n.to_string(),
+        Value::SmallInt(n) => n.to_string(),
+        Value::Int(n) => n.to_string(),
+        Value::BigInt(n) => n.to_string(),
+        Value::HugeInt(n) => n.to_string(),
+        Value::UTinyInt(n) => n.to_string(),
+        Value::USmallInt(n) => n.to_string(),
+        Value::UInt(n) => n.to_string(),
+        Value::UBigInt(n) => n.to_string(),
+        Value::Float(n) => n.to_string(),
+        Value::Double(n) => n.to_string(),
+        Value::Text(s) => s,
+        Value::Date32(days) => chrono::DateTime::from_timestamp(i64::from(days) * 86_400, 0)
+            .map(|d| d.date_naive().to_string())
+            .unwrap_or_else(|| days.to_string()),
+        Value::Timestamp(unit, n) => chrono::DateTime::from_timestamp_micros(unit.to_micros(n))
+            .map(|d| d.naive_utc().to_string())
+            .unwrap_or_else(|| n.to_string()),
+        other => format!("{other:?}"),
+    })
+}
+
+fn query_error(e: duckdb::Error) -> Error {
+    Error::new(format!("Query failed: {e}"))
+}
@@ -0,0 +1,180 @@
+//! RDF serialization of Croissant metadata
+//!
+//! The JSON-LD document is expanded into RDF quads with its own `@context`,
+//! then written as Turtle or N-Quads. Remote contexts are never fetched, so
+//! only the inline Croissant context is used. Relative IRIs such as
+//! `"@id": "data.csv"` are resolved against a base IRI, normally the
+//! location of the metadata file.
+//!
+//! [`canonicalize`] gives the RDFC-1.0 canonical N-Quads of a document, for
+//! hashing, signing and content-addressable storage.
+
+use crate::croissant::errors::{Error, Result};
+use oxjsonld::JsonLdParser;
+use oxrdf::{BlankNode, GraphName, NamedOrBlankNode, Quad, Term, Triple};
+use oxttl::{NQuadsSerializer, TurtleSerializer};
+use std::collections::HashMap;
+
+/// Base IRI relative IRIs are resolved against in canonical output, so that
+/// the canonical form of a document does not depend on where it is stored
+pub const CANONICAL_BASE_IRI: &str = "file:///";
+
+/// RDF serializations metadata can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RdfFormat {
+    Turtle,
+    NQuads,
+}
+
+/// RDF quads expanded from a JSON-LD document
+#[derive(Debug, Clone, Default)]
+pub struct RdfDataset {
+    pub quads: Vec<Quad>,
+    /// Prefixes declared by the document's context, as (name, IRI)
+    pub prefixes: Vec<(String, String)>,
+}
+
+/// Expand a JSON-LD document into RDF quads
+///
+/// The parser labels blank nodes randomly; they are relabelled `b0`, `b1`,
+/// ... in order of first appearance, and prefixes are sorted by name, so the
+/// same document always gives the same output.
+pub fn to_quads(json_ld: &str, base_iri: &str) -> Result<RdfDataset> {
+    let mut parser = JsonLdParser::new()
+        .with_base_iri(base_iri)
+        .map_err(|e| Error::invalid_format(format!("Invalid base IRI {base_iri}: {e}")))?
+        .for_slice(json_ld);
+
+    let mut labels = HashMap::new();
+    let mut relabel = |node: &BlankNode| {
+        let next = labels.len();
+        labels
+            .entry(node.as_str().to_string())
+            .or_insert_with(|| BlankNode::new_unchecked(format!("b{next}")))
+            .clone()
+    };
+
+    let mut quads = Vec::new();
+    for quad in parser.by_ref() {
+        let mut quad = quad.map_err(|e| Error::invalid_format(format!("JSON-LD error: {e}")))?;
+        if let NamedOrBlankNode::BlankNode(node) = &quad.subject {
+            quad.subject = relabel(node).into();
+        }
+        if let Term::BlankNode(node) = &quad.object {
+            quad.object = relabel(node).into();
+        }
+        if let GraphName::BlankNode(node) = &quad.graph_name {
+            quad.graph_name = relabel(node).into();
+        }
+        quads.push(quad);
+    }
+    let mut prefixes = parser
+        .prefixes()
+        .map(|(name, iri)| (name.to_string(), iri.to_string()))
+        .collect::<Vec<_>>();
+    prefixes.sort();
+    Ok(RdfDataset { quads, prefixes })
+}
+
+/// Convert a JSON-LD document to Turtle or N-Quads
+///
+/// Turtle has no named graphs, so graph names are dropped; Croissant
+/// documents only use the default graph.
+pub fn json_ld_to_rdf(json_ld: &str, base_iri: &str, format: RdfFormat) -> Result<String> {
+    let RdfDataset { quads, prefixes } = to_quads(json_ld, base_iri)?;
+    tracing::debug!(quads = quads.len(), "expanded JSON-LD to RDF");
+
+    let bytes = match format {
+        RdfFormat::Turtle => {
+            let mut serializer = TurtleSerializer::new();
+            for (name, iri) in &prefixes {
+                // Prefixes that are not valid IRIs (e.g. keyword aliases)
+                // are simply not used for abbreviation
+                serializer = match serializer.clone().with_prefix(name, iri) {
+                    Ok(with_prefix) => with_prefix,
+                    Err(_) => serializer,
+                };
+            }
+            let mut writer = serializer.for_writer(Vec::new());
+            for quad in quads {
+                writer.serialize_triple(&Triple::from(quad))?;
+            }
+            writer.finish()?
+        }
+        RdfFormat::NQuads => {
+            let mut writer = NQuadsSerializer::new().for_writer(Vec::new());
+            for quad in &quads {
+                writer.serialize_quad(quad)?;
+            }
+            writer.finish()
+        }
+    };
+
+    String::from_utf8(bytes).map_err(|e| Error::new(format!("Invalid RDF output: {e}")))
+}
+
+/// Canonicalize a JSON-LD document with RDFC-1.0 (the successor of
+/// URDNA2015), returning its canonical N-Quads
+///
+/// Blank nodes are relabelled deterministically and quads are sorted, so
+/// documents describing the same graph give byte-identical output whatever
+/// their key order, formatting or blank node labels. Relative IRIs are
+/// resolved against [`CANONICAL_BASE_IRI`].
+pub fn canonicalize(json_ld: &str) -> Result<String> {
+    let RdfDataset { quads, .. } = to_quads(json_ld, CANONICAL_BASE_IRI)?;
+    let quads = quads.iter().map(canon_quad).collect::<Vec<_>>();
+    rdf_canon::canonicalize_quads(&quads)
+        .map_err(|e| Error::new(format!("RDF canonicalization failed: {e}")))
+}
+
+/// Hex SHA-256 digest of the canonical N-Quads of a JSON-LD document,
+/// usable as a content address of the metadata graph
+pub fn canonical_sha256(json_ld: &str) -> Result<String> {
+    use sha2::{Digest, Sha256};
+
+    Ok(hex::encode(Sha256::digest(canonicalize(json_ld)?)))
+}
+
+// Conversion to the oxrdf release rdf-canon is built on
+
+fn canon_quad(quad: &Quad) -> rdf_canon_oxrdf::Quad {
+    use rdf_canon_oxrdf as canon;
+
+    let subject: canon::Subject = match &quad.subject {
+        NamedOrBlankNode::NamedNode(node) => canon_named_node(node.as_str()).into(),
+        NamedOrBlankNode::BlankNode(node) => canon_blank_node(node.as_str()).into(),
+    };
+    let object: canon::Term = match &quad.object {
+        Term::NamedNode(node) => canon_named_node(node.as_str()).into(),
+        Term::BlankNode(node) => canon_blank_node(node.as_str()).into(),
+        Term::Literal(literal) => match literal.language() {
+            Some(language) => {
+                canon::Literal::new_language_tagged_literal_unchecked(literal.value(), language)
+            }
+            None => canon::Literal::new_typed_literal(
+                literal.value(),
+                canon_named_node(literal.datatype().as_str()),
+            ),
+        }
+        .into(),
+    };
+    let graph_name = match &quad.graph_name {
+        GraphName::NamedNode(node) => canon_named_node(node.as_str()).into(),
+        GraphName::BlankNode(node) => canon_blank_node(node.as_str()).into(),
+        GraphName::DefaultGraph => canon::GraphName::DefaultGraph,
+    };
+    canon::Quad::new(
+        subject,
+        canon_named_node(quad.predicate.as_str()),
+        object,
+        graph_name,
+    )
+}
+
+fn canon_named_node(iri: &str) -> rdf_canon_oxrdf::NamedNode {
+    rdf_canon_oxrdf::NamedNode::new_unchecked(iri)
+}
+
+fn canon_blank_node(id: &str) -> rdf_canon_oxrdf::BlankNode {
+    rdf_canon_oxrdf::BlankNode::new_unchecked(id)
+}
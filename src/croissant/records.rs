@@ -0,0 +1,2041 @@
+//! Materialization of record sets
+//!
+//! The records of a record set are read from the file its fields' sources
+//! point at and parsed into the fields' dataTypes, so exports, statistics
+//! and previews all see the data the way the metadata describes it. Fields
+//! extracting a `column` read the columns of a CSV file, or the keys of the
+//! objects of a JSON Lines file; fields extracting a `fileProperty` read the
+//! file's path or name, each of its lines, or its whole content. Record sets
+//! drawing from a file set read these properties of each of its files in
+//! turn; record sets drawing from several distributions are rejected.
+//!
+//! The transforms declared on a field's source are applied to each raw value
+//! before it is parsed: `regex` keeps the first capture group, `replace`
+//! substitutes a regular expression (`$1` refers to a group), `separator`
+//! splits the value into a list, `jsonPath` selects part of a JSON value
+//! (`$`, `.key`, `['key']`, `[0]` and `*` are supported), and `format` gives
+//! the strftime format of dates and datetimes.
+//!
+//! Fields with `subField`s hold objects made of their subfields' values, and
+//! `repeated` fields hold lists, read from JSON arrays unless a transform
+//! splits the value; the subfields of a repeated field are zipped into a
+//! list of objects.
+//!
+//! Fields whose values reference a field of another record set can be
+//! resolved: the referenced record set is read into a hash table keyed by
+//! the referenced field, and each record is extended with the fields of the
+//! record it references, giving denormalized records.
+//!
+//! Records can be filtered by split or by simple conditions such as
+//! `year >= 2020`, sampled with an offset and a limit, and projected onto
+//! some of their fields; with a limit, reading stops as soon as enough
+//! records have been found.
+//!
+//! Records are pulled one at a time, so memory use does not grow with the
+//! size of the data. [`RecordReader::buffered`] reads ahead on a background
+//! thread into a bounded buffer, and, with the `tokio` feature,
+//! [`RecordReader::into_stream`] does the same for async consumers; either
+//! way reading pauses while the buffer is full.
+
+use crate::croissant::core::{
+    BadRowPolicy, DataType, Distribution, Field, FieldSource, FileProperty, Metadata, RecordSet,
+    Transform, is_null_value,
+};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::fileset::list_files;
+use crate::croissant::utils::{
+    CsvFileReader, DELIMITED_FORMATS, accept_csv_row, open_csv_reader_with_delimiter,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
+use regex::Regex;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// dataTypes of split fields and of the record sets enumerating splits
+const SPLIT_TYPES: &[&str] = &["cr:Split", "http://mlcommons.org/croissant/Split"];
+
+/// Default number of records read ahead by [`RecordReader::buffered`] and
+/// [`RecordReader::into_stream`]
+pub const DEFAULT_BUFFER_SIZE: usize = 1024;
+
+/// Encoding formats read as JSON Lines
+const JSON_LINES_FORMATS: &[&str] = &[
+    "application/jsonlines",
+    "application/jsonl",
+    "application/x-jsonlines",
+    "application/x-ndjson",
+];
+
+/// A value of a record, typed by its field's dataType
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    Date(NaiveDate),
+    /// Datetimes without a timezone are taken to be UTC
+    DateTime(DateTime<Utc>),
+    Time(NaiveTime),
+    Text(String),
+    /// Values of a repeated field, or split by a `separator` transform
+    List(Vec<Value>),
+    /// Values of the subfields of a field, in field order
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Parse a raw value as `data_type`; blank values are null
+    pub fn parse(raw: &str, data_type: &DataType) -> Result<Self> {
+        if is_null_value(raw) {
+            return Ok(Self::Null);
+        }
+        let trimmed = raw.trim();
+        let invalid = || Error::invalid_data_type(raw, data_type.to_schema_org());
+        Ok(match data_type {
+            DataType::Integer => Self::Integer(trimmed.parse().map_err(|_| invalid())?),
+            DataType::Float => Self::Float(trimmed.parse().map_err(|_| invalid())?),
+            DataType::Boolean => Self::Boolean(parse_boolean(trimmed).ok_or_else(invalid)?),
+            DataType::Date => Self::Date(parse_date(trimmed).ok_or_else(invalid)?),
+            DataType::DateTime => Self::DateTime(parse_datetime(trimmed).ok_or_else(invalid)?),
+            DataType::Time => Self::Time(parse_time(trimmed).ok_or_else(invalid)?),
+            DataType::Text | DataType::Url => Self::Text(raw.to_string()),
+        })
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// JSON representation: numbers and booleans as such, dates and
+    /// datetimes as ISO 8601 strings
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Null => serde_json::Value::Null,
+            Self::Boolean(b) => serde_json::Value::Bool(*b),
+            Self::Integer(n) => serde_json::Value::from(*n),
+            // Non-finite floats have no JSON representation
+            Self::Float(n) => serde_json::Number::from_f64(*n)
+                .map_or(serde_json::Value::Null, serde_json::Value::Number),
+            Self::Date(_) | Self::DateTime(_) | Self::Time(_) | Self::Text(_) => {
+                serde_json::Value::String(self.to_string())
+            }
+            Self::List(values) => values.iter().map(Value::to_json).collect(),
+            Self::Object(members) => members
+                .iter()
+                .map(|(name, value)| (name.clone(), value.to_json()))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+        }
+    }
+
+    /// JSON text of the value, keeping object members in field order
+    pub fn to_json_string(&self) -> String {
+        match self {
+            Self::List(values) => {
+                let items = values.iter().map(Value::to_json_string).collect::<Vec<_>>();
+                format!("[{}]", items.join(","))
+            }
+            Self::Object(members) => {
+                let members = members
+                    .iter()
+                    .map(|(name, value)| {
+                        format!(
+                            "{}:{}",
+                            serde_json::Value::from(name.as_str()),
+                            value.to_json_string()
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!("{{{}}}", members.join(","))
+            }
+            value => value.to_json().to_string(),
+        }
+    }
+}
+
+/// Text form of a value, as written to CSV; null is empty, and lists and
+/// objects are JSON
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => Ok(()),
+            Self::Boolean(b) => write!(f, "{b}"),
+            Self::Integer(n) => write!(f, "{n}"),
+            Self::Float(n) => write!(f, "{n}"),
+            Self::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            Self::DateTime(datetime) => {
+                write!(
+                    f,
+                    "{}",
+                    datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                )
+            }
+            Self::Time(time) => write!(f, "{}", time.format("%H:%M:%S%.f")),
+            Self::Text(text) => f.write_str(text),
+            Self::List(_) | Self::Object(_) => f.write_str(&self.to_json_string()),
+        }
+    }
+}
+
+/// A value per field of the record set, in field order
+pub type Record = Vec<Value>;
+
+/// Name and type of a field of materialized records
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordField {
+    pub name: String,
+    pub data_type: DataType,
+    /// Whether the values are lists of `data_type` values, or of objects
+    pub repeated: bool,
+    /// Subfields of object values; empty for other values
+    pub fields: Vec<RecordField>,
+}
+
+/// How a field's raw value is extracted from the file
+#[derive(Debug, Clone)]
+enum Extraction {
+    /// Index of a CSV column
+    Column(usize),
+    /// Key of a JSON Lines object
+    Key(String),
+    Property(FileProperty),
+}
+
+/// Path and name of the file records are read from, for fields extracting
+/// them
+struct FileNames {
+    fullpath: String,
+    filename: String,
+}
+
+impl FileNames {
+    fn get(&self, property: FileProperty) -> Option<String> {
+        match property {
+            FileProperty::Fullpath => Some(self.fullpath.clone()),
+            FileProperty::Filename => Some(self.filename.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Raw values of a record, `None` where the file has no value
+type RawRow = Vec<Option<String>>;
+
+/// Raw records, read lazily
+type RawRows = Box<dyn Iterator<Item = Result<RawRow>> + Send>;
+
+/// Options for reading records
+#[derive(Debug, Clone, Default)]
+pub struct RecordOptions {
+    /// Extend each record with the fields of the records its fields
+    /// reference, named `<field>.<referenced field>`
+    pub resolve_references: bool,
+    /// Keep only the records of this split, such as `train`
+    pub split: Option<String>,
+    /// Keep only the records meeting all of these conditions
+    pub conditions: Vec<Condition>,
+    /// Number of (matching) records skipped
+    pub offset: usize,
+    /// Most records read, after the offset
+    pub limit: Option<usize>,
+    /// Fields kept, in this order (`None`: all)
+    pub fields: Option<Vec<String>>,
+    /// What to do with CSV rows that do not have as many fields as the
+    /// header
+    pub on_bad_row: BadRowPolicy,
+}
+
+/// Read the records of a record set, looked up by name or `@id`
+///
+/// Relative content URLs are resolved against `base_dir`, normally the
+/// directory of the metadata file. Records are read lazily, so files of
+/// any size can be processed.
+pub fn read_records(
+    metadata: &Metadata,
+    record_set: &str,
+    base_dir: &Path,
+) -> Result<RecordReader> {
+    read_records_with_options(metadata, record_set, base_dir, &RecordOptions::default())
+}
+
+/// Read the records of a record set with the given options
+///
+/// When references are resolved, every referenced record set is read into
+/// memory first; the records of `record_set` itself are still read lazily.
+#[tracing::instrument(level = "debug", skip(metadata, base_dir))]
+pub fn read_records_with_options(
+    metadata: &Metadata,
+    record_set: &str,
+    base_dir: &Path,
+    options: &RecordOptions,
+) -> Result<RecordReader> {
+    let record_set = metadata
+        .find_record_set(record_set)
+        .ok_or_else(|| Error::missing_field(format!("recordSet {record_set}")))?;
+    let mut reader = open_record_set(metadata, record_set, base_dir, options.on_bad_row)?;
+    if let Some(split) = &options.split {
+        reader
+            .filters
+            .push(split_filter(metadata, record_set, split)?);
+    }
+    if options.resolve_references {
+        for (index, field) in record_set.field.iter().enumerate() {
+            if let Some(reference) = &field.references {
+                let join = Join::build(
+                    metadata,
+                    base_dir,
+                    index,
+                    &reference.field.id,
+                    options.on_bad_row,
+                )?;
+                reader
+                    .fields
+                    .extend(join.fields.iter().map(|referenced| RecordField {
+                        name: format!("{}.{}", field.name, referenced.name),
+                        data_type: referenced.data_type.clone(),
+                        repeated: referenced.repeated,
+                        fields: referenced.fields.clone(),
+                    }));
+                reader.joins.push(join);
+            }
+        }
+    }
+
+    let position = |fields: &[RecordField], name: &str| {
+        fields
+            .iter()
+            .position(|field| field.name == name)
+            .ok_or_else(|| {
+                Error::missing_field(format!("field {name} in recordSet {}", record_set.name))
+            })
+    };
+    for condition in &options.conditions {
+        let field = position(&reader.fields, &condition.field)?;
+        let data_type = &reader.fields[field].data_type;
+        let value = Value::parse(&condition.value, data_type).map_err(|_| {
+            Error::invalid_format(format!(
+                "{} cannot be compared with field {} of dataType {}",
+                condition.value,
+                condition.field,
+                data_type.to_schema_org()
+            ))
+        })?;
+        reader.filters.push(Filter::Compare {
+            field,
+            comparison: condition.comparison,
+            value,
+        });
+    }
+    if let Some(names) = &options.fields {
+        let indices = names
+            .iter()
+            .map(|name| position(&reader.fields, name))
+            .collect::<Result<Vec<_>>>()?;
+        let fields = indices.iter().map(|&i| reader.fields[i].clone()).collect();
+        reader.projection = Some((indices, fields));
+    }
+    reader.offset = options.offset;
+    reader.remaining = options.limit;
+    Ok(reader)
+}
+
+/// Open the records of a record set, without resolving references
+fn open_record_set(
+    metadata: &Metadata,
+    record_set: &RecordSet,
+    base_dir: &Path,
+    on_bad_row: BadRowPolicy,
+) -> Result<RecordReader> {
+    let bad_rows = Arc::new(AtomicUsize::new(0));
+    let leaves = leaf_fields(&record_set.field);
+    let pipelines = leaves
+        .iter()
+        .map(|field| Pipeline::compile(field))
+        .collect::<Result<Vec<_>>>()?;
+    let (rows, path, location) = match &record_set.data {
+        Some(data) => (
+            Box::new(inline_rows(&leaves, data.clone())) as RawRows,
+            None,
+            format!("recordSet {}", record_set.name),
+        ),
+        None => {
+            let bad_rows = BadRows {
+                policy: on_bad_row,
+                count: bad_rows.clone(),
+            };
+            let (rows, path) = file_rows_of(metadata, record_set, &leaves, base_dir, bad_rows)?;
+            let location = path.display().to_string();
+            (rows, Some(path), location)
+        }
+    };
+    let mut next_leaf = 0;
+    let (fields, shapes) = record_set
+        .field
+        .iter()
+        .map(|field| describe(field, &pipelines, &mut next_leaf))
+        .unzip();
+    Ok(RecordReader {
+        fields,
+        leaves: leaves
+            .iter()
+            .map(|field| (field.name.clone(), field_data_type(field)))
+            .collect(),
+        shapes,
+        rows,
+        pipelines,
+        joins: Vec::new(),
+        filters: Vec::new(),
+        offset: 0,
+        remaining: None,
+        projection: None,
+        path,
+        location,
+        record: 0,
+        bad_rows,
+        unresolved_references: 0,
+        done: false,
+    })
+}
+
+/// The fields whose values are extracted, those without subfields, depth
+/// first
+fn leaf_fields(fields: &[Field]) -> Vec<&Field> {
+    fields
+        .iter()
+        .flat_map(|field| {
+            if field.sub_field.is_empty() {
+                vec![field]
+            } else {
+                leaf_fields(&field.sub_field)
+            }
+        })
+        .collect()
+}
+
+fn field_data_type(field: &Field) -> DataType {
+    DataType::from_schema_org(&field.data_type).unwrap_or(DataType::Text)
+}
+
+/// How the values of a field are assembled from those of the leaf fields
+enum Shape {
+    /// Index of the leaf field
+    Leaf(usize),
+    Object {
+        names: Vec<String>,
+        children: Vec<Shape>,
+        repeated: bool,
+    },
+}
+
+impl Shape {
+    /// The value of the field, taking the leaf values it is made of
+    fn assemble(&self, leaves: &mut [Value]) -> Value {
+        let (names, children, repeated) = match self {
+            Self::Leaf(index) => return std::mem::replace(&mut leaves[*index], Value::Null),
+            Self::Object {
+                names,
+                children,
+                repeated,
+            } => (names, children, *repeated),
+        };
+        let values = children
+            .iter()
+            .map(|child| child.assemble(leaves))
+            .collect::<Vec<_>>();
+        if !repeated {
+            return Value::Object(names.iter().cloned().zip(values).collect());
+        }
+        if values.iter().all(Value::is_null) {
+            return Value::Null;
+        }
+        // Subfields holding lists are zipped; others are repeated in each
+        // object
+        let len = values
+            .iter()
+            .filter_map(|value| match value {
+                Value::List(items) => Some(items.len()),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(1);
+        Value::List(
+            (0..len)
+                .map(|i| {
+                    let members = names.iter().zip(&values).map(|(name, value)| {
+                        let member = match value {
+                            Value::List(items) => items.get(i).cloned().unwrap_or(Value::Null),
+                            value => value.clone(),
+                        };
+                        (name.clone(), member)
+                    });
+                    Value::Object(members.collect())
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The materialized field and shape of a field, numbering its leaves from
+/// `next_leaf`
+fn describe(field: &Field, pipelines: &[Pipeline], next_leaf: &mut usize) -> (RecordField, Shape) {
+    if field.sub_field.is_empty() {
+        let index = *next_leaf;
+        *next_leaf += 1;
+        let record_field = RecordField {
+            name: field.name.clone(),
+            data_type: field_data_type(field),
+            repeated: pipelines[index].is_list(),
+            fields: Vec::new(),
+        };
+        return (record_field, Shape::Leaf(index));
+    }
+    let (fields, children): (Vec<_>, Vec<_>) = field
+        .sub_field
+        .iter()
+        .map(|sub_field| describe(sub_field, pipelines, next_leaf))
+        .unzip();
+    let shape = Shape::Object {
+        names: fields.iter().map(|f| f.name.clone()).collect(),
+        children,
+        repeated: field.repeated,
+    };
+    let record_field = RecordField {
+        name: field.name.clone(),
+        data_type: DataType::Text,
+        repeated: field.repeated,
+        fields,
+    };
+    (record_field, shape)
+}
+
+/// Raw records of the file a record set's fields are extracted from, and
+/// the file's path
+fn file_rows_of(
+    metadata: &Metadata,
+    record_set: &RecordSet,
+    leaves: &[&Field],
+    base_dir: &Path,
+    bad_rows: BadRows,
+) -> Result<(RawRows, PathBuf)> {
+    let sources = leaves
+        .iter()
+        .map(|field| field.require_source())
+        .collect::<Result<Vec<_>>>()?;
+    let distribution = source_distribution(metadata, record_set, &sources)?;
+    let url = &distribution.content_url;
+    if url.contains("://") {
+        return Err(Error::invalid_format(format!(
+            "Distribution {} is not a local file: {url}",
+            distribution.id
+        )));
+    }
+    let path = base_dir.join(url);
+
+    let properties = sources
+        .iter()
+        .filter_map(|source| source.extract.file_property)
+        .collect::<Vec<_>>();
+    let has_columns = sources
+        .iter()
+        .any(|source| source.extract.file_property.is_none());
+    let per_line = properties
+        .iter()
+        .any(|p| matches!(p, FileProperty::Lines | FileProperty::LineNumbers));
+    let content = properties.contains(&FileProperty::Content);
+    if [has_columns, per_line, content]
+        .iter()
+        .filter(|&&b| b)
+        .count()
+        > 1
+    {
+        return Err(Error::invalid_format(format!(
+            "RecordSet {} mixes columns, lines and the content of its file",
+            record_set.name
+        )));
+    }
+
+    if distribution.is_file_set() {
+        if has_columns {
+            return Err(Error::invalid_format(format!(
+                "RecordSet {} reads columns of FileSet {}, only file properties can be read from file sets",
+                record_set.name, distribution.id
+            )));
+        }
+        let extractions = extractions(&sources, |_| unreachable!("no columns"))?;
+        let rows = file_set_rows(metadata, distribution, base_dir, extractions, per_line)?;
+        return Ok((rows, base_dir.to_path_buf()));
+    }
+
+    let names = FileNames {
+        fullpath: url.clone(),
+        filename: Path::new(url)
+            .file_name()
+            .map_or_else(|| url.clone(), |name| name.to_string_lossy().into_owned()),
+    };
+    let rows: RawRows = if per_line {
+        let extractions = extractions(&sources, |_| unreachable!("no columns"))?;
+        Box::new(line_rows(&path, extractions, names)?)
+    } else if content || !has_columns {
+        let extractions = extractions(&sources, |_| unreachable!("no columns"))?;
+        Box::new(file_rows(&path, extractions, names))
+    } else if JSON_LINES_FORMATS.contains(&distribution.encoding_format.as_str()) {
+        let extractions = extractions(&sources, |column| Ok(Extraction::Key(column.to_string())))?;
+        Box::new(json_lines_rows(&path, extractions, names)?)
+    } else if DELIMITED_FORMATS.contains(&distribution.encoding_format.as_str()) {
+        let delimiter = distribution.field_delimiter(&path)?;
+        let mut reader = open_csv_reader_with_delimiter(&path, delimiter)?;
+        let headers = reader.headers()?.clone();
+        let extractions = extractions(&sources, |column| {
+            headers
+                .iter()
+                .position(|header| header.trim() == column)
+                .map(Extraction::Column)
+                .ok_or_else(|| {
+                    Error::invalid_format(format!(
+                        "Column {column} of recordSet {} not found in {}",
+                        record_set.name,
+                        path.display()
+                    ))
+                })
+        })?;
+        Box::new(csv_rows(
+            reader,
+            headers.len(),
+            extractions,
+            names,
+            bad_rows,
+        ))
+    } else {
+        return Err(Error::invalid_format(format!(
+            "Distribution {} has encoding format {}, only CSV, TSV and JSON Lines columns can be read",
+            distribution.id, distribution.encoding_format
+        )));
+    };
+
+    Ok((rows, path))
+}
+
+/// Raw records of the files of a file set: one per file, or, when
+/// `per_line`, one per line of each file
+fn file_set_rows(
+    metadata: &Metadata,
+    file_set: &Distribution,
+    base_dir: &Path,
+    extractions: Vec<Extraction>,
+    per_line: bool,
+) -> Result<RawRows> {
+    let files = list_files(metadata, file_set, base_dir)?;
+    let base_dir = base_dir.to_path_buf();
+    Ok(Box::new(files.into_iter().flat_map(move |fullpath| {
+        let path = base_dir.join(&fullpath);
+        let names = FileNames {
+            filename: fullpath.rsplit('/').next().unwrap_or_default().to_string(),
+            fullpath,
+        };
+        if per_line {
+            match line_rows(&path, extractions.clone(), names) {
+                Ok(rows) => Box::new(rows) as RawRows,
+                Err(e) => Box::new(std::iter::once(Err(e))),
+            }
+        } else {
+            Box::new(file_rows(&path, extractions.clone(), names))
+        }
+    })))
+}
+
+/// Keeps the records whose value of a field is one of a set
+enum Filter {
+    /// The value's text is one of a set
+    OneOf {
+        field: usize,
+        values: HashSet<String>,
+    },
+    /// The value compares to another as a condition requires
+    Compare {
+        field: usize,
+        comparison: Comparison,
+        value: Value,
+    },
+}
+
+impl Filter {
+    fn matches(&self, record: &Record) -> bool {
+        match self {
+            Self::OneOf { field, values } => {
+                let value = &record[*field];
+                !value.is_null() && values.contains(&value.to_string())
+            }
+            Self::Compare {
+                field,
+                comparison,
+                value,
+            } => comparison.holds(&record[*field], value),
+        }
+    }
+}
+
+/// Comparison operators of [`Condition`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    /// Operators, two-character ones first so that they are matched before
+    /// their prefixes
+    const OPERATORS: &'static [(&'static str, Comparison)] = &[
+        ("!=", Comparison::Ne),
+        ("<=", Comparison::Le),
+        (">=", Comparison::Ge),
+        ("=", Comparison::Eq),
+        ("<", Comparison::Lt),
+        (">", Comparison::Gt),
+    ];
+
+    /// Whether `left` compares to `right` this way; a null only equals a
+    /// null, and values of different types are never ordered
+    fn holds(&self, left: &Value, right: &Value) -> bool {
+        match (left.is_null(), right.is_null()) {
+            (true, true) => return *self == Self::Eq,
+            (true, false) | (false, true) => return *self == Self::Ne,
+            (false, false) => {}
+        }
+        let Some(ordering) = compare_values(left, right) else {
+            return *self == Self::Ne;
+        };
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Ne => ordering.is_ne(),
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+            Self::Gt => ordering.is_gt(),
+            Self::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+/// A condition on a field's values, such as `year >= 2020`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: String,
+    pub comparison: Comparison,
+    /// Value compared to, parsed as the field's dataType; empty for null
+    pub value: String,
+}
+
+impl Condition {
+    /// Parse `<field> <operator> <value>`, where the operator is one of
+    /// `=`, `!=`, `<`, `<=`, `>` and `>=`, and the value may be quoted
+    pub fn parse(condition: &str) -> Result<Self> {
+        let (position, operator, comparison) = Comparison::OPERATORS
+            .iter()
+            .filter_map(|&(operator, comparison)| {
+                condition
+                    .find(operator)
+                    .map(|position| (position, operator, comparison))
+            })
+            .min_by_key(|(position, _, _)| *position)
+            .ok_or_else(|| {
+                Error::invalid_format(format!(
+                    "Condition {condition} has no operator (=, !=, <, <=, >, >=)"
+                ))
+            })?;
+        let field = condition[..position].trim();
+        if field.is_empty() {
+            return Err(Error::invalid_format(format!(
+                "Condition {condition} has no field"
+            )));
+        }
+        let value = condition[position + operator.len()..].trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|&quote| value.strip_prefix(quote)?.strip_suffix(quote))
+            .unwrap_or(value);
+        Ok(Self {
+            field: field.to_string(),
+            comparison,
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Order of two values of the same type, integers and floats comparing as
+/// numbers
+pub(crate) fn compare_values(left: &Value, right: &Value) -> Option<std::cmp::Ordering> {
+    match (left, right) {
+        (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+        (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
+        (Value::Integer(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
+        (Value::Float(a), Value::Integer(b)) => a.partial_cmp(&(*b as f64)),
+        (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+        (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+        (Value::DateTime(a), Value::DateTime(b)) => Some(a.cmp(b)),
+        (Value::Time(a), Value::Time(b)) => Some(a.cmp(b)),
+        (Value::Text(a), Value::Text(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+/// Filter keeping the records of a split
+///
+/// The split field is the field whose dataType is `cr:Split`, or which
+/// references a record set enumerating splits. When that record set gives
+/// its splits inline, `split` may be any value of a split, such as its name
+/// or URL, and records holding any other of them are kept too.
+fn split_filter(metadata: &Metadata, record_set: &RecordSet, split: &str) -> Result<Filter> {
+    let (field, splits) = split_field(metadata, record_set).ok_or_else(|| {
+        Error::missing_field(format!(
+            "split field (of dataType cr:Split) in recordSet {}",
+            record_set.name
+        ))
+    })?;
+
+    let mut values = HashSet::from([split.to_string()]);
+    if let Some(splits) = splits
+        && let Some(data) = &splits.data
+    {
+        let texts = |object: &serde_json::Map<String, serde_json::Value>| {
+            object
+                .values()
+                .filter_map(|value| value.as_str().map(str::to_string))
+                .collect::<Vec<_>>()
+        };
+        let known = data.iter().map(texts).collect::<Vec<_>>();
+        let matching = known
+            .iter()
+            .find(|texts| texts.iter().any(|text| text == split))
+            .ok_or_else(|| {
+                let names = known
+                    .iter()
+                    .filter_map(|texts| texts.first().cloned())
+                    .collect::<Vec<_>>();
+                Error::invalid_format(format!(
+                    "Unknown split {split}; recordSet {} lists {}",
+                    splits.name,
+                    names.join(", ")
+                ))
+            })?;
+        values.extend(matching.iter().cloned());
+    }
+    Ok(Filter::OneOf { field, values })
+}
+
+/// Index of the split field of a record set, with the record set
+/// enumerating splits it references, if any; see [`split_filter`]
+pub(crate) fn split_field<'a>(
+    metadata: &'a Metadata,
+    record_set: &RecordSet,
+) -> Option<(usize, Option<&'a RecordSet>)> {
+    let is_split = |data_type: &str| SPLIT_TYPES.contains(&data_type);
+    record_set
+        .field
+        .iter()
+        .enumerate()
+        .find_map(|(index, field)| {
+            let referenced = field.references.as_ref().and_then(|reference| {
+                metadata.record_set.iter().find(|rs| {
+                    rs.data_type.as_deref().is_some_and(is_split)
+                        && rs.field.iter().any(|f| f.id == reference.field.id)
+                })
+            });
+            if referenced.is_some() || is_split(&field.data_type) {
+                Some((index, referenced))
+            } else {
+                None
+            }
+        })
+}
+
+/// A hash join of records with the record set one of their fields
+/// references
+struct Join {
+    /// Index of the referencing field in the records
+    field: usize,
+    /// Fields of the referenced record set appended to the records, all but
+    /// the referenced one
+    fields: Vec<RecordField>,
+    /// Values of those fields by the text of the referenced value
+    table: HashMap<String, Record>,
+}
+
+impl Join {
+    /// Read the record set holding the field `target` into a hash table
+    fn build(
+        metadata: &Metadata,
+        base_dir: &Path,
+        field: usize,
+        target: &str,
+        on_bad_row: BadRowPolicy,
+    ) -> Result<Self> {
+        let (record_set, key) = metadata
+            .record_set
+            .iter()
+            .find_map(|rs| {
+                let key = rs.field.iter().position(|f| f.id == target)?;
+                Some((rs, key))
+            })
+            .ok_or_else(|| Error::missing_field(format!("referenced field {target}")))?;
+        let reader = open_record_set(metadata, record_set, base_dir, on_bad_row)?;
+        let mut fields = reader.fields.clone();
+        fields.remove(key);
+
+        let mut table = HashMap::new();
+        let mut duplicates = 0usize;
+        for record in reader {
+            let mut record = record?;
+            let value = record.remove(key);
+            if value.is_null() {
+                continue;
+            }
+            // Keys are meant to be unique; the first record of a key wins
+            match table.entry(value.to_string()) {
+                Entry::Occupied(_) => duplicates += 1,
+                Entry::Vacant(entry) => {
+                    entry.insert(record);
+                }
+            }
+        }
+        if duplicates > 0 {
+            tracing::warn!(
+                field = target,
+                duplicates,
+                "referenced values are not unique, using the first record of each"
+            );
+        }
+        Ok(Self {
+            field,
+            fields,
+            table,
+        })
+    }
+
+    /// Append the fields of the record `record` references, or nulls if it
+    /// references none; returns whether its value references a record that
+    /// does not exist
+    fn extend(&self, record: &mut Record) -> bool {
+        let value = &record[self.field];
+        let referenced = Some(value)
+            .filter(|value| !value.is_null())
+            .map(|value| self.table.get(&value.to_string()));
+        match referenced {
+            Some(Some(values)) => {
+                record.extend(values.iter().cloned());
+                false
+            }
+            referenced => {
+                record.extend(std::iter::repeat_n(Value::Null, self.fields.len()));
+                referenced.is_some()
+            }
+        }
+    }
+}
+
+/// The distribution every field of a record set is extracted from
+fn source_distribution<'a>(
+    metadata: &'a Metadata,
+    record_set: &RecordSet,
+    sources: &[&FieldSource],
+) -> Result<&'a Distribution> {
+    let mut file_ids = sources.iter().map(|source| source.distribution_id());
+    let file_id = file_ids
+        .next()
+        .ok_or_else(|| Error::missing_field(format!("field in recordSet {}", record_set.name)))?;
+    if file_ids.any(|id| id != file_id) {
+        return Err(Error::invalid_format(format!(
+            "RecordSet {} draws from several files, which is not supported",
+            record_set.name
+        )));
+    }
+    metadata
+        .distribution
+        .iter()
+        .find(|d| d.id == file_id)
+        .ok_or_else(|| Error::missing_field(format!("distribution {file_id}")))
+}
+
+/// The extraction of each field, with `column` mapping column names
+fn extractions(
+    sources: &[&FieldSource],
+    column: impl Fn(&str) -> Result<Extraction>,
+) -> Result<Vec<Extraction>> {
+    sources
+        .iter()
+        .map(|source| match source.extract.file_property {
+            Some(property) => Ok(Extraction::Property(property)),
+            None => column(&source.extract.column),
+        })
+        .collect()
+}
+
+/// Records given inline in the metadata, whose values are keyed by field
+/// `@id` or, failing that, name
+fn inline_rows(
+    leaves: &[&Field],
+    data: Vec<serde_json::Map<String, serde_json::Value>>,
+) -> impl Iterator<Item = Result<RawRow>> + use<> {
+    let keys = leaves
+        .iter()
+        .map(|field| (field.id.clone(), field.name.clone()))
+        .collect::<Vec<_>>();
+    data.into_iter().map(move |object| {
+        Ok(keys
+            .iter()
+            .map(
+                |(id, name)| match object.get(id).or_else(|| object.get(name)) {
+                    None | Some(serde_json::Value::Null) => None,
+                    Some(serde_json::Value::String(text)) => Some(text.clone()),
+                    Some(value) => Some(value.to_string()),
+                },
+            )
+            .collect())
+    })
+}
+
+/// How rows of a CSV file without as many fields as its header are
+/// handled, and how many were skipped
+struct BadRows {
+    policy: BadRowPolicy,
+    count: Arc<AtomicUsize>,
+}
+
+/// Records of a CSV file with `columns` columns
+fn csv_rows(
+    mut reader: CsvFileReader,
+    columns: usize,
+    extractions: Vec<Extraction>,
+    names: FileNames,
+    bad_rows: BadRows,
+) -> impl Iterator<Item = Result<RawRow>> + use<> {
+    let mut record = csv::ByteRecord::new();
+    std::iter::from_fn(move || {
+        loop {
+            match reader.read_byte_record(&mut record) {
+                Ok(true) => {
+                    match accept_csv_row(&record, columns, bad_rows.policy, &names.fullpath) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            bad_rows.count.fetch_add(1, Ordering::Relaxed);
+                            continue;
+                        }
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e.into())),
+            }
+            return Some(Ok(extractions
+                .iter()
+                .map(|extraction| match extraction {
+                    Extraction::Column(index) => record
+                        .get(*index)
+                        .map(|bytes| String::from_utf8_lossy(bytes).into_owned()),
+                    Extraction::Property(property) => names.get(*property),
+                    Extraction::Key(_) => None,
+                })
+                .collect()));
+        }
+    })
+}
+
+/// Records of a JSON Lines file, one per non-blank line
+fn json_lines_rows(
+    path: &Path,
+    extractions: Vec<Extraction>,
+    names: FileNames,
+) -> Result<impl Iterator<Item = Result<RawRow>> + use<>> {
+    let mut lines = open_lines(path)?;
+    Ok(std::iter::from_fn(move || {
+        let line = loop {
+            match lines.next()? {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => break line,
+                Err(e) => return Some(Err(e.into())),
+            }
+        };
+        let object = match serde_json::from_str::<serde_json::Value>(&line) {
+            Ok(serde_json::Value::Object(object)) => object,
+            Ok(_) => {
+                return Some(Err(Error::invalid_format(
+                    "JSON Lines record is not an object",
+                )));
+            }
+            Err(e) => return Some(Err(e.into())),
+        };
+        Some(Ok(extractions
+            .iter()
+            .map(|extraction| match extraction {
+                Extraction::Key(key) => match object.get(key) {
+                    None | Some(serde_json::Value::Null) => None,
+                    Some(serde_json::Value::String(text)) => Some(text.clone()),
+                    Some(value) => Some(value.to_string()),
+                },
+                Extraction::Property(property) => names.get(*property),
+                Extraction::Column(_) => None,
+            })
+            .collect()))
+    }))
+}
+
+/// Records of the lines of a file
+fn line_rows(
+    path: &Path,
+    extractions: Vec<Extraction>,
+    names: FileNames,
+) -> Result<impl Iterator<Item = Result<RawRow>> + use<>> {
+    let mut lines = open_lines(path)?.enumerate();
+    Ok(std::iter::from_fn(move || {
+        let (index, line) = lines.next()?;
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => return Some(Err(e.into())),
+        };
+        Some(Ok(extractions
+            .iter()
+            .map(|extraction| match extraction {
+                Extraction::Property(FileProperty::Lines) => Some(line.clone()),
+                Extraction::Property(FileProperty::LineNumbers) => Some((index + 1).to_string()),
+                Extraction::Property(property) => names.get(*property),
+                Extraction::Column(_) | Extraction::Key(_) => None,
+            })
+            .collect()))
+    }))
+}
+
+/// The single record of a file
+fn file_rows(
+    path: &Path,
+    extractions: Vec<Extraction>,
+    names: FileNames,
+) -> impl Iterator<Item = Result<RawRow>> + use<> {
+    let path = path.to_path_buf();
+    let read_content = || -> Result<String> {
+        let mut bytes = Vec::new();
+        File::open(&path)
+            .map_err(|_| Error::file_not_found(&path))?
+            .read_to_end(&mut bytes)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    };
+    let row = extractions
+        .iter()
+        .map(|extraction| match extraction {
+            Extraction::Property(FileProperty::Content) => read_content().map(Some),
+            Extraction::Property(property) => Ok(names.get(*property)),
+            Extraction::Column(_) | Extraction::Key(_) => Ok(None),
+        })
+        .collect::<Result<RawRow>>();
+    std::iter::once(row)
+}
+
+/// Lines of a text file, without line endings
+fn open_lines(path: &Path) -> Result<impl Iterator<Item = std::io::Result<String>> + use<>> {
+    let file = File::open(path).map_err(|_| Error::file_not_found(path))?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map(|line| line.map(|line| line.trim_end_matches('\r').to_string())))
+}
+
+/// A transform compiled for execution
+enum Step {
+    Regex(Regex),
+    Replace(Regex, String),
+    Separator(String),
+    JsonPath(Vec<PathSegment>),
+}
+
+/// The transforms of a field, turning a raw value into a typed one
+struct Pipeline {
+    /// Each step, with the transform it was compiled from for errors
+    steps: Vec<(String, Step)>,
+    format: Option<String>,
+    /// Whether the field is repeated, its values read as lists
+    repeated: bool,
+    /// Values standing for missing values besides blank ones
+    null_values: Vec<String>,
+}
+
+impl Pipeline {
+    fn compile(field: &Field) -> Result<Self> {
+        let invalid = |transform: &str, message: String| {
+            Error::invalid_format(format!(
+                "Field {}, transform {transform}: {message}",
+                field.name
+            ))
+        };
+        let regex = |transform: &str, pattern: &str| {
+            Regex::new(pattern).map_err(|e| invalid(transform, e.to_string()))
+        };
+        let mut steps = Vec::new();
+        let mut format = None;
+        for transform in field.source.iter().flat_map(|source| &source.transform) {
+            let (label, step) = match transform {
+                Transform::Regex(pattern) => {
+                    let label = format!("regex {pattern}");
+                    let step = Step::Regex(regex(&label, pattern)?);
+                    (label, step)
+                }
+                Transform::Replace(replace) => {
+                    let label = format!("replace {replace}");
+                    let (pattern, replacement) = replace.split_once('/').ok_or_else(|| {
+                        invalid(&label, "expected pattern/replacement".to_string())
+                    })?;
+                    let step = Step::Replace(regex(&label, pattern)?, replacement.to_string());
+                    (label, step)
+                }
+                Transform::Separator(separator) => (
+                    format!("separator {separator:?}"),
+                    Step::Separator(separator.clone()),
+                ),
+                Transform::JsonPath(path) => {
+                    let label = format!("jsonPath {path}");
+                    let segments = parse_json_path(path)
+                        .ok_or_else(|| invalid(&label, "unsupported JSONPath".to_string()))?;
+                    (label, Step::JsonPath(segments))
+                }
+                Transform::Format(pattern) => {
+                    let data_type =
+                        DataType::from_schema_org(&field.data_type).unwrap_or(DataType::Text);
+                    if !matches!(
+                        data_type,
+                        DataType::Date | DataType::DateTime | DataType::Time
+                    ) {
+                        return Err(invalid(
+                            &format!("format {pattern}"),
+                            format!(
+                                "formats apply to sc:Date, sc:DateTime and sc:Time, not {}",
+                                field.data_type
+                            ),
+                        ));
+                    }
+                    format = Some(pattern.clone());
+                    continue;
+                }
+            };
+            steps.push((label, step));
+        }
+        Ok(Self {
+            steps,
+            format,
+            repeated: field.repeated,
+            null_values: field.null_values.clone(),
+        })
+    }
+
+    /// Whether the values are lists
+    fn is_list(&self) -> bool {
+        self.repeated || self.splits()
+    }
+
+    /// Whether a transform splits values into lists
+    fn splits(&self) -> bool {
+        self.steps.iter().any(|(_, step)| match step {
+            Step::Separator(_) => true,
+            Step::JsonPath(segments) => segments.contains(&PathSegment::Wildcard),
+            _ => false,
+        })
+    }
+
+    /// Transform and parse a raw value; errors are messages to follow the
+    /// field's name
+    fn value(&self, raw: String, data_type: &DataType) -> std::result::Result<Value, String> {
+        let mut values = vec![raw];
+        let list = self.is_list();
+        for (label, step) in &self.steps {
+            let mut transformed = Vec::with_capacity(values.len());
+            for value in values {
+                match step {
+                    Step::Regex(regex) => {
+                        let captures = regex.captures(&value).ok_or_else(|| {
+                            format!(", transform {label}: {value} does not match")
+                        })?;
+                        let kept = captures
+                            .iter()
+                            .skip(1)
+                            .flatten()
+                            .next()
+                            .or_else(|| captures.get(0))
+                            .map_or("", |m| m.as_str());
+                        transformed.push(kept.to_string());
+                    }
+                    Step::Replace(regex, replacement) => transformed
+                        .push(regex.replace_all(&value, replacement.as_str()).into_owned()),
+                    Step::Separator(separator) => {
+                        transformed.extend(value.split(separator.as_str()).map(str::to_string));
+                    }
+                    Step::JsonPath(segments) => {
+                        let json =
+                            serde_json::from_str::<serde_json::Value>(&value).map_err(|e| {
+                                format!(", transform {label}: {value} is not JSON: {e}")
+                            })?;
+                        transformed.extend(select(&json, segments).into_iter().filter_map(
+                            |selected| match selected {
+                                serde_json::Value::Null => None,
+                                serde_json::Value::String(text) => Some(text.clone()),
+                                other => Some(other.to_string()),
+                            },
+                        ));
+                    }
+                }
+            }
+            values = transformed;
+        }
+        // Repeated values not split by a transform are JSON arrays
+        if self.repeated && !self.splits() {
+            values = values.into_iter().flat_map(json_items).collect();
+        }
+
+        let parse = |raw: &str| {
+            if self.null_values.iter().any(|null| null == raw.trim()) {
+                return Ok(Value::Null);
+            }
+            parse_value(raw, data_type, self.format.as_deref()).ok_or_else(|| {
+                let mut message =
+                    format!(": {raw} cannot be parsed as {}", data_type.to_schema_org());
+                if let Some(format) = &self.format {
+                    message += &format!(" with format {format}");
+                }
+                message
+            })
+        };
+        if list {
+            values
+                .iter()
+                .map(|value| parse(value))
+                .collect::<std::result::Result<_, _>>()
+                .map(Value::List)
+        } else {
+            values.first().map_or(Ok(Value::Null), |value| parse(value))
+        }
+    }
+}
+
+/// Items of a JSON array, as raw values; other values are a single item
+fn json_items(value: String) -> Vec<String> {
+    match serde_json::from_str::<serde_json::Value>(&value) {
+        Ok(serde_json::Value::Array(items)) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                serde_json::Value::Null => None,
+                serde_json::Value::String(text) => Some(text),
+                item => Some(item.to_string()),
+            })
+            .collect(),
+        _ => vec![value],
+    }
+}
+
+/// Parse a value as `data_type`, dates and datetimes with `format` if given
+fn parse_value(raw: &str, data_type: &DataType, format: Option<&str>) -> Option<Value> {
+    let Some(format) = format.filter(|_| !is_null_value(raw)) else {
+        return Value::parse(raw, data_type).ok();
+    };
+    let trimmed = raw.trim();
+    match data_type {
+        DataType::Date => NaiveDate::parse_from_str(trimmed, format)
+            .ok()
+            .map(Value::Date),
+        DataType::DateTime => DateTime::parse_from_str(trimmed, format)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .or_else(|_| {
+                NaiveDateTime::parse_from_str(trimmed, format).map(|datetime| datetime.and_utc())
+            })
+            .ok()
+            .map(Value::DateTime),
+        DataType::Time => NaiveTime::parse_from_str(trimmed, format)
+            .ok()
+            .map(Value::Time),
+        _ => Value::parse(raw, data_type).ok(),
+    }
+}
+
+/// Step of a JSONPath
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    /// Every element of an array or value of an object
+    Wildcard,
+}
+
+/// Parse the supported subset of JSONPath: `$` followed by `.key`, `.*`,
+/// `['key']`, `["key"]`, `[0]` and `[*]`
+fn parse_json_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut rest = path.trim().strip_prefix('$')?;
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            segments.push(match key {
+                "" => return None,
+                "*" => PathSegment::Wildcard,
+                key => PathSegment::Key(key.to_string()),
+            });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let inner = after[..end].trim();
+            segments.push(if inner == "*" {
+                PathSegment::Wildcard
+            } else if let Some(key) = inner
+                .strip_prefix('\'')
+                .and_then(|key| key.strip_suffix('\''))
+                .or_else(|| {
+                    inner
+                        .strip_prefix('"')
+                        .and_then(|key| key.strip_suffix('"'))
+                })
+            {
+                PathSegment::Key(key.to_string())
+            } else {
+                PathSegment::Index(inner.parse().ok()?)
+            });
+            rest = &after[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(segments)
+}
+
+/// Values of `json` a parsed JSONPath selects
+fn select<'a>(json: &'a serde_json::Value, segments: &[PathSegment]) -> Vec<&'a serde_json::Value> {
+    segments.iter().fold(vec![json], |values, segment| {
+        values
+            .into_iter()
+            .flat_map(|value| -> Vec<&serde_json::Value> {
+                match (segment, value) {
+                    (PathSegment::Key(key), serde_json::Value::Object(object)) => {
+                        object.get(key).into_iter().collect()
+                    }
+                    (PathSegment::Index(index), serde_json::Value::Array(array)) => {
+                        array.get(*index).into_iter().collect()
+                    }
+                    (PathSegment::Wildcard, serde_json::Value::Array(array)) => {
+                        array.iter().collect()
+                    }
+                    (PathSegment::Wildcard, serde_json::Value::Object(object)) => {
+                        object.values().collect()
+                    }
+                    _ => Vec::new(),
+                }
+            })
+            .collect()
+    })
+}
+
+/// Iterator over the records of a record set
+pub struct RecordReader {
+    fields: Vec<RecordField>,
+    /// Name and type of each leaf field, whose values are extracted
+    leaves: Vec<(String, DataType)>,
+    /// How the record set's own fields are assembled from the leaves
+    shapes: Vec<Shape>,
+    rows: RawRows,
+    /// Transforms of the leaf fields
+    pipelines: Vec<Pipeline>,
+    joins: Vec<Join>,
+    filters: Vec<Filter>,
+    /// Matching records still to skip
+    offset: usize,
+    /// Records still to return, if limited
+    remaining: Option<usize>,
+    /// Indices and fields of the fields kept, if not all
+    projection: Option<(Vec<usize>, Vec<RecordField>)>,
+    path: Option<PathBuf>,
+    /// File or record set named in errors
+    location: String,
+    /// Number of records read so far
+    record: usize,
+    /// Number of CSV rows skipped for not having as many fields as the
+    /// header
+    bad_rows: Arc<AtomicUsize>,
+    /// Number of values referencing no record of the referenced record set
+    unresolved_references: usize,
+    done: bool,
+}
+
+impl RecordReader {
+    /// Number of CSV rows skipped so far for not having as many fields as
+    /// the header, with a [`BadRowPolicy`] other than failing
+    pub fn bad_rows(&self) -> usize {
+        self.bad_rows.load(Ordering::Relaxed)
+    }
+
+    /// Number of values read so far that reference no record of the
+    /// record set their field references, when references are resolved
+    pub fn unresolved_references(&self) -> usize {
+        self.unresolved_references
+    }
+
+    /// Fields of the records, in the order of their values
+    pub fn fields(&self) -> &[RecordField] {
+        self.projection
+            .as_ref()
+            .map_or(&self.fields, |(_, fields)| fields)
+    }
+
+    /// File the records are read from; none for records given inline
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Read records ahead on a background thread, holding at most
+    /// `capacity` of them until they are pulled
+    ///
+    /// Dropping the returned iterator stops the thread once it next has a
+    /// record to hand over.
+    pub fn buffered(self, capacity: usize) -> BufferedRecords {
+        let fields = self.fields().to_vec();
+        let (sender, receiver) = std::sync::mpsc::sync_channel(capacity.max(1));
+        let span = tracing::Span::current();
+        std::thread::spawn(move || {
+            let _entered = span.enter();
+            for record in self {
+                if sender.send(record).is_err() {
+                    break;
+                }
+            }
+        });
+        BufferedRecords { fields, receiver }
+    }
+
+    /// Read records ahead on tokio's blocking thread pool, as a [`Stream`]
+    /// holding at most `capacity` records until they are polled
+    ///
+    /// Must be called from within a tokio runtime.
+    ///
+    /// [`Stream`]: futures_core::Stream
+    #[cfg(feature = "tokio")]
+    pub fn into_stream(self, capacity: usize) -> RecordStream {
+        let fields = self.fields().to_vec();
+        let (sender, receiver) = tokio::sync::mpsc::channel(capacity.max(1));
+        let span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || {
+            let _entered = span.enter();
+            for record in self {
+                if sender.blocking_send(record).is_err() {
+                    break;
+                }
+            }
+        });
+        RecordStream { fields, receiver }
+    }
+
+    fn next_record(&mut self) -> Option<Result<Record>> {
+        let row = self.rows.next()?;
+        self.record += 1;
+        let location = format!("{}, record {}", self.location, self.record);
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => return Some(Err(Error::invalid_format(format!("{location}: {e}")))),
+        };
+        let leaves = self
+            .leaves
+            .iter()
+            .zip(&self.pipelines)
+            .zip(row)
+            .map(|(((name, data_type), pipeline), raw)| match raw {
+                Some(raw) => pipeline.value(raw, data_type).map_err(|message| {
+                    Error::invalid_format(format!("{location}, field {name}{message}"))
+                }),
+                None => Ok(Value::Null),
+            })
+            .collect::<Result<Vec<_>>>();
+        Some(leaves.map(|mut leaves| {
+            let mut record = self
+                .shapes
+                .iter()
+                .map(|shape| shape.assemble(&mut leaves))
+                .collect::<Record>();
+            for join in &self.joins {
+                if join.extend(&mut record) {
+                    self.unresolved_references += 1;
+                }
+            }
+            record
+        }))
+    }
+}
+
+impl Iterator for RecordReader {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.remaining == Some(0) {
+            return None;
+        }
+        loop {
+            let record = match self.next_record() {
+                Some(Ok(record)) => record,
+                // Stop after the end of the file or the first error
+                other => {
+                    self.done = true;
+                    return other;
+                }
+            };
+            if !self.filters.iter().all(|filter| filter.matches(&record)) {
+                continue;
+            }
+            if self.offset > 0 {
+                self.offset -= 1;
+                continue;
+            }
+            if let Some(remaining) = &mut self.remaining {
+                *remaining -= 1;
+            }
+            return Some(Ok(match &self.projection {
+                Some((indices, _)) => indices.iter().map(|&i| record[i].clone()).collect(),
+                None => record,
+            }));
+        }
+    }
+}
+
+/// Records read ahead into a bounded buffer; see [`RecordReader::buffered`]
+pub struct BufferedRecords {
+    fields: Vec<RecordField>,
+    receiver: std::sync::mpsc::Receiver<Result<Record>>,
+}
+
+impl BufferedRecords {
+    /// Fields of the records, in the order of their values
+    pub fn fields(&self) -> &[RecordField] {
+        &self.fields
+    }
+}
+
+impl Iterator for BufferedRecords {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// Async stream of records read ahead into a bounded buffer; see
+/// [`RecordReader::into_stream`]
+#[cfg(feature = "tokio")]
+pub struct RecordStream {
+    fields: Vec<RecordField>,
+    receiver: tokio::sync::mpsc::Receiver<Result<Record>>,
+}
+
+#[cfg(feature = "tokio")]
+impl RecordStream {
+    /// Fields of the records, in the order of their values
+    pub fn fields(&self) -> &[RecordField] {
+        &self.fields
+    }
+
+    /// The next record, once read
+    pub async fn next(&mut self) -> Option<Result<Record>> {
+        self.receiver.recv().await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl futures_core::Stream for RecordStream {
+    type Item = Result<Record>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Formats records can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// CSV with a header row of field names
+    Csv,
+    /// One JSON object per line, keyed by field name
+    NdJson,
+    /// Parquet with Snappy compression (`parquet` feature)
+    Parquet,
+}
+
+impl RecordFormat {
+    pub const ALL: &'static [RecordFormat] = &[
+        RecordFormat::Csv,
+        RecordFormat::NdJson,
+        RecordFormat::Parquet,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecordFormat::Csv => "csv",
+            RecordFormat::NdJson => "ndjson",
+            RecordFormat::Parquet => "parquet",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown record format: {name}")))
+    }
+}
+
+/// Write every record of `reader`, returning how many were written
+#[tracing::instrument(level = "debug", skip(reader, output))]
+pub fn write_records(
+    reader: RecordReader,
+    format: RecordFormat,
+    mut output: impl Write + Send,
+) -> Result<usize> {
+    let names = reader
+        .fields()
+        .iter()
+        .map(|field| field.name.clone())
+        .collect::<Vec<_>>();
+    let mut written = 0;
+    match format {
+        RecordFormat::Parquet => written = write_parquet(reader, output)?,
+        RecordFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(output);
+            writer.write_record(&names)?;
+            for record in reader {
+                writer.write_record(record?.iter().map(Value::to_string))?;
+                written += 1;
+            }
+            writer.flush()?;
+        }
+        RecordFormat::NdJson => {
+            // Written by hand to keep the keys in field order
+            let keys = names
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<serde_json::Result<Vec<_>>>()?;
+            for record in reader {
+                let members = keys
+                    .iter()
+                    .zip(record?)
+                    .map(|(key, value)| format!("{key}:{}", value.to_json_string()))
+                    .collect::<Vec<_>>();
+                writeln!(output, "{{{}}}", members.join(","))?;
+                written += 1;
+            }
+            output.flush()?;
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(reader: RecordReader, output: impl Write + Send) -> Result<usize> {
+    crate::croissant::parquet::write_parquet(reader, output)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_reader: RecordReader, _output: impl Write + Send) -> Result<usize> {
+    Err(Error::new(
+        "Parquet output requires rustcroissant to be built with the parquet feature",
+    ))
+}
+
+pub(crate) fn parse_boolean(value: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// An ISO 8601 datetime, in UTC; naive datetimes are taken to be UTC
+pub(crate) fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f"))
+        .ok()
+        .map(|datetime| datetime.and_utc())
+}
+
+/// An ISO 8601 time of day, with or without seconds
+pub(crate) fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Field of `data_type` extracted from a column with the given
+    /// transforms, as written in Croissant
+    fn field(data_type: &str, transforms: serde_json::Value) -> Field {
+        serde_json::from_value(serde_json::json!({
+            "@type": "cr:Field",
+            "@id": "records/value",
+            "name": "value",
+            "description": "A value",
+            "dataType": data_type,
+            "source": {
+                "fileObject": { "@id": "records.csv" },
+                "extract": { "column": "value" },
+                "transform": transforms,
+            },
+        }))
+        .expect("valid field")
+    }
+
+    fn compile(data_type: &str, transforms: serde_json::Value) -> Pipeline {
+        Pipeline::compile(&field(data_type, transforms)).expect("transforms compile")
+    }
+
+    fn text(value: &str) -> Value {
+        Value::Text(value.to_string())
+    }
+
+    #[test]
+    fn condition_parses_operators_and_quoted_values() {
+        let condition = Condition::parse("year >= 2020").unwrap();
+        assert_eq!(condition.field, "year");
+        assert_eq!(condition.comparison, Comparison::Ge);
+        assert_eq!(condition.value, "2020");
+
+        // Two-character operators win over their prefixes
+        assert_eq!(Condition::parse("a<=1").unwrap().comparison, Comparison::Le);
+        assert_eq!(Condition::parse("a!=1").unwrap().comparison, Comparison::Ne);
+
+        let condition = Condition::parse("name = 'a >= b'").unwrap();
+        assert_eq!(condition.comparison, Comparison::Eq);
+        assert_eq!(condition.value, "a >= b");
+        assert_eq!(Condition::parse("name = \"x\"").unwrap().value, "x");
+        assert_eq!(Condition::parse("notes =").unwrap().value, "");
+    }
+
+    #[test]
+    fn condition_without_operator_or_field_is_an_error() {
+        assert!(Condition::parse("year 2020").is_err());
+        assert!(Condition::parse(" = 2020").is_err());
+    }
+
+    #[test]
+    fn comparison_treats_null_as_only_equal_to_null() {
+        assert!(Comparison::Eq.holds(&Value::Null, &Value::Null));
+        assert!(!Comparison::Ne.holds(&Value::Null, &Value::Null));
+        assert!(!Comparison::Eq.holds(&Value::Null, &Value::Integer(1)));
+        assert!(Comparison::Ne.holds(&Value::Integer(1), &Value::Null));
+        for comparison in [
+            Comparison::Lt,
+            Comparison::Le,
+            Comparison::Gt,
+            Comparison::Ge,
+        ] {
+            assert!(!comparison.holds(&Value::Null, &Value::Integer(1)));
+            assert!(!comparison.holds(&Value::Null, &Value::Null));
+        }
+    }
+
+    #[test]
+    fn comparison_orders_numbers_and_never_orders_other_types() {
+        assert!(Comparison::Lt.holds(&Value::Integer(1), &Value::Float(1.5)));
+        assert!(Comparison::Ge.holds(&Value::Float(2.0), &Value::Integer(2)));
+        assert!(Comparison::Eq.holds(&text("a"), &text("a")));
+        assert!(!Comparison::Lt.holds(&text("1"), &Value::Integer(2)));
+        assert!(!Comparison::Eq.holds(&text("1"), &Value::Integer(1)));
+        assert!(Comparison::Ne.holds(&text("1"), &Value::Integer(1)));
+    }
+
+    #[test]
+    fn shape_zips_list_subfields_of_repeated_fields() {
+        let shape = Shape::Object {
+            names: vec!["id".to_string(), "label".to_string(), "source".to_string()],
+            children: vec![Shape::Leaf(0), Shape::Leaf(1), Shape::Leaf(2)],
+            repeated: true,
+        };
+        let mut leaves = vec![
+            Value::List(vec![Value::Integer(1), Value::Integer(2)]),
+            Value::List(vec![text("one")]),
+            text("survey"),
+        ];
+        let object = |id: i64, label: Value| {
+            Value::Object(vec![
+                ("id".to_string(), Value::Integer(id)),
+                ("label".to_string(), label),
+                ("source".to_string(), text("survey")),
+            ])
+        };
+        assert_eq!(
+            shape.assemble(&mut leaves),
+            Value::List(vec![object(1, text("one")), object(2, Value::Null)])
+        );
+        // The leaves are taken
+        assert!(leaves.iter().all(Value::is_null));
+    }
+
+    #[test]
+    fn shape_of_repeated_field_without_values_is_null() {
+        let shape = Shape::Object {
+            names: vec!["id".to_string()],
+            children: vec![Shape::Leaf(0)],
+            repeated: true,
+        };
+        assert_eq!(shape.assemble(&mut [Value::Null]), Value::Null);
+
+        let shape = Shape::Object {
+            names: vec!["id".to_string()],
+            children: vec![Shape::Leaf(0)],
+            repeated: false,
+        };
+        assert_eq!(
+            shape.assemble(&mut [Value::Null]),
+            Value::Object(vec![("id".to_string(), Value::Null)])
+        );
+    }
+
+    #[test]
+    fn join_appends_referenced_fields_or_nulls() {
+        let join = Join {
+            field: 0,
+            fields: vec![RecordField {
+                name: "label".to_string(),
+                data_type: DataType::Text,
+                repeated: false,
+                fields: Vec::new(),
+            }],
+            table: HashMap::from([("1".to_string(), vec![text("one")])]),
+        };
+
+        let mut record = vec![Value::Integer(1)];
+        assert!(!join.extend(&mut record));
+        assert_eq!(record, vec![Value::Integer(1), text("one")]);
+
+        // A missing key is reported; a null references nothing
+        let mut record = vec![Value::Integer(2)];
+        assert!(join.extend(&mut record));
+        assert_eq!(record, vec![Value::Integer(2), Value::Null]);
+        let mut record = vec![Value::Null];
+        assert!(!join.extend(&mut record));
+        assert_eq!(record, vec![Value::Null, Value::Null]);
+    }
+
+    #[test]
+    fn regex_step_keeps_first_capture_group_or_match() {
+        let pipeline = compile("sc:Integer", serde_json::json!([{ "regex": "id-(\\d+)" }]));
+        assert_eq!(
+            pipeline.value("id-42".to_string(), &DataType::Integer),
+            Ok(Value::Integer(42))
+        );
+        assert!(pipeline.value("x".to_string(), &DataType::Integer).is_err());
+
+        let pipeline = compile("sc:Text", serde_json::json!([{ "regex": "[a-z]+" }]));
+        assert_eq!(
+            pipeline.value("42abc7".to_string(), &DataType::Text),
+            Ok(text("abc"))
+        );
+    }
+
+    #[test]
+    fn replace_step_replaces_every_match() {
+        let pipeline = compile("sc:Float", serde_json::json!([{ "replace": "[$,]/" }]));
+        assert_eq!(
+            pipeline.value("$1,299.50".to_string(), &DataType::Float),
+            Ok(Value::Float(1299.5))
+        );
+        assert!(
+            Pipeline::compile(&field("sc:Text", serde_json::json!([{ "replace": "x" }]))).is_err()
+        );
+    }
+
+    #[test]
+    fn separator_step_splits_into_lists() {
+        let pipeline = compile("sc:Integer", serde_json::json!([{ "separator": ";" }]));
+        assert!(pipeline.is_list());
+        assert_eq!(
+            pipeline.value("1;2;3".to_string(), &DataType::Integer),
+            Ok(Value::List(vec![
+                Value::Integer(1),
+                Value::Integer(2),
+                Value::Integer(3)
+            ]))
+        );
+    }
+
+    #[test]
+    fn json_path_step_selects_values() {
+        let pipeline = compile("sc:Text", serde_json::json!([{ "jsonPath": "$.name" }]));
+        assert!(!pipeline.is_list());
+        assert_eq!(
+            pipeline.value(r#"{"name": "Ada"}"#.to_string(), &DataType::Text),
+            Ok(text("Ada"))
+        );
+        assert_eq!(
+            pipeline.value(r#"{"age": 36}"#.to_string(), &DataType::Text),
+            Ok(Value::Null)
+        );
+        assert!(
+            pipeline
+                .value("not json".to_string(), &DataType::Text)
+                .is_err()
+        );
+
+        let pipeline = compile(
+            "sc:Integer",
+            serde_json::json!([{ "jsonPath": "$.items[*]" }]),
+        );
+        assert!(pipeline.is_list());
+        assert_eq!(
+            pipeline.value(r#"{"items": [1, 2]}"#.to_string(), &DataType::Integer),
+            Ok(Value::List(vec![Value::Integer(1), Value::Integer(2)]))
+        );
+    }
+
+    #[test]
+    fn format_parses_dates_and_applies_only_to_temporal_types() {
+        let pipeline = compile("sc:Date", serde_json::json!([{ "format": "%d/%m/%Y" }]));
+        assert_eq!(
+            pipeline.value("15/03/2024".to_string(), &DataType::Date),
+            Ok(Value::Date(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap()))
+        );
+        assert!(
+            pipeline
+                .value("2024-13-45".to_string(), &DataType::Date)
+                .is_err()
+        );
+        assert!(
+            Pipeline::compile(&field(
+                "sc:Integer",
+                serde_json::json!([{ "format": "%Y" }])
+            ))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn steps_apply_in_order() {
+        let pipeline = compile(
+            "sc:Integer",
+            serde_json::json!([{ "separator": "," }, { "regex": "#(\\d+)" }]),
+        );
+        assert_eq!(
+            pipeline.value("#1,#2".to_string(), &DataType::Integer),
+            Ok(Value::List(vec![Value::Integer(1), Value::Integer(2)]))
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! Credentials for remote content, per host
+//!
+//! Hosts are configured in a YAML file, `$CROISSANT_AUTH_FILE` or
+//! `rustcroissant/auth.yaml` in the user's configuration directory:
+//!
+//! ```yaml
+//! hosts:
+//!   data.example.org:
+//!     bearer: ${EXAMPLE_TOKEN}
+//!   "*.mirror.example.com":
+//!     username: reader
+//!     password: ${MIRROR_PASSWORD}
+//!   api.example.net:
+//!     headers:
+//!       X-Api-Key: ${EXAMPLE_API_KEY}
+//! ```
+//!
+//! `${NAME}` is replaced by the environment variable `NAME`, so secrets
+//! need not be written into the file. A bearer token or basic credentials
+//! may also be given without a file, in `CROISSANT_TOKEN_<HOST>` or
+//! `CROISSANT_BASIC_AUTH_<HOST>` (as `user:password`), where `<HOST>` is
+//! the host name in upper case with every other character replaced by `_`;
+//! these take precedence over the file. Usernames and passwords also apply
+//! to SFTP and FTP servers.
+//!
+//! Hosts the auth file does not match, exactly or by a wildcard, may also
+//! be configured in the `auth` section of a project configuration given
+//! explicitly with `--config`, which [`set_project_auth`] hands over. A
+//! configuration found in the working directory may come with a cloned
+//! repository, so its credentials, which could send any environment
+//! variable to any host, are never used.
+//!
+//! Presigned URLs, which carry their own signature in the query string, are
+//! passed through unchanged: object stores reject requests signed twice.
+//!
+//! Redirects drop the `Authorization` header when they leave the host, but
+//! would keep configured `headers`, so clients from [`client`] refuse to
+//! follow a host that has them to another host.
+
+use crate::croissant::errors::{Error, Result};
+use reqwest::RequestBuilder;
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable naming the auth file
+pub const AUTH_FILE_ENV: &str = "CROISSANT_AUTH_FILE";
+
+/// Prefix of the environment variables holding a host's bearer token
+pub const TOKEN_ENV_PREFIX: &str = "CROISSANT_TOKEN_";
+
+/// Prefix of the environment variables holding a host's basic credentials
+pub const BASIC_AUTH_ENV_PREFIX: &str = "CROISSANT_BASIC_AUTH_";
+
+/// Query parameters carrying the signature of a presigned URL: S3, Google
+/// Cloud Storage, Azure shared access signatures and CloudFront
+const SIGNATURE_PARAMS: &[&str] = &["X-Amz-Signature", "X-Goog-Signature", "sig", "Signature"];
+
+/// Credentials for one host
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HostAuth {
+    /// Token sent as `Authorization: Bearer`
+    pub bearer: Option<String>,
+    /// User for basic authentication, and for SFTP and FTP
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Further headers sent with every request, such as API keys
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Credentials of every configured host
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Credentials by host name; `*.example.org` matches every subdomain
+    /// of `example.org`
+    #[serde(default)]
+    pub hosts: BTreeMap<String, HostAuth>,
+    /// Credentials used for hosts none of `hosts` matches
+    #[serde(skip)]
+    fallback: Option<Box<AuthConfig>>,
+}
+
+impl AuthConfig {
+    /// Parse an auth file, substituting environment variables
+    pub fn parse(yaml: &str) -> Result<Self> {
+        let config: Self =
+            serde_yaml::from_str(yaml).map_err(|e| Error::invalid_format(e.to_string()))?;
+        config.resolve()
+    }
+
+    /// Substitute environment variables and check the credentials
+    pub fn resolve(mut self) -> Result<Self> {
+        for (host, auth) in &mut self.hosts {
+            for value in [&mut auth.bearer, &mut auth.username, &mut auth.password]
+                .into_iter()
+                .flatten()
+                .chain(auth.headers.values_mut())
+            {
+                *value = expand_env(value)?;
+            }
+            if auth.bearer.is_some() && auth.username.is_some() {
+                return Err(Error::invalid_format(format!(
+                    "{host} has both a bearer token and a username"
+                )));
+            }
+            for (name, value) in &auth.headers {
+                HeaderName::try_from(name.as_str())
+                    .map_err(|_| Error::invalid_format(format!("Invalid header name {name}")))?;
+                HeaderValue::try_from(value.as_str()).map_err(|_| {
+                    Error::invalid_format(format!("Invalid value of header {name} for {host}"))
+                })?;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Read an auth file
+    pub fn load(path: &Path) -> Result<Self> {
+        let yaml = std::fs::read_to_string(path).map_err(|_| Error::file_not_found(path))?;
+        Self::parse(&yaml).map_err(|e| match e {
+            Error::InvalidFormat { message } => {
+                Error::invalid_format(format!("{}: {message}", path.display()))
+            }
+            e => e,
+        })
+    }
+
+    /// The auth file, if there is one; a file named by
+    /// `CROISSANT_AUTH_FILE` must exist
+    pub fn load_default() -> Result<Self> {
+        match default_auth_file() {
+            Some(path) if path.is_file() || std::env::var_os(AUTH_FILE_ENV).is_some() => {
+                Self::load(&path)
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Use the credentials of `fallback` for the hosts this configuration
+    /// does not match
+    pub fn with_fallback(mut self, fallback: AuthConfig) -> Self {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+
+    /// The configured credentials of a host: those of its own entry, else
+    /// of the longest wildcard matching it, else of the fallback
+    fn lookup(&self, host: &str) -> Option<&HostAuth> {
+        self.hosts
+            .get(host)
+            .or_else(|| {
+                self.hosts
+                    .iter()
+                    .filter(|(pattern, _)| {
+                        pattern
+                            .strip_prefix("*.")
+                            .and_then(|domain| host.strip_suffix(domain))
+                            .is_some_and(|sub| sub.ends_with('.'))
+                    })
+                    .max_by_key(|(pattern, _)| pattern.len())
+                    .map(|(_, auth)| auth)
+            })
+            .or_else(|| self.fallback.as_ref()?.lookup(host))
+    }
+
+    /// Credentials for a host, with those from the environment taking
+    /// precedence over the file
+    pub fn for_host(&self, host: &str) -> HostAuth {
+        let mut auth = self.lookup(host).cloned().unwrap_or_default();
+
+        let key = env_key(host);
+        if let Ok(token) = std::env::var(format!("{TOKEN_ENV_PREFIX}{key}")) {
+            auth.bearer = Some(token);
+            auth.username = None;
+            auth.password = None;
+        } else if let Ok(credentials) = std::env::var(format!("{BASIC_AUTH_ENV_PREFIX}{key}")) {
+            let (username, password) = credentials
+                .split_once(':')
+                .unwrap_or((credentials.as_str(), ""));
+            auth.bearer = None;
+            auth.username = Some(username.to_string());
+            auth.password = Some(password.to_string());
+        }
+        auth
+    }
+
+    /// Check whether a request to `original` that is redirected to `to`
+    /// would carry headers configured for another host
+    pub fn leaks_headers(&self, original: &reqwest::Url, to: &reqwest::Url) -> bool {
+        original.host_str() != to.host_str()
+            && !is_presigned(original)
+            && original
+                .host_str()
+                .is_some_and(|host| !self.for_host(host).headers.is_empty())
+    }
+
+    /// Add the credentials of the URL's host to an HTTP(S) request, unless
+    /// the URL is presigned
+    pub fn authorize(&self, request: RequestBuilder, url: &str) -> RequestBuilder {
+        let Ok(parsed) = reqwest::Url::parse(url) else {
+            return request;
+        };
+        let Some(host) = parsed.host_str() else {
+            return request;
+        };
+        if is_presigned(&parsed) {
+            tracing::debug!(host, "passing presigned URL through");
+            return request;
+        }
+
+        let auth = self.for_host(host);
+        let mut request = match (&auth.bearer, &auth.username) {
+            (Some(token), _) => request.bearer_auth(token),
+            (None, Some(username)) => request.basic_auth(username, auth.password.as_ref()),
+            (None, None) => request,
+        };
+        for (name, value) in &auth.headers {
+            request = request.header(name, value);
+        }
+        request
+    }
+}
+
+/// The auth file given by `CROISSANT_AUTH_FILE`, or `rustcroissant/auth.yaml`
+/// in the user's configuration directory
+pub fn default_auth_file() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(AUTH_FILE_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("rustcroissant").join("auth.yaml"))
+}
+
+/// Credentials of the project configuration, used for hosts the auth file
+/// does not match
+static PROJECT_AUTH: OnceLock<AuthConfig> = OnceLock::new();
+
+/// Use the credentials of a project configuration file for the hosts the
+/// auth file does not match; takes effect only before the first request.
+/// Only pass credentials of a file the user chose, never of one found in
+/// the working directory.
+pub fn set_project_auth(config: AuthConfig) {
+    if PROJECT_AUTH.set(config).is_err() {
+        tracing::warn!("project credentials were already set");
+    }
+}
+
+/// The auth configuration of this process, read once
+pub fn auth_config() -> Result<&'static AuthConfig> {
+    static CONFIG: OnceLock<std::result::Result<AuthConfig, String>> = OnceLock::new();
+    CONFIG
+        .get_or_init(|| load_process_auth().map_err(|e| e.to_string()))
+        .as_ref()
+        .map_err(|e| Error::new(e.clone()))
+}
+
+/// The auth file, falling back to the project credentials
+fn load_process_auth() -> Result<AuthConfig> {
+    let config = AuthConfig::load_default()?;
+    match PROJECT_AUTH.get() {
+        Some(project) => Ok(config.with_fallback(project.clone().resolve()?)),
+        None => Ok(config),
+    }
+}
+
+/// Add the configured credentials of the URL's host to an HTTP(S) request
+pub fn authorize(request: RequestBuilder, url: &str) -> Result<RequestBuilder> {
+    Ok(auth_config()?.authorize(request, url))
+}
+
+/// Most redirects followed for one request, as in reqwest's default policy
+const MAX_REDIRECTS: usize = 10;
+
+/// HTTP client for requests carrying configured credentials, which does
+/// not follow redirects that would send configured headers to another host
+pub fn client() -> reqwest::Client {
+    let policy = reqwest::redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        let leaks_headers = attempt.previous().first().is_some_and(|original| {
+            auth_config().is_ok_and(|config| config.leaks_headers(original, attempt.url()))
+        });
+        if leaks_headers {
+            let to = attempt.url().host_str().unwrap_or_default().to_string();
+            return attempt.error(format!(
+                "not following a redirect to {to}, which would receive the headers configured for another host"
+            ));
+        }
+        attempt.follow()
+    });
+    reqwest::Client::builder()
+        .redirect(policy)
+        .build()
+        .expect("HTTP client with default TLS settings")
+}
+
+/// Check whether a URL carries its own signature in the query string
+pub fn is_presigned(url: &reqwest::Url) -> bool {
+    url.query_pairs()
+        .any(|(name, _)| SIGNATURE_PARAMS.contains(&name.as_ref()))
+}
+
+/// Host name as used in environment variable names: `data.example.org`
+/// becomes `DATA_EXAMPLE_ORG`
+fn env_key(host: &str) -> String {
+    host.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Replace each `${NAME}` with the environment variable `NAME`
+fn expand_env(text: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| Error::invalid_format(format!("Unterminated ${{ in {text}")))?;
+        let name = &after[..end];
+        let value = std::env::var(name).map_err(|_| {
+            Error::invalid_format(format!("Environment variable {name} is not set"))
+        })?;
+        expanded.push_str(&value);
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> AuthConfig {
+        AuthConfig::parse(yaml).expect("valid auth config")
+    }
+
+    #[test]
+    fn auth_file_wildcard_takes_precedence_over_project_host() {
+        let file = config("hosts:\n  \"*.example.org\":\n    bearer: file-token\n");
+        let project = config("hosts:\n  data.example.org:\n    bearer: project-token\n");
+        let auth = file.with_fallback(project);
+        assert_eq!(
+            auth.for_host("data.example.org").bearer.as_deref(),
+            Some("file-token")
+        );
+    }
+
+    #[test]
+    fn project_credentials_apply_to_hosts_the_auth_file_does_not_match() {
+        let file = config("hosts:\n  \"*.example.org\":\n    bearer: file-token\n");
+        let project = config("hosts:\n  data.example.net:\n    bearer: project-token\n");
+        let auth = file.with_fallback(project);
+        assert_eq!(
+            auth.for_host("data.example.net").bearer.as_deref(),
+            Some("project-token")
+        );
+        assert_eq!(auth.for_host("other.example.com"), HostAuth::default());
+    }
+
+    #[test]
+    fn wildcards_match_subdomains_only() {
+        let auth = config(
+            "hosts:\n  \"*.example.org\":\n    bearer: any\n  \"*.data.example.org\":\n    bearer: data\n",
+        );
+        assert_eq!(
+            auth.for_host("www.example.org").bearer.as_deref(),
+            Some("any")
+        );
+        // The longest matching wildcard wins
+        assert_eq!(
+            auth.for_host("eu.data.example.org").bearer.as_deref(),
+            Some("data")
+        );
+        assert_eq!(auth.for_host("example.org"), HostAuth::default());
+        assert_eq!(auth.for_host("badexample.org"), HostAuth::default());
+    }
+
+    #[test]
+    fn exact_hosts_take_precedence_over_wildcards() {
+        let auth = config(
+            "hosts:\n  \"*.example.org\":\n    bearer: any\n  data.example.org:\n    username: reader\n",
+        );
+        let data = auth.for_host("data.example.org");
+        assert_eq!(data.bearer, None);
+        assert_eq!(data.username.as_deref(), Some("reader"));
+    }
+
+    #[test]
+    fn expand_env_substitutes_variables() {
+        // PATH is set wherever the tests run
+        let path = std::env::var("PATH").expect("PATH is set");
+        assert_eq!(
+            expand_env("a${PATH}b").expect("expands"),
+            format!("a{path}b")
+        );
+        assert_eq!(expand_env("no variables").expect("expands"), "no variables");
+        assert!(expand_env("${RUSTCROISSANT_TEST_UNSET_VARIABLE}").is_err());
+        assert!(expand_env("${PATH").is_err());
+    }
+
+    #[test]
+    fn env_key_upper_cases_and_replaces_other_characters() {
+        assert_eq!(env_key("data.example-1.org"), "DATA_EXAMPLE_1_ORG");
+    }
+
+    #[test]
+    fn presigned_urls_are_recognized_by_their_signature() {
+        let url = |text: &str| reqwest::Url::parse(text).expect("valid URL");
+        assert!(is_presigned(&url(
+            "https://bucket.s3.amazonaws.com/a.csv?X-Amz-Signature=abc"
+        )));
+        assert!(is_presigned(&url(
+            "https://account.blob.core.windows.net/c/a.csv?sv=1&sig=abc"
+        )));
+        assert!(!is_presigned(&url(
+            "https://data.example.org/a.csv?version=2"
+        )));
+    }
+
+    #[test]
+    fn redirects_to_other_hosts_leak_configured_headers() {
+        let url = |text: &str| reqwest::Url::parse(text).expect("valid URL");
+        let auth = config("hosts:\n  api.example.net:\n    headers:\n      X-Api-Key: secret\n");
+        let original = url("https://api.example.net/a.csv");
+        assert!(auth.leaks_headers(&original, &url("https://evil.example/a.csv")));
+        assert!(!auth.leaks_headers(&original, &url("https://api.example.net/b.csv")));
+        assert!(!auth.leaks_headers(
+            &url("https://data.example.org/a.csv"),
+            &url("https://evil.example/a.csv")
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+//! Object storage (`s3://`, `gs://`, `az://` and Azure Blob Storage
+//! `https://`) content
+//!
+//! Objects are listed, stat'ed and streamed through [`object_store`], with
+//! credentials found the way the provider's own tools find them: for S3
+//! from environment variables, web identity tokens and container or
+//! instance metadata, and for GCS from a service account named in the
+//! environment or the application-default credentials, and for Azure from
+//! the `AZURE_STORAGE_*` variables or a managed identity.
+
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::remote::retry::{policy, wait_for_host};
+use crate::croissant::remote::{Progress, RemoteDigest, RemoteSample, SampleHasher};
+use crate::croissant::utils::{is_object_store_url, require_url_support};
+use futures_util::StreamExt;
+use object_store::ObjectStore;
+use object_store::path::Path as ObjectPath;
+
+/// Size and entity tag of an object
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectInfo {
+    pub url: String,
+    pub size: u64,
+    pub e_tag: Option<String>,
+}
+
+/// Check whether a content URL refers to an object in a store this build
+/// supports
+pub fn is_cloud_url(url: &str) -> bool {
+    is_object_store_url(url) && require_url_support(url).is_ok()
+}
+
+/// Name of an object, the last segment of its URL
+pub fn object_name(url: &str) -> &str {
+    url.trim_end_matches('/').rsplit('/').next().unwrap_or(url)
+}
+
+/// Store holding the object at `url`, and the object's path in it, once a
+/// request to the store stays within its rate limit
+async fn open(url: &str) -> Result<(Box<dyn ObjectStore>, ObjectPath)> {
+    let store_error = |e: object_store::Error| Error::remote(url, e.to_string());
+    let (root, key) = split_url(url);
+    let path = ObjectPath::from_url_path(key).map_err(|e| Error::remote(url, e.to_string()))?;
+    wait_for_host(root).await;
+
+    let scheme = root.split_once("://").map_or("", |(scheme, _)| scheme);
+    let store: Box<dyn ObjectStore> = match scheme {
+        #[cfg(feature = "s3")]
+        "s3" => Box::new(
+            object_store::aws::AmazonS3Builder::from_env()
+                .with_url(url)
+                .with_retry(retry_config())
+                .build()
+                .map_err(store_error)?,
+        ),
+        #[cfg(feature = "gcs")]
+        "gs" => Box::new(
+            object_store::gcp::GoogleCloudStorageBuilder::from_env()
+                .with_url(url)
+                .with_retry(retry_config())
+                .build()
+                .map_err(store_error)?,
+        ),
+        #[cfg(feature = "azure")]
+        "az" | "https" => Box::new(
+            object_store::azure::MicrosoftAzureBuilder::from_env()
+                .with_url(root)
+                .with_retry(retry_config())
+                .build()
+                .map_err(store_error)?,
+        ),
+        _ => {
+            require_url_support(url)?;
+            return Err(Error::remote(url, "not an object storage URL"));
+        }
+    };
+    Ok((store, path))
+}
+
+/// Retries of object store requests, following the network policy
+fn retry_config() -> object_store::RetryConfig {
+    let policy = policy();
+    object_store::RetryConfig {
+        backoff: object_store::BackoffConfig {
+            init_backoff: policy.initial_backoff,
+            max_backoff: policy.max_backoff,
+            base: 2.0,
+        },
+        max_retries: policy.retries as usize,
+        ..Default::default()
+    }
+}
+
+/// Split an object URL into the URL of its bucket or container and the
+/// object's key
+fn split_url(url: &str) -> (&str, &str) {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    // Azure Blob Storage URLs name the account in the host and the
+    // container in the first path segment
+    let root_segments = if scheme == "https" { 2 } else { 1 };
+    let root_len = rest
+        .match_indices('/')
+        .nth(root_segments - 1)
+        .map_or(rest.len(), |(index, _)| index);
+    let root_end = scheme.len() + "://".len() + root_len;
+    (&url[..root_end], url[root_end..].trim_start_matches('/'))
+}
+
+/// URL of an object listed from the store of `base_url`
+fn object_url(base_url: &str, path: &ObjectPath) -> String {
+    format!("{}/{path}", split_url(base_url).0)
+}
+
+/// Size and entity tag of the object at `url`
+#[tracing::instrument(level = "debug")]
+pub async fn stat(url: &str) -> Result<ObjectInfo> {
+    let (store, path) = open(url).await?;
+    let meta = store
+        .head(&path)
+        .await
+        .map_err(|e| Error::remote(url, e.to_string()))?;
+    Ok(ObjectInfo {
+        url: url.to_string(),
+        size: meta.size,
+        e_tag: meta.e_tag,
+    })
+}
+
+/// Every object under the prefix `url`, sorted by URL
+///
+/// The prefix is matched on whole path segments, so `s3://bucket/data`
+/// lists `data/a.csv` but not `data2.csv`.
+#[tracing::instrument(level = "debug")]
+pub async fn list(url: &str) -> Result<Vec<ObjectInfo>> {
+    let (store, prefix) = open(url).await?;
+    let prefix = (!prefix.as_ref().is_empty()).then_some(prefix);
+    let mut listing = store.list(prefix.as_ref());
+
+    let mut objects = Vec::new();
+    while let Some(meta) = listing.next().await {
+        let meta = meta.map_err(|e| Error::remote(url, e.to_string()))?;
+        objects.push(ObjectInfo {
+            url: object_url(url, &meta.location),
+            size: meta.size,
+            e_tag: meta.e_tag,
+        });
+    }
+    objects.sort_by(|a, b| a.url.cmp(&b.url));
+    tracing::debug!(objects = objects.len(), "listed objects");
+    Ok(objects)
+}
+
+/// Stream an object and compute its SHA-256 digest, counting the bytes
+/// received in `progress`
+#[tracing::instrument(level = "debug", skip(progress))]
+pub async fn fetch_sha256(url: &str, progress: &Progress) -> Result<RemoteDigest> {
+    stream_sample(url, 0, progress)
+        .await
+        .map(|sample| sample.digest)
+}
+
+/// Stream an object, keeping its first `lines` lines (at most
+/// [`MAX_SAMPLE_BYTES`](crate::croissant::remote::MAX_SAMPLE_BYTES)) and
+/// computing the SHA-256 digest of all of it
+///
+/// Only the kept lines are held in memory, so large objects can be sampled
+/// for type inference and hashed in a single pass.
+#[tracing::instrument(level = "debug")]
+pub async fn fetch_sample(url: &str, lines: usize) -> Result<RemoteSample> {
+    stream_sample(url, lines, &Progress::new()).await
+}
+
+async fn stream_sample(url: &str, lines: usize, progress: &Progress) -> Result<RemoteSample> {
+    let (store, path) = open(url).await?;
+    let mut stream = store
+        .get(&path)
+        .await
+        .map_err(|e| Error::remote(url, e.to_string()))?
+        .into_stream();
+
+    let mut sampler = SampleHasher::new(lines, progress);
+    while let Some(chunk) = stream.next().await {
+        sampler.update(&chunk.map_err(|e| Error::remote(url, e.to_string()))?);
+    }
+    let sample = sampler.finish(url);
+    tracing::debug!(sha256 = %sample.digest.sha256, content_size = sample.digest.content_size, "hashed object");
+    Ok(sample)
+}
@@ -0,0 +1,203 @@
+//! Resolution of DOIs cited by a dataset
+//!
+//! DOIs found in `identifier` and `citeAs` are resolved through doi.org
+//! content negotiation as CSL JSON. The registered title, and the creators
+//! when `citeAs` names authors, are compared with the Croissant file so
+//! that a DOI copied from another dataset is caught. The comparison is
+//! deliberately loose: only metadata sharing no words with the record is
+//! reported.
+
+use crate::croissant::Metadata;
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::remote::{auth, retry};
+use crate::croissant::validate::{IssuePath, Rule, ValidationIssues};
+use reqwest::StatusCode;
+use reqwest::header::ACCEPT;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Environment variable overriding the DOI resolver
+pub const RESOLVER_ENV: &str = "CROISSANT_DOI_RESOLVER";
+
+const DEFAULT_RESOLVER: &str = "https://doi.org";
+
+/// Title and creators registered for a DOI
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoiRecord {
+    pub doi: String,
+    pub title: Option<String>,
+    /// Family names, or full names of organizations
+    pub creators: Vec<String>,
+}
+
+/// The DOIs mentioned in a text, in order of appearance, such as
+/// `10.5281/zenodo.123` in `https://doi.org/10.5281/zenodo.123`
+pub fn find_dois(text: &str) -> Vec<String> {
+    let mut dois = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("10.") {
+        let preceded_by_word = rest[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '.');
+        let candidate = &rest[start..];
+        rest = &candidate["10.".len()..];
+        if preceded_by_word {
+            continue;
+        }
+        let Some((registrant, suffix)) = candidate["10.".len()..].split_once('/') else {
+            continue;
+        };
+        if registrant.len() < 4 || !registrant.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            continue;
+        }
+        let suffix_len = suffix
+            .find(|c: char| c.is_whitespace() || "\"'<>{},;".contains(c))
+            .unwrap_or(suffix.len());
+        let suffix = suffix[..suffix_len].trim_end_matches(['.', ')', ']']);
+        if !suffix.is_empty() {
+            let doi = format!("10.{registrant}/{suffix}");
+            rest = &candidate[doi.len()..];
+            if !dois.contains(&doi) {
+                dois.push(doi);
+            }
+        }
+    }
+    dois
+}
+
+/// Resolve a DOI to its registered title and creators
+#[tracing::instrument(level = "debug", skip(client))]
+pub async fn resolve(client: &reqwest::Client, doi: &str) -> Result<DoiRecord> {
+    let resolver = std::env::var(RESOLVER_ENV).unwrap_or_else(|_| DEFAULT_RESOLVER.to_string());
+    let url = format!("{}/{doi}", resolver.trim_end_matches('/'));
+    let remote_error = |e: reqwest::Error| Error::remote(&url, e.to_string());
+
+    let request = client
+        .get(&url)
+        .header(ACCEPT, "application/vnd.citationstyles.csl+json");
+    let response = retry::send(request, &url).await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Err(Error::remote(&url, "DOI is not registered"));
+    }
+    let bytes = response
+        .error_for_status()
+        .map_err(remote_error)?
+        .bytes()
+        .await
+        .map_err(remote_error)?;
+    let csl: Value = serde_json::from_slice(&bytes)
+        .map_err(|e| Error::remote(&url, format!("invalid CSL JSON: {e}")))?;
+
+    let creators = csl["author"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|author| author["family"].as_str().or(author["literal"].as_str()))
+        .map(str::to_string)
+        .collect();
+    Ok(DoiRecord {
+        doi: doi.to_string(),
+        title: csl["title"].as_str().map(str::to_string),
+        creators,
+    })
+}
+
+/// Resolve the DOIs in the metadata's `identifier` and `citeAs`, adding a
+/// warning for each DOI that does not resolve or whose record does not
+/// match the metadata
+#[tracing::instrument(skip_all, fields(dataset = %metadata.name))]
+pub async fn check_dois(metadata: &Metadata, issues: &mut ValidationIssues) {
+    let path = || IssuePath::new().metadata(&metadata.name);
+    let mut dois = Vec::new();
+    for text in [&metadata.identifier, &metadata.cite_as]
+        .into_iter()
+        .flatten()
+    {
+        for doi in find_dois(text) {
+            if !dois.contains(&doi) {
+                dois.push(doi);
+            }
+        }
+    }
+
+    let client = auth::client();
+    let cited_creators = metadata
+        .cite_as
+        .as_deref()
+        .map(bibtex_authors)
+        .unwrap_or_default();
+    for doi in dois {
+        let record = match resolve(&client, &doi).await {
+            Ok(record) => record,
+            Err(e) => {
+                issues.add_rule_warning(
+                    Rule::Doi,
+                    format!("DOI {doi} could not be resolved: {e}"),
+                    path(),
+                );
+                continue;
+            }
+        };
+        if let Some(title) = &record.title
+            && !shares_word(title, &metadata.name)
+            && !shares_word(title, &metadata.description)
+        {
+            issues.add_rule_warning(
+                Rule::Doi,
+                format!(
+                    "DOI {doi} is registered for \"{title}\", which does not match the dataset name"
+                ),
+                path(),
+            );
+        }
+        if !cited_creators.is_empty()
+            && !record.creators.is_empty()
+            && !cited_creators
+                .iter()
+                .any(|cited| record.creators.iter().any(|c| shares_word(c, cited)))
+        {
+            issues.add_rule_warning(
+                Rule::Doi,
+                format!(
+                    "DOI {doi} is registered to {}, none of whom are authors in citeAs",
+                    record.creators.join(", ")
+                ),
+                path(),
+            );
+        }
+    }
+}
+
+/// Authors of a BibTeX entry's `author` field
+fn bibtex_authors(entry: &str) -> Vec<String> {
+    let Some(start) = entry.find("author") else {
+        return Vec::new();
+    };
+    let value = entry[start + "author".len()..]
+        .trim_start()
+        .trim_start_matches('=')
+        .trim_start();
+    let value = match value.strip_prefix(['{', '"']) {
+        Some(value) => value.split(['}', '"']).next().unwrap_or(value),
+        None => value.split([',', '\n']).next().unwrap_or(value),
+    };
+    value
+        .split(" and ")
+        .map(str::trim)
+        .filter(|author| !author.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Check whether two texts have a word of three or more letters in common,
+/// ignoring case
+fn shares_word(a: &str, b: &str) -> bool {
+    let words = |text: &str| {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.chars().count() >= 3)
+            .map(str::to_lowercase)
+            .collect::<HashSet<_>>()
+    };
+    !words(a).is_disjoint(&words(b))
+}
@@ -0,0 +1,375 @@
+//! On-disk cache of downloaded remote files
+//!
+//! Each HTTP(S) download is kept under the cache directory, keyed by its
+//! URL, together with the entity tag the server sent for it. A cached file
+//! is revalidated with a conditional request and only downloaded again when
+//! the server reports a change (for SFTP and FTP, when the file's size or
+//! modification time changed), so repeated runs over the same remote
+//! dataset transfer each file once. Files are hashed while they are
+//! written, so a download never needs to be read back for its digest.
+
+use crate::croissant::cache::default_cache_dir;
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::remote::auth::{self, authorize};
+use crate::croissant::remote::retry::send;
+use crate::croissant::remote::{
+    Progress, RemoteDigest, fetch_any_sha256, fetch_each_unique, is_remote_url,
+};
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// Subdirectory of the cache directory downloads are kept in
+const DOWNLOADS_DIR_NAME: &str = "downloads";
+
+/// Check whether downloads of a URL can be kept in the cache: HTTP(S)
+/// URLs, and SFTP and FTP URLs with the `sftp` feature
+pub fn is_cacheable(url: &str) -> bool {
+    is_remote_url(url) || (cfg!(feature = "sftp") && crate::croissant::utils::is_ftp_url(url))
+}
+
+/// File name to save a distribution's download under, taken from its name
+/// or else its `@id`
+///
+/// Both come from the metadata, so only a plain file name is accepted:
+/// absolute paths, path separators and `.`/`..` would let the metadata
+/// choose where the file is written.
+pub fn local_file_name<'a>(name: &'a str, id: &'a str) -> Option<&'a str> {
+    [name, id].into_iter().find(|candidate| {
+        !candidate.contains(['/', '\\'])
+            && Path::new(candidate).file_name() == Some(std::ffi::OsStr::new(candidate))
+    })
+}
+
+/// How the download cache treats files it already holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Revalidate cached files with the server, downloading changed ones
+    #[default]
+    Revalidate,
+    /// Only use cached files, never touching the network
+    Offline,
+    /// Download every file again, replacing the cached copy
+    Refresh,
+}
+
+/// A file in the download cache
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedDownload {
+    pub url: String,
+    /// Location of the downloaded content
+    #[serde(skip)]
+    pub path: PathBuf,
+    pub sha256: String,
+    pub content_size: u64,
+    /// Entity tag sent with the download, used to revalidate it
+    pub e_tag: Option<String>,
+}
+
+impl CachedDownload {
+    /// Digest of the downloaded content
+    pub fn digest(&self) -> RemoteDigest {
+        RemoteDigest {
+            url: self.url.clone(),
+            sha256: self.sha256.clone(),
+            content_size: self.content_size,
+        }
+    }
+}
+
+/// Cache of downloaded remote files
+#[derive(Debug, Clone)]
+pub struct DownloadCache {
+    dir: PathBuf,
+    mode: CacheMode,
+}
+
+impl DownloadCache {
+    /// Cache storing downloads directly in `dir`
+    pub fn open(dir: impl Into<PathBuf>, mode: CacheMode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    /// Cache storing downloads in the `downloads` subdirectory of the cache
+    /// directory `dir`
+    pub fn in_cache_dir(dir: &Path, mode: CacheMode) -> Self {
+        Self::open(dir.join(DOWNLOADS_DIR_NAME), mode)
+    }
+
+    /// Cache in the default cache directory, if one can be determined
+    pub fn open_default(mode: CacheMode) -> Option<Self> {
+        default_cache_dir().map(|dir| Self::in_cache_dir(&dir, mode))
+    }
+
+    /// Directory downloads are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    /// Path of the content and entry files of a URL
+    fn entry_paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = hex::encode(Sha256::digest(url.as_bytes()));
+        (
+            self.dir.join(&key),
+            self.dir.join(key).with_extension("json"),
+        )
+    }
+
+    /// The cached download of a URL, if its content is still on disk
+    async fn lookup(&self, url: &str) -> Option<CachedDownload> {
+        let (content_path, entry_path) = self.entry_paths(url);
+        let entry = tokio::fs::read_to_string(&entry_path).await.ok()?;
+        let mut cached: CachedDownload = serde_json::from_str(&entry)
+            .inspect_err(|e| {
+                tracing::warn!(path = %entry_path.display(), error = %e, "ignoring corrupt download cache entry");
+            })
+            .ok()?;
+        let size = tokio::fs::metadata(&content_path).await.ok()?.len();
+        (cached.url == url && size == cached.content_size).then(|| {
+            cached.path = content_path;
+            cached
+        })
+    }
+
+    /// Local copy of a remote file, downloading it if the cache mode
+    /// requires
+    pub async fn fetch(&self, client: &reqwest::Client, url: &str) -> Result<CachedDownload> {
+        self.fetch_counted(client, url, &Progress::new()).await
+    }
+
+    /// Like [`fetch`](Self::fetch), counting the bytes downloaded or found
+    /// in the cache in `progress`
+    #[tracing::instrument(level = "debug", skip(self, client, progress))]
+    async fn fetch_counted(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        progress: &Progress,
+    ) -> Result<CachedDownload> {
+        let cached = match self.mode {
+            CacheMode::Refresh => None,
+            CacheMode::Revalidate | CacheMode::Offline => self.lookup(url).await,
+        };
+        if self.mode == CacheMode::Offline {
+            let cached =
+                cached.ok_or_else(|| Error::remote(url, "not in the download cache (offline)"))?;
+            progress.add_bytes(cached.content_size);
+            return Ok(cached);
+        }
+        #[cfg(feature = "sftp")]
+        if crate::croissant::utils::is_ftp_url(url) {
+            return self.download_ftp(url, cached, progress).await;
+        }
+        self.download(client, url, cached, progress).await
+    }
+
+    /// Download a file into the cache, unless the server confirms that the
+    /// cached copy is still current
+    async fn download(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        cached: Option<CachedDownload>,
+        progress: &Progress,
+    ) -> Result<CachedDownload> {
+        let remote_error = |e: reqwest::Error| Error::remote(url, e.to_string());
+
+        let mut request = authorize(client.get(url), url)?;
+        if let Some(e_tag) = cached.as_ref().and_then(|c| c.e_tag.as_deref()) {
+            request = request.header(IF_NONE_MATCH, e_tag);
+        }
+        let response = send(request, url).await?;
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            tracing::debug!("cached download is current");
+            progress.add_bytes(cached.content_size);
+            return Ok(cached);
+        }
+        let mut response = response.error_for_status().map_err(remote_error)?;
+        let e_tag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        // Write to a partial file first so an interrupted download never
+        // leaves a truncated file behind
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let (content_path, entry_path) = self.entry_paths(url);
+        let partial_path = content_path.with_extension("part");
+        let mut file = tokio::fs::File::create(&partial_path).await?;
+        let mut hasher = Sha256::new();
+        let mut content_size = 0u64;
+        while let Some(chunk) = response.chunk().await.map_err(remote_error)? {
+            content_size += chunk.len() as u64;
+            hasher.update(&chunk);
+            progress.add_bytes(chunk.len() as u64);
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        drop(file);
+        tokio::fs::rename(&partial_path, &content_path).await?;
+
+        let download = CachedDownload {
+            url: url.to_string(),
+            path: content_path,
+            sha256: hex::encode(hasher.finalize()),
+            content_size,
+            e_tag,
+        };
+        tokio::fs::write(&entry_path, serde_json::to_string(&download)?).await?;
+        tracing::debug!(sha256 = %download.sha256, content_size, "downloaded into cache");
+        Ok(download)
+    }
+
+    /// Download a file from an SFTP or FTP server into the cache, unless
+    /// its size and modification time show that the cached copy is still
+    /// current
+    #[cfg(feature = "sftp")]
+    async fn download_ftp(
+        &self,
+        url: &str,
+        cached: Option<CachedDownload>,
+        progress: &Progress,
+    ) -> Result<CachedDownload> {
+        use crate::croissant::remote::ftp;
+
+        let (content_path, entry_path) = self.entry_paths(url);
+        let dir = self.dir.clone();
+        let url = url.to_string();
+        let progress = progress.clone();
+        tokio::task::spawn_blocking(move || {
+            let validator = ftp::stat(&url)?.validator();
+            if let Some(cached) = cached
+                && cached.e_tag.as_deref() == Some(validator.as_str())
+            {
+                tracing::debug!("cached download is current");
+                progress.add_bytes(cached.content_size);
+                return Ok(cached);
+            }
+
+            std::fs::create_dir_all(&dir)?;
+            let partial_path = content_path.with_extension("part");
+            let digest = ftp::download(&url, &partial_path, &progress)?;
+            std::fs::rename(&partial_path, &content_path)?;
+
+            let download = CachedDownload {
+                url,
+                path: content_path,
+                sha256: digest.sha256,
+                content_size: digest.content_size,
+                e_tag: Some(validator),
+            };
+            std::fs::write(&entry_path, serde_json::to_string(&download)?)?;
+            tracing::debug!(sha256 = %download.sha256, "downloaded into cache");
+            Ok(download)
+        })
+        .await
+        .map_err(|e| Error::new(format!("Transfer task failed: {e}")))?
+    }
+
+    /// Local copies of several remote files, downloaded concurrently
+    ///
+    /// Each distinct URL is fetched once, at most `max_concurrent` at a
+    /// time, with bytes and completed files counted in `progress`. Results
+    /// are returned in the same order as `urls`.
+    #[tracing::instrument(level = "debug", skip_all, fields(files = urls.len()))]
+    pub async fn fetch_many(
+        &self,
+        urls: &[String],
+        max_concurrent: usize,
+        progress: &Progress,
+    ) -> Vec<Result<CachedDownload>> {
+        let client = auth::client();
+        fetch_each_unique(urls, max_concurrent, move |url| {
+            let client = client.clone();
+            let cache = self.clone();
+            let progress = progress.clone();
+            async move {
+                let download = cache.fetch_counted(&client, &url, &progress).await;
+                progress.finish_file();
+                download
+            }
+        })
+        .await
+    }
+
+    /// Hash several remote files concurrently, downloading cacheable files
+    /// through the cache
+    ///
+    /// Object storage content is streamed through the hasher without being
+    /// cached. Otherwise behaves like
+    /// [`fetch_sha256_many`](crate::croissant::remote::fetch_sha256_many).
+    #[tracing::instrument(level = "debug", skip_all, fields(files = urls.len()))]
+    pub async fn fetch_sha256_many(
+        &self,
+        urls: &[String],
+        max_concurrent: usize,
+        progress: &Progress,
+    ) -> Vec<Result<RemoteDigest>> {
+        let client = auth::client();
+        fetch_each_unique(urls, max_concurrent, move |url| {
+            let client = client.clone();
+            let cache = self.clone();
+            let progress = progress.clone();
+            async move {
+                let digest = if is_cacheable(&url) {
+                    cache
+                        .fetch_counted(&client, &url, &progress)
+                        .await
+                        .map(|d| d.digest())
+                } else {
+                    fetch_any_sha256(&client, &url, &progress).await
+                };
+                progress.finish_file();
+                digest
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_file_name_keeps_plain_names() {
+        assert_eq!(local_file_name("data.csv", "data"), Some("data.csv"));
+    }
+
+    #[test]
+    fn local_file_name_falls_back_to_the_id() {
+        assert_eq!(
+            local_file_name("../escaped.csv", "data.csv"),
+            Some("data.csv")
+        );
+        assert_eq!(local_file_name("", "data.csv"), Some("data.csv"));
+    }
+
+    #[test]
+    fn local_file_name_rejects_paths() {
+        for name in [
+            "../escaped.csv",
+            "/etc/passwd",
+            "sub/data.csv",
+            "sub\\data.csv",
+            "..",
+            ".",
+            "",
+        ] {
+            assert_eq!(local_file_name(name, name), None, "{name}");
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! SFTP (`sftp://`) and FTP (`ftp://`) content
+//!
+//! Transfers are blocking; async callers run them on tokio's blocking
+//! thread pool. A password may be given in the URL, but since content URLs
+//! end up in published metadata it is better taken from the host's entry
+//! in the auth file or its `CROISSANT_BASIC_AUTH_<HOST>` variable (see
+//! `auth`), which is never sent to other hosts. Without a password, SFTP
+//! tries the SSH agent and then the key in `CROISSANT_SFTP_KEY` or the
+//! default `~/.ssh` keys. It only connects to hosts listed in
+//! `~/.ssh/known_hosts`. FTP without a user logs in anonymously.
+
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::remote::auth::auth_config;
+use crate::croissant::remote::retry::retry_blocking;
+use crate::croissant::remote::{Progress, RemoteDigest, RemoteSample, SampleHasher};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the private key used for SFTP
+pub const SFTP_KEY_ENV: &str = "CROISSANT_SFTP_KEY";
+
+/// Environment variable holding the passphrase of the SFTP private key
+pub const SFTP_PASSPHRASE_ENV: &str = "CROISSANT_SFTP_PASSPHRASE";
+
+/// Size and modification time of a file on an SFTP or FTP server
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileStat {
+    pub url: String,
+    pub size: u64,
+    /// Modification time as reported by the server, if it reports one
+    pub modified: Option<String>,
+}
+
+impl FileStat {
+    /// Validator that changes whenever the file does, for revalidating a
+    /// cached download
+    pub fn validator(&self) -> String {
+        format!("{}-{}", self.size, self.modified.as_deref().unwrap_or(""))
+    }
+}
+
+/// Server, credentials and path of an SFTP or FTP URL
+struct Location {
+    sftp: bool,
+    host: String,
+    port: u16,
+    user: Option<String>,
+    password: Option<String>,
+    path: String,
+}
+
+fn parse(url: &str) -> Result<Location> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| Error::remote(url, e.to_string()))?;
+    let sftp = match parsed.scheme() {
+        "sftp" => true,
+        "ftp" => false,
+        scheme => {
+            return Err(Error::remote(
+                url,
+                format!("unsupported URL scheme {scheme}"),
+            ));
+        }
+    };
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| Error::remote(url, "missing host"))?
+        .to_string();
+    let decode = |value: &str| {
+        percent_decode(value).ok_or_else(|| Error::remote(url, "invalid percent-encoding"))
+    };
+    let user = Some(parsed.username())
+        .filter(|user| !user.is_empty())
+        .map(decode)
+        .transpose()?;
+    // The configured password only belongs to the configured user
+    let auth = auth_config()?.for_host(&host);
+    let configured = auth.username.is_some() && (user.is_none() || user == auth.username);
+    let (user, auth_password) = if configured {
+        (auth.username, auth.password)
+    } else {
+        (user, None)
+    };
+
+    Ok(Location {
+        sftp,
+        port: parsed.port().unwrap_or(if sftp { 22 } else { 21 }),
+        user,
+        password: match parsed.password() {
+            Some(password) => Some(decode(password)?),
+            None => auth_password,
+        },
+        path: decode(parsed.path())?,
+        host,
+    })
+}
+
+/// Decode `%XX` escapes of a URL component
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Authenticated SFTP session with the server of `location`, retried as
+/// the network policy allows
+fn sftp_session(url: &str, location: &Location) -> Result<ssh2::Session> {
+    retry_blocking(url, &location.host, || connect_sftp(url, location))
+}
+
+fn connect_sftp(url: &str, location: &Location) -> Result<ssh2::Session> {
+    let ssh_error = |e: ssh2::Error| Error::remote(url, e.to_string());
+
+    // Left an I/O error, so that only failing to connect is retried
+    let tcp = TcpStream::connect((location.host.as_str(), location.port))?;
+    let mut session = ssh2::Session::new().map_err(ssh_error)?;
+    session.set_tcp_stream(tcp);
+    session.handshake().map_err(ssh_error)?;
+    check_known_host(url, &session, location)?;
+
+    let user = match &location.user {
+        Some(user) => user.clone(),
+        None => std::env::var("USER").map_err(|_| Error::remote(url, "no user name given"))?,
+    };
+    if let Some(password) = &location.password {
+        session
+            .userauth_password(&user, password)
+            .map_err(ssh_error)?;
+        return Ok(session);
+    }
+    if session.userauth_agent(&user).is_ok() {
+        return Ok(session);
+    }
+    let passphrase = std::env::var(SFTP_PASSPHRASE_ENV).ok();
+    for key in private_keys() {
+        if session
+            .userauth_pubkey_file(&user, None, &key, passphrase.as_deref())
+            .is_ok()
+        {
+            tracing::debug!(key = %key.display(), "authenticated with private key");
+            return Ok(session);
+        }
+    }
+    Err(Error::remote(
+        url,
+        format!("could not authenticate as {user} with the SSH agent or a private key"),
+    ))
+}
+
+/// Private keys tried for SFTP authentication, in order
+fn private_keys() -> Vec<PathBuf> {
+    if let Some(key) = std::env::var_os(SFTP_KEY_ENV) {
+        return vec![PathBuf::from(key)];
+    }
+    let Some(home) = std::env::var_os("HOME") else {
+        return Vec::new();
+    };
+    let ssh_dir = Path::new(&home).join(".ssh");
+    ["id_ed25519", "id_ecdsa", "id_rsa"]
+        .iter()
+        .map(|name| ssh_dir.join(name))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Refuse servers whose host key is not in `~/.ssh/known_hosts`
+fn check_known_host(url: &str, session: &ssh2::Session, location: &Location) -> Result<()> {
+    let ssh_error = |e: ssh2::Error| Error::remote(url, e.to_string());
+
+    let mut known_hosts = session.known_hosts().map_err(ssh_error)?;
+    if let Some(home) = std::env::var_os("HOME") {
+        let file = Path::new(&home).join(".ssh").join("known_hosts");
+        if file.is_file() {
+            known_hosts
+                .read_file(&file, ssh2::KnownHostFileKind::OpenSSH)
+                .map_err(ssh_error)?;
+        }
+    }
+    let (key, _) = session
+        .host_key()
+        .ok_or_else(|| Error::remote(url, "server sent no host key"))?;
+    match known_hosts.check_port(&location.host, location.port, key) {
+        ssh2::CheckResult::Match => Ok(()),
+        ssh2::CheckResult::NotFound => Err(Error::remote(
+            url,
+            format!("host key of {} is not in ~/.ssh/known_hosts", location.host),
+        )),
+        ssh2::CheckResult::Mismatch => Err(Error::remote(
+            url,
+            format!(
+                "host key of {} does not match ~/.ssh/known_hosts",
+                location.host
+            ),
+        )),
+        ssh2::CheckResult::Failure => Err(Error::remote(url, "could not check the host key")),
+    }
+}
+
+/// Logged-in FTP connection to the server of `location`, in binary mode,
+/// retried as the network policy allows
+fn ftp_connection(url: &str, location: &Location) -> Result<suppaftp::FtpStream> {
+    retry_blocking(url, &location.host, || connect_ftp(url, location))
+}
+
+fn connect_ftp(url: &str, location: &Location) -> Result<suppaftp::FtpStream> {
+    let ftp_error = |e: suppaftp::FtpError| Error::remote(url, e.to_string());
+
+    // Left an I/O error, so that only failing to connect is retried
+    let mut ftp = suppaftp::FtpStream::connect((location.host.as_str(), location.port)).map_err(
+        |e| match e {
+            suppaftp::FtpError::ConnectionError(e) => Error::Io(e),
+            e => ftp_error(e),
+        },
+    )?;
+    let (user, password) = match &location.user {
+        Some(user) => (user.as_str(), location.password.as_deref().unwrap_or("")),
+        None => ("anonymous", "anonymous@"),
+    };
+    ftp.login(user, password).map_err(ftp_error)?;
+    ftp.transfer_type(suppaftp::types::FileType::Binary)
+        .map_err(ftp_error)?;
+    Ok(ftp)
+}
+
+/// Run `read` over the content of the file at `url`
+fn read_with<T>(url: &str, mut read: impl FnMut(&mut dyn Read) -> std::io::Result<T>) -> Result<T> {
+    let location = parse(url)?;
+    if location.sftp {
+        let session = sftp_session(url, &location)?;
+        let sftp = session
+            .sftp()
+            .map_err(|e| Error::remote(url, e.to_string()))?;
+        let mut file = sftp
+            .open(Path::new(&location.path))
+            .map_err(|e| Error::remote(url, e.to_string()))?;
+        read(&mut file).map_err(|e| Error::remote(url, e.to_string()))
+    } else {
+        let mut ftp = ftp_connection(url, &location)?;
+        let value = ftp
+            .retr(&location.path, |reader| {
+                read(reader).map_err(suppaftp::FtpError::ConnectionError)
+            })
+            .map_err(|e| Error::remote(url, e.to_string()))?;
+        // The file has been read; failing to log out cleanly does not matter
+        let _ = ftp.quit();
+        Ok(value)
+    }
+}
+
+/// Size and modification time of the file at `url`
+#[tracing::instrument(level = "debug")]
+pub fn stat(url: &str) -> Result<FileStat> {
+    let location = parse(url)?;
+    if location.sftp {
+        let session = sftp_session(url, &location)?;
+        let stat = session
+            .sftp()
+            .and_then(|sftp| sftp.stat(Path::new(&location.path)))
+            .map_err(|e| Error::remote(url, e.to_string()))?;
+        Ok(FileStat {
+            url: url.to_string(),
+            size: stat.size.unwrap_or(0),
+            modified: stat.mtime.map(|mtime| mtime.to_string()),
+        })
+    } else {
+        let ftp_error = |e: suppaftp::FtpError| Error::remote(url, e.to_string());
+        let mut ftp = ftp_connection(url, &location)?;
+        let size = ftp.size(&location.path).map_err(ftp_error)?;
+        // Not every server supports MDTM
+        let modified = ftp.mdtm(&location.path).ok().map(|time| time.to_string());
+        let _ = ftp.quit();
+        Ok(FileStat {
+            url: url.to_string(),
+            size: size as u64,
+            modified,
+        })
+    }
+}
+
+/// Read a file and compute its SHA-256 digest while streaming, counting
+/// the bytes received in `progress`
+#[tracing::instrument(level = "debug", skip(progress))]
+pub fn fetch_sha256(url: &str, progress: &Progress) -> Result<RemoteDigest> {
+    read_sample(url, 0, progress).map(|sample| sample.digest)
+}
+
+/// Read a file, keeping its first `lines` lines (at most
+/// [`MAX_SAMPLE_BYTES`](crate::croissant::remote::MAX_SAMPLE_BYTES)) and
+/// computing the SHA-256 digest of all of it
+#[tracing::instrument(level = "debug")]
+pub fn fetch_sample(url: &str, lines: usize) -> Result<RemoteSample> {
+    read_sample(url, lines, &Progress::new())
+}
+
+fn read_sample(url: &str, lines: usize, progress: &Progress) -> Result<RemoteSample> {
+    let sample = read_with(url, |reader| {
+        let mut sampler = SampleHasher::new(lines, progress);
+        copy_chunks(reader, |chunk| {
+            sampler.update(chunk);
+            Ok(())
+        })?;
+        Ok(sampler)
+    })?
+    .finish(url);
+    tracing::debug!(sha256 = %sample.digest.sha256, content_size = sample.digest.content_size, "hashed file");
+    Ok(sample)
+}
+
+/// Download a file to `destination`, computing its SHA-256 digest while
+/// writing and counting the bytes received in `progress`
+#[tracing::instrument(level = "debug", skip(destination, progress))]
+pub fn download(url: &str, destination: &Path, progress: &Progress) -> Result<RemoteDigest> {
+    let mut file = std::fs::File::create(destination)?;
+    let sample = read_with(url, |reader| {
+        let mut sampler = SampleHasher::new(0, progress);
+        copy_chunks(reader, |chunk| {
+            sampler.update(chunk);
+            file.write_all(chunk)
+        })?;
+        Ok(sampler)
+    })?
+    .finish(url);
+    file.flush()?;
+    Ok(sample.digest)
+}
+
+/// Feed everything `reader` yields to `write`, one buffer at a time
+fn copy_chunks(
+    reader: &mut dyn Read,
+    mut write: impl FnMut(&[u8]) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => write(&buffer[..n])?,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
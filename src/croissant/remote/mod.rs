@@ -0,0 +1,307 @@
+//! Fetching and hashing of remote content
+//!
+//! Downloads are streamed straight into the hasher, so remote files are
+//! never buffered in memory or written to disk, and several files can be
+//! fetched concurrently. Object storage URLs are handled by `cloud`, and
+//! SFTP and FTP URLs by `ftp`; `doi` resolves DOIs cited by a dataset.
+//! Requests carry the per-host credentials configured in `auth`, and are
+//! retried and rate limited as configured in `retry`.
+
+use crate::croissant::errors::{Error, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+pub mod auth;
+#[cfg(feature = "object-store")]
+pub mod cloud;
+pub mod doi;
+pub mod download;
+#[cfg(feature = "sftp")]
+pub mod ftp;
+pub mod retry;
+
+/// Default number of remote files fetched at the same time
+pub const DEFAULT_MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// Most bytes kept for type inference when sampling a remote file; files
+/// are still hashed in full
+pub const MAX_SAMPLE_BYTES: usize = 64 << 20;
+
+/// SHA-256 digest and size of a remote file
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteDigest {
+    pub url: String,
+    pub sha256: String,
+    pub content_size: u64,
+}
+
+/// Bytes and files transferred so far, shared between concurrent fetches
+/// for progress reporting
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    bytes: Arc<AtomicU64>,
+    files: Arc<AtomicUsize>,
+    /// Expected totals; zero bytes when the size of some file is unknown
+    total_bytes: Arc<AtomicU64>,
+    total_files: Arc<AtomicUsize>,
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes fetched so far, counting files found current in the download
+    /// cache as fetched
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Files completely fetched (or failed) so far
+    pub fn files(&self) -> usize {
+        self.files.load(Ordering::Relaxed)
+    }
+
+    /// Record how many files, and when known how many bytes, are about to
+    /// be fetched
+    pub fn set_totals(&self, files: usize, bytes: Option<u64>) {
+        self.total_files.store(files, Ordering::Relaxed);
+        self.total_bytes
+            .store(bytes.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn total_files(&self) -> usize {
+        self.total_files.load(Ordering::Relaxed)
+    }
+
+    pub fn total_bytes(&self) -> Option<u64> {
+        Some(self.total_bytes.load(Ordering::Relaxed)).filter(|bytes| *bytes > 0)
+    }
+
+    pub(crate) fn add_bytes(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn finish_file(&self) {
+        self.files.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Leading lines of a remote file, and the digest of all of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteSample {
+    pub digest: RemoteDigest,
+    pub head: Vec<u8>,
+}
+
+/// Hashes a file streamed in chunks while keeping its first lines, up to
+/// [`MAX_SAMPLE_BYTES`]
+#[cfg(any(feature = "object-store", feature = "sftp"))]
+pub(crate) struct SampleHasher {
+    hasher: Sha256,
+    content_size: u64,
+    head: Vec<u8>,
+    head_lines: usize,
+    lines: usize,
+    progress: Progress,
+}
+
+#[cfg(any(feature = "object-store", feature = "sftp"))]
+impl SampleHasher {
+    pub(crate) fn new(lines: usize, progress: &Progress) -> Self {
+        Self {
+            hasher: Sha256::new(),
+            content_size: 0,
+            head: Vec::new(),
+            head_lines: 0,
+            lines,
+            progress: progress.clone(),
+        }
+    }
+
+    pub(crate) fn update(&mut self, chunk: &[u8]) {
+        self.content_size += chunk.len() as u64;
+        self.hasher.update(chunk);
+        self.progress.add_bytes(chunk.len() as u64);
+
+        for &byte in chunk {
+            if self.head_lines >= self.lines || self.head.len() >= MAX_SAMPLE_BYTES {
+                break;
+            }
+            self.head.push(byte);
+            if byte == b'\n' {
+                self.head_lines += 1;
+            }
+        }
+    }
+
+    pub(crate) fn finish(mut self, url: &str) -> RemoteSample {
+        // Drop a line cut short by the byte limit
+        if self.head_lines < self.lines && self.head.len() >= MAX_SAMPLE_BYTES {
+            let end = self
+                .head
+                .iter()
+                .rposition(|&byte| byte == b'\n')
+                .map_or(0, |i| i + 1);
+            self.head.truncate(end);
+        }
+
+        RemoteSample {
+            digest: RemoteDigest {
+                url: url.to_string(),
+                sha256: hex::encode(self.hasher.finalize()),
+                content_size: self.content_size,
+            },
+            head: self.head,
+        }
+    }
+}
+
+/// Check whether a content URL refers to a remote HTTP(S) resource
+pub fn is_remote_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Download a remote file and compute its SHA-256 digest while streaming,
+/// counting the bytes received in `progress`
+#[tracing::instrument(level = "debug", skip(client, progress))]
+pub async fn fetch_sha256(
+    client: &reqwest::Client,
+    url: &str,
+    progress: &Progress,
+) -> Result<RemoteDigest> {
+    let mut response = retry::send(auth::authorize(client.get(url), url)?, url)
+        .await?
+        .error_for_status()
+        .map_err(|e| Error::remote(url, e.to_string()))?;
+
+    let mut hasher = Sha256::new();
+    let mut content_size = 0u64;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| Error::remote(url, e.to_string()))?
+    {
+        content_size += chunk.len() as u64;
+        hasher.update(&chunk);
+        progress.add_bytes(chunk.len() as u64);
+    }
+
+    let sha256 = hex::encode(hasher.finalize());
+    tracing::debug!(%sha256, content_size, "hashed remote file");
+    Ok(RemoteDigest {
+        url: url.to_string(),
+        sha256,
+        content_size,
+    })
+}
+
+/// Download and hash several remote files concurrently
+///
+/// HTTP(S) URLs are downloaded with a shared client, and object storage,
+/// SFTP and FTP URLs are streamed from their server when the matching
+/// feature is enabled. Each distinct URL is downloaded once, even if it
+/// appears several times. At most `max_concurrent` downloads run at once,
+/// and their bytes and completed files are counted in `progress`. Results
+/// are returned in the same order as `urls`.
+#[tracing::instrument(level = "debug", skip_all, fields(files = urls.len()))]
+pub async fn fetch_sha256_many(
+    urls: &[String],
+    max_concurrent: usize,
+    progress: &Progress,
+) -> Vec<Result<RemoteDigest>> {
+    let client = auth::client();
+    fetch_each_unique(urls, max_concurrent, move |url| {
+        let client = client.clone();
+        let progress = progress.clone();
+        async move {
+            let digest = fetch_any_sha256(&client, &url, &progress).await;
+            progress.finish_file();
+            digest
+        }
+    })
+    .await
+}
+
+/// Run `fetch` once for each distinct URL, at most `max_concurrent` at a
+/// time, and return the results in the order of `urls`
+pub(crate) async fn fetch_each_unique<T, F, Fut>(
+    urls: &[String],
+    max_concurrent: usize,
+    fetch: F,
+) -> Vec<Result<T>>
+where
+    T: Clone + Send + 'static,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T>> + Send + 'static,
+{
+    let mut unique: Vec<&str> = Vec::new();
+    let mut slots: HashMap<&str, usize> = HashMap::new();
+    for url in urls {
+        slots.entry(url.as_str()).or_insert_with(|| {
+            unique.push(url.as_str());
+            unique.len() - 1
+        });
+    }
+    tracing::debug!(unique = unique.len(), "deduplicated remote references");
+
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut tasks = JoinSet::new();
+
+    for (index, url) in unique.iter().enumerate() {
+        let semaphore = Arc::clone(&semaphore);
+        let fetched = fetch(url.to_string());
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("semaphore closed");
+            (index, fetched.await)
+        });
+    }
+
+    let mut results: Vec<Result<T>> = unique
+        .iter()
+        .map(|url| Err(Error::remote(*url, "fetch task failed")))
+        .collect();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, result)) => results[index] = result,
+            Err(e) => tracing::error!(error = %e, "fetch task failed"),
+        }
+    }
+
+    // The first reference to a failed URL gets the original error, later
+    // references a copy of its message
+    urls.iter()
+        .map(|url| match &mut results[slots[url.as_str()]] {
+            Ok(result) => Ok(result.clone()),
+            Err(e) => {
+                let copy = Error::new(e.to_string());
+                Err(std::mem::replace(e, copy))
+            }
+        })
+        .collect()
+}
+
+/// Hash a remote file from wherever its URL points
+pub(crate) async fn fetch_any_sha256(
+    client: &reqwest::Client,
+    url: &str,
+    progress: &Progress,
+) -> Result<RemoteDigest> {
+    #[cfg(feature = "object-store")]
+    if cloud::is_cloud_url(url) {
+        return cloud::fetch_sha256(url, progress).await;
+    }
+    #[cfg(feature = "sftp")]
+    if crate::croissant::utils::is_ftp_url(url) {
+        let url = url.to_string();
+        let progress = progress.clone();
+        return tokio::task::spawn_blocking(move || ftp::fetch_sha256(&url, &progress))
+            .await
+            .map_err(|e| Error::new(format!("Transfer task failed: {e}")))?;
+    }
+    fetch_sha256(client, url, progress).await
+}
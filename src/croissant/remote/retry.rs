@@ -0,0 +1,346 @@
+//! Retries, backoff and per-host rate limits for network requests
+//!
+//! Requests that fail to connect, time out, or are answered with 429 or a
+//! 5xx gateway status are retried with exponential backoff and jitter,
+//! honoring `Retry-After`. Requests to each host are spaced out to stay
+//! under its rate limit, so CI runs over large datasets are neither
+//! throttled nor flaky. A transfer that breaks off once its body is
+//! streaming is not retried, since its content has already been hashed in
+//! part.
+//!
+//! The policy is read from `CROISSANT_RETRIES`, `CROISSANT_RETRY_BACKOFF`
+//! (initial delay in seconds) and `CROISSANT_RATE_LIMIT` (requests per
+//! second to each host), unless set with [`configure`].
+
+use crate::croissant::errors::{Error, Result};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Environment variable holding the number of retries
+pub const RETRIES_ENV: &str = "CROISSANT_RETRIES";
+
+/// Environment variable holding the delay before the first retry, in seconds
+pub const BACKOFF_ENV: &str = "CROISSANT_RETRY_BACKOFF";
+
+/// Environment variable holding the requests per second allowed to a host
+pub const RATE_LIMIT_ENV: &str = "CROISSANT_RATE_LIMIT";
+
+/// How failed requests are retried and requests to a host are spaced out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkPolicy {
+    /// Retries after the first attempt; 0 disables retrying
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each further one
+    pub initial_backoff: Duration,
+    /// Longest delay between attempts, also capping `Retry-After`
+    pub max_backoff: Duration,
+    /// Requests per second allowed to each host (`None`: unlimited)
+    pub rate_limit: Option<f64>,
+}
+
+impl Default for NetworkPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            rate_limit: None,
+        }
+    }
+}
+
+impl NetworkPolicy {
+    /// The default policy, adjusted by the environment variables that are
+    /// set
+    pub fn from_env() -> Result<Self> {
+        let mut policy = Self::default();
+        if let Some(retries) = env_number::<u32>(RETRIES_ENV)? {
+            policy.retries = retries;
+        }
+        if let Some(seconds) = env_number::<f64>(BACKOFF_ENV)? {
+            policy.initial_backoff = Duration::try_from_secs_f64(seconds)
+                .map_err(|_| Error::new(format!("Invalid {BACKOFF_ENV}: {seconds}")))?;
+        }
+        if let Some(rate) = env_number::<f64>(RATE_LIMIT_ENV)? {
+            policy.rate_limit = Some(rate);
+        }
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Check that the rate limit, if any, is positive and leaves a
+    /// representable time between requests
+    pub fn validate(&self) -> Result<()> {
+        match self.rate_limit {
+            Some(rate)
+                if !(rate > 0.0 && rate.is_finite())
+                    || Duration::try_from_secs_f64(1.0 / rate).is_err() =>
+            {
+                Err(Error::new(format!(
+                    "The rate limit must be a positive number of requests per second, not {rate}"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Delay before retry number `attempt` (from 0): the exponential
+    /// backoff with up to a quarter of random jitter, at most `max_backoff`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        let jitter = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |now| now.subsec_nanos())
+            % 1000;
+        (delay + delay.mul_f64(f64::from(jitter) / 4000.0)).min(self.max_backoff)
+    }
+
+    /// Minimum time between two requests to the same host
+    fn request_interval(&self) -> Option<Duration> {
+        self.rate_limit
+            .and_then(|rate| Duration::try_from_secs_f64(1.0 / rate).ok())
+    }
+}
+
+static POLICY: OnceLock<NetworkPolicy> = OnceLock::new();
+
+/// Set the policy of this process; only possible before the first request
+pub fn configure(policy: NetworkPolicy) -> Result<()> {
+    policy.validate()?;
+    POLICY
+        .set(policy)
+        .map_err(|_| Error::new("The network policy is already in use"))
+}
+
+/// The policy of this process: the configured one, otherwise the one from
+/// the environment
+pub fn policy() -> NetworkPolicy {
+    *POLICY.get_or_init(|| {
+        NetworkPolicy::from_env().unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "ignoring invalid network policy");
+            NetworkPolicy::default()
+        })
+    })
+}
+
+/// Time at which the next request to each host may be sent
+static NEXT_REQUEST: OnceLock<Mutex<HashMap<String, Instant>>> = OnceLock::new();
+
+/// Reserve the next request slot for `host`, returning how long to wait
+/// for it
+fn reserve_slot(host: &str) -> Duration {
+    let Some(interval) = policy().request_interval() else {
+        return Duration::ZERO;
+    };
+    let mut next = NEXT_REQUEST
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let now = Instant::now();
+    let slot = next.get(host).copied().unwrap_or(now).max(now);
+    next.insert(host.to_string(), slot + interval);
+    slot - now
+}
+
+/// Wait until a request to `host` stays within its rate limit
+pub async fn wait_for_host(host: &str) {
+    let delay = reserve_slot(host);
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Blocking version of [`wait_for_host`]
+pub fn wait_for_host_blocking(host: &str) {
+    let delay = reserve_slot(host);
+    if !delay.is_zero() {
+        std::thread::sleep(delay);
+    }
+}
+
+/// Send a request within the rate limit of the URL's host, retrying
+/// failures that may be transient
+///
+/// The response is returned whatever its status once retries are
+/// exhausted, so callers can handle statuses such as 304 and 404
+/// themselves. Requests with a streaming body cannot be repeated and are
+/// sent once.
+pub async fn send(request: RequestBuilder, url: &str) -> Result<Response> {
+    let policy = policy();
+    let host = url_host(url);
+    let mut attempt = 0;
+    loop {
+        let Some(current) = request.try_clone() else {
+            wait_for_host(&host).await;
+            return request
+                .send()
+                .await
+                .map_err(|e| Error::remote(url, e.to_string()));
+        };
+        wait_for_host(&host).await;
+        let delay = match current.send().await {
+            Ok(response) if attempt < policy.retries && is_retryable(response.status()) => {
+                let delay = retry_after(&response)
+                    .map_or_else(|| policy.backoff(attempt), |d| d.min(policy.max_backoff));
+                tracing::warn!(url, status = %response.status(), ?delay, "retrying request");
+                delay
+            }
+            Ok(response) => return Ok(response),
+            Err(e) if attempt < policy.retries && (e.is_connect() || e.is_timeout()) => {
+                let delay = policy.backoff(attempt);
+                tracing::warn!(url, error = %e, ?delay, "retrying request");
+                delay
+            }
+            Err(e) => return Err(Error::remote(url, e.to_string())),
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Run a blocking operation within the rate limit of `host`, retrying it
+/// with backoff when it fails to connect or times out
+///
+/// Meant for establishing connections, which can be repeated safely. The
+/// operation reports failures worth retrying as [`Error::Io`], which is
+/// returned as a remote error of `url` once retries are exhausted; any
+/// other error, such as a failed login or an unknown host key, is returned
+/// at once, so wrong credentials are not sent again.
+pub fn retry_blocking<T>(
+    url: &str,
+    host: &str,
+    mut operation: impl FnMut() -> Result<T>,
+) -> Result<T> {
+    let policy = policy();
+    let mut attempt = 0;
+    loop {
+        wait_for_host_blocking(host);
+        match operation() {
+            Err(Error::Io(e)) if attempt < policy.retries && is_transient(&e) => {
+                let delay = policy.backoff(attempt);
+                tracing::warn!(url, error = %e, ?delay, "retrying");
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(Error::Io(e)) => return Err(Error::remote(url, e.to_string())),
+            result => return result,
+        }
+    }
+}
+
+/// I/O errors worth retrying: refused, dropped and timed out connections
+fn is_transient(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+
+    matches!(
+        error.kind(),
+        ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::TimedOut
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NetworkUnreachable
+    )
+}
+
+/// Statuses worth retrying: rate limiting and overloaded or unreachable
+/// upstream servers
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Delay asked for by a `Retry-After` header given in seconds
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    seconds.trim().parse().ok().map(Duration::from_secs)
+}
+
+/// Host (and port) a URL's requests are rate limited under
+pub(crate) fn url_host(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            let host = url.host_str()?.to_string();
+            Some(match url.port() {
+                Some(port) => format!("{host}:{port}"),
+                None => host,
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Parse an environment variable as a number, if it is set
+fn env_number<T: std::str::FromStr>(name: &str) -> Result<Option<T>> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::new(format!("Invalid {name}: {value}"))),
+        Err(_) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_rate_limit(rate: f64) -> NetworkPolicy {
+        NetworkPolicy {
+            rate_limit: Some(rate),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn positive_rate_limits_are_valid() {
+        let policy = with_rate_limit(4.0);
+        assert!(policy.validate().is_ok());
+        assert_eq!(policy.request_interval(), Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn rate_limits_without_a_representable_interval_are_rejected() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY, 1e-30] {
+            assert!(with_rate_limit(rate).validate().is_err(), "{rate}");
+        }
+        assert_eq!(with_rate_limit(1e-30).request_interval(), None);
+    }
+
+    #[test]
+    fn only_connection_failures_are_retried() {
+        let mut attempts = 0;
+        let result: Result<()> = retry_blocking("sftp://localhost/x", "retry-test", || {
+            attempts += 1;
+            Err(Error::remote("sftp://localhost/x", "authentication failed"))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+
+        assert!(is_transient(&std::io::ErrorKind::ConnectionRefused.into()));
+        assert!(is_transient(&std::io::ErrorKind::TimedOut.into()));
+        assert!(!is_transient(&std::io::ErrorKind::PermissionDenied.into()));
+    }
+
+    #[test]
+    fn io_errors_are_reported_as_remote_errors() {
+        let result: Result<()> = retry_blocking("sftp://localhost/x", "retry-test", || {
+            Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied).into())
+        });
+        assert!(matches!(result, Err(Error::Remote { .. })));
+    }
+}
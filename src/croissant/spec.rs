@@ -0,0 +1,478 @@
+//! Structural validation against the Croissant 1.0 specification
+//!
+//! The properties the specification defines for each kind of node, with
+//! whether they are mandatory or recommended, how many values they take and
+//! which values they allow, come from a schema embedded in the library,
+//! `spec/croissant-1.0.json`. Documents are checked as JSON rather than as
+//! parsed [`Metadata`](crate::croissant::Metadata), so that properties the
+//! specification does not define can be reported; properties written as
+//! compact or full IRIs, such as those of extensions, are left alone, and
+//! terms the document's context maps into a namespace other than those of
+//! schema.org, Croissant and Dublin Core are only noted as extensions. Each
+//! issue refers to the section of the specification defining the property.
+
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::is_yaml_path;
+use crate::croissant::validate::{IssuePath, Rule, ValidationIssue, ValidationIssues};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::OnceLock;
+
+const SCHEMA: &str = include_str!("spec/croissant-1.0.json");
+
+/// Namespaces of the properties the specification defines
+const SPEC_NAMESPACES: &[&str] = &[
+    "https://schema.org/",
+    "http://schema.org/",
+    "http://mlcommons.org/croissant/",
+    "http://purl.org/dc/terms/",
+];
+
+/// Embedded description of the specification
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpecSchema {
+    /// URL of the specification, which section anchors are appended to
+    spec: String,
+    nodes: HashMap<String, NodeSchema>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeSchema {
+    /// `@type`s telling this kind of node apart from the others a property
+    /// may hold
+    #[serde(default)]
+    types: Vec<String>,
+    section: String,
+    /// Segment of the issue path the node adds, if any
+    path: Option<String>,
+    properties: BTreeMap<String, PropertySchema>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PropertySchema {
+    iri: Option<String>,
+    level: Level,
+    /// Whether the property takes a list of values
+    many: bool,
+    kind: Kind,
+    /// Values the property allows, when limited
+    values: Vec<String>,
+    /// Section defining the property, when not that of its node
+    section: Option<String>,
+    /// Kinds of node the values are, by name
+    node: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Level {
+    Mandatory,
+    Recommended,
+    #[default]
+    Optional,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    #[default]
+    Any,
+    Text,
+    Boolean,
+    /// ISO 8601 date, or date and time
+    Date,
+}
+
+fn schema() -> &'static SpecSchema {
+    static SPEC: OnceLock<SpecSchema> = OnceLock::new();
+    SPEC.get_or_init(|| serde_json::from_str(SCHEMA).expect("embedded specification schema"))
+}
+
+/// Check a Croissant document, as JSON, against the specification
+///
+/// Reports missing mandatory and recommended properties, properties the
+/// specification does not define, several values where it allows one, and
+/// values of the wrong kind or outside those it allows.
+pub fn validate_spec(document: &Value) -> ValidationIssues {
+    let schema = schema();
+    let mut issues = ValidationIssues::new();
+    Checker {
+        schema,
+        extension_terms: extension_terms(document),
+        issues: &mut issues,
+    }
+    .check_node(&["Dataset".to_string()], document, IssuePath::new(), "");
+    issues
+}
+
+/// Check a Croissant file, JSON-LD or YAML, against the specification
+pub fn validate_spec_file(file_path: &Path) -> Result<ValidationIssues> {
+    let content =
+        std::fs::read_to_string(file_path).map_err(|_| Error::file_not_found(file_path))?;
+    let document: Value = if is_yaml_path(file_path) {
+        serde_yaml::from_str(&content)?
+    } else {
+        serde_json::from_str(&content)?
+    };
+    Ok(validate_spec(&document))
+}
+
+struct Checker<'a> {
+    schema: &'static SpecSchema,
+    /// IRIs of the terms the document's context defines in other
+    /// namespaces than the specification's
+    extension_terms: HashMap<String, String>,
+    issues: &'a mut ValidationIssues,
+}
+
+impl Checker<'_> {
+    /// Check a node that is one of `kinds`, found at `location` (a dotted
+    /// property path below the last issue path segment, if any)
+    fn check_node(&mut self, kinds: &[String], value: &Value, path: IssuePath, location: &str) {
+        let Some(object) = value.as_object() else {
+            return;
+        };
+        let node_type = object.get("@type").and_then(Value::as_str);
+        let Some(node) = kinds
+            .iter()
+            .filter_map(|kind| self.schema.nodes.get(kind))
+            .find(|node| node_type.is_some_and(|t| node.types.iter().any(|ty| ty == t)))
+            .or_else(|| kinds.first().and_then(|kind| self.schema.nodes.get(kind)))
+        else {
+            return;
+        };
+
+        let name = object.get("name").and_then(Value::as_str).unwrap_or("");
+        let (path, location) = match node.path.as_deref() {
+            Some("metadata") => (path.metadata(name), String::new()),
+            Some("file-object") => (path.file_object(name), String::new()),
+            Some("record-set") => (path.record_set(name), String::new()),
+            Some("field") => (path.field(name), String::new()),
+            _ => (path, location.to_string()),
+        };
+        let prefix = if location.is_empty() {
+            String::new()
+        } else {
+            format!("In {location}: ")
+        };
+
+        for (key, property) in &node.properties {
+            let iri = property.iri.as_deref().unwrap_or(key);
+            let reference = self.reference(property.section.as_deref().unwrap_or(&node.section));
+            let Some(value) = object.get(key).filter(|value| !is_empty(value)) else {
+                match property.level {
+                    Level::Mandatory => self.issues.push(
+                        ValidationIssue::error(format!(
+                            "{prefix}Property \"{iri}\" is mandatory, but does not exist."
+                        ))
+                        .with_path(path.clone())
+                        .with_rule(Rule::MandatoryProperty)
+                        .with_reference(reference),
+                    ),
+                    Level::Recommended => self.issues.push(
+                        ValidationIssue::warning(format!(
+                            "{prefix}Property \"{iri}\" is recommended, but does not exist."
+                        ))
+                        .with_path(path.clone())
+                        .with_rule(Rule::RecommendedProperty)
+                        .with_reference(reference),
+                    ),
+                    Level::Optional => {}
+                }
+                continue;
+            };
+
+            let values = match value {
+                Value::Array(values) => {
+                    if !property.many && values.len() > 1 {
+                        self.issues.push(
+                            ValidationIssue::error(format!(
+                                "{prefix}Property \"{iri}\" takes a single value, but has {}.",
+                                values.len()
+                            ))
+                            .with_path(path.clone())
+                            .with_rule(Rule::Cardinality)
+                            .with_reference(reference.clone()),
+                        );
+                    }
+                    values.iter().collect()
+                }
+                value => vec![value],
+            };
+            for value in values {
+                if let Some(problem) = self.value_problem(property, value) {
+                    self.issues.push(
+                        ValidationIssue::error(format!("{prefix}Property \"{iri}\" {problem}."))
+                            .with_path(path.clone())
+                            .with_rule(Rule::PropertyValue)
+                            .with_reference(reference.clone()),
+                    );
+                } else if !property.node.is_empty() {
+                    let location = if location.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{location}.{key}")
+                    };
+                    self.check_node(&property.node, value, path.clone(), &location);
+                }
+            }
+        }
+
+        for key in object.keys() {
+            if node.properties.contains_key(key) || key.contains(':') {
+                continue;
+            }
+            if let Some(iri) = self.extension_terms.get(key) {
+                self.issues.push(
+                    ValidationIssue::info(format!(
+                        "{prefix}Property \"{iri}\" is an extension, not defined by the Croissant specification."
+                    ))
+                    .with_path(path.clone())
+                    .with_rule(Rule::ExtensionProperty),
+                );
+                continue;
+            }
+            self.issues.push(
+                ValidationIssue::warning(format!(
+                    "{prefix}Property \"{key}\" is not defined by the Croissant specification."
+                ))
+                .with_path(path.clone())
+                .with_rule(Rule::UnknownProperty)
+                .with_reference(self.reference(&node.section)),
+            );
+        }
+    }
+
+    /// Why a value does not fit the property, if it does not
+    fn value_problem(&self, property: &PropertySchema, value: &Value) -> Option<String> {
+        if !property.node.is_empty() {
+            return (!value.is_object()).then(|| "should hold an object".to_string());
+        }
+        let fits = match property.kind {
+            Kind::Any => true,
+            Kind::Text => value.is_string(),
+            Kind::Boolean => value.is_boolean(),
+            Kind::Date => value.as_str().is_some_and(is_date),
+        };
+        if !fits {
+            let kind = match property.kind {
+                Kind::Any | Kind::Text => "text",
+                Kind::Boolean => "a boolean",
+                Kind::Date => "an ISO 8601 date",
+            };
+            return Some(format!("should be {kind}, but is {value}"));
+        }
+        if let Some(text) = value.as_str()
+            && !property.values.is_empty()
+            && !property.values.iter().any(|allowed| allowed == text)
+        {
+            return Some(format!(
+                "should be one of {}, but is \"{text}\"",
+                property.values.join(", ")
+            ));
+        }
+        None
+    }
+
+    fn reference(&self, section: &str) -> String {
+        format!("{}#{section}", self.schema.spec)
+    }
+}
+
+/// Terms of a document's top-level `@context` that map to IRIs outside the
+/// specification's namespaces, with those IRIs
+fn extension_terms(document: &Value) -> HashMap<String, String> {
+    let Some(context) = document.get("@context").and_then(Value::as_object) else {
+        return HashMap::new();
+    };
+    let term_iri = |definition: &Value| {
+        definition
+            .get("@id")
+            .unwrap_or(definition)
+            .as_str()
+            .map(str::to_string)
+    };
+    context
+        .iter()
+        .filter(|(term, _)| !term.starts_with('@'))
+        .filter_map(|(term, definition)| {
+            let iri = term_iri(definition)?;
+            let iri = match iri.split_once(':') {
+                Some((prefix, suffix)) if !suffix.starts_with("//") => {
+                    format!("{}{suffix}", context.get(prefix).and_then(term_iri)?)
+                }
+                _ => iri,
+            };
+            // Prefixes, such as `sc` or `rc`, are namespaces, not properties
+            let is_prefix = iri.ends_with(['/', '#']);
+            (!is_prefix && !SPEC_NAMESPACES.iter().any(|ns| iri.starts_with(ns)))
+                .then(|| (term.clone(), iri))
+        })
+        .collect()
+}
+
+/// Whether a value counts as missing: null, an empty string or an empty
+/// list
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(text) => text.is_empty(),
+        Value::Array(values) => values.is_empty(),
+        _ => false,
+    }
+}
+
+/// Whether text is an ISO 8601 date, or date and time
+pub(crate) fn is_date(text: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok()
+        || chrono::DateTime::parse_from_rfc3339(text).is_ok()
+        || chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S").is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A document with every mandatory and recommended property
+    fn dataset() -> Value {
+        json!({
+            "@context": {
+                "@vocab": "https://schema.org/",
+                "cr": "http://mlcommons.org/croissant/",
+                "rc": "https://github.com/beyondcivic/rustcroissant/ns#",
+                "blake3": "rc:blake3"
+            },
+            "@type": "sc:Dataset",
+            "name": "example",
+            "description": "An example dataset",
+            "conformsTo": "http://mlcommons.org/croissant/1.0",
+            "license": "https://creativecommons.org/licenses/by/4.0/",
+            "url": "https://example.com/dataset",
+            "creator": { "@type": "sc:Person", "name": "Ada" },
+            "datePublished": "2024-01-31",
+            "version": "1.0.0",
+            "distribution": [{
+                "@type": "cr:FileObject",
+                "@id": "data.csv",
+                "name": "data.csv",
+                "contentUrl": "data.csv",
+                "encodingFormat": "text/csv"
+            }],
+            "recordSet": [{
+                "@type": "cr:RecordSet",
+                "name": "data",
+                "field": [{
+                    "@type": "cr:Field",
+                    "name": "id",
+                    "dataType": "sc:Integer",
+                    "source": {
+                        "fileObject": { "@id": "data.csv" },
+                        "extract": { "column": "id" }
+                    }
+                }]
+            }]
+        })
+    }
+
+    /// Rules of the issues found in a document
+    fn rules(document: &Value) -> Vec<Rule> {
+        validate_spec(document)
+            .issues()
+            .iter()
+            .filter_map(|issue| issue.rule)
+            .collect()
+    }
+
+    #[test]
+    fn complete_dataset_has_no_issues() {
+        assert_eq!(rules(&dataset()), []);
+    }
+
+    #[test]
+    fn missing_mandatory_property() {
+        let mut document = dataset();
+        document["distribution"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("contentUrl");
+        let issues = validate_spec(&document);
+        assert_eq!(rules(&document), [Rule::MandatoryProperty]);
+        let issue = &issues.issues()[0];
+        assert!(issue.message.contains("https://schema.org/contentUrl"));
+        assert_eq!(
+            issue.context().as_deref(),
+            Some("Metadata(example) > FileObject(data.csv)")
+        );
+        assert!(issue.reference.as_deref().unwrap().ends_with("#fileobject"));
+    }
+
+    #[test]
+    fn missing_recommended_property() {
+        let mut document = dataset();
+        document.as_object_mut().unwrap().remove("version");
+        assert_eq!(rules(&document), [Rule::RecommendedProperty]);
+        assert!(!validate_spec(&document).has_errors());
+    }
+
+    #[test]
+    fn several_values_of_a_single_valued_property() {
+        let mut document = dataset();
+        document["recordSet"][0]["field"][0]["source"] = json!([
+            { "fileObject": { "@id": "data.csv" } },
+            { "fileObject": { "@id": "data.csv" } }
+        ]);
+        assert_eq!(rules(&document), [Rule::Cardinality]);
+    }
+
+    #[test]
+    fn values_of_the_wrong_kind_or_not_allowed() {
+        let mut document = dataset();
+        document["datePublished"] = json!("31/01/2024");
+        assert_eq!(rules(&document), [Rule::PropertyValue]);
+
+        let mut document = dataset();
+        document["recordSet"][0]["field"][0]["source"]["extract"] =
+            json!({ "fileProperty": "size" });
+        let issues = validate_spec(&document);
+        assert_eq!(rules(&document), [Rule::PropertyValue]);
+        assert!(issues.issues()[0].message.contains("source.extract"));
+
+        let mut document = dataset();
+        document["creator"] = json!("Ada");
+        assert_eq!(rules(&document), [Rule::PropertyValue]);
+    }
+
+    #[test]
+    fn property_the_specification_does_not_define() {
+        let mut document = dataset();
+        document["recordSet"][0]["field"][0]["colour"] = json!("red");
+        let issues = validate_spec(&document);
+        assert_eq!(rules(&document), [Rule::UnknownProperty]);
+        assert_eq!(
+            issues.issues()[0].context().as_deref(),
+            Some("Metadata(example) > RecordSet(data) > Field(id)")
+        );
+    }
+
+    #[test]
+    fn extension_property_is_noted() {
+        let mut document = dataset();
+        document["distribution"][0]["blake3"] = json!("00");
+        // Compact IRIs are left alone
+        document["distribution"][0]["rc:delimiter"] = json!(";");
+        let issues = validate_spec(&document);
+        assert_eq!(rules(&document), [Rule::ExtensionProperty]);
+        assert_eq!(issues.info_count(), 1);
+        assert!(
+            issues.issues()[0]
+                .message
+                .contains("https://github.com/beyondcivic/rustcroissant/ns#blake3")
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Per-field statistics of materialized records
+//!
+//! One pass over the records of a record set gives, for each field, its null
+//! rate, its number of distinct values, the minimum, maximum and mean of
+//! numbers, the range of dates, and the distribution of text lengths.
+//! Distinct values are counted exactly up to [`EXACT_DISTINCT_LIMIT`] and
+//! estimated with HyperLogLog beyond, so memory stays bounded on datasets of
+//! any size.
+
+use crate::croissant::core::{DataType, ValueStatistics};
+use crate::croissant::errors::Result;
+use crate::croissant::records::{RecordField, RecordReader, Value, compare_values};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Distinct values counted exactly before switching to an estimate
+pub const EXACT_DISTINCT_LIMIT: usize = 100_000;
+
+/// HyperLogLog registers are indexed by this many bits of a hash, for a
+/// standard error of about 0.8%
+const HLL_PRECISION: u32 = 14;
+
+/// Statistics of the records of a record set
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecordStatistics {
+    pub records: u64,
+    pub fields: Vec<FieldStatistics>,
+}
+
+/// Statistics of one field's values
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldStatistics {
+    pub name: String,
+    pub data_type: String,
+    pub count: u64,
+    pub null_count: u64,
+    /// Share of null values, from 0 to 1
+    pub null_rate: f64,
+    pub distinct_count: u64,
+    /// Whether `distinct_count` is a HyperLogLog estimate
+    pub distinct_estimated: bool,
+    /// Smallest number, date, datetime or time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<serde_json::Value>,
+    /// Largest number, date, datetime or time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<serde_json::Value>,
+    /// Mean of numbers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean: Option<f64>,
+    /// Lengths of text values, in characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<LengthStatistics>,
+}
+
+impl FieldStatistics {
+    /// Whether every value is null
+    pub fn is_empty(&self) -> bool {
+        self.null_count == self.count
+    }
+
+    /// Whether all non-null values are the same
+    pub fn is_constant(&self) -> bool {
+        self.distinct_count == 1 && !self.distinct_estimated
+    }
+}
+
+/// The statistics recorded in metadata
+impl From<&FieldStatistics> for ValueStatistics {
+    fn from(statistics: &FieldStatistics) -> Self {
+        Self {
+            count: statistics.count,
+            null_count: statistics.null_count,
+            distinct_count: statistics.distinct_count,
+            distinct_estimated: statistics.distinct_estimated,
+            min: statistics.min.clone(),
+            max: statistics.max.clone(),
+            mean: statistics.mean,
+        }
+    }
+}
+
+/// Distribution of the lengths of text values
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LengthStatistics {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    /// Number of values per range of lengths: 0, 1, 2–3, 4–7, 8–15, and so
+    /// on, omitting empty ranges
+    pub histogram: Vec<LengthBucket>,
+}
+
+/// Number of text values whose length is within a range
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LengthBucket {
+    pub min: usize,
+    pub max: usize,
+    pub count: u64,
+}
+
+/// Compute the statistics of every record of `reader`
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn record_statistics(reader: RecordReader) -> Result<RecordStatistics> {
+    let mut accumulators = reader
+        .fields()
+        .iter()
+        .map(FieldAccumulator::new)
+        .collect::<Vec<_>>();
+    let mut records = 0;
+    for record in reader {
+        for (accumulator, value) in accumulators.iter_mut().zip(&record?) {
+            accumulator.add(value);
+        }
+        records += 1;
+    }
+    Ok(RecordStatistics {
+        records,
+        fields: accumulators
+            .into_iter()
+            .map(FieldAccumulator::finish)
+            .collect(),
+    })
+}
+
+/// Running statistics of a field, updated value by value
+#[derive(Debug, Clone)]
+pub struct FieldAccumulator {
+    name: String,
+    data_type: DataType,
+    count: u64,
+    null_count: u64,
+    distinct: DistinctCounter,
+    min: Option<Value>,
+    max: Option<Value>,
+    sum: f64,
+    numbers: u64,
+    texts: u64,
+    text_length_sum: u64,
+    min_length: usize,
+    max_length: usize,
+    /// Number of text values by bucket: length 0, then lengths below each
+    /// power of two
+    length_buckets: Vec<u64>,
+}
+
+impl FieldAccumulator {
+    pub fn new(field: &RecordField) -> Self {
+        Self {
+            name: field.name.clone(),
+            data_type: field.data_type.clone(),
+            count: 0,
+            null_count: 0,
+            distinct: DistinctCounter::default(),
+            min: None,
+            max: None,
+            sum: 0.0,
+            numbers: 0,
+            texts: 0,
+            text_length_sum: 0,
+            min_length: usize::MAX,
+            max_length: 0,
+            length_buckets: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, value: &Value) {
+        self.count += 1;
+        if value.is_null() {
+            self.null_count += 1;
+            return;
+        }
+        self.distinct.add(value);
+
+        match value {
+            Value::Integer(n) => self.add_number(*n as f64),
+            Value::Float(n) if n.is_finite() => self.add_number(*n),
+            Value::Text(text) => self.add_text(text),
+            _ => {}
+        }
+        if matches!(
+            value,
+            Value::Integer(_)
+                | Value::Float(_)
+                | Value::Date(_)
+                | Value::DateTime(_)
+                | Value::Time(_)
+        ) {
+            if self
+                .min
+                .as_ref()
+                .is_none_or(|min| compare_values(value, min).is_some_and(Ordering::is_lt))
+            {
+                self.min = Some(value.clone());
+            }
+            if self
+                .max
+                .as_ref()
+                .is_none_or(|max| compare_values(value, max).is_some_and(Ordering::is_gt))
+            {
+                self.max = Some(value.clone());
+            }
+        }
+    }
+
+    fn add_number(&mut self, n: f64) {
+        self.sum += n;
+        self.numbers += 1;
+    }
+
+    fn add_text(&mut self, text: &str) {
+        let length = text.chars().count();
+        self.texts += 1;
+        self.text_length_sum += length as u64;
+        self.min_length = self.min_length.min(length);
+        self.max_length = self.max_length.max(length);
+        let bucket = (usize::BITS - length.leading_zeros()) as usize;
+        if self.length_buckets.len() <= bucket {
+            self.length_buckets.resize(bucket + 1, 0);
+        }
+        self.length_buckets[bucket] += 1;
+    }
+
+    pub fn finish(self) -> FieldStatistics {
+        let length = (self.texts > 0).then(|| LengthStatistics {
+            min: self.min_length,
+            max: self.max_length,
+            mean: self.text_length_sum as f64 / self.texts as f64,
+            histogram: self
+                .length_buckets
+                .iter()
+                .enumerate()
+                .filter(|(_, count)| **count > 0)
+                .map(|(bucket, count)| LengthBucket {
+                    min: if bucket == 0 { 0 } else { 1 << (bucket - 1) },
+                    max: if bucket == 0 { 0 } else { (1 << bucket) - 1 },
+                    count: *count,
+                })
+                .collect(),
+        });
+        FieldStatistics {
+            name: self.name,
+            data_type: self.data_type.to_schema_org().to_string(),
+            count: self.count,
+            null_count: self.null_count,
+            null_rate: if self.count == 0 {
+                0.0
+            } else {
+                self.null_count as f64 / self.count as f64
+            },
+            distinct_count: self.distinct.count(),
+            distinct_estimated: self.distinct.is_estimate(),
+            min: self.min.map(|value| value.to_json()),
+            max: self.max.map(|value| value.to_json()),
+            mean: (self.numbers > 0).then(|| self.sum / self.numbers as f64),
+            length,
+        }
+    }
+}
+
+/// Counts distinct values exactly, by hash, up to [`EXACT_DISTINCT_LIMIT`],
+/// then estimates them
+#[derive(Debug, Clone)]
+enum DistinctCounter {
+    Exact(HashSet<u64>),
+    Estimated(HyperLogLog),
+}
+
+impl Default for DistinctCounter {
+    fn default() -> Self {
+        Self::Exact(HashSet::new())
+    }
+}
+
+impl DistinctCounter {
+    fn add(&mut self, value: &Value) {
+        let mut hasher = DefaultHasher::new();
+        value.to_json_string().hash(&mut hasher);
+        let hash = hasher.finish();
+        match self {
+            Self::Exact(hashes) => {
+                hashes.insert(hash);
+                if hashes.len() > EXACT_DISTINCT_LIMIT {
+                    let mut estimate = HyperLogLog::new();
+                    hashes.iter().for_each(|&hash| estimate.add(hash));
+                    *self = Self::Estimated(estimate);
+                }
+            }
+            Self::Estimated(estimate) => estimate.add(hash),
+        }
+    }
+
+    fn count(&self) -> u64 {
+        match self {
+            Self::Exact(hashes) => hashes.len() as u64,
+            Self::Estimated(estimate) => estimate.estimate().round() as u64,
+        }
+    }
+
+    fn is_estimate(&self) -> bool {
+        matches!(self, Self::Estimated(_))
+    }
+}
+
+/// HyperLogLog cardinality estimator over 64-bit hashes
+#[derive(Debug, Clone)]
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    fn new() -> Self {
+        Self {
+            registers: vec![0; 1 << HLL_PRECISION],
+        }
+    }
+
+    fn add(&mut self, hash: u64) {
+        let index = (hash >> (64 - HLL_PRECISION)) as usize;
+        // Rank of the first set bit of the remaining bits, bounded by a
+        // sentinel bit
+        let rest = (hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&rank| 2f64.powi(-i32::from(rank)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&rank| rank == 0).count();
+        // Linear counting is more accurate for small cardinalities
+        if estimate <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Fast top-level summaries of Croissant metadata files
+//!
+//! Only the top-level scalar properties are deserialized; distributions and
+//! record sets are skipped over as raw JSON and merely counted, which keeps
+//! triage over thousands of files cheap.
+
+use crate::croissant::errors::{Error, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::value::RawValue;
+use std::path::Path;
+
+/// Top-level properties of a Croissant metadata document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataSummary {
+    pub name: Option<String>,
+    pub type_: Option<String>,
+    pub conforms_to: Option<String>,
+    pub version: Option<String>,
+    pub date_published: Option<String>,
+    pub distribution_count: usize,
+    pub record_set_count: usize,
+}
+
+/// Scalar properties are kept as JSON values so that e.g. a numeric
+/// `version` does not fail the whole summary
+#[derive(Deserialize)]
+struct RawMetadata<'a> {
+    name: Option<Value>,
+    #[serde(rename = "@type")]
+    type_: Option<Value>,
+    #[serde(rename = "conformsTo")]
+    conforms_to: Option<Value>,
+    version: Option<Value>,
+    #[serde(rename = "datePublished")]
+    date_published: Option<Value>,
+    #[serde(borrow, default)]
+    distribution: Vec<&'a RawValue>,
+    #[serde(rename = "recordSet", borrow, default)]
+    record_set: Vec<&'a RawValue>,
+}
+
+/// Summarize a Croissant metadata document without deserializing its
+/// distributions and record sets
+pub fn summarize_metadata(content: &str) -> Result<MetadataSummary> {
+    let raw: RawMetadata = serde_json::from_str(content)?;
+
+    Ok(MetadataSummary {
+        name: raw.name.map(value_to_string),
+        type_: raw.type_.map(value_to_string),
+        conforms_to: raw.conforms_to.map(value_to_string),
+        version: raw.version.map(value_to_string),
+        date_published: raw.date_published.map(value_to_string),
+        distribution_count: raw.distribution.len(),
+        record_set_count: raw.record_set.len(),
+    })
+}
+
+fn value_to_string(value: Value) -> String {
+    match value {
+        Value::String(text) => text,
+        other => other.to_string(),
+    }
+}
+
+/// Summarize a Croissant metadata file
+#[tracing::instrument(level = "debug", skip_all, fields(path = %file_path.display()))]
+pub fn summarize_file(file_path: &Path) -> Result<MetadataSummary> {
+    let content =
+        std::fs::read_to_string(file_path).map_err(|_| Error::file_not_found(file_path))?;
+    summarize_metadata(&content)
+}
@@ -0,0 +1,126 @@
+//! Units of measurement named in CSV column headers
+//!
+//! Headers often carry the unit of their values, either in brackets at the
+//! end (`weight (kg)`, `flow [m^3/s]`) or as an abbreviated suffix
+//! (`temperature_c`, `distance_km`). Bracketed units are taken as written;
+//! suffixes only when they are among [`UNIT_SUFFIXES`], so that names such
+//! as `user_id` are left alone.
+
+/// Header suffixes recognized as units, lowercase, with the unit text
+/// recorded for them
+pub const UNIT_SUFFIXES: &[(&str, &str)] = &[
+    // Temperature
+    ("c", "°C"),
+    ("f", "°F"),
+    ("k", "K"),
+    ("celsius", "°C"),
+    ("fahrenheit", "°F"),
+    ("kelvin", "K"),
+    // Mass
+    ("mg", "mg"),
+    ("g", "g"),
+    ("kg", "kg"),
+    ("lb", "lb"),
+    ("lbs", "lb"),
+    ("oz", "oz"),
+    // Length and area
+    ("mm", "mm"),
+    ("cm", "cm"),
+    ("m", "m"),
+    ("km", "km"),
+    ("ft", "ft"),
+    ("mi", "mi"),
+    ("m2", "m²"),
+    ("km2", "km²"),
+    // Volume
+    ("ml", "mL"),
+    ("l", "L"),
+    ("m3", "m³"),
+    // Time
+    ("ms", "ms"),
+    ("s", "s"),
+    ("sec", "s"),
+    ("seconds", "s"),
+    ("min", "min"),
+    ("minutes", "min"),
+    ("h", "h"),
+    ("hr", "h"),
+    ("hours", "h"),
+    ("days", "d"),
+    // Speed
+    ("kmh", "km/h"),
+    ("kph", "km/h"),
+    ("mph", "mph"),
+    ("mps", "m/s"),
+    // Energy, power and electricity
+    ("j", "J"),
+    ("kj", "kJ"),
+    ("kcal", "kcal"),
+    ("w", "W"),
+    ("kw", "kW"),
+    ("mw", "MW"),
+    ("kwh", "kWh"),
+    ("mwh", "MWh"),
+    ("v", "V"),
+    // Frequency
+    ("hz", "Hz"),
+    ("khz", "kHz"),
+    ("mhz", "MHz"),
+    ("ghz", "GHz"),
+    // Data
+    ("bytes", "B"),
+    ("kb", "kB"),
+    ("mb", "MB"),
+    ("gb", "GB"),
+    ("tb", "TB"),
+    // Ratios
+    ("pct", "%"),
+    ("percent", "%"),
+];
+
+/// Unit found in a column header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderUnit {
+    /// Header without the unit, such as `weight` for `weight (kg)`
+    pub name: String,
+    /// Unit text, such as `kg`
+    pub unit: String,
+}
+
+/// Find the unit named in a column header, if any
+pub fn unit_from_header(header: &str) -> Option<HeaderUnit> {
+    let header = header.trim();
+    bracketed_unit(header).or_else(|| suffixed_unit(header))
+}
+
+/// Unit in brackets or parentheses ending the header
+fn bracketed_unit(header: &str) -> Option<HeaderUnit> {
+    let (open, close) = [('(', ')'), ('[', ']')]
+        .into_iter()
+        .find(|(_, close)| header.ends_with(*close))?;
+    let start = header.rfind(open)?;
+    let unit = header[start + open.len_utf8()..header.len() - close.len_utf8()].trim();
+    let name = trim_separators(&header[..start]);
+    (!unit.is_empty() && !name.is_empty()).then(|| HeaderUnit {
+        name: name.to_string(),
+        unit: unit.to_string(),
+    })
+}
+
+/// Known unit abbreviation after the last `_`, `-` or space of the header
+fn suffixed_unit(header: &str) -> Option<HeaderUnit> {
+    let start = header.rfind(['_', '-', ' '])?;
+    let suffix = header[start + 1..].to_lowercase();
+    let name = trim_separators(&header[..start]);
+    let (_, unit) = UNIT_SUFFIXES
+        .iter()
+        .find(|(abbreviation, _)| *abbreviation == suffix)?;
+    (!name.is_empty()).then(|| HeaderUnit {
+        name: name.to_string(),
+        unit: unit.to_string(),
+    })
+}
+
+fn trim_separators(name: &str) -> &str {
+    name.trim_end_matches(['_', '-', ' '])
+}
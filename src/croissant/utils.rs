@@ -1,14 +1,44 @@
 //! Utility functions for file operations and CSV processing
 
+use crate::croissant::core::{
+    BadRowPolicy, DataType, InferenceOptions, NumberAffix, NumberLocale, SemanticType,
+    TEMPORAL_FORMATS, Transform, infer_data_type, matches_temporal_format,
+};
+use crate::croissant::encoding::{DETECTION_BYTES, DecodingReader, TextEncoding};
 use crate::croissant::errors::{Error, Result};
 use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
 use std::fs::File;
 use std::io::{BufReader, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Files at least this large are hashed through a memory map when the
+/// `mmap` feature is enabled
+#[cfg(feature = "mmap")]
+const MMAP_THRESHOLD: u64 = 64 * 1024 * 1024;
 
 /// Calculate the SHA-256 hash of a file
+#[tracing::instrument(level = "debug", skip_all, fields(path = %file_path.display()))]
 pub fn calculate_sha256(file_path: &Path) -> Result<String> {
     let file = File::open(file_path).map_err(|_| Error::file_not_found(file_path))?;
+
+    #[cfg(feature = "mmap")]
+    if file.metadata()?.len() >= MMAP_THRESHOLD {
+        // SAFETY: the map is read-only and dropped before returning. If the
+        // file is truncated concurrently the digest is meaningless anyway, the
+        // same as with a concurrent modification under buffered reads.
+        match unsafe { memmap2::Mmap::map(&file) } {
+            Ok(map) => {
+                let digest = hex::encode(Sha256::digest(&map[..]));
+                tracing::debug!(sha256 = %digest, "hashed memory-mapped file");
+                return Ok(digest);
+            }
+            Err(e) => tracing::debug!(error = %e, "memory map failed, using buffered reads"),
+        }
+    }
+
     let mut reader = BufReader::new(file);
     let mut hasher = Sha256::new();
     let mut buffer = [0; 8192];
@@ -21,14 +51,663 @@ pub fn calculate_sha256(file_path: &Path) -> Result<String> {
         hasher.update(&buffer[..bytes_read]);
     }
 
+    let digest = hex::encode(hasher.finalize());
+    tracing::debug!(sha256 = %digest, "hashed file");
+    Ok(digest)
+}
+
+/// Calculate the BLAKE3 hash of a file
+#[cfg(feature = "blake3")]
+#[tracing::instrument(level = "debug", skip_all, fields(path = %file_path.display()))]
+pub fn calculate_blake3(file_path: &Path) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+
+    #[cfg(feature = "mmap")]
+    hasher
+        .update_mmap(file_path)
+        .map_err(|_| Error::file_not_found(file_path))?;
+
+    #[cfg(not(feature = "mmap"))]
+    {
+        let file = File::open(file_path).map_err(|_| Error::file_not_found(file_path))?;
+        hasher.update_reader(BufReader::new(file))?;
+    }
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Calculate the SHA-256 hashes of several files concurrently
+///
+/// Results are returned in the same order as `paths`. Paths that resolve to
+/// the same file are hashed only once. At most `max_threads` files are
+/// hashed at once; `None` uses the available parallelism.
+#[tracing::instrument(level = "debug", skip_all, fields(files = paths.len()))]
+pub fn calculate_sha256_many<P>(paths: &[P], max_threads: Option<usize>) -> Vec<Result<String>>
+where
+    P: AsRef<Path> + Sync,
+{
+    // Map every path to its canonical form, keeping the first occurrence of
+    // each distinct file
+    let keys: Vec<PathBuf> = paths
+        .iter()
+        .map(|path| {
+            let path = path.as_ref();
+            path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+        })
+        .collect();
+    let mut unique: Vec<&Path> = Vec::new();
+    let mut slots: HashMap<&Path, usize> = HashMap::new();
+    for key in &keys {
+        slots.entry(key.as_path()).or_insert_with(|| {
+            unique.push(key.as_path());
+            unique.len() - 1
+        });
+    }
+    tracing::debug!(unique = unique.len(), "deduplicated file references");
+
+    let mut digests = parallel_map(&unique, max_threads, |path| calculate_sha256(path));
+
+    // The first reference to a failed file gets the original error, later
+    // references a copy of its message
+    keys.iter()
+        .map(|key| match &mut digests[slots[key.as_path()]] {
+            Ok(digest) => Ok(digest.clone()),
+            Err(e) => {
+                let copy = Error::new(e.to_string());
+                Err(std::mem::replace(e, copy))
+            }
+        })
+        .collect()
+}
+
+/// Apply `f` to every item on a bounded pool of scoped threads
+///
+/// Results are returned in the same order as `items`. At most `max_threads`
+/// items are processed at once; `None` uses the available parallelism.
+pub(crate) fn parallel_map<T, R, F>(items: &[T], max_threads: Option<usize>, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = max_threads
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+        .clamp(1, items.len().max(1));
+
+    // Threads cannot be spawned on wasm32-unknown-unknown, and a single
+    // worker gains nothing from one
+    if threads == 1 || cfg!(target_arch = "wasm32") {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+
+    let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut processed = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break;
+                        };
+                        processed.push((index, f(item)));
+                    }
+                    processed
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("worker thread panicked"))
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Calculate the SHA-256 hash of a file without blocking the async runtime
+#[cfg(feature = "tokio")]
+#[tracing::instrument(level = "debug", skip_all, fields(path = %file_path.display()))]
+pub async fn calculate_sha256_async(file_path: &Path) -> Result<String> {
+    use tokio::io::AsyncReadExt;
+
+    let mut file = tokio::fs::File::open(file_path)
+        .await
+        .map_err(|_| Error::file_not_found(file_path))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let bytes_read = file.read(&mut buffer).await?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+
     Ok(hex::encode(hasher.finalize()))
 }
 
-/// Get CSV column headers and optionally the first data row
-pub fn get_csv_columns(csv_path: &Path) -> Result<(Vec<String>, Option<Vec<String>>)> {
+/// Size of the read buffer used when streaming CSV files
+const CSV_BUFFER_CAPACITY: usize = 64 * 1024;
+
+/// CSV reader over a file transcoded to UTF-8
+pub(crate) type CsvFileReader = csv::Reader<DecodingReader<File>>;
+
+/// encodingFormat of tab-separated files
+pub const TSV_ENCODING_FORMAT: &str = "text/tab-separated-values";
+
+/// Encoding formats read as delimited text
+pub const DELIMITED_FORMATS: &[&str] = &["text/csv", TSV_ENCODING_FORMAT];
+
+/// Delimiters [`sniff_delimiter`] chooses from, preferred in this order
+/// when they occur as often
+const CANDIDATE_DELIMITERS: &[u8] = b",\t;|";
+
+/// Open a CSV file for streaming, transcoding it to UTF-8 if it is in
+/// another encoding (see [`crate::croissant::encoding`]), with the
+/// delimiter of [`detect_delimiter`]
+///
+/// The reader holds a fixed-size buffer; callers should read rows into a
+/// reused record with `read_byte_record` so memory use stays bounded by the
+/// widest row rather than the file size. Rows may have any number of
+/// fields; callers check them with [`accept_csv_row`].
+pub(crate) fn open_csv_reader(csv_path: &Path) -> Result<CsvFileReader> {
+    open_csv_reader_with_delimiter(csv_path, detect_delimiter(csv_path)?)
+}
+
+/// Open a CSV file like [`open_csv_reader`], with the given delimiter
+pub(crate) fn open_csv_reader_with_delimiter(
+    csv_path: &Path,
+    delimiter: u8,
+) -> Result<CsvFileReader> {
     let file = File::open(csv_path).map_err(|_| Error::file_not_found(csv_path))?;
-    let mut reader = csv::Reader::from_reader(file);
+    Ok(csv_reader_builder(delimiter).from_reader(DecodingReader::new(file)?))
+}
+
+/// Builder of the CSV readers of [`open_csv_reader`]
+pub(crate) fn csv_reader_builder(delimiter: u8) -> csv::ReaderBuilder {
+    let mut builder = csv::ReaderBuilder::new();
+    builder
+        .buffer_capacity(CSV_BUFFER_CAPACITY)
+        .flexible(true)
+        .delimiter(delimiter);
+    builder
+}
+
+/// Field delimiter of a delimited text file: a tab for `.tsv` and `.tab`
+/// files, otherwise that found by [`sniff_delimiter`] in its header line
+pub fn detect_delimiter(path: &Path) -> Result<u8> {
+    if is_tsv_path(path) {
+        return Ok(b'\t');
+    }
+    let file = File::open(path).map_err(|_| Error::file_not_found(path))?;
+    let mut head = Vec::new();
+    DecodingReader::new(file)?
+        .take(CSV_BUFFER_CAPACITY as u64)
+        .read_to_end(&mut head)?;
+    Ok(sniff_delimiter(&head))
+}
+
+/// Delimiter of delimited text starting with `head`: whichever of comma,
+/// tab, semicolon and pipe occurs most often outside quotes in its first
+/// line, or a comma if none does
+///
+/// ```
+/// use rustcroissant::croissant::utils::sniff_delimiter;
+///
+/// assert_eq!(sniff_delimiter(b"station|flow|\"a|b\"\n"), b'|');
+/// assert_eq!(sniff_delimiter(b"station\n"), b',');
+/// ```
+pub fn sniff_delimiter(head: &[u8]) -> u8 {
+    let mut counts = [0usize; CANDIDATE_DELIMITERS.len()];
+    let mut quoted = false;
+    for &byte in head {
+        match byte {
+            b'"' => quoted = !quoted,
+            b'\n' | b'\r' if !quoted => break,
+            _ if !quoted => {
+                if let Some(index) = CANDIDATE_DELIMITERS.iter().position(|&d| d == byte) {
+                    counts[index] += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    // The first of the most frequent candidates, a comma if none occurs
+    let mut best = 0;
+    for (index, &count) in counts.iter().enumerate() {
+        if count > counts[best] {
+            best = index;
+        }
+    }
+    CANDIDATE_DELIMITERS[best]
+}
+
+/// Parse a delimiter: `tab` or `\t` for a tab, or a single ASCII
+/// character other than a quote or a line break
+pub fn parse_delimiter(text: &str) -> Result<u8> {
+    match text {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        _ => match text.as_bytes() {
+            [byte] if byte.is_ascii() && !matches!(byte, b'"' | b'\n' | b'\r') => Ok(*byte),
+            _ => Err(Error::invalid_format(format!(
+                "Invalid delimiter {text:?}: expected tab or a single ASCII character"
+            ))),
+        },
+    }
+}
+
+/// encodingFormat of delimited text with the given delimiter
+pub fn delimited_encoding_format(delimiter: u8) -> &'static str {
+    if delimiter == b'\t' {
+        TSV_ENCODING_FORMAT
+    } else {
+        "text/csv"
+    }
+}
+
+/// Check that a CSV row has as many fields as the header, failing or
+/// logging as the policy says if not; returns whether to use the row
+///
+/// `location` names the file in messages.
+pub(crate) fn accept_csv_row(
+    record: &csv::ByteRecord,
+    columns: usize,
+    policy: BadRowPolicy,
+    location: &str,
+) -> Result<bool> {
+    if record.len() == columns {
+        return Ok(true);
+    }
+    let line = record.position().map_or(0, |position| position.line());
+    let message = format!(
+        "{location}, line {line}: row has {} fields, the header has {columns}",
+        record.len()
+    );
+    match policy {
+        BadRowPolicy::Error => Err(Error::invalid_format(message)),
+        BadRowPolicy::Warn => {
+            tracing::warn!("{message}; skipping it");
+            Ok(false)
+        }
+        BadRowPolicy::Skip => Ok(false),
+    }
+}
+
+/// Detect the character encoding of a file from its leading bytes
+pub fn detect_file_encoding(path: &Path) -> Result<TextEncoding> {
+    let file = File::open(path).map_err(|_| Error::file_not_found(path))?;
+    let mut start = Vec::with_capacity(DETECTION_BYTES);
+    file.take(DETECTION_BYTES as u64).read_to_end(&mut start)?;
+    Ok(TextEncoding::detect(&start))
+}
+
+/// Data type and missing values of a CSV column, inferred from sampled rows
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ColumnSchema {
+    pub data_type: DataType,
+    /// Whether a sampled value was missing
+    pub nullable: bool,
+    /// Null values other than blank ones that were sampled, in the order of
+    /// the inference options
+    pub null_values: Vec<String>,
+    /// Transforms reading the values as `data_type`: replacements for
+    /// grouped or decimal-comma numbers, and the format of non-ISO dates and
+    /// times
+    pub transform: Vec<Transform>,
+    /// Kind of text all sampled values are, such as URLs
+    pub semantic_type: Option<SemanticType>,
+    /// Distinct sampled values, in sorted order, of a column taken as an
+    /// enumeration
+    pub enum_values: Option<Vec<String>>,
+    /// Unit of numbers written with a currency symbol or percent sign: the
+    /// currency code or `%`
+    pub unit: Option<String>,
+}
+
+/// A locale every sampled value of a column may be a number in
+#[derive(Debug, Clone, Copy)]
+struct LocaleCandidate {
+    locale: NumberLocale,
+    /// Whether every value is an integer in this locale
+    integer: bool,
+    /// Whether a value is grouped or has a decimal comma, so that it is not
+    /// a plain number
+    localized: bool,
+}
+
+impl LocaleCandidate {
+    /// Account for a value, returning false if it is not a number in this
+    /// locale
+    fn accept(&mut self, value: &str) -> bool {
+        let Some(normalized) = self.locale.normalize(value) else {
+            return false;
+        };
+        if normalized.parse::<i64>().is_err() {
+            if normalized.parse::<f64>().is_err() {
+                return false;
+            }
+            self.integer = false;
+        }
+        self.localized |= normalized != value.trim();
+        true
+    }
+}
+
+/// Parts of a column name suggesting that its integers are Unix timestamps
+const EPOCH_NAME_HINTS: &[&str] = &["time", "date", "epoch"];
+
+/// Whole words of a column name, such as in `created_at` or `ts_ms`,
+/// suggesting that its integers are Unix timestamps
+const EPOCH_NAME_WORDS: &[&str] = &["at", "ts"];
+
+/// Unix timestamps in seconds taken as such: from 1980 to 2100
+const EPOCH_SECONDS: std::ops::RangeInclusive<i64> = 315_532_800..=4_102_444_800;
+
+/// Whether a value is a number written with leading zeros, such as a ZIP
+/// code or identifier like `007`, whose zeros would be lost as a number
+fn is_zero_padded(value: &str) -> bool {
+    let trimmed = value.trim();
+    let digits = trimmed.strip_prefix(['-', '+']).unwrap_or(trimmed);
+    let mut bytes = digits.bytes();
+    bytes.next() == Some(b'0') && bytes.next().is_some_and(|b| b.is_ascii_digit())
+}
+
+/// What the sampled values of a column have shown so far
+struct ColumnSample {
+    observed: Option<DataType>,
+    nullable: bool,
+    /// Which null values were seen, by index into the options'
+    nulls: Vec<bool>,
+    /// Currency symbol or percent sign written with the numbers
+    affix: Option<NumberAffix>,
+    /// Whether values have different affixes
+    mixed_affixes: bool,
+    /// Locales all values, without their affix, may be numbers in
+    locales: Vec<LocaleCandidate>,
+    /// Indexes into [`TEMPORAL_FORMATS`] of the formats all values fit
+    formats: Vec<usize>,
+    /// Smallest and largest integer
+    integers: Option<(i64, i64)>,
+    /// Kinds of text all values are
+    semantic_types: Vec<SemanticType>,
+    /// Number of non-null values
+    values: usize,
+    /// Distinct values, while enumerations are detected and there are few
+    /// enough of them
+    distinct: Option<BTreeSet<String>>,
+}
+
+impl ColumnSample {
+    fn new(options: &InferenceOptions) -> Self {
+        let locales = match options.number_locale {
+            Some(locale) => vec![locale],
+            None => NumberLocale::ALL.to_vec(),
+        };
+        Self {
+            observed: None,
+            nullable: false,
+            affix: None,
+            mixed_affixes: false,
+            nulls: vec![false; options.null_values.len()],
+            locales: locales
+                .into_iter()
+                .map(|locale| LocaleCandidate {
+                    locale,
+                    integer: true,
+                    localized: false,
+                })
+                .collect(),
+            formats: (0..TEMPORAL_FORMATS.len()).collect(),
+            integers: None,
+            semantic_types: SemanticType::ALL.to_vec(),
+            values: 0,
+            distinct: options.enum_max_values.map(|_| BTreeSet::new()),
+        }
+    }
+
+    /// Account for a raw value; `None` is content that is not UTF-8
+    fn add(&mut self, value: Option<&str>, options: &InferenceOptions) {
+        let data_type = match value {
+            Some(value) if options.is_null(value) => {
+                self.nullable = true;
+                if let Some(index) = options
+                    .null_values
+                    .iter()
+                    .position(|null| null == value.trim())
+                {
+                    self.nulls[index] = true;
+                }
+                return;
+            }
+            Some(value) => {
+                self.values += 1;
+                if let Some(distinct) = &mut self.distinct {
+                    if !distinct.contains(value.trim()) {
+                        distinct.insert(value.trim().to_string());
+                    }
+                    if options
+                        .enum_max_values
+                        .is_some_and(|max| distinct.len() > max)
+                    {
+                        self.distinct = None;
+                    }
+                }
+                let number = match NumberAffix::split(value) {
+                    Some((affix, number)) => {
+                        self.mixed_affixes |= self.affix.is_some_and(|seen| seen != affix);
+                        self.affix.get_or_insert(affix);
+                        Cow::Owned(number)
+                    }
+                    None => Cow::Borrowed(value),
+                };
+                let zero_padded = is_zero_padded(&number);
+                if zero_padded {
+                    self.locales.clear();
+                } else {
+                    self.locales
+                        .retain_mut(|candidate| candidate.accept(&number));
+                }
+                self.formats.retain(|&index| {
+                    let (format, data_type) = &TEMPORAL_FORMATS[index];
+                    matches_temporal_format(value, format, data_type)
+                });
+                self.semantic_types
+                    .retain(|semantic_type| semantic_type.matches(value));
+                let data_type = if zero_padded {
+                    DataType::Text
+                } else {
+                    infer_data_type(value)
+                };
+                if data_type == DataType::Integer
+                    && let Ok(n) = value.trim().parse::<i64>()
+                {
+                    let (min, max) = self.integers.unwrap_or((n, n));
+                    self.integers = Some((min.min(n), max.max(n)));
+                }
+                data_type
+            }
+            None => {
+                self.locales.clear();
+                self.formats.clear();
+                self.semantic_types.clear();
+                self.distinct = None;
+                DataType::Text
+            }
+        };
+        self.observed = Some(match self.observed.take() {
+            Some(previous) => previous.merge(&data_type),
+            None => data_type,
+        });
+    }
+
+    fn finish(self, header: &str, options: &InferenceOptions) -> ColumnSchema {
+        // Columns without any value are Text, even though every format and
+        // kind of text was left as a candidate
+        let sampled = self.observed.is_some();
+        let mut data_type = self.observed.clone().unwrap_or(DataType::Text);
+        let mut transform = Vec::new();
+        let mut semantic_type = None;
+
+        // Plain numbers are kept as such unless a locale was asked for;
+        // numbers with a currency symbol or percent sign are not plain
+        let affix = self.affix.filter(|_| !self.mixed_affixes);
+        let localized = self.locales.first().filter(|candidate| {
+            affix.is_some()
+                || candidate.localized
+                    && (options.number_locale.is_some()
+                        || !matches!(data_type, DataType::Integer | DataType::Float))
+        });
+        let mut unit = None;
+        if let Some(candidate) = localized {
+            data_type = if candidate.integer {
+                DataType::Integer
+            } else {
+                DataType::Float
+            };
+            if let Some(affix) = affix {
+                transform.push(affix.transform());
+                unit = Some(affix.unit().to_string());
+            }
+            if candidate.localized {
+                transform.extend(candidate.locale.transforms());
+            }
+        } else if data_type == DataType::Text
+            && sampled
+            && let Some(&index) = self.formats.first()
+        {
+            let (format, format_type) = &TEMPORAL_FORMATS[index];
+            data_type = format_type.clone();
+            transform.push(Transform::Format(format.to_string()));
+        } else if data_type == DataType::Integer
+            && let Some(epoch) = self.epoch_transforms(header)
+        {
+            data_type = DataType::DateTime;
+            transform = epoch;
+        } else if data_type == DataType::Text
+            && sampled
+            && let Some(&found) = self.semantic_types.first()
+        {
+            data_type = found.data_type();
+            semantic_type = Some(found);
+        }
+
+        // Values must repeat, so that columns of unique names or identifiers
+        // are not taken for enumerations in small samples
+        let enum_values = self
+            .distinct
+            .filter(|distinct| {
+                data_type == DataType::Text
+                    && semantic_type.is_none()
+                    && !distinct.is_empty()
+                    && distinct.len() * 2 <= self.values
+            })
+            .map(|distinct| distinct.into_iter().collect());
+
+        ColumnSchema {
+            data_type,
+            nullable: self.nullable,
+            null_values: options
+                .null_values
+                .iter()
+                .zip(self.nulls)
+                .filter(|(_, seen)| *seen)
+                .map(|(null, _)| null.clone())
+                .collect(),
+            transform,
+            semantic_type,
+            enum_values,
+            unit,
+        }
+    }
 
+    /// Transforms reading integers as Unix timestamps, in seconds or
+    /// milliseconds, if the column's name suggests they are and they all
+    /// fall between 1980 and 2100
+    fn epoch_transforms(&self, header: &str) -> Option<Vec<Transform>> {
+        let (min, max) = self.integers?;
+        let header = header.to_lowercase();
+        if !EPOCH_NAME_HINTS.iter().any(|hint| header.contains(hint))
+            && !header
+                .split(|c: char| !c.is_alphanumeric())
+                .any(|word| EPOCH_NAME_WORDS.contains(&word))
+        {
+            return None;
+        }
+        if EPOCH_SECONDS.contains(&min) && EPOCH_SECONDS.contains(&max) {
+            return Some(vec![Transform::Format("%s".to_string())]);
+        }
+        let millis = EPOCH_SECONDS.start() * 1000..=EPOCH_SECONDS.end() * 1000;
+        if millis.contains(&min) && millis.contains(&max) {
+            // chrono has no milliseconds field, so the last three digits
+            // become a fraction of seconds
+            return Some(vec![
+                Transform::Replace("^(-?\\d+)(\\d{3})$/$1.$2".to_string()),
+                Transform::Format("%s%.f".to_string()),
+            ]);
+        }
+        None
+    }
+}
+
+/// Headers and inferred columns of a CSV file
+#[derive(Debug, Clone)]
+pub(crate) struct CsvSchema {
+    pub headers: Vec<String>,
+    pub columns: Vec<ColumnSchema>,
+    /// Number of sampled rows skipped for not having as many fields as the
+    /// header
+    pub bad_rows: usize,
+}
+
+/// Read CSV column headers and infer each column's data type from a sample
+/// of data rows
+///
+/// Rows are streamed through a single reused byte record, so memory use
+/// depends on neither the sample size nor the file size, and no String is
+/// allocated per value. Null values, blank or among the options' null
+/// values, are ignored, and columns without any non-null sampled value are
+/// typed as Text, as are numbers with leading zeros, such as ZIP codes.
+/// Columns of numbers with digit grouping or a decimal comma
+/// are typed as numbers of the options' locale, or of the first locale all
+/// their values fit, as are numbers written with a currency symbol or
+/// percent sign, whose unit is recorded; columns of dates and times in one
+/// of the [`TEMPORAL_FORMATS`], or of Unix timestamps, get that format.
+/// Text columns of URLs are typed as `sc:URL`, and those of URLs, email
+/// addresses or UUIDs are given their [`SemanticType`]. When the options
+/// ask for it, Text columns with few distinct values, each repeated on
+/// average, are taken as enumerations. Rows without as many fields as the
+/// header are handled as the options' [`BadRowPolicy`] says.
+#[tracing::instrument(level = "debug", skip_all, fields(path = %csv_path.display()))]
+pub(crate) fn infer_csv_schema(
+    csv_path: &Path,
+    delimiter: u8,
+    options: &InferenceOptions,
+) -> Result<CsvSchema> {
+    infer_csv_schema_from_reader(
+        open_csv_reader_with_delimiter(csv_path, delimiter)?,
+        &csv_path.display().to_string(),
+        options,
+    )
+}
+
+/// Read CSV column headers and infer each column's data type from a sample
+/// of data rows of an open reader, from a reader built with
+/// [`csv_reader_builder`]; see [`infer_csv_schema`]
+pub(crate) fn infer_csv_schema_from_reader<R: Read>(
+    mut reader: csv::Reader<R>,
+    location: &str,
+    options: &InferenceOptions,
+) -> Result<CsvSchema> {
     // Read headers
     let headers = reader
         .headers()?
@@ -36,31 +715,79 @@ pub fn get_csv_columns(csv_path: &Path) -> Result<(Vec<String>, Option<Vec<Strin
         .map(|h| h.trim().to_string())
         .collect::<Vec<String>>();
 
-    // Try to read the first data row for type inference
-    let first_row = if let Some(result) = reader.records().next() {
-        let record = result?;
-        Some(
-            record
-                .iter()
-                .map(|field| field.trim().to_string())
-                .collect(),
-        )
-    } else {
-        None
-    };
+    let mut samples = headers
+        .iter()
+        .map(|_| ColumnSample::new(options))
+        .collect::<Vec<_>>();
+    let mut record = csv::ByteRecord::new();
+    let mut rows = 0;
+    let mut bad_rows = 0;
+    while rows < options.row_limit() && reader.read_byte_record(&mut record)? {
+        rows += 1;
+        if !accept_csv_row(&record, headers.len(), options.on_bad_row, location)? {
+            bad_rows += 1;
+            continue;
+        }
+        for (sample, bytes) in samples.iter_mut().zip(record.iter()) {
+            // Validate UTF-8 per field in place instead of allocating a
+            // String for every value; non-UTF-8 content can only be Text
+            sample.add(std::str::from_utf8(bytes).ok(), options);
+        }
+    }
+    tracing::debug!(rows, bad_rows, "sampled rows for type inference");
+
+    let columns = samples
+        .into_iter()
+        .zip(&headers)
+        .map(|(sample, header)| sample.finish(header, options))
+        .collect();
+
+    Ok(CsvSchema {
+        headers,
+        columns,
+        bad_rows,
+    })
+}
+
+/// Infer each column's data type from sampled rows of text values, such as
+/// the cells of a spreadsheet; see [`infer_csv_schema`]
+#[cfg(feature = "xlsx")]
+pub(crate) fn infer_schema_from_rows(
+    headers: Vec<String>,
+    rows: impl Iterator<Item = Vec<String>>,
+    options: &InferenceOptions,
+) -> CsvSchema {
+    let mut samples = headers
+        .iter()
+        .map(|_| ColumnSample::new(options))
+        .collect::<Vec<_>>();
+    for row in rows.take(options.row_limit()) {
+        for (sample, value) in samples.iter_mut().zip(&row) {
+            sample.add(Some(value), options);
+        }
+    }
+    let columns = samples
+        .into_iter()
+        .zip(&headers)
+        .map(|(sample, header)| sample.finish(header, options))
+        .collect();
 
-    Ok((headers, first_row))
+    CsvSchema {
+        headers,
+        columns,
+        bad_rows: 0,
+    }
 }
 
 /// Validate if the given path is a valid output file path
 pub fn validate_output_path(output_path: &Path) -> Result<()> {
     // Check if the parent directory exists or can be created
-    if let Some(parent) = output_path.parent() {
-        if !parent.exists() {
-            std::fs::create_dir_all(parent).map_err(|e| {
-                Error::invalid_output_path(output_path, format!("Cannot create directory: {e}"))
-            })?;
-        }
+    if let Some(parent) = output_path.parent()
+        && !parent.exists()
+    {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            Error::invalid_output_path(output_path, format!("Cannot create directory: {e}"))
+        })?;
     }
 
     // Check if we can write to the file by creating a temporary file
@@ -85,6 +812,22 @@ pub fn normalize_path(path: &Path) -> Result<std::path::PathBuf> {
         .map_err(|e| Error::invalid_format(format!("Invalid path: {e}")))
 }
 
+/// Date of generated metadata as `YYYY-MM-DD`: that of `SOURCE_DATE_EPOCH`
+/// when set, so builds reproduce it byte for byte, and today otherwise
+pub fn publication_date() -> String {
+    let date = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| match epoch.trim().parse::<i64>() {
+            Ok(seconds) => chrono::DateTime::from_timestamp(seconds, 0),
+            Err(_) => {
+                tracing::warn!(%epoch, "SOURCE_DATE_EPOCH is not a number of seconds; using today");
+                None
+            }
+        })
+        .unwrap_or_else(chrono::Utc::now);
+    date.format("%Y-%m-%d").to_string()
+}
+
 /// Get file size in a human-readable format
 pub fn format_file_size(size: u64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
@@ -103,6 +846,85 @@ pub fn format_file_size(size: u64) -> String {
     }
 }
 
+/// Parse a content size such as `892 B` or `1.5 MB` into bytes
+///
+/// The inverse of [`format_file_size`]; units are binary multiples, and
+/// the `KiB` spellings are accepted too.
+pub fn parse_file_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "KB" | "KIB" => 1 << 10,
+        "MB" | "MIB" => 1 << 20,
+        "GB" | "GIB" => 1 << 30,
+        "TB" | "TIB" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64).round() as u64)
+}
+
+/// Kind of server a URL points into that needs optional support, as named
+/// in messages, with the feature rustcroissant needs to be built with to
+/// read it and whether it was
+///
+/// Azure Blob Storage `https://` URLs are only recognized with the `azure`
+/// feature; without it they are fetched as plain HTTPS, which works for
+/// public containers.
+fn url_support(url: &str) -> Option<(&'static str, &'static str, bool)> {
+    if url.starts_with("s3://") {
+        Some(("s3://", "s3", cfg!(feature = "s3")))
+    } else if url.starts_with("gs://") {
+        Some(("gs://", "gcs", cfg!(feature = "gcs")))
+    } else if url.starts_with("az://") {
+        Some(("az://", "azure", cfg!(feature = "azure")))
+    } else if cfg!(feature = "azure") && is_azure_blob_url(url) {
+        Some(("Azure Blob Storage", "azure", true))
+    } else if url.starts_with("sftp://") {
+        Some(("sftp://", "sftp", cfg!(feature = "sftp")))
+    } else if url.starts_with("ftp://") {
+        Some(("ftp://", "sftp", cfg!(feature = "sftp")))
+    } else {
+        None
+    }
+}
+
+/// Check whether a URL is an `https://<account>.blob.core.windows.net/`
+/// Azure Blob Storage URL
+pub fn is_azure_blob_url(url: &str) -> bool {
+    url.strip_prefix("https://")
+        .and_then(|rest| rest.split('/').next())
+        .is_some_and(|host| host.ends_with(".blob.core.windows.net"))
+}
+
+/// Check whether a URL refers to an object in object storage, whether or
+/// not this build can read it
+pub fn is_object_store_url(url: &str) -> bool {
+    url_support(url).is_some_and(|(_, feature, _)| matches!(feature, "s3" | "gcs" | "azure"))
+}
+
+/// Check whether a URL refers to a file on an SFTP or FTP server, whether
+/// or not this build can read it
+pub fn is_ftp_url(url: &str) -> bool {
+    url_support(url).is_some_and(|(_, feature, _)| feature == "sftp")
+}
+
+/// Fail for an object storage, SFTP or FTP URL that rustcroissant was
+/// built without support for
+pub fn require_url_support(url: &str) -> Result<()> {
+    match url_support(url) {
+        Some((store, feature, false)) => Err(Error::new(format!(
+            "{store} URLs require rustcroissant to be built with the {feature} feature"
+        ))),
+        _ => Ok(()),
+    }
+}
+
 /// Check if a file exists and is readable
 pub fn is_file_readable(path: &Path) -> bool {
     path.exists() && path.is_file() && File::open(path).is_ok()
@@ -115,10 +937,64 @@ pub fn get_file_extension(path: &Path) -> Option<String> {
         .map(|ext| ext.to_lowercase())
 }
 
+/// Media type of a file going by its extension, if known
+pub fn media_type(path: &Path) -> Option<&'static str> {
+    Some(match get_file_extension(path)?.as_str() {
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
+        "jsonl" => "application/jsonl",
+        "json" => "application/json",
+        "parquet" => "application/x-parquet",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "txt" => "text/plain",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "tif" | "tiff" => "image/tiff",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "flac" => "audio/flac",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "pdf" => "application/pdf",
+        "xml" => "application/xml",
+        "html" | "htm" => "text/html",
+        "md" => "text/markdown",
+        _ => return None,
+    })
+}
+
+/// Check whether a metadata path uses the YAML (`.yaml`/`.yml`) rather
+/// than the JSON-LD serialization
+pub fn is_yaml_path(path: &Path) -> bool {
+    matches!(get_file_extension(path).as_deref(), Some("yaml" | "yml"))
+}
+
+/// Check whether a data file is a Parquet file, by its `.parquet`
+/// extension
+pub fn is_parquet_path(path: &Path) -> bool {
+    get_file_extension(path).as_deref() == Some("parquet")
+}
+
+/// Check whether a data file is an Excel workbook, by its `.xlsx`
+/// extension
+pub fn is_xlsx_path(path: &Path) -> bool {
+    get_file_extension(path).as_deref() == Some("xlsx")
+}
+
+/// Check whether a data file is tab-separated, by its `.tsv` or `.tab`
+/// extension
+pub fn is_tsv_path(path: &Path) -> bool {
+    matches!(get_file_extension(path).as_deref(), Some("tsv" | "tab"))
+}
+
 /// Validate CSV file format by attempting to read headers
 pub fn validate_csv_format(csv_path: &Path) -> Result<()> {
-    let file = File::open(csv_path).map_err(|_| Error::file_not_found(csv_path))?;
-    let mut reader = csv::Reader::from_reader(file);
+    let mut reader = open_csv_reader(csv_path)?;
 
     // Try to read headers
     let headers = reader.headers()?;
@@ -144,3 +1020,176 @@ pub fn validate_csv_format(csv_path: &Path) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infer(values: &[&str]) -> ColumnSchema {
+        infer_with("value", values, &InferenceOptions::default())
+    }
+
+    fn infer_with(header: &str, values: &[&str], options: &InferenceOptions) -> ColumnSchema {
+        let mut sample = ColumnSample::new(options);
+        for value in values {
+            sample.add(Some(value), options);
+        }
+        sample.finish(header, options)
+    }
+
+    #[test]
+    fn integers_widen_to_floats_then_text() {
+        assert_eq!(infer(&["1", "2"]).data_type, DataType::Integer);
+        assert_eq!(infer(&["1", "2.5"]).data_type, DataType::Float);
+        assert_eq!(infer(&["1", "2.5", "abc"]).data_type, DataType::Text);
+        assert_eq!(infer(&["2.5", "true"]).data_type, DataType::Text);
+    }
+
+    #[test]
+    fn zero_padded_integers_are_text() {
+        let column = infer(&["007", "123"]);
+        assert_eq!(column.data_type, DataType::Text);
+        assert!(column.transform.is_empty());
+        assert_eq!(infer(&["-01"]).data_type, DataType::Text);
+        assert_eq!(infer(&["$007"]).data_type, DataType::Text);
+        assert_eq!(infer(&["0", "10", "-0"]).data_type, DataType::Integer);
+        assert_eq!(infer(&["0.5", "0"]).data_type, DataType::Float);
+    }
+
+    #[test]
+    fn dates_widen_to_date_times() {
+        assert_eq!(infer(&["2024-01-31"]).data_type, DataType::Date);
+        assert_eq!(
+            infer(&["2024-01-31", "2024-02-01T10:00:00"]).data_type,
+            DataType::DateTime
+        );
+        assert_eq!(infer(&["2024-01-31", "12"]).data_type, DataType::Text);
+    }
+
+    fn with_locale(locale: NumberLocale) -> InferenceOptions {
+        InferenceOptions {
+            number_locale: Some(locale),
+            ..InferenceOptions::default()
+        }
+    }
+
+    fn replace(pattern: &str) -> Transform {
+        Transform::Replace(pattern.to_string())
+    }
+
+    /// Apply `pattern/replacement` transforms the way records are read
+    fn apply(transforms: &[Transform], value: &str) -> String {
+        transforms
+            .iter()
+            .fold(value.to_string(), |value, transform| {
+                let Transform::Replace(replace) = transform else {
+                    return value;
+                };
+                let (pattern, replacement) = replace.split_once('/').expect("pattern/replacement");
+                regex::Regex::new(pattern)
+                    .expect("valid regex")
+                    .replace_all(&value, replacement)
+                    .into_owned()
+            })
+    }
+
+    #[test]
+    fn grouped_number_follows_the_locale() {
+        let en = infer_with("value", &["1,234"], &with_locale(NumberLocale::En));
+        assert_eq!(en.data_type, DataType::Integer);
+        assert_eq!(en.transform, [replace(",/")]);
+
+        let de = infer_with("value", &["1,234"], &with_locale(NumberLocale::De));
+        assert_eq!(de.data_type, DataType::Float);
+        assert_eq!(de.transform, [replace("\\./"), replace(",/.")]);
+        assert_eq!(apply(&de.transform, "1,234"), "1.234");
+
+        // Without a locale, the first one all values fit is taken
+        let auto = infer(&["1,234"]);
+        assert_eq!(auto.data_type, DataType::Integer);
+        assert_eq!(auto.transform, en.transform);
+        assert_eq!(apply(&auto.transform, "1,234"), "1234");
+    }
+
+    #[test]
+    fn decimal_comma_is_told_from_grouping() {
+        let column = infer(&["1.234,5", "2,5"]);
+        assert_eq!(column.data_type, DataType::Float);
+        assert_eq!(column.transform, NumberLocale::De.transforms());
+        assert_eq!(apply(&column.transform, "1.234,5"), "1234.5");
+
+        let column = infer(&["1.234.567"]);
+        assert_eq!(column.data_type, DataType::Integer);
+        assert_eq!(column.transform, NumberLocale::De.transforms());
+
+        let column = infer(&["1 234,5"]);
+        assert_eq!(column.data_type, DataType::Float);
+        assert_eq!(column.transform, NumberLocale::Fr.transforms());
+
+        // Plain numbers are kept as such
+        let column = infer(&["2.5", "1234"]);
+        assert_eq!(column.data_type, DataType::Float);
+        assert!(column.transform.is_empty());
+    }
+
+    #[test]
+    fn epoch_seconds_and_milliseconds_are_told_apart() {
+        let seconds = infer_with(
+            "created_at",
+            &["1700000000", "1700003600"],
+            &InferenceOptions::default(),
+        );
+        assert_eq!(seconds.data_type, DataType::DateTime);
+        assert_eq!(seconds.transform, [Transform::Format("%s".to_string())]);
+
+        let millis = infer_with(
+            "ts_ms",
+            &["1700000000123", "1700003600000"],
+            &InferenceOptions::default(),
+        );
+        assert_eq!(millis.data_type, DataType::DateTime);
+        assert_eq!(millis.transform[1], Transform::Format("%s%.f".to_string()));
+        let value = apply(&millis.transform, "1700000000123");
+        assert_eq!(value, "1700000000.123");
+        assert!(chrono::NaiveDateTime::parse_from_str(&value, "%s%.f").is_ok());
+
+        // Neither a name hinting at time nor a plausible range
+        let count = infer_with("count", &["1700000000"], &InferenceOptions::default());
+        assert_eq!(count.data_type, DataType::Integer);
+        let small = infer_with("created_at", &["12", "13"], &InferenceOptions::default());
+        assert_eq!(small.data_type, DataType::Integer);
+    }
+
+    #[test]
+    fn affix_replacements_are_valid_regexes() {
+        for &(symbol, code) in crate::croissant::core::CURRENCY_SYMBOLS {
+            let affix = NumberAffix::Currency { symbol, code };
+            let transform = [affix.transform()];
+            assert_eq!(apply(&transform, &format!("{symbol}12")), "12", "{symbol}");
+            assert_eq!(
+                apply(&transform, &format!("-12 {symbol}")),
+                "-12 ",
+                "{symbol}"
+            );
+        }
+        assert_eq!(apply(&[NumberAffix::Percent.transform()], "12.5%"), "12.5");
+
+        let column = infer(&["$1,299.00", "$5"]);
+        assert_eq!(column.data_type, DataType::Float);
+        assert_eq!(column.unit.as_deref(), Some("USD"));
+        assert_eq!(apply(&column.transform, "$1,299.00"), "1299.00");
+    }
+
+    #[test]
+    fn nulls_are_recorded_without_changing_the_type() {
+        let column = infer(&["1", "", "NA", "2", "null"]);
+        assert_eq!(column.data_type, DataType::Integer);
+        assert!(column.nullable);
+        assert_eq!(column.null_values, ["NA", "null"]);
+
+        let column = infer(&["NA", ""]);
+        assert_eq!(column.data_type, DataType::Text);
+        assert!(column.nullable);
+        assert!(!infer(&["1"]).nullable);
+    }
+}
@@ -1,8 +1,17 @@
 //! Validation logic for Croissant metadata
 use crate::croissant::core::Metadata;
-use crate::croissant::core::RecordSet;
+use crate::croissant::core::{
+    BadRowPolicy, DataType, Distribution, Field, RAI_NAMESPACE, RecordSet, create_default_context,
+};
 use crate::croissant::errors::{Error, Result};
-use std::collections::HashSet;
+use crate::croissant::fileset::glob_regex;
+use crate::croissant::jsonld;
+use crate::croissant::records::{RecordOptions, read_records_with_options};
+use crate::croissant::spec::is_date;
+use crate::croissant::utils::is_yaml_path;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 use std::path::Path;
 
 /// Issue severity levels
@@ -10,6 +19,312 @@ use std::path::Path;
 pub enum IssueSeverity {
     Error,
     Warning,
+    /// Noted for information only, such as the use of an extension
+    Info,
+}
+
+impl IssueSeverity {
+    /// Name used in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            IssueSeverity::Error => "error",
+            IssueSeverity::Warning => "warning",
+            IssueSeverity::Info => "info",
+        }
+    }
+}
+
+/// Check reporting an issue, whose level can be configured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// A mandatory property is missing
+    MandatoryProperty,
+    /// A node does not have the expected `@type`
+    NodeType,
+    /// A recommended property is missing
+    RecommendedProperty,
+    /// A distribution has no SHA-256 digest
+    MissingChecksum,
+    /// A SHA-256 digest is not 64 hexadecimal characters
+    ChecksumFormat,
+    /// A field has no data type
+    MissingDataType,
+    /// A field has another data type than the standard ones
+    UnknownDataType,
+    /// A field has no source
+    MissingSource,
+    /// A field references a file object or field that does not exist
+    DanglingReference,
+    /// A field's type differs from that of the field it references, or its
+    /// values reference no record
+    ReferentialIntegrity,
+    /// Records of a record set cannot be read
+    UnreadableRecords,
+    /// CSV rows without as many fields as the header were skipped
+    BadRows,
+    /// A DOI cannot be resolved or does not match the metadata
+    Doi,
+    /// A property is not defined by the specification
+    UnknownProperty,
+    /// A property is defined by an extension namespace rather than the
+    /// specification
+    ExtensionProperty,
+    /// A property defined to take one value has several
+    Cardinality,
+    /// A value is of the wrong kind or not among those allowed
+    PropertyValue,
+    /// A distribution's file does not match its recorded digest or size
+    ContentMismatch,
+    /// Responsible AI properties are incomplete or invalid
+    ResponsibleAi,
+    /// The document cannot be read as Croissant metadata
+    Unparseable,
+}
+
+impl Rule {
+    /// All rules, in the order they are listed to users
+    pub const ALL: &'static [Rule] = &[
+        Rule::MandatoryProperty,
+        Rule::NodeType,
+        Rule::RecommendedProperty,
+        Rule::MissingChecksum,
+        Rule::ChecksumFormat,
+        Rule::MissingDataType,
+        Rule::UnknownDataType,
+        Rule::MissingSource,
+        Rule::DanglingReference,
+        Rule::ReferentialIntegrity,
+        Rule::UnreadableRecords,
+        Rule::BadRows,
+        Rule::Doi,
+        Rule::UnknownProperty,
+        Rule::ExtensionProperty,
+        Rule::Cardinality,
+        Rule::PropertyValue,
+        Rule::ContentMismatch,
+        Rule::ResponsibleAi,
+        Rule::Unparseable,
+    ];
+
+    /// Name used in configuration files and JSON reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::MandatoryProperty => "mandatory-property",
+            Rule::NodeType => "node-type",
+            Rule::RecommendedProperty => "recommended-property",
+            Rule::MissingChecksum => "missing-checksum",
+            Rule::ChecksumFormat => "checksum-format",
+            Rule::MissingDataType => "missing-data-type",
+            Rule::UnknownDataType => "unknown-data-type",
+            Rule::MissingSource => "missing-source",
+            Rule::DanglingReference => "dangling-reference",
+            Rule::ReferentialIntegrity => "referential-integrity",
+            Rule::UnreadableRecords => "unreadable-records",
+            Rule::BadRows => "bad-rows",
+            Rule::Doi => "doi",
+            Rule::UnknownProperty => "unknown-property",
+            Rule::ExtensionProperty => "extension-property",
+            Rule::Cardinality => "cardinality",
+            Rule::PropertyValue => "property-value",
+            Rule::ContentMismatch => "content-mismatch",
+            Rule::ResponsibleAi => "responsible-ai",
+            Rule::Unparseable => "unparseable",
+        }
+    }
+
+    /// One-sentence description of what the rule checks
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::MandatoryProperty => "A mandatory property is missing",
+            Rule::NodeType => "A node does not have the expected @type",
+            Rule::RecommendedProperty => "A recommended property is missing",
+            Rule::MissingChecksum => "A distribution has no SHA-256 digest",
+            Rule::ChecksumFormat => "A SHA-256 digest is not 64 hexadecimal characters",
+            Rule::MissingDataType => "A field has no data type",
+            Rule::UnknownDataType => "A field has another data type than the standard ones",
+            Rule::MissingSource => "A field has no source",
+            Rule::DanglingReference => {
+                "A field references a file object or field that does not exist"
+            }
+            Rule::ReferentialIntegrity => {
+                "A field's type differs from that of the field it references, or its values reference no record"
+            }
+            Rule::UnreadableRecords => "Records of a record set cannot be read",
+            Rule::BadRows => "CSV rows without as many fields as the header were skipped",
+            Rule::Doi => "A DOI cannot be resolved or does not match the metadata",
+            Rule::UnknownProperty => "A property is not defined by the specification",
+            Rule::ExtensionProperty => {
+                "A property is defined by an extension namespace rather than the specification"
+            }
+            Rule::Cardinality => "A property defined to take one value has several",
+            Rule::PropertyValue => "A value is of the wrong kind or not among those allowed",
+            Rule::ContentMismatch => {
+                "A distribution's file does not match its recorded digest or size"
+            }
+            Rule::ResponsibleAi => "Responsible AI properties are incomplete or invalid",
+            Rule::Unparseable => "The document cannot be read as Croissant metadata",
+        }
+    }
+
+    /// Parse a rule name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|rule| rule.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown validation rule: {name}")))
+    }
+}
+
+/// Formats validation issues can be reported in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Human-readable lines, as from [`ValidationIssues::report`]
+    #[default]
+    Text,
+    /// [`ValidationIssues::to_json`]
+    Json,
+    /// SARIF 2.1.0, as from [`ValidationIssues::to_sarif`], for code
+    /// scanning tools
+    Sarif,
+}
+
+impl ReportFormat {
+    /// All formats, in the order they are listed to users
+    pub const ALL: &'static [ReportFormat] =
+        &[ReportFormat::Text, ReportFormat::Json, ReportFormat::Sarif];
+
+    /// Command-line name of the format
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReportFormat::Text => "text",
+            ReportFormat::Json => "json",
+            ReportFormat::Sarif => "sarif",
+        }
+    }
+
+    /// Parse a command-line format name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown report format: {name}")))
+    }
+}
+
+/// Level the issues of a rule are reported at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleLevel {
+    Error,
+    Warning,
+    Info,
+    /// Not reported
+    Off,
+}
+
+impl RuleLevel {
+    /// All levels, in the order they are listed to users
+    pub const ALL: &'static [RuleLevel] = &[
+        RuleLevel::Error,
+        RuleLevel::Warning,
+        RuleLevel::Info,
+        RuleLevel::Off,
+    ];
+
+    /// Name used in configuration files
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleLevel::Error => "error",
+            RuleLevel::Warning => "warning",
+            RuleLevel::Info => "info",
+            RuleLevel::Off => "off",
+        }
+    }
+
+    /// Parse a level name
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|level| level.name() == name)
+            .ok_or_else(|| Error::invalid_format(format!("Unknown rule level: {name}")))
+    }
+}
+
+/// One step of the path from the dataset to the node an issue refers to
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    Metadata(String),
+    FileObject(String),
+    RecordSet(String),
+    Field(String),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Metadata(name) => write!(f, "Metadata({name})"),
+            PathSegment::FileObject(name) => write!(f, "FileObject({name})"),
+            PathSegment::RecordSet(name) => write!(f, "RecordSet({name})"),
+            PathSegment::Field(name) => write!(f, "Field({name})"),
+        }
+    }
+}
+
+/// Structured location of a validation issue, rendered as
+/// `Metadata(a) > RecordSet(b) > Field(c)` only when displayed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IssuePath {
+    segments: Vec<PathSegment>,
+}
+
+impl IssuePath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn metadata(self, name: &str) -> Self {
+        self.push(PathSegment::Metadata(name.to_string()))
+    }
+
+    pub fn file_object(self, name: &str) -> Self {
+        self.push(PathSegment::FileObject(name.to_string()))
+    }
+
+    pub fn record_set(self, name: &str) -> Self {
+        self.push(PathSegment::RecordSet(name.to_string()))
+    }
+
+    pub fn field(self, name: &str) -> Self {
+        self.push(PathSegment::Field(name.to_string()))
+    }
+
+    fn push(mut self, segment: PathSegment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Check whether this path lies at or below `prefix`
+    pub fn starts_with(&self, prefix: &[PathSegment]) -> bool {
+        self.segments.starts_with(prefix)
+    }
+}
+
+impl fmt::Display for IssuePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" > ")?;
+            }
+            write!(f, "{segment}")?;
+        }
+        Ok(())
+    }
 }
 
 /// A single validation issue
@@ -17,7 +332,11 @@ pub enum IssueSeverity {
 pub struct ValidationIssue {
     pub severity: IssueSeverity,
     pub message: String,
-    pub context: Option<String>,
+    pub path: Option<IssuePath>,
+    /// Check that reported the issue, if any
+    pub rule: Option<Rule>,
+    /// URL of the section of the specification the issue is about
+    pub reference: Option<String>,
 }
 
 impl ValidationIssue {
@@ -25,7 +344,9 @@ impl ValidationIssue {
         Self {
             severity: IssueSeverity::Error,
             message: message.into(),
-            context: None,
+            path: None,
+            rule: None,
+            reference: None,
         }
     }
 
@@ -33,14 +354,61 @@ impl ValidationIssue {
         Self {
             severity: IssueSeverity::Warning,
             message: message.into(),
-            context: None,
+            path: None,
+            rule: None,
+            reference: None,
+        }
+    }
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Info,
+            message: message.into(),
+            path: None,
+            rule: None,
+            reference: None,
         }
     }
 
-    pub fn with_context(mut self, context: impl Into<String>) -> Self {
-        self.context = Some(context.into());
+    pub fn with_path(mut self, path: IssuePath) -> Self {
+        self.path = Some(path);
         self
     }
+
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rule = Some(rule);
+        self
+    }
+
+    pub fn with_reference(mut self, reference: impl Into<String>) -> Self {
+        self.reference = Some(reference.into());
+        self
+    }
+
+    /// Message followed by the specification reference, if any
+    fn described(&self) -> String {
+        match &self.reference {
+            Some(reference) => format!("{} (see {reference})", self.message),
+            None => self.message.clone(),
+        }
+    }
+
+    /// Rendered location of the issue, e.g. `Metadata(a) > RecordSet(b)`
+    pub fn context(&self) -> Option<String> {
+        self.path.as_ref().map(IssuePath::to_string)
+    }
+}
+
+/// Renders the issue on one line, e.g.
+/// `error: [Metadata(a) > RecordSet(b)] Record set has no fields`
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = self.severity.name();
+        match &self.path {
+            Some(path) => write!(f, "{severity}: [{path}] {}", self.described()),
+            None => write!(f, "{severity}: {}", self.described()),
+        }
+    }
 }
 
 /// Collection of validation issues
@@ -54,6 +422,17 @@ impl ValidationIssues {
         Self { issues: Vec::new() }
     }
 
+    /// Issues of a document that cannot be read as metadata: a single
+    /// error of the [`Rule::Unparseable`] rule
+    pub fn unparseable(error: &Error) -> Self {
+        let mut issues = Self::new();
+        issues.push(
+            ValidationIssue::error(format!("Metadata cannot be read: {error}"))
+                .with_rule(Rule::Unparseable),
+        );
+        issues
+    }
+
     pub fn add_error(&mut self, message: impl Into<String>) {
         self.issues.push(ValidationIssue::error(message));
     }
@@ -62,22 +441,93 @@ impl ValidationIssues {
         self.issues.push(ValidationIssue::warning(message));
     }
 
-    pub fn add_error_with_context(
-        &mut self,
-        message: impl Into<String>,
-        context: impl Into<String>,
-    ) {
+    pub fn add_error_at(&mut self, message: impl Into<String>, path: IssuePath) {
         self.issues
-            .push(ValidationIssue::error(message).with_context(context));
+            .push(ValidationIssue::error(message).with_path(path));
     }
 
-    pub fn add_warning_with_context(
-        &mut self,
-        message: impl Into<String>,
-        context: impl Into<String>,
-    ) {
+    pub fn add_warning_at(&mut self, message: impl Into<String>, path: IssuePath) {
         self.issues
-            .push(ValidationIssue::warning(message).with_context(context));
+            .push(ValidationIssue::warning(message).with_path(path));
+    }
+
+    pub fn add_rule_error(&mut self, rule: Rule, message: impl Into<String>, path: IssuePath) {
+        self.issues.push(
+            ValidationIssue::error(message)
+                .with_path(path)
+                .with_rule(rule),
+        );
+    }
+
+    pub fn add_rule_warning(&mut self, rule: Rule, message: impl Into<String>, path: IssuePath) {
+        self.issues.push(
+            ValidationIssue::warning(message)
+                .with_path(path)
+                .with_rule(rule),
+        );
+    }
+
+    pub fn push(&mut self, issue: ValidationIssue) {
+        self.issues.push(issue);
+    }
+
+    /// Add the issues of another check, taking the specification reference
+    /// of those already reported from it instead of repeating them
+    pub fn merge(&mut self, other: ValidationIssues) {
+        for issue in other.issues {
+            let reported = self.issues.iter_mut().find(|reported| {
+                reported.severity == issue.severity
+                    && reported.rule == issue.rule
+                    && reported.path == issue.path
+                    && reported.message == issue.message
+            });
+            match reported {
+                Some(reported) => {
+                    reported.reference = reported.reference.take().or(issue.reference)
+                }
+                None => self.issues.push(issue),
+            }
+        }
+    }
+
+    /// Report the issues of the given rules at the given levels, dropping
+    /// those of rules that are off
+    pub fn with_levels(self, levels: &BTreeMap<Rule, RuleLevel>) -> ValidationIssues {
+        Self {
+            issues: self
+                .issues
+                .into_iter()
+                .filter_map(|mut issue| {
+                    // A document that cannot be read never passes
+                    let rule = issue.rule.filter(|rule| *rule != Rule::Unparseable);
+                    match rule.and_then(|rule| levels.get(&rule)) {
+                        Some(RuleLevel::Error) => issue.severity = IssueSeverity::Error,
+                        Some(RuleLevel::Warning) => issue.severity = IssueSeverity::Warning,
+                        Some(RuleLevel::Info) => issue.severity = IssueSeverity::Info,
+                        Some(RuleLevel::Off) => return None,
+                        None => {}
+                    }
+                    Some(issue)
+                })
+                .collect(),
+        }
+    }
+
+    /// Keep only the issues located at or below `prefix`
+    pub fn filter_path(&self, prefix: &[PathSegment]) -> ValidationIssues {
+        Self {
+            issues: self
+                .issues
+                .iter()
+                .filter(|issue| {
+                    issue
+                        .path
+                        .as_ref()
+                        .is_some_and(|path| path.starts_with(prefix))
+                })
+                .cloned()
+                .collect(),
+        }
     }
 
     pub fn has_errors(&self) -> bool {
@@ -106,6 +556,13 @@ impl ValidationIssues {
             .count()
     }
 
+    pub fn info_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == IssueSeverity::Info)
+            .count()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.issues.is_empty()
     }
@@ -117,44 +574,31 @@ impl ValidationIssues {
         }
 
         let mut result = String::new();
-        let errors: Vec<_> = self
-            .issues
-            .iter()
-            .filter(|issue| issue.severity == IssueSeverity::Error)
-            .collect();
-        let warnings: Vec<_> = self
-            .issues
-            .iter()
-            .filter(|issue| issue.severity == IssueSeverity::Warning)
-            .collect();
-
-        if !errors.is_empty() {
-            result.push_str(&format!(
-                "Found the following {} error(s) during the validation:\n",
-                errors.len()
-            ));
-            for issue in errors {
-                if let Some(ref context) = issue.context {
-                    result.push_str(&format!("  -  [{}] {}\n", context, issue.message));
-                } else {
-                    result.push_str(&format!("  -  {}\n", issue.message));
-                }
+        for (severity, label) in [
+            (IssueSeverity::Error, "error(s)"),
+            (IssueSeverity::Warning, "warning(s)"),
+            (IssueSeverity::Info, "note(s)"),
+        ] {
+            let issues: Vec<_> = self
+                .issues
+                .iter()
+                .filter(|issue| issue.severity == severity)
+                .collect();
+            if issues.is_empty() {
+                continue;
             }
-        }
-
-        if !warnings.is_empty() {
             if !result.is_empty() {
                 result.push('\n');
             }
             result.push_str(&format!(
-                "Found the following {} warning(s) during the validation:\n",
-                warnings.len()
+                "Found the following {} {label} during the validation:\n",
+                issues.len()
             ));
-            for issue in warnings {
-                if let Some(ref context) = issue.context {
-                    result.push_str(&format!("  -  [{}] {}\n", context, issue.message));
+            for issue in issues {
+                if let Some(ref path) = issue.path {
+                    result.push_str(&format!("  -  [{}] {}\n", path, issue.described()));
                 } else {
-                    result.push_str(&format!("  -  {}\n", issue.message));
+                    result.push_str(&format!("  -  {}\n", issue.described()));
                 }
             }
         }
@@ -165,6 +609,109 @@ impl ValidationIssues {
     pub fn issues(&self) -> &[ValidationIssue] {
         &self.issues
     }
+
+    /// Serialize the issues as a JSON document with error/warning counts and
+    /// one entry per issue
+    pub fn to_json(&self) -> serde_json::Value {
+        let issues: Vec<_> = self
+            .issues
+            .iter()
+            .map(|issue| {
+                serde_json::json!({
+                    "severity": issue.severity.name(),
+                    "message": issue.message,
+                    "path": issue.context(),
+                    "rule": issue.rule.map(|rule| rule.name()),
+                    "reference": issue.reference,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "errors": self.error_count(),
+            "warnings": self.warning_count(),
+            "issues": issues,
+        })
+    }
+
+    /// Serialize the issues as a SARIF 2.1.0 log, located in the metadata
+    /// file `artifact_uri` and, through their path, the node they refer to
+    ///
+    /// Each issue's rule is its `ruleId`, and the rules reported are
+    /// described in the tool's driver.
+    pub fn to_sarif(&self, artifact_uri: &str) -> serde_json::Value {
+        let version = crate::version::get_version();
+        let rules = Rule::ALL
+            .iter()
+            .filter(|rule| self.issues.iter().any(|issue| issue.rule == Some(**rule)))
+            .map(|rule| {
+                serde_json::json!({
+                    "id": rule.name(),
+                    "shortDescription": { "text": rule.description() },
+                })
+            })
+            .collect::<Vec<_>>();
+        let results = self
+            .issues
+            .iter()
+            .map(|issue| {
+                let mut location = serde_json::json!({
+                    "physicalLocation": { "artifactLocation": { "uri": artifact_uri } },
+                });
+                if let Some(context) = issue.context() {
+                    location["logicalLocations"] =
+                        serde_json::json!([{ "fullyQualifiedName": context }]);
+                }
+                let mut result = serde_json::json!({
+                    "level": match issue.severity {
+                        IssueSeverity::Error => "error",
+                        IssueSeverity::Warning => "warning",
+                        IssueSeverity::Info => "note",
+                    },
+                    "message": { "text": issue.described() },
+                    "locations": [location],
+                });
+                if let Some(rule) = issue.rule {
+                    result["ruleId"] = rule.name().into();
+                }
+                result
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": version.app_name,
+                        "version": version.version,
+                        "rules": rules,
+                    },
+                },
+                "results": results,
+            }],
+        })
+    }
+
+    /// Render the issues in the given format, for `artifact_uri` in SARIF
+    ///
+    /// Unlike [`report`](Self::report), the text format says when there
+    /// are no issues.
+    pub fn render(&self, format: ReportFormat, artifact_uri: &str) -> String {
+        match format {
+            ReportFormat::Text if self.is_empty() => {
+                "Validation passed with no issues.".to_string()
+            }
+            ReportFormat::Text => self.report(),
+            ReportFormat::Json => to_pretty_json(&self.to_json()),
+            ReportFormat::Sarif => to_pretty_json(&self.to_sarif(artifact_uri)),
+        }
+    }
+}
+
+fn to_pretty_json(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).expect("JSON values always serialize")
 }
 
 impl Default for ValidationIssues {
@@ -173,118 +720,445 @@ impl Default for ValidationIssues {
     }
 }
 
+impl std::fmt::Display for ValidationIssues {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.report())
+    }
+}
+
+impl std::error::Error for ValidationIssues {}
+
+impl From<ValidationIssues> for Error {
+    fn from(issues: ValidationIssues) -> Self {
+        Error::ValidationFailed(issues.report())
+    }
+}
+
+impl Metadata {
+    /// Run all validation checks against this metadata
+    pub fn validate(&self) -> ValidationIssues {
+        validate_metadata(self)
+    }
+
+    /// Return the metadata if it passes validation without errors, or the
+    /// issues found otherwise (warnings alone do not cause a failure)
+    pub fn into_validated(self) -> std::result::Result<Self, ValidationIssues> {
+        let issues = self.validate();
+        if issues.has_errors() {
+            Err(issues)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
 /// Validate a Croissant metadata file
+#[tracing::instrument(skip_all, fields(path = %file_path.display()))]
 pub fn validate_file(file_path: &Path) -> Result<ValidationIssues> {
+    let metadata = read_metadata(file_path)?;
+    Ok(validate_metadata(&metadata))
+}
+
+/// Validate Croissant metadata given as a JSON-LD string
+pub fn validate_str(content: &str) -> Result<ValidationIssues> {
+    let metadata = parse_json(content)?;
+    Ok(validate_metadata(&metadata))
+}
+
+/// Validate Croissant metadata given as a string, read as YAML when
+/// `file_path`, the file it comes from, has a `.yaml` or `.yml` extension
+/// and as JSON-LD otherwise
+pub fn validate_content(content: &str, file_path: &Path) -> Result<ValidationIssues> {
+    let metadata = parse_metadata(content, file_path)?;
+    Ok(validate_metadata(&metadata))
+}
+
+/// Validate Croissant metadata given as a JSON-LD string and return the
+/// issues in the format of [`ValidationIssues::to_json`]
+///
+/// A document that cannot be parsed is reported as a single error issue,
+/// as from [`ValidationIssues::unparseable`].
+pub fn validate_str_to_json(content: &str) -> serde_json::Value {
+    validate_str(content)
+        .unwrap_or_else(|e| ValidationIssues::unparseable(&e))
+        .to_json()
+}
+
+/// Read and parse a Croissant metadata file
+///
+/// Files with a `.yaml` or `.yml` extension are read as YAML, anything else
+/// as JSON-LD.
+#[tracing::instrument(level = "debug", skip_all, fields(path = %file_path.display()))]
+pub fn read_metadata(file_path: &Path) -> Result<Metadata> {
     let content =
         std::fs::read_to_string(file_path).map_err(|_| Error::file_not_found(file_path))?;
 
-    let metadata: Metadata = serde_json::from_str(&content)?;
+    parse_metadata(&content, file_path)
+}
+
+fn parse_metadata(content: &str, file_path: &Path) -> Result<Metadata> {
+    if is_yaml_path(file_path) {
+        parse_with(serde_yaml::from_str(content), || {
+            serde_yaml::from_str(content).ok()
+        })
+    } else {
+        parse_json(content)
+    }
+}
+
+fn parse_json(content: &str) -> Result<Metadata> {
+    parse_with(serde_json::from_str(content), || {
+        serde_json::from_str(content).ok()
+    })
+}
+
+/// Metadata as parsed directly or, failing that, from the parsed document
+/// through JSON-LD processing; the direct parse's error is kept when both
+/// fail, being about the document as written
+fn parse_with<E: Into<Error>>(
+    parsed: std::result::Result<Metadata, E>,
+    document: impl FnOnce() -> Option<Value>,
+) -> Result<Metadata> {
+    parsed.or_else(|error| {
+        document()
+            .and_then(|document| metadata_from_json_ld(&document).ok())
+            .ok_or_else(|| error.into())
+    })
+}
+
+/// Metadata from a JSON-LD document, whatever context it is written with
+///
+/// The document's `sc:Dataset` node is framed and compacted with the
+/// context rustcroissant writes, which the metadata then has.
+pub fn metadata_from_json_ld(document: &Value) -> Result<Metadata> {
+    let mut context = create_default_context();
+    context.add_file_set_terms();
+    context.add_rai_terms();
+    let context = serde_json::to_value(&context)?;
+
+    let dataset = jsonld::frame(document, "https://schema.org/Dataset")?;
+    let mut dataset = jsonld::compact(&dataset, &context)?;
+    for key in ["distribution", "recordSet"] {
+        as_list(&mut dataset, key);
+    }
+    for record_set in list_mut(&mut dataset, "recordSet") {
+        as_list(record_set, "field");
+        for field in list_mut(record_set, "field") {
+            fields_as_lists(field);
+        }
+    }
+    dataset["@context"] = serde_json::to_value(create_default_context())?;
+
+    let mut metadata: Metadata = serde_json::from_value(dataset)?;
+    if metadata.distribution.iter().any(Distribution::is_file_set) {
+        metadata.context.add_file_set_terms();
+    }
+    if !metadata.rai.is_empty() {
+        metadata.context.add_rai_terms();
+    }
+    Ok(metadata)
+}
+
+/// Put a single value of a list-valued property in a list
+fn as_list(object: &mut Value, key: &str) {
+    if let Some(value) = object.get_mut(key)
+        && !value.is_array()
+    {
+        *value = Value::Array(vec![value.take()]);
+    }
+}
+
+/// Items of a list-valued property, if there are any
+fn list_mut<'a>(object: &'a mut Value, key: &str) -> impl Iterator<Item = &'a mut Value> {
+    object
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+fn fields_as_lists(field: &mut Value) {
+    as_list(field, "nullValues");
+    as_list(field, "subField");
+    for sub_field in list_mut(field, "subField") {
+        fields_as_lists(sub_field);
+    }
+}
+
+/// Validate a Croissant metadata file without blocking the async runtime
+#[cfg(feature = "tokio")]
+#[tracing::instrument(skip_all, fields(path = %file_path.display()))]
+pub async fn validate_file_async(file_path: &Path) -> Result<ValidationIssues> {
+    let content = tokio::fs::read_to_string(file_path)
+        .await
+        .map_err(|_| Error::file_not_found(file_path))?;
+
+    let metadata = parse_metadata(&content, file_path)?;
     Ok(validate_metadata(&metadata))
 }
 
+/// Validate metadata and check that the content of its remote
+/// distributions matches their recorded digest and size, without blocking
+/// the async runtime
+///
+/// At most `max_concurrent` files are downloaded at once; see
+/// [`verify_remote`](crate::croissant::verify::verify_remote).
+#[cfg(feature = "remote")]
+#[tracing::instrument(skip_all, fields(dataset = %metadata.name))]
+pub async fn validate_remote(metadata: &Metadata, max_concurrent: usize) -> ValidationIssues {
+    use crate::croissant::remote::Progress;
+    use crate::croissant::verify::verify_remote;
+
+    let mut issues = validate_metadata(metadata);
+    let report = verify_remote(metadata, max_concurrent, &Progress::default()).await;
+    issues.merge(report.to_issues(metadata));
+    issues
+}
+
 /// Validate Croissant metadata structure
+#[tracing::instrument(skip_all, fields(dataset = %metadata.name))]
 pub fn validate_metadata(metadata: &Metadata) -> ValidationIssues {
     let mut issues = ValidationIssues::new();
 
-    validate_metadata_basic(&mut issues, metadata);
-    validate_distributions(&mut issues, metadata);
-    validate_record_sets(&mut issues, metadata);
-    validate_references(&mut issues, metadata);
+    tracing::debug_span!("validate_metadata_basic")
+        .in_scope(|| validate_metadata_basic(&mut issues, metadata));
+    tracing::debug_span!("validate_distributions")
+        .in_scope(|| validate_distributions(&mut issues, metadata));
+    tracing::debug_span!("validate_record_sets")
+        .in_scope(|| validate_record_sets(&mut issues, metadata));
+    tracing::debug_span!("validate_references")
+        .in_scope(|| validate_references(&mut issues, metadata));
+    tracing::debug_span!("validate_rai").in_scope(|| validate_rai(&mut issues, metadata));
 
+    tracing::info!(
+        errors = issues.error_count(),
+        warnings = issues.warning_count(),
+        "validation finished"
+    );
+    issues
+}
+
+/// Validate the data of every record set by reading all its records
+///
+/// Relative content URLs are resolved against `base_dir`. Record sets
+/// whose records cannot all be read get an error naming the first record
+/// that fails. CSV rows without as many fields as the header are errors
+/// too with [`BadRowPolicy::Error`]; with other policies they are skipped
+/// and counted in a warning.
+#[tracing::instrument(skip_all, fields(dataset = %metadata.name))]
+pub fn validate_data(
+    metadata: &Metadata,
+    base_dir: &Path,
+    on_bad_row: BadRowPolicy,
+) -> ValidationIssues {
+    let mut issues = ValidationIssues::new();
+    let options = RecordOptions {
+        on_bad_row,
+        resolve_references: true,
+        ..RecordOptions::default()
+    };
+    for record_set in &metadata.record_set {
+        let path = || {
+            IssuePath::new()
+                .metadata(&metadata.name)
+                .record_set(&record_set.name)
+        };
+        // Record sets that cannot be joined with those they reference are
+        // still read on their own; the referenced ones report their errors
+        let reader = read_records_with_options(metadata, &record_set.id, base_dir, &options)
+            .or_else(|_| {
+                let options = RecordOptions {
+                    resolve_references: false,
+                    ..options.clone()
+                };
+                read_records_with_options(metadata, &record_set.id, base_dir, &options)
+            });
+        let mut reader = match reader {
+            Ok(reader) => reader,
+            Err(e) => {
+                issues.add_rule_error(
+                    Rule::UnreadableRecords,
+                    format!("Records cannot be read: {e}"),
+                    path(),
+                );
+                continue;
+            }
+        };
+        let mut records = 0usize;
+        for record in reader.by_ref() {
+            if let Err(e) = record {
+                issues.add_rule_error(
+                    Rule::UnreadableRecords,
+                    format!("Records cannot be read: {e}"),
+                    path(),
+                );
+                break;
+            }
+            records += 1;
+        }
+        let bad_rows = reader.bad_rows();
+        if bad_rows > 0 {
+            issues.add_rule_warning(
+                Rule::BadRows,
+                format!("Skipped {bad_rows} CSV rows without as many fields as the header"),
+                path(),
+            );
+        }
+        let unresolved = reader.unresolved_references();
+        if unresolved > 0 {
+            issues.add_rule_error(
+                Rule::ReferentialIntegrity,
+                format!(
+                    "{unresolved} values reference no record of the record set their field references"
+                ),
+                path(),
+            );
+        }
+        tracing::debug!(record_set = %record_set.name, records, bad_rows, "read records");
+    }
     issues
 }
 
 fn validate_metadata_basic(issues: &mut ValidationIssues, metadata: &Metadata) {
-    let context = format!("Metadata({})", metadata.name);
+    let path = || IssuePath::new().metadata(&metadata.name);
 
     // Validate required fields
     if metadata.name.is_empty() {
-        issues.add_error_with_context(
+        issues.add_rule_error(
+            Rule::MandatoryProperty,
             "Property \"https://schema.org/name\" is mandatory, but does not exist.",
-            &context,
+            path(),
         );
     }
 
     // Validate type
     if metadata.type_ != "sc:Dataset" {
-        issues.add_error_with_context(
+        issues.add_rule_error(
+            Rule::NodeType,
             "The current JSON-LD doesn't extend https://schema.org/Dataset.",
-            &context,
+            path(),
         );
     }
 
     // Validate conformsTo is set
     if metadata.conforms_to.is_empty() {
-        issues.add_warning_with_context(
+        issues.add_rule_warning(
+            Rule::RecommendedProperty,
             "Property \"http://purl.org/dc/terms/conformsTo\" is recommended, but does not exist.",
-            &context,
+            path(),
         );
     }
 
     // Validate description
     if metadata.description.is_empty() {
-        issues.add_warning_with_context(
+        issues.add_rule_warning(
+            Rule::RecommendedProperty,
             "Property \"https://schema.org/description\" is recommended, but does not exist.",
-            &context,
+            path(),
         );
     }
 }
 
 fn validate_distributions(issues: &mut ValidationIssues, metadata: &Metadata) {
     for distribution in &metadata.distribution {
-        let context = format!(
-            "Metadata({}) > FileObject({})",
-            metadata.name, distribution.name
-        );
+        let path = || {
+            IssuePath::new()
+                .metadata(&metadata.name)
+                .file_object(&distribution.name)
+        };
 
         // Validate required fields
         if distribution.name.is_empty() {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::MandatoryProperty,
                 "Property \"https://schema.org/name\" is mandatory, but does not exist.",
-                &context,
+                path(),
             );
         }
 
         // Validate type
         if distribution.type_ != "cr:FileObject" && distribution.type_ != "cr:FileSet" {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::NodeType,
                 format!(
                     "\"{}\" should have an attribute \"@type\": \"http://mlcommons.org/croissant/FileObject\" or \"@type\": \"http://mlcommons.org/croissant/FileSet\". Got {} instead.",
                     distribution.name,
                     distribution.type_
                 ),
-                &context
+                path()
             );
         }
 
+        if distribution.is_file_set() {
+            validate_file_set(issues, distribution, &path);
+            continue;
+        }
+
         // Validate content URL
         if distribution.content_url.is_empty() {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::MandatoryProperty,
                 "Property \"https://schema.org/contentUrl\" is mandatory, but does not exist.",
-                &context,
+                path(),
             );
         }
 
         // Validate encoding format
         if distribution.encoding_format.is_empty() {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::MandatoryProperty,
                 "Property \"https://schema.org/encodingFormat\" is mandatory, but does not exist.",
-                &context,
+                path(),
             );
         }
 
         // Validate SHA256
         if distribution.sha256.is_empty() {
-            issues.add_warning_with_context(
+            issues.add_rule_warning(
+                Rule::MissingChecksum,
                 "Property \"https://schema.org/sha256\" is recommended for file integrity verification.",
-                &context
+                path()
             );
         } else if distribution.sha256.len() != 64
             || !distribution.sha256.chars().all(|c| c.is_ascii_hexdigit())
         {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::ChecksumFormat,
                 "Invalid SHA256 hash format. Expected 64 hexadecimal characters.",
-                &context,
+                path(),
+            );
+        }
+    }
+}
+
+/// File sets have no content URL or digest of their own, but glob patterns
+/// of the files they hold
+fn validate_file_set(
+    issues: &mut ValidationIssues,
+    file_set: &Distribution,
+    path: &dyn Fn() -> IssuePath,
+) {
+    if file_set.encoding_format.is_empty() {
+        issues.add_rule_error(
+            Rule::MandatoryProperty,
+            "Property \"https://schema.org/encodingFormat\" is mandatory, but does not exist.",
+            path(),
+        );
+    }
+
+    if file_set.includes.is_empty() {
+        issues.add_rule_error(
+            Rule::MandatoryProperty,
+            "Property \"http://mlcommons.org/croissant/includes\" is mandatory, but does not exist.",
+            path(),
+        );
+    }
+    for pattern in file_set.includes.iter().chain(&file_set.excludes) {
+        if glob_regex(pattern).is_err() {
+            issues.add_rule_error(
+                Rule::PropertyValue,
+                format!("Invalid glob pattern: {pattern}"),
+                path(),
             );
         }
     }
@@ -292,90 +1166,119 @@ fn validate_distributions(issues: &mut ValidationIssues, metadata: &Metadata) {
 
 fn validate_record_sets(issues: &mut ValidationIssues, metadata: &Metadata) {
     for record_set in &metadata.record_set {
-        let context = format!(
-            "Metadata({}) > RecordSet({})",
-            metadata.name, record_set.name
-        );
+        let path = || {
+            IssuePath::new()
+                .metadata(&metadata.name)
+                .record_set(&record_set.name)
+        };
 
         // Validate required fields
         if record_set.name.is_empty() {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::MandatoryProperty,
                 "Property \"https://schema.org/name\" is mandatory, but does not exist.",
-                &context,
+                path(),
             );
         }
 
         // Validate type
         if record_set.type_ != "cr:RecordSet" {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::NodeType,
                 format!(
                     "\"{}\" should have an attribute \"@type\": \"http://mlcommons.org/croissant/RecordSet\". Got {} instead.",
                     record_set.name,
                     record_set.type_
                 ),
-                &context
+                path()
             );
         }
 
         // Validate fields
-        validate_fields(issues, metadata, record_set);
+        validate_fields(issues, metadata, record_set, &record_set.field);
     }
 }
 
-fn validate_fields(issues: &mut ValidationIssues, metadata: &Metadata, record_set: &RecordSet) {
-    for field in &record_set.field {
-        let context = format!(
-            "Metadata({}) > RecordSet({}) > Field({})",
-            metadata.name, record_set.name, field.name
-        );
+fn validate_fields(
+    issues: &mut ValidationIssues,
+    metadata: &Metadata,
+    record_set: &RecordSet,
+    fields: &[Field],
+) {
+    for field in fields {
+        let path = || {
+            IssuePath::new()
+                .metadata(&metadata.name)
+                .record_set(&record_set.name)
+                .field(&field.name)
+        };
 
         // Validate required fields
         if field.name.is_empty() {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::MandatoryProperty,
                 "Property \"https://schema.org/name\" is mandatory, but does not exist.",
-                &context,
+                path(),
             );
         }
 
         // Validate type
         if field.type_ != "cr:Field" {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::NodeType,
                 format!(
                     "\"{}\" should have an attribute \"@type\": \"http://mlcommons.org/croissant/Field\". Got {} instead.",
                     field.name,
                     field.type_
                 ),
-                &context
+                path()
             );
         }
 
+        // Fields with subfields take their values, and so their types and
+        // sources, from them
+        if !field.sub_field.is_empty() {
+            validate_fields(issues, metadata, record_set, &field.sub_field);
+            continue;
+        }
+
         // Validate data type
         if field.data_type.is_empty() {
-            issues.add_error_with_context(
+            issues.add_rule_error(
+                Rule::MissingDataType,
                 format!(
                     "The field does not specify a valid http://mlcommons.org/croissant/dataType, neither does any of its predecessor. Got: {}",
                     field.data_type
                 ),
-                &context
+                path()
             );
         } else {
-            validate_data_type(&field.data_type, issues, &context);
+            validate_data_type(&field.data_type, issues, &path);
         }
 
         // Validate source
-        if field.source.extract.column.is_empty() || field.source.file_object.id.is_empty() {
-            issues.add_error_with_context(
+        let has_source = field.source.as_ref().is_some_and(|source| {
+            (!source.extract.column.is_empty() || source.extract.file_property.is_some())
+                && !source.distribution_id().is_empty()
+        });
+        if !has_source && record_set.data.is_none() {
+            issues.add_rule_error(
+                Rule::MissingSource,
                 format!(
                     "Node \"{}\" is a field and has no source. Please, use http://mlcommons.org/croissant/source to specify the source.",
                     field.id
                 ),
-                &context
+                path()
             );
         }
     }
 }
 
-fn validate_data_type(data_type: &str, issues: &mut ValidationIssues, context: &str) {
+fn validate_data_type(
+    data_type: &str,
+    issues: &mut ValidationIssues,
+    path: &dyn Fn() -> IssuePath,
+) {
     let valid_types = [
         "sc:Text",
         "sc:Integer",
@@ -386,14 +1289,113 @@ fn validate_data_type(data_type: &str, issues: &mut ValidationIssues, context: &
         "sc:Time",
         "sc:URL",
         "sc:Number",
+        "sc:ImageObject",
+        "sc:AudioObject",
+        "sc:VideoObject",
+        "cr:Split",
     ];
 
     if !valid_types.contains(&data_type) {
-        issues.add_warning_with_context(
+        issues.add_rule_warning(
+            Rule::UnknownDataType,
             format!("Unknown data type: {data_type}. Consider using a standard schema.org type."),
-            context,
+            path(),
+        );
+    }
+}
+
+/// Properties of the Responsible AI extension recommended for datasets
+/// that have any
+const RECOMMENDED_RAI_PROPERTIES: &[&str] = &[
+    "dataCollection",
+    "dataCollectionType",
+    "dataBiases",
+    "dataLimitations",
+    "dataUseCases",
+    "personalSensitiveInformation",
+];
+
+/// Check the Responsible AI properties against the RAI 1.0 extension, when
+/// the dataset has any
+fn validate_rai(issues: &mut ValidationIssues, metadata: &Metadata) {
+    let rai = &metadata.rai;
+    if rai.is_empty() {
+        return;
+    }
+    let path = || IssuePath::new().metadata(&metadata.name);
+
+    match &metadata.context.rai {
+        None => issues.add_rule_error(
+            Rule::ResponsibleAi,
+            "Responsible AI properties are used, but the context does not define the rai prefix.",
+            path(),
+        ),
+        Some(iri) if iri != RAI_NAMESPACE => issues.add_rule_error(
+            Rule::ResponsibleAi,
+            format!("The rai prefix is {iri}, expected {RAI_NAMESPACE}."),
+            path(),
+        ),
+        Some(_) => {}
+    }
+
+    let properties = rai.properties();
+    for (name, values) in &properties {
+        if values.is_empty() && RECOMMENDED_RAI_PROPERTIES.contains(name) {
+            issues.add_rule_warning(
+                Rule::ResponsibleAi,
+                format!("Property \"{RAI_NAMESPACE}{name}\" is recommended, but does not exist."),
+                path(),
+            );
+        }
+        if values.iter().any(|value| value.trim().is_empty()) {
+            issues.add_rule_warning(
+                Rule::ResponsibleAi,
+                format!("Property \"{RAI_NAMESPACE}{name}\" has an empty value."),
+                path(),
+            );
+        }
+    }
+
+    for value in &rai.data_collection_timeframe {
+        if !is_date(value) {
+            issues.add_rule_error(
+                Rule::ResponsibleAi,
+                format!(
+                    "Property \"{RAI_NAMESPACE}dataCollectionTimeframe\" has value {value}, which is not an ISO 8601 date or datetime."
+                ),
+                path(),
+            );
+        }
+    }
+    if rai.data_collection_timeframe.len() > 2 {
+        issues.add_rule_warning(
+            Rule::ResponsibleAi,
+            format!(
+                "Property \"{RAI_NAMESPACE}dataCollectionTimeframe\" has {} values, expected a start and an end.",
+                rai.data_collection_timeframe.len()
+            ),
+            path(),
         );
     }
+
+    let annotated = rai.annotations_per_item.is_some()
+        || !rai.annotator_demographics.is_empty()
+        || !rai.data_annotation_platform.is_empty()
+        || !rai.data_annotation_analysis.is_empty();
+    if annotated && rai.data_annotation_protocol.is_none() {
+        issues.add_rule_warning(
+            Rule::ResponsibleAi,
+            format!(
+                "Annotations are described, but property \"{RAI_NAMESPACE}dataAnnotationProtocol\" does not exist."
+            ),
+            path(),
+        );
+    }
+}
+
+/// A data type as written by rustcroissant, such as `sc:Text` for `Text`
+fn canonical_data_type(data_type: &str) -> &str {
+    DataType::from_schema_org(data_type).map_or(data_type, |data_type| data_type.to_schema_org())
 }
 
 fn validate_references(issues: &mut ValidationIssues, metadata: &Metadata) {
@@ -404,19 +1406,111 @@ fn validate_references(issues: &mut ValidationIssues, metadata: &Metadata) {
         .map(|dist| dist.id.as_str())
         .collect();
 
+    // Validate the distributions files are contained in
+    for distribution in &metadata.distribution {
+        for container in &distribution.contained_in {
+            if !distribution_ids.contains(container.id.as_str()) {
+                issues.add_rule_error(
+                    Rule::DanglingReference,
+                    format!(
+                        "Distribution is contained in non-existent file object: {}",
+                        container.id
+                    ),
+                    IssuePath::new()
+                        .metadata(&metadata.name)
+                        .file_object(&distribution.name),
+                );
+            }
+        }
+    }
+
     // Validate field references to file objects
     for record_set in &metadata.record_set {
         for field in &record_set.field {
-            let file_object_id = &field.source.file_object.id;
-            if !file_object_id.is_empty() && !distribution_ids.contains(file_object_id.as_str()) {
-                let context = format!(
-                    "Metadata({}) > RecordSet({}) > Field({})",
-                    metadata.name, record_set.name, field.name
-                );
-                issues.add_error_with_context(
+            let Some(source) = &field.source else {
+                continue;
+            };
+            let path = || {
+                IssuePath::new()
+                    .metadata(&metadata.name)
+                    .record_set(&record_set.name)
+                    .field(&field.name)
+            };
+            let file_object_id = source.distribution_id();
+            match metadata
+                .distribution
+                .iter()
+                .find(|distribution| distribution.id == file_object_id)
+            {
+                None if !file_object_id.is_empty() => issues.add_rule_error(
+                    Rule::DanglingReference,
                     format!("Field references non-existent file object: {file_object_id}"),
-                    &context,
-                );
+                    path(),
+                ),
+                Some(distribution) if distribution.is_file_set() != source.file_set.is_some() => {
+                    let (property, kind) = if distribution.is_file_set() {
+                        ("fileObject", "FileSet")
+                    } else {
+                        ("fileSet", "FileObject")
+                    };
+                    issues.add_rule_error(
+                        Rule::NodeType,
+                        format!(
+                            "Field source refers to {file_object_id} as a {property}, but it is a {kind}"
+                        ),
+                        path(),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Validate field references to other fields
+    fn collect<'a>(fields: &'a [Field], by_id: &mut BTreeMap<&'a str, &'a Field>) {
+        for field in fields {
+            by_id.insert(field.id.as_str(), field);
+            collect(&field.sub_field, by_id);
+        }
+    }
+    let mut fields_by_id = BTreeMap::new();
+    for record_set in &metadata.record_set {
+        collect(&record_set.field, &mut fields_by_id);
+    }
+    for record_set in &metadata.record_set {
+        for field in &record_set.field {
+            let Some(reference) = &field.references else {
+                continue;
+            };
+            let path = || {
+                IssuePath::new()
+                    .metadata(&metadata.name)
+                    .record_set(&record_set.name)
+                    .field(&field.name)
+            };
+            let target = &reference.field.id;
+            match fields_by_id.get(target.as_str()) {
+                None => issues.add_rule_error(
+                    Rule::DanglingReference,
+                    format!("Field references non-existent field: {target}"),
+                    path(),
+                ),
+                Some(referenced)
+                    if !field.data_type.is_empty()
+                        && !referenced.data_type.is_empty()
+                        && canonical_data_type(&field.data_type)
+                            != canonical_data_type(&referenced.data_type) =>
+                {
+                    issues.add_rule_warning(
+                        Rule::ReferentialIntegrity,
+                        format!(
+                            "Field has dataType {}, but the field it references, {target}, has dataType {}",
+                            field.data_type, referenced.data_type
+                        ),
+                        path(),
+                    );
+                }
+                _ => {}
             }
         }
     }
@@ -0,0 +1,417 @@
+//! Verification of distributions against the files they describe
+//!
+//! Each distribution's file is hashed and compared with its recorded
+//! SHA-256 digest and, when it is given as an exact byte count, its content
+//! size. Local files are resolved against the metadata's directory; remote
+//! files are only fetched when asked for, since they may be large. They are
+//! fetched with the built-in clients of the `remote` and object storage
+//! features, or through a [`Fetcher`] of the caller's.
+//!
+//! A [`VerifyReport`] can be turned into validation issues, so differences
+//! are reported alongside the other problems of the metadata.
+
+use crate::croissant::core::{Distribution, Metadata};
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::{
+    calculate_sha256_many, is_ftp_url, is_object_store_url, parallel_map, require_url_support,
+};
+use crate::croissant::validate::{IssuePath, Rule, ValidationIssue, ValidationIssues};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Source of remote content, replacing the built-in clients, for instance
+/// to go through a proxy, read from a mirror or serve content in tests
+pub trait Fetcher: Send + Sync {
+    /// The content at a URL, read as a stream
+    fn open(&self, url: &str) -> Result<Box<dyn Read + Send>>;
+}
+
+impl fmt::Debug for dyn Fetcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Fetcher")
+    }
+}
+
+/// Options controlling verification
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions {
+    /// Also fetch and hash remote (HTTP(S), object storage, SFTP and FTP)
+    /// content; requires the `remote` feature
+    pub remote: bool,
+    /// Maximum number of local files hashed in parallel, and of remote
+    /// files fetched at once (`None`: the available parallelism, and
+    /// [`DEFAULT_MAX_CONCURRENT_FETCHES`](crate::croissant::remote::DEFAULT_MAX_CONCURRENT_FETCHES)
+    /// fetches)
+    pub max_threads: Option<usize>,
+    /// Keep downloaded HTTP(S) files in this cache instead of only
+    /// streaming them through the hasher
+    #[cfg(feature = "remote")]
+    pub download_cache: Option<crate::croissant::remote::download::DownloadCache>,
+    /// Counts the bytes and files fetched, for reporting the progress of
+    /// long remote verifications
+    #[cfg(feature = "remote")]
+    pub progress: Option<crate::croissant::remote::Progress>,
+    /// Fetch remote content, of any URL scheme, through this rather than
+    /// the built-in clients; works without the `remote` feature
+    pub fetcher: Option<Arc<dyn Fetcher>>,
+}
+
+/// Result of checking one distribution
+#[derive(Debug)]
+pub enum VerifyOutcome {
+    /// The file matches the recorded digest and size
+    Verified,
+    /// The file differs from the metadata, with a message per difference
+    Mismatch(Vec<String>),
+    /// The file was not checked, with the reason
+    Skipped(String),
+    /// The file could not be read or fetched
+    Failed(Error),
+}
+
+/// Check of a single distribution
+#[derive(Debug)]
+pub struct DistributionCheck {
+    pub id: String,
+    pub content_url: String,
+    pub outcome: VerifyOutcome,
+}
+
+/// Checks of every distribution of a dataset, in distribution order
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub checks: Vec<DistributionCheck>,
+}
+
+impl VerifyReport {
+    /// Distributions that do not match their files, or whose files cannot
+    /// be read, as validation errors
+    pub fn to_issues(&self, metadata: &Metadata) -> ValidationIssues {
+        let mut issues = ValidationIssues::new();
+        for (check, distribution) in self.checks.iter().zip(&metadata.distribution) {
+            let message = match &check.outcome {
+                VerifyOutcome::Mismatch(differences) => format!(
+                    "Content of {} does not match the metadata: {}",
+                    check.content_url,
+                    differences.join("; ")
+                ),
+                VerifyOutcome::Failed(e) => {
+                    format!("Content of {} cannot be checked: {e}", check.content_url)
+                }
+                VerifyOutcome::Verified | VerifyOutcome::Skipped(_) => continue,
+            };
+            issues.push(
+                ValidationIssue::error(message)
+                    .with_path(
+                        IssuePath::new()
+                            .metadata(&metadata.name)
+                            .file_object(&distribution.name),
+                    )
+                    .with_rule(Rule::ContentMismatch),
+            );
+        }
+        issues
+    }
+
+    /// Whether any distribution did not match or could not be checked
+    /// because of an error
+    pub fn has_failures(&self) -> bool {
+        self.checks.iter().any(|check| {
+            matches!(
+                check.outcome,
+                VerifyOutcome::Mismatch(_) | VerifyOutcome::Failed(_)
+            )
+        })
+    }
+}
+
+/// Where a distribution's content is read from
+enum Location {
+    Local(PathBuf),
+    Remote,
+    Unsupported(String),
+}
+
+fn locate(content_url: &str, base_dir: &Path) -> Location {
+    if content_url.starts_with("http://") || content_url.starts_with("https://") {
+        return Location::Remote;
+    }
+    if is_object_store_url(content_url) || is_ftp_url(content_url) {
+        return match require_url_support(content_url) {
+            Ok(()) => Location::Remote,
+            Err(e) => Location::Unsupported(e.to_string()),
+        };
+    }
+    if content_url.contains("://") {
+        return Location::Unsupported(format!("Unsupported content URL scheme: {content_url}"));
+    }
+    Location::Local(base_dir.join(content_url))
+}
+
+/// Verify every distribution of the metadata
+///
+/// Relative content URLs are resolved against `base_dir`.
+#[tracing::instrument(skip_all, fields(distributions = metadata.distribution.len()))]
+pub fn verify_metadata(
+    metadata: &Metadata,
+    base_dir: &Path,
+    options: &VerifyOptions,
+) -> VerifyReport {
+    let mut outcomes: Vec<Option<VerifyOutcome>> = Vec::new();
+    let mut local = Vec::new();
+    let mut remote = Vec::new();
+    for (index, distribution) in metadata.distribution.iter().enumerate() {
+        let outcome = if distribution.is_file_set() {
+            Some(VerifyOutcome::Skipped(
+                "file sets have no digest of their own".to_string(),
+            ))
+        } else if distribution.sha256.is_empty() {
+            Some(VerifyOutcome::Skipped("no sha256 recorded".to_string()))
+        } else {
+            let location = match locate(&distribution.content_url, base_dir) {
+                Location::Unsupported(_) if options.fetcher.is_some() => Location::Remote,
+                location => location,
+            };
+            match location {
+                Location::Local(path) => {
+                    local.push((index, path));
+                    None
+                }
+                Location::Remote if options.remote => {
+                    remote.push((index, distribution.content_url.clone()));
+                    None
+                }
+                Location::Remote => Some(VerifyOutcome::Skipped(
+                    "remote content is only checked with --remote".to_string(),
+                )),
+                Location::Unsupported(reason) => Some(VerifyOutcome::Failed(Error::new(reason))),
+            }
+        };
+        outcomes.push(outcome);
+    }
+
+    let paths = local.iter().map(|(_, path)| path).collect::<Vec<_>>();
+    let digests = calculate_sha256_many(&paths, options.max_threads);
+    for ((index, path), digest) in local.iter().zip(digests) {
+        let distribution = &metadata.distribution[*index];
+        outcomes[*index] = Some(
+            digest
+                .and_then(|sha256| Ok((sha256, std::fs::metadata(path)?.len())))
+                .map_or_else(VerifyOutcome::Failed, |(sha256, size)| {
+                    compare(distribution, &sha256, size)
+                }),
+        );
+    }
+
+    if !remote.is_empty() {
+        let urls = remote
+            .iter()
+            .map(|(_, url)| url.clone())
+            .collect::<Vec<_>>();
+        #[cfg(feature = "remote")]
+        if let Some(progress) = &options.progress {
+            let distributions = remote
+                .iter()
+                .map(|(index, _)| &metadata.distribution[*index]);
+            let (files, bytes) = transfer_size(distributions);
+            progress.set_totals(files, bytes);
+        }
+        let digests = match &options.fetcher {
+            Some(fetcher) => parallel_map(&urls, options.max_threads, |url| {
+                fetch_with(fetcher.as_ref(), url)
+            }),
+            None => fetch_remote(&urls, options),
+        };
+        for ((index, _), digest) in remote.iter().zip(digests) {
+            let distribution = &metadata.distribution[*index];
+            outcomes[*index] = Some(digest.map_or_else(VerifyOutcome::Failed, |(sha256, size)| {
+                compare(distribution, &sha256, size)
+            }));
+        }
+    }
+
+    let checks = metadata
+        .distribution
+        .iter()
+        .zip(outcomes)
+        .map(|(distribution, outcome)| DistributionCheck {
+            id: distribution.id.clone(),
+            content_url: distribution.content_url.clone(),
+            outcome: outcome.expect("every distribution is checked"),
+        })
+        .collect();
+    VerifyReport { checks }
+}
+
+/// Verify the remote distributions of the metadata on the caller's async
+/// runtime
+///
+/// Like [`verify_metadata`] with [`VerifyOptions::remote`], but files are
+/// fetched with [`fetch_sha256_many`](crate::croissant::remote::fetch_sha256_many)
+/// on the running runtime instead of one started for the purpose, which
+/// async code cannot do. Local files are skipped.
+#[cfg(feature = "remote")]
+#[tracing::instrument(skip_all, fields(distributions = metadata.distribution.len()))]
+pub async fn verify_remote(
+    metadata: &Metadata,
+    max_concurrent: usize,
+    progress: &crate::croissant::remote::Progress,
+) -> VerifyReport {
+    use crate::croissant::remote::fetch_sha256_many;
+
+    let mut outcomes: Vec<Option<VerifyOutcome>> = Vec::new();
+    let mut remote = Vec::new();
+    for (index, distribution) in metadata.distribution.iter().enumerate() {
+        let outcome = if distribution.is_file_set() {
+            Some(VerifyOutcome::Skipped(
+                "file sets have no digest of their own".to_string(),
+            ))
+        } else if distribution.sha256.is_empty() {
+            Some(VerifyOutcome::Skipped("no sha256 recorded".to_string()))
+        } else {
+            match locate(&distribution.content_url, Path::new("")) {
+                Location::Remote => {
+                    remote.push((index, distribution.content_url.clone()));
+                    None
+                }
+                Location::Local(_) => Some(VerifyOutcome::Skipped(
+                    "local content is checked by verify_metadata".to_string(),
+                )),
+                Location::Unsupported(reason) => Some(VerifyOutcome::Failed(Error::new(reason))),
+            }
+        };
+        outcomes.push(outcome);
+    }
+
+    let urls = remote
+        .iter()
+        .map(|(_, url)| url.clone())
+        .collect::<Vec<_>>();
+    let (files, bytes) = transfer_size(
+        remote
+            .iter()
+            .map(|(index, _)| &metadata.distribution[*index]),
+    );
+    progress.set_totals(files, bytes);
+    let digests = fetch_sha256_many(&urls, max_concurrent, progress).await;
+    for ((index, _), digest) in remote.iter().zip(digests) {
+        let distribution = &metadata.distribution[*index];
+        outcomes[*index] = Some(digest.map_or_else(VerifyOutcome::Failed, |digest| {
+            compare(distribution, &digest.sha256, digest.content_size)
+        }));
+    }
+
+    let checks = metadata
+        .distribution
+        .iter()
+        .zip(outcomes)
+        .map(|(distribution, outcome)| DistributionCheck {
+            id: distribution.id.clone(),
+            content_url: distribution.content_url.clone(),
+            outcome: outcome.expect("every distribution is checked"),
+        })
+        .collect();
+    VerifyReport { checks }
+}
+
+/// Compare a file's digest and size with its distribution
+fn compare(distribution: &Distribution, sha256: &str, size: u64) -> VerifyOutcome {
+    let mut differences = Vec::new();
+    if !distribution.sha256.eq_ignore_ascii_case(sha256) {
+        differences.push(format!(
+            "sha256 is {sha256}, expected {}",
+            distribution.sha256
+        ));
+    }
+    if let Some(expected) = distribution.content_size_bytes()
+        && expected != size
+    {
+        differences.push(format!("size is {size} B, expected {expected} B"));
+    }
+
+    if differences.is_empty() {
+        VerifyOutcome::Verified
+    } else {
+        VerifyOutcome::Mismatch(differences)
+    }
+}
+
+/// Number of distinct files of the distributions, and their total size if
+/// every distribution gives it in bytes
+pub fn transfer_size<'a>(
+    distributions: impl IntoIterator<Item = &'a Distribution>,
+) -> (usize, Option<u64>) {
+    let mut urls = std::collections::HashSet::new();
+    let mut bytes = Some(0u64);
+    for distribution in distributions {
+        if urls.insert(distribution.content_url.as_str()) {
+            bytes = bytes
+                .zip(distribution.content_size_bytes())
+                .map(|(a, b)| a + b);
+        }
+    }
+    (urls.len(), bytes)
+}
+
+/// SHA-256 digest and size of a remote file read through a fetcher
+fn fetch_with(fetcher: &dyn Fetcher, url: &str) -> Result<(String, u64)> {
+    let mut content = fetcher.open(url)?;
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut content, &mut hasher)
+        .map_err(|e| Error::new(format!("Error reading {url}: {e}")))?;
+    Ok((hex::encode(hasher.finalize()), size))
+}
+
+/// SHA-256 digest and size of each remote file
+#[cfg(feature = "remote")]
+fn fetch_remote(urls: &[String], options: &VerifyOptions) -> Vec<Result<(String, u64)>> {
+    use crate::croissant::remote::{DEFAULT_MAX_CONCURRENT_FETCHES, fetch_sha256_many};
+
+    let max_concurrent = options
+        .max_threads
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_FETCHES);
+    let progress = options.progress.clone().unwrap_or_default();
+
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            return urls
+                .iter()
+                .map(|url| Err(Error::remote(url, e.to_string())))
+                .collect();
+        }
+    };
+    let digests = runtime.block_on(async {
+        match &options.download_cache {
+            Some(cache) => {
+                cache
+                    .fetch_sha256_many(urls, max_concurrent, &progress)
+                    .await
+            }
+            None => fetch_sha256_many(urls, max_concurrent, &progress).await,
+        }
+    });
+    digests
+        .into_iter()
+        .map(|digest| digest.map(|digest| (digest.sha256, digest.content_size)))
+        .collect()
+}
+
+/// SHA-256 digest and size of each remote file; always fails, since
+/// rustcroissant was built without the `remote` feature
+#[cfg(not(feature = "remote"))]
+fn fetch_remote(urls: &[String], _options: &VerifyOptions) -> Vec<Result<(String, u64)>> {
+    urls.iter()
+        .map(|_| {
+            Err(Error::new(
+                "Remote verification requires rustcroissant to be built with the remote feature",
+            ))
+        })
+        .collect()
+}
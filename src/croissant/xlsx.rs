@@ -0,0 +1,79 @@
+//! Excel workbooks: sheets of workbooks to describe
+//!
+//! Each worksheet is described as a table: its first row gives the column
+//! headers, and the cells below are sampled for type inference like the
+//! values of a CSV file. Numbers Excel stores as whole floats are taken for
+//! integers, and date cells for dates, or datetimes when they have a time
+//! of day. Empty sheets are skipped.
+
+use crate::croissant::core::InferenceOptions;
+use crate::croissant::errors::{Error, Result};
+use crate::croissant::utils::{CsvSchema, infer_schema_from_rows};
+use calamine::{Data, Reader, Xlsx, XlsxError, open_workbook};
+use std::path::Path;
+
+/// encodingFormat of Excel workbook distributions
+pub const XLSX_ENCODING_FORMAT: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Name and columns of each non-empty worksheet of a workbook, in workbook
+/// order
+pub(crate) fn read_xlsx_schemas(
+    path: &Path,
+    options: &InferenceOptions,
+) -> Result<Vec<(String, CsvSchema)>> {
+    if !path.is_file() {
+        return Err(Error::file_not_found(path));
+    }
+    let mut workbook: Xlsx<_> = open_workbook(path).map_err(|e: XlsxError| {
+        Error::invalid_format(format!("{} is not an Excel workbook: {e}", path.display()))
+    })?;
+
+    let mut schemas = Vec::new();
+    for sheet in workbook.sheet_names() {
+        let range = workbook.worksheet_range(&sheet).map_err(|e| {
+            Error::invalid_format(format!(
+                "Sheet {sheet} of {} cannot be read: {e}",
+                path.display()
+            ))
+        })?;
+        let mut rows = range.rows();
+        let Some(header) = rows.next() else {
+            tracing::debug!(sheet, "skipping empty sheet");
+            continue;
+        };
+        let headers = header
+            .iter()
+            .map(|cell| cell_text(cell).trim().to_string())
+            .collect();
+        let rows = rows.map(|row| row.iter().map(cell_text).collect());
+        schemas.push((sheet, infer_schema_from_rows(headers, rows, options)));
+    }
+    if schemas.is_empty() {
+        return Err(Error::invalid_format(format!(
+            "{} has no sheet with a header row",
+            path.display()
+        )));
+    }
+    Ok(schemas)
+}
+
+/// A cell's value as text, blank for empty and error cells
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(text) | Data::DateTimeIso(text) | Data::DurationIso(text) => text.clone(),
+        Data::Int(n) => n.to_string(),
+        Data::Float(n) if n.fract() == 0.0 && n.abs() < 2f64.powi(53) => (*n as i64).to_string(),
+        Data::Float(n) => n.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(datetime) if datetime.is_duration() => datetime.as_f64().to_string(),
+        Data::DateTime(datetime) => match datetime.as_datetime() {
+            Some(datetime) if datetime.time() == chrono::NaiveTime::MIN => {
+                datetime.format("%Y-%m-%d").to_string()
+            }
+            Some(datetime) => datetime.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            None => datetime.as_f64().to_string(),
+        },
+    }
+}
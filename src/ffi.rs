@@ -0,0 +1,152 @@
+//! C FFI bindings
+//!
+//! All returned strings are allocated by Rust and must be released with
+//! `rustcroissant_free_string`. Functions returning `NULL` record an error
+//! message that can be retrieved with `rustcroissant_last_error`; a panic
+//! does not unwind into the caller, but is reported the same way. The C
+//! declarations live in `include/rustcroissant.h`.
+
+use crate::croissant::generate::{GenerateOptions, generate_metadata_from_csv, metadata_to_json};
+use crate::croissant::validate::validate_str_to_json;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::path::Path;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message.into()));
+}
+
+/// Run the body of an exported function, turning a panic into a NULL
+/// result with its message as the last error, since unwinding across the
+/// C boundary aborts the process
+fn catch_panic(body: impl FnOnce() -> *mut c_char + std::panic::UnwindSafe) -> *mut c_char {
+    std::panic::catch_unwind(body).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown cause".to_string());
+        set_last_error(format!("rustcroissant panicked: {message}"));
+        std::ptr::null_mut()
+    })
+}
+
+/// Convert a Rust string into a C string owned by the caller
+fn into_c_string(value: String) -> *mut c_char {
+    match CString::new(value) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => {
+            set_last_error("result contains an interior NUL byte");
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Borrow a C string argument as UTF-8
+///
+/// # Safety
+///
+/// `value` must be NULL or a valid NUL-terminated string.
+unsafe fn borrow_str<'a>(value: *const c_char, name: &str) -> Option<&'a str> {
+    if value.is_null() {
+        set_last_error(format!("{name} is NULL"));
+        return None;
+    }
+    // SAFETY: non-NULL and NUL-terminated per the caller's contract
+    match unsafe { CStr::from_ptr(value) }.to_str() {
+        Ok(text) => Some(text),
+        Err(_) => {
+            set_last_error(format!("{name} is not valid UTF-8"));
+            None
+        }
+    }
+}
+
+/// Generate Croissant metadata for a CSV file
+///
+/// Returns the metadata as a JSON-LD string, or NULL on error. When
+/// `output_path` is not NULL the metadata is also written to that file.
+///
+/// # Safety
+///
+/// `csv_path` must be a valid NUL-terminated string; `output_path` must be
+/// NULL or a valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rustcroissant_generate_from_csv(
+    csv_path: *const c_char,
+    output_path: *const c_char,
+) -> *mut c_char {
+    catch_panic(|| {
+        // SAFETY: forwarded from this function's contract
+        let Some(csv_path) = (unsafe { borrow_str(csv_path, "csv_path") }) else {
+            return std::ptr::null_mut();
+        };
+        let output_path = if output_path.is_null() {
+            None
+        } else {
+            // SAFETY: forwarded from this function's contract
+            match unsafe { borrow_str(output_path, "output_path") } {
+                Some(path) => Some(Path::new(path)),
+                None => return std::ptr::null_mut(),
+            }
+        };
+
+        let result = generate_metadata_from_csv(Path::new(csv_path), output_path)
+            .and_then(|metadata| metadata_to_json(&metadata, &GenerateOptions::default()));
+        match result {
+            Ok(json) => into_c_string(json),
+            Err(e) => {
+                set_last_error(e.to_string());
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Validate a Croissant JSON-LD document
+///
+/// Returns the issues as a JSON string of the form
+/// `{"errors": n, "warnings": n, "issues": [...]}`, or NULL if `json` is not
+/// a valid string.
+///
+/// # Safety
+///
+/// `json` must be a valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rustcroissant_validate_json(json: *const c_char) -> *mut c_char {
+    catch_panic(|| {
+        // SAFETY: forwarded from this function's contract
+        let Some(content) = (unsafe { borrow_str(json, "json") }) else {
+            return std::ptr::null_mut();
+        };
+        into_c_string(validate_str_to_json(content).to_string())
+    })
+}
+
+/// Return the message of the last error on this thread, or NULL if none
+///
+/// The returned string must be released with `rustcroissant_free_string`.
+#[unsafe(no_mangle)]
+pub extern "C" fn rustcroissant_last_error() -> *mut c_char {
+    LAST_ERROR
+        .with(|last| last.borrow().clone())
+        .map_or(std::ptr::null_mut(), into_c_string)
+}
+
+/// Release a string returned by this library
+///
+/// # Safety
+///
+/// `value` must be NULL or a pointer returned by this library that has not
+/// been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rustcroissant_free_string(value: *mut c_char) {
+    if !value.is_null() {
+        // SAFETY: the pointer came from CString::into_raw per the contract
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
@@ -1,2 +1,7 @@
 pub mod croissant;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod prelude;
 pub mod version;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,5 +1,7 @@
 use clap::Command;
 
+mod cli;
+
 // Import your version module
 use rustcroissant::version;
 
@@ -10,40 +12,916 @@ fn main() {
         .version(version::get_version().version)
         .subcommand_required(true)
         .arg_required_else_help(true)
+        .arg(clap::Arg::new("verbose")
+            .short('v')
+            .long("verbose")
+            .help("Increase log verbosity (-v info, -vv debug, -vvv trace)")
+            .action(clap::ArgAction::Count)
+            .global(true)
+        )
+        .arg(clap::Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .help("Only log errors, leaving out status messages and warnings")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("verbose")
+            .global(true)
+        )
+        .arg(clap::Arg::new("log-format")
+            .long("log-format")
+            .help("Format of status messages and logs on standard error; results always go to standard output")
+            .value_name("FORMAT")
+            .value_parser(
+                cli::log::LogFormat::ALL
+                    .iter()
+                    .map(|format| format.name())
+                    .collect::<Vec<_>>(),
+            )
+            .default_value("text")
+            .global(true)
+        )
+        .arg(clap::Arg::new("config")
+            .long("config")
+            .help("Project configuration file with defaults for dataset properties, inference, validation rules and, only when given here, credentials (default: rustcroissant.toml in the working directory or a parent)")
+            .value_name("FILE")
+            .global(true)
+        )
+        .arg(clap::Arg::new("timings")
+            .long("timings")
+            .help("Print a breakdown of where time was spent after the command")
+            .action(clap::ArgAction::SetTrue)
+            .global(true)
+        )
         .subcommand(
             Command::new("version")
                 .about("Print the version information")
                 .long_about("Print the version, git hash, and build time information of the rustcroissant tool")
         )
+        .subcommand(
+            Command::new("check")
+                .about("Quickly check the structure of Croissant files, e.g. from a pre-commit hook")
+                .long_about("Validate only the structure of Croissant files, without hashing or network access, printing one line per issue. Files are taken from the arguments, from the git index with --staged, or one per line from standard input.")
+                .arg(clap::Arg::new("files")
+                    .help("Croissant JSON-LD files to check (\"-\" or none: read paths from stdin)")
+                    .num_args(0..)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("staged")
+                    .long("staged")
+                    .help("Check the content of the .jsonld, and Croissant JSON and YAML, files staged in git")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("files")
+                )
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Compare two versions of a Croissant metadata file")
+                .long_about("List the changes from OLD to NEW: the version bump, changed dataset properties, added and removed distributions and record sets, changed content hashes, and added, removed and renamed fields and changed dataTypes. Exits 1 when the files differ, like diff(1).")
+                .arg(clap::Arg::new("old")
+                    .help("Earlier Croissant JSON-LD (or .yaml/.yml) file")
+                    .required(true)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("new")
+                    .help("Later Croissant JSON-LD (or .yaml/.yml) file")
+                    .required(true)
+                    .index(2)
+                )
+                .arg(clap::Arg::new("format")
+                    .long("format")
+                    .help("Output format")
+                    .value_name("FORMAT")
+                    .value_parser(["text", "json"])
+                    .default_value("text")
+                )
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export Croissant metadata to another format")
+                .arg(clap::Arg::new("input")
+                    .help("Input JSON-LD file to export")
+                    .required(true)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("format")
+                    .short('f')
+                    .long("format")
+                    .help("Output format")
+                    .required_unless_present("canonical")
+                    .conflicts_with("canonical")
+                    .requires_if("csv", "dictionary")
+                    .requires_if("xlsx", "dictionary")
+                    .value_name("FORMAT")
+                    .value_parser(
+                        rustcroissant::croissant::export::ExportFormat::ALL
+                            .iter()
+                            .map(|format| format.name())
+                            .collect::<Vec<_>>(),
+                    )
+                )
+                .arg(clap::Arg::new("jsonld-form")
+                    .long("jsonld-form")
+                    .help("JSON-LD form of jsonld and dcat output")
+                    .required(false)
+                    .value_name("FORM")
+                    .value_parser(
+                        rustcroissant::croissant::jsonld::JsonLdForm::ALL
+                            .iter()
+                            .map(|form| form.name())
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("compact")
+                )
+                .arg(clap::Arg::new("dialect")
+                    .long("dialect")
+                    .help("SQL dialect of sql output")
+                    .required(false)
+                    .value_name("DIALECT")
+                    .value_parser(
+                        rustcroissant::croissant::export::sql::SqlDialect::ALL
+                            .iter()
+                            .map(|dialect| dialect.name())
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("postgres")
+                )
+                .arg(clap::Arg::new("compact")
+                    .long("compact")
+                    .help("Write JSON formats minified instead of pretty-printed")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("canonical")
+                )
+                .arg(clap::Arg::new("dictionary")
+                    .long("dictionary")
+                    .help("Export the data dictionary of the fields (with --format csv or xlsx)")
+                    .action(clap::ArgAction::SetTrue)
+                    .requires("format")
+                )
+                .arg(clap::Arg::new("canonical")
+                    .long("canonical")
+                    .help("Output the RDFC-1.0 canonical N-Quads of the metadata graph, for hashing and signing")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .help("Output file, or - for standard output (the default)")
+                    .required(false)
+                    .value_name("FILE")
+                )
+        )
+        .subcommand(
+            Command::new("fingerprint")
+                .about("Hash the records of record sets, independently of their files' bytes")
+                .long_about("Compute a SHA-256 fingerprint of the typed records of each record set. Records are normalized first (fields in name order, values in their typed form, records in any order), so the fingerprint only changes when the data does: re-encoding, re-compressing, re-sorting or converting the files keeps it.")
+                .arg(clap::Arg::new("input")
+                    .help("Croissant JSON-LD (or .yaml/.yml) file describing the dataset")
+                    .required(true)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("record-set")
+                    .short('r')
+                    .long("record-set")
+                    .help("Name or @id of the record set (default: every record set)")
+                    .required(false)
+                    .value_name("NAME")
+                )
+                .arg(clap::Arg::new("format")
+                    .long("format")
+                    .help("Output format")
+                    .value_name("FORMAT")
+                    .value_parser(["text", "json"])
+                    .default_value("text")
+                )
+        )
         .subcommand(
             Command::new("generate")
-                .about("Generate Croissant metadata from a CSV file")
+                .about("Generate Croissant metadata from a CSV, Parquet or Excel file or a directory of them")
+                .arg(clap::Arg::new("input")
+                    .help("Input CSV, Parquet (parquet feature) or Excel .xlsx (xlsx feature, a record set per sheet) file or HTTP(S) URL (remote feature), or a directory to generate metadata for each such file in it (a directory of files of one other type, such as images, is described as a file set); several files give one document with a distribution and record set for each; s3://, gs:// and az:// URLs (with a trailing / for a prefix) need the s3, gcs and azure features, sftp:// and ftp:// URLs the sftp feature")
+                    .required(true)
+                    .num_args(1..)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .help("Output JSON-LD file, or YAML with a .yaml/.yml extension (output directory when the input is a directory); - writes the JSON-LD to standard output")
+                    .required(false)
+                    .value_name("FILE")
+                )
+                .arg(clap::Arg::new("print")
+                    .long("print")
+                    .help("Write the JSON-LD to standard output, with status messages on standard error (same as -o -)")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("output")
+                )
+                .arg(clap::Arg::new("compact")
+                    .long("compact")
+                    .help("Write minified JSON-LD instead of pretty-printed")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("force")
+                    .long("force")
+                    .help("Overwrite output files that already exist")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("no-input")
+                    .long("no-input")
+                    .help("Never prompt for a missing name, description or license, even on a terminal")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("dry-run")
+                    .long("dry-run")
+                    .help("Print the metadata and whether each output file would be created or replaced, without writing anything (for a directory, only list the files)")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("name")
+                    .long("name")
+                    .help("Name of the dataset (default: <file stem>_dataset)")
+                    .required(false)
+                    .value_name("NAME")
+                )
+                .arg(clap::Arg::new("description")
+                    .long("description")
+                    .help("Description of the dataset (default: Dataset created from <file>)")
+                    .required(false)
+                    .value_name("TEXT")
+                )
+                .arg(clap::Arg::new("dataset-version")
+                    .long("dataset-version")
+                    .help("Version of the dataset (default: 1.0.0)")
+                    .required(false)
+                    .value_name("VERSION")
+                )
+                .arg(clap::Arg::new("date-published")
+                    .long("date-published")
+                    .help("Publication date of the dataset, as YYYY-MM-DD (default: the date of SOURCE_DATE_EPOCH if set, else today)")
+                    .required(false)
+                    .value_name("DATE")
+                    .value_parser(|date: &str| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+                )
+                .arg(clap::Arg::new("license")
+                    .long("license")
+                    .help("License of the dataset, such as https://creativecommons.org/licenses/by/4.0/; may be repeated")
+                    .required(false)
+                    .value_name("URL")
+                    .action(clap::ArgAction::Append)
+                )
+                .arg(clap::Arg::new("creator")
+                    .long("creator")
+                    .help("Name of a person who created the dataset; may be repeated")
+                    .required(false)
+                    .value_name("NAME")
+                    .action(clap::ArgAction::Append)
+                )
+                .arg(clap::Arg::new("url")
+                    .long("url")
+                    .help("Web page of the dataset")
+                    .required(false)
+                    .value_name("URL")
+                )
+                .arg(clap::Arg::new("base-url")
+                    .long("base-url")
+                    .help("URL the files are published under, such as https://data.example.org/v2/; content URLs of local files become this URL followed by the file name instead of the bare file name")
+                    .required(false)
+                    .value_name("URL")
+                )
+                .arg(clap::Arg::new("relative-paths")
+                    .long("relative-paths")
+                    .help("Write the content URLs of local files as paths relative to the metadata file, such as ../data/file.csv, instead of the bare file name")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("base-url")
+                )
+                .arg(clap::Arg::new("keywords")
+                    .long("keywords")
+                    .help("Comma-separated keywords describing the dataset")
+                    .required(false)
+                    .value_name("KEYWORDS")
+                    .value_delimiter(',')
+                )
+                .arg(clap::Arg::new("cite-as")
+                    .long("cite-as")
+                    .help("Citation of the dataset, such as a BibTeX entry")
+                    .required(false)
+                    .value_name("CITATION")
+                )
+                .arg(clap::Arg::new("dictionary")
+                    .short('d')
+                    .long("dictionary")
+                    .help("Data dictionary (CSV or JSON) with column descriptions, units and semantic types")
+                    .required(false)
+                    .value_name("FILE")
+                )
+                .arg(clap::Arg::new("infer-rows")
+                    .long("infer-rows")
+                    .help("Number of data rows sampled for type inference")
+                    .required(false)
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                    .default_value("1000")
+                )
+                .arg(clap::Arg::new("full-scan")
+                    .long("full-scan")
+                    .help("Read every data row for type inference instead of a sample")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("infer-rows")
+                )
+                .arg(clap::Arg::new("null-values")
+                    .long("null-values")
+                    .help("Comma-separated values taken as missing besides blank ones, recorded on fields as nullValues (default: NA,N/A,null,-; an empty list disables them)")
+                    .required(false)
+                    .value_name("VALUES")
+                    .value_delimiter(',')
+                )
+                .arg(clap::Arg::new("number-locale")
+                    .long("number-locale")
+                    .help("How numbers with digit grouping or a decimal comma are written: en (1,234.56), de (1.234,56), fr (1 234,56), or auto to detect it; such columns get replace transforms turning them into plain numbers")
+                    .required(false)
+                    .value_name("LOCALE")
+                    .value_parser(
+                        ["auto"]
+                            .into_iter()
+                            .chain(
+                                rustcroissant::croissant::NumberLocale::ALL
+                                    .iter()
+                                    .map(|locale| locale.name()),
+                            )
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("auto")
+                )
+                .arg(clap::Arg::new("detect-enums")
+                    .long("detect-enums")
+                    .help("Take text columns with few distinct sampled values for enumerations, listing their values in a record set of their own that the field references")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("enum-max-values")
+                    .long("enum-max-values")
+                    .help("Largest number of distinct values of an enumeration column, with --detect-enums")
+                    .required(false)
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                    .default_value("20")
+                )
+                .arg(clap::Arg::new("strip-unit-names")
+                    .long("strip-unit-names")
+                    .help("Name fields after their column without the unit in its header, such as weight for \"weight (kg)\" or temperature for temperature_c")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("id-strategy")
+                    .long("id-strategy")
+                    .help("How column names become field @ids: keep them, slugify them (\"My Column\" becomes my_column), percent-encode them, or reject names that need either; names and extracted columns keep the header")
+                    .required(false)
+                    .value_name("STRATEGY")
+                    .value_parser(
+                        rustcroissant::croissant::generate::IdStrategy::ALL
+                            .iter()
+                            .map(|strategy| strategy.name())
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("keep")
+                )
+                .arg(clap::Arg::new("record-encoding")
+                    .long("record-encoding")
+                    .help("Record the detected character encoding of the CSV file (UTF-8, UTF-16 or Latin-1) in its distribution's description")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("statistics")
+                    .long("statistics")
+                    .help("Stream every row of CSV files once more and record each field's count, null count, distinct count (estimated beyond 100000 values), minimum, maximum and mean in its statistics")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("delimiter")
+                    .long("delimiter")
+                    .help("Field delimiter of CSV files: tab or a single character such as ; or | (default: tab for .tsv files, otherwise the comma, tab, semicolon or pipe found in the header line); tab-separated files are described as text/tab-separated-values")
+                    .required(false)
+                    .value_name("DELIMITER")
+                    .value_parser(|text: &str| rustcroissant::croissant::utils::parse_delimiter(text))
+                )
+                .arg(clap::Arg::new("on-bad-row")
+                    .long("on-bad-row")
+                    .help("What to do with sampled CSV rows without as many fields as the header, such as ragged rows or rows broken by unbalanced quotes: fail, skip them with a warning each, or skip them silently")
+                    .required(false)
+                    .value_name("POLICY")
+                    .value_parser(
+                        rustcroissant::croissant::BadRowPolicy::ALL
+                            .iter()
+                            .map(|policy| policy.name())
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("error")
+                )
+                .arg(clap::Arg::new("no-cache")
+                    .long("no-cache")
+                    .help("Always rehash files instead of using the hash cache")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("cache-dir")
+                    .long("cache-dir")
+                    .help("Directory of the hash and download caches (default: $CROISSANT_CACHE_DIR or ~/.cache/rustcroissant)")
+                    .required(false)
+                    .value_name("DIR")
+                )
+                .arg(clap::Arg::new("offline")
+                    .long("offline")
+                    .help("For a URL input, use the cached download without contacting the server")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("refresh")
+                )
+                .arg(clap::Arg::new("refresh")
+                    .long("refresh")
+                    .help("For a URL input, download the file again even if it is cached")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("jobs")
+                    .short('j')
+                    .long("jobs")
+                    .help("Maximum number of files processed in parallel (default: number of CPUs)")
+                    .required(false)
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                )
+                .arg(clap::Arg::new("blake3")
+                    .long("blake3")
+                    .help("Also record a BLAKE3 digest of each file (non-standard, requires the blake3 feature)")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("jsonld-form")
+                    .long("jsonld-form")
+                    .help("JSON-LD form of the written metadata (default: compact)")
+                    .required(false)
+                    .value_name("FORM")
+                    .value_parser(
+                        rustcroissant::croissant::jsonld::JsonLdForm::ALL
+                            .iter()
+                            .map(|form| form.name())
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("compact")
+                )
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Convert metadata in another format to Croissant")
+                .arg(clap::Arg::new("input")
+                    .help("Input metadata file to convert")
+                    .required(true)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("format")
+                    .short('f')
+                    .long("format")
+                    .help("Input format")
+                    .required(true)
+                    .value_name("FORMAT")
+                    .value_parser(
+                        rustcroissant::croissant::import::ImportFormat::ALL
+                            .iter()
+                            .map(|format| format.name())
+                            .collect::<Vec<_>>(),
+                    )
+                )
+                .arg(clap::Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .help("Output JSON-LD file, or - for standard output (the default)")
+                    .required(false)
+                    .value_name("FILE")
+                )
+        )
+        .subcommand(
+            Command::new("inspect")
+                .about("Summarize a Croissant metadata file")
+                .long_about("Print the dataset's name, version and licenses, its distributions with their formats and sizes, and each record set with the name and dataType of its fields. --format json gives the same summary for scripts.")
+                .arg(clap::Arg::new("input")
+                    .help("Croissant JSON-LD (or .yaml/.yml) file describing the dataset")
+                    .required(true)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("format")
+                    .long("format")
+                    .help("Output format")
+                    .value_name("FORMAT")
+                    .value_parser(["text", "json"])
+                    .default_value("text")
+                )
+        )
+        .subcommand(
+            Command::new("mcp")
+                .about("Run a Model Context Protocol server on stdio")
+                .long_about("Run a Model Context Protocol (MCP) server on stdin/stdout exposing the generate_croissant, validate_croissant and inspect_dataset tools to LLM agents")
+        )
+        .subcommand(
+            Command::new("preview")
+                .about("Show the first records of a record set as a table")
+                .long_about("Read the first records of a record set and print them as an aligned table headed by each field's name and dataType, to check that fields are wired to the right sources and columns. Long values are cut, nulls are shown as null and empty text as \"\".")
+                .arg(clap::Arg::new("input")
+                    .help("Croissant JSON-LD (or .yaml/.yml) file describing the dataset")
+                    .required(true)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("record-set")
+                    .short('r')
+                    .long("record-set")
+                    .help("Name or @id of the record set (default: the only record set)")
+                    .required(false)
+                    .value_name("NAME")
+                )
+                .arg(clap::Arg::new("rows")
+                    .short('n')
+                    .long("rows")
+                    .help("Number of records to show")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("10")
+                )
+                .arg(clap::Arg::new("max-width")
+                    .long("max-width")
+                    .help("Longest value shown, in characters, before it is cut")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(u64).range(2..))
+                    .default_value("40")
+                )
+        )
+        .subcommand(
+            Command::new("records")
+                .about("Read the records of a record set, typed as the metadata describes them")
+                .long_about("Materialize the records of a record set from the files its fields are extracted from: CSV columns, JSON Lines keys, or file properties such as each line of a file. Values are parsed into the fields' dataTypes, and the records are written as NDJSON or CSV.")
                 .arg(clap::Arg::new("input")
-                    .help("Input CSV file")
+                    .help("Croissant JSON-LD (or .yaml/.yml) file describing the dataset")
                     .required(true)
                     .index(1)
                 )
+                .arg(clap::Arg::new("record-set")
+                    .short('r')
+                    .long("record-set")
+                    .help("Name or @id of the record set (default: the only record set)")
+                    .required(false)
+                    .value_name("NAME")
+                )
+                .arg(clap::Arg::new("to")
+                    .long("to")
+                    .help("Output format (parquet requires the parquet feature)")
+                    .value_name("FORMAT")
+                    .value_parser(
+                        rustcroissant::croissant::records::RecordFormat::ALL
+                            .iter()
+                            .map(|format| format.name())
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("ndjson")
+                )
+                .arg(clap::Arg::new("split")
+                    .long("split")
+                    .help("Keep only the records of a split, such as train, as given by the record set's cr:Split field")
+                    .value_name("SPLIT")
+                )
+                .arg(clap::Arg::new("where")
+                    .long("where")
+                    .help("Keep only the records meeting a condition such as \"year >= 2020\" (=, !=, <, <=, >, >=); may be repeated")
+                    .value_name("CONDITION")
+                    .action(clap::ArgAction::Append)
+                )
+                .arg(clap::Arg::new("fields")
+                    .long("fields")
+                    .help("Comma-separated fields to keep, in this order")
+                    .value_name("FIELDS")
+                    .value_delimiter(',')
+                )
+                .arg(clap::Arg::new("offset")
+                    .long("offset")
+                    .help("Number of records to skip")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("0")
+                )
+                .arg(clap::Arg::new("limit")
+                    .long("limit")
+                    .help("Most records to read; reading stops once they are found")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(usize))
+                )
+                .arg(clap::Arg::new("resolve-references")
+                    .long("resolve-references")
+                    .help("Join the records each field references, adding their fields as <field>.<referenced field>")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("on-bad-row")
+                    .long("on-bad-row")
+                    .help("What to do with CSV rows without as many fields as the header: fail, skip them with a warning each, or skip them silently")
+                    .required(false)
+                    .value_name("POLICY")
+                    .value_parser(
+                        rustcroissant::croissant::BadRowPolicy::ALL
+                            .iter()
+                            .map(|policy| policy.name())
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("error")
+                )
                 .arg(clap::Arg::new("output")
                     .short('o')
                     .long("output")
-                    .help("Output JSON-LD file")
+                    .help("Output file, or - for standard output (the default)")
                     .required(false)
                     .value_name("FILE")
                 )
         )
+        .subcommand(
+            Command::new("stats")
+                .about("Summarize the values of each field of a record set")
+                .long_about("Read the records of a record set and report, for each field, its null rate, its number of distinct values (estimated with HyperLogLog beyond 100,000), the minimum, maximum and mean of numbers and dates, and the lengths of text values.")
+                .arg(clap::Arg::new("input")
+                    .help("Croissant JSON-LD (or .yaml/.yml) file describing the dataset")
+                    .required(true)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("record-set")
+                    .short('r')
+                    .long("record-set")
+                    .help("Name or @id of the record set (default: every record set)")
+                    .required(false)
+                    .value_name("NAME")
+                )
+                .arg(clap::Arg::new("format")
+                    .long("format")
+                    .help("Output format")
+                    .value_name("FORMAT")
+                    .value_parser(["text", "json"])
+                    .default_value("text")
+                )
+        )
         .subcommand(
             Command::new("validate")
-                .about("Validate a Croissant JSON-LD metadata file")
+                .about("Validate a Croissant JSON-LD or YAML metadata file")
+                .arg(clap::Arg::new("input")
+                    .help("Input JSON-LD (or .yaml/.yml) file to validate")
+                    .required(true)
+                    .index(1)
+                )
+                .arg(clap::Arg::new("spec")
+                    .long("spec")
+                    .help("Also check the document against the Croissant 1.0 specification: properties it does not define, missing recommended ones, single-valued properties with several values and values it does not allow")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("deep")
+                    .long("deep")
+                    .help("Also resolve DOIs in identifier and citeAs through doi.org and warn when their title or creators do not match (requires the remote feature)")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("data")
+                    .long("data")
+                    .help("Also read every record of every record set from its local files, reporting records that cannot be read and malformed CSV rows")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("verify")
+                    .long("verify")
+                    .help("Also hash the local file of every distribution and report those not matching their recorded sha256 or, when given in bytes, contentSize as errors")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("on-bad-row")
+                    .long("on-bad-row")
+                    .help("With --data, what to do with CSV rows without as many fields as the header: report the first as an error, or skip them and report their number as a warning (warn also logs each)")
+                    .required(false)
+                    .value_name("POLICY")
+                    .value_parser(
+                        rustcroissant::croissant::BadRowPolicy::ALL
+                            .iter()
+                            .map(|policy| policy.name())
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("error")
+                )
+                .arg(clap::Arg::new("format")
+                    .long("format")
+                    .help("Output format: text, JSON with counts and one entry per issue, or SARIF for code scanning tools")
+                    .value_name("FORMAT")
+                    .value_parser(
+                        rustcroissant::croissant::validate::ReportFormat::ALL
+                            .iter()
+                            .map(|format| format.name())
+                            .collect::<Vec<_>>(),
+                    )
+                    .default_value("text")
+                )
+        )
+        .subcommand(
+            Command::new("verify")
+                .about("Check distributions against their recorded SHA-256 digests and sizes")
+                .long_about("Hash the file of every distribution and compare it with the recorded sha256 and, when given in bytes, contentSize. Relative content URLs are resolved against the metadata file's directory; HTTP(S) and object storage content is only fetched with --remote.")
                 .arg(clap::Arg::new("input")
-                    .help("Input JSON-LD file to validate")
+                    .help("Croissant JSON-LD (or .yaml/.yml) file to verify")
                     .required(true)
                     .index(1)
                 )
+                .arg(clap::Arg::new("remote")
+                    .long("remote")
+                    .help("Also fetch and hash remote content (requires the remote feature; object storage, SFTP and FTP URLs need the s3, gcs, azure or sftp feature)")
+                    .action(clap::ArgAction::SetTrue)
+                )
+                .arg(clap::Arg::new("cache")
+                    .long("cache")
+                    .help("Keep downloaded HTTP(S) files in the download cache instead of only hashing them")
+                    .action(clap::ArgAction::SetTrue)
+                    .requires("remote")
+                )
+                .arg(clap::Arg::new("cache-dir")
+                    .long("cache-dir")
+                    .help("Directory of the download cache (default: $CROISSANT_CACHE_DIR or ~/.cache/rustcroissant)")
+                    .required(false)
+                    .value_name("DIR")
+                    .requires("remote")
+                )
+                .arg(clap::Arg::new("offline")
+                    .long("offline")
+                    .help("Check cached downloads without contacting servers (implies --cache)")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("refresh")
+                    .requires("remote")
+                )
+                .arg(clap::Arg::new("refresh")
+                    .long("refresh")
+                    .help("Download files again even if they are cached (implies --cache)")
+                    .action(clap::ArgAction::SetTrue)
+                    .requires("remote")
+                )
+                .arg(clap::Arg::new("jobs")
+                    .short('j')
+                    .long("jobs")
+                    .help("Maximum number of local files hashed in parallel, and of remote files fetched at once (default: number of CPUs, and 8 fetches)")
+                    .required(false)
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                )
+                .arg(clap::Arg::new("progress")
+                    .long("progress")
+                    .help("Report bytes fetched and the transfer rate on stderr even when it is not a terminal")
+                    .action(clap::ArgAction::SetTrue)
+                    .requires("remote")
+                )
+        );
+
+    #[cfg(feature = "duckdb")]
+    let app = app.subcommand(
+        Command::new("query")
+            .about("Run a SQL query over the record sets of a Croissant dataset")
+            .long_about("Run a SQL query with an embedded DuckDB over the record sets of a Croissant dataset; each record set is a table named after it, with the declared field names and data types and its records read as the metadata describes them. Results are printed as CSV.")
+            .arg(clap::Arg::new("input")
+                .help("Croissant JSON-LD file describing the dataset")
+                .required(true)
+                .index(1)
+            )
+            .arg(clap::Arg::new("sql")
+                .help("SQL query, e.g. \"SELECT * FROM main LIMIT 10\"")
+                .required(true)
+                .index(2)
+            )
+    );
+
+    #[cfg(feature = "remote")]
+    let app = app.subcommand(
+        Command::new("fetch")
+            .about("Download the remote files of a Croissant dataset into the download cache")
+            .long_about("Download every HTTP(S) (and, with the sftp feature, SFTP and FTP) distribution of a dataset into the download cache, checking each against its recorded sha256, so later runs can use --offline. Cached files are revalidated with the server and only downloaded again when they changed.")
+            .arg(clap::Arg::new("input")
+                .help("Croissant JSON-LD (or .yaml/.yml) file describing the dataset")
+                .required(true)
+                .index(1)
+            )
+            .arg(clap::Arg::new("output")
+                .short('o')
+                .long("output")
+                .help("Also copy the files into this directory, named after their distributions")
+                .required(false)
+                .value_name("DIR")
+            )
+            .arg(clap::Arg::new("force")
+                .long("force")
+                .help("Overwrite files that already exist in the --output directory")
+                .action(clap::ArgAction::SetTrue)
+                .requires("output")
+            )
+            .arg(clap::Arg::new("cache-dir")
+                .long("cache-dir")
+                .help("Directory of the download cache (default: $CROISSANT_CACHE_DIR or ~/.cache/rustcroissant)")
+                .required(false)
+                .value_name("DIR")
+            )
+            .arg(clap::Arg::new("offline")
+                .long("offline")
+                .help("Only use cached files, without contacting servers")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("refresh")
+            )
+            .arg(clap::Arg::new("refresh")
+                .long("refresh")
+                .help("Download files again even if they are cached")
+                .action(clap::ArgAction::SetTrue)
+            )
+            .arg(clap::Arg::new("progress")
+                .long("progress")
+                .help("Report bytes fetched and the transfer rate on stderr even when it is not a terminal")
+                .action(clap::ArgAction::SetTrue)
+            )
+    );
+
+    #[cfg(feature = "publish")]
+    let app = app.subcommand(
+        Command::new("publish")
+            .about("Publish a Croissant file, and its data, to the Hugging Face Hub or Zenodo")
+            .long_about("Upload a Croissant file to a Hugging Face Hub dataset repository as croissant.json in a single commit, creating the repository if needed and setting the dataset card's pretty_name (and, with --data, its configs) from the metadata. The token is read from HF_TOKEN or the huggingface-cli login token file.\n\nWith --target zenodo, create a Zenodo deposition (or a new version of --deposition) from the metadata, reserve a DOI, write it into the Croissant file's identifier and citeAs, and upload the Croissant file and the local files of its distributions. The deposition stays a draft unless --submit is given. The token is read from ZENODO_TOKEN.")
+            .override_usage("rustcroissant publish [OPTIONS] hf://user/dataset <INPUT>\n       rustcroissant publish [OPTIONS] --target zenodo --creator <NAME> <INPUT>")
+            .arg(clap::Arg::new("repository")
+                .help("Hugging Face Hub dataset repository, e.g. hf://user/dataset (omitted with --target zenodo)")
+                .required(true)
+                .index(1)
+            )
+            .arg(clap::Arg::new("input")
+                .help("Croissant JSON-LD (or .yaml/.yml) file to publish")
+                .required(false)
+                .index(2)
+            )
+            .arg(clap::Arg::new("target")
+                .long("target")
+                .help("Where to publish (default: huggingface)")
+                .value_parser(["huggingface", "zenodo"])
+                .default_value("huggingface")
+            )
+            .arg(clap::Arg::new("data")
+                .long("data")
+                .help("Also upload the local files of the distributions, at their content URLs (always done for Zenodo)")
+                .action(clap::ArgAction::SetTrue)
+            )
+            .arg(clap::Arg::new("private")
+                .long("private")
+                .help("Create the repository as private if it does not exist yet; on Zenodo, make the files closed access")
+                .action(clap::ArgAction::SetTrue)
+            )
+            .arg(clap::Arg::new("message")
+                .short('m')
+                .long("message")
+                .help("Commit message (Hugging Face Hub)")
+                .required(false)
+                .value_name("MESSAGE")
+            )
+            .arg(clap::Arg::new("creator")
+                .long("creator")
+                .help("Creator of the dataset, as \"Family, Given\" (Zenodo; repeatable, at least one required)")
+                .action(clap::ArgAction::Append)
+                .value_name("NAME")
+            )
+            .arg(clap::Arg::new("deposition")
+                .long("deposition")
+                .help("Add a new version to this Zenodo deposition instead of creating one")
+                .required(false)
+                .value_name("ID")
+                .value_parser(clap::value_parser!(u64))
+            )
+            .arg(clap::Arg::new("submit")
+                .long("submit")
+                .help("Publish the Zenodo deposition, minting its DOI (cannot be undone)")
+                .action(clap::ArgAction::SetTrue)
+            )
+    );
+
+    #[cfg(feature = "remote")]
+    let app = app
+        .arg(clap::Arg::new("retries")
+            .long("retries")
+            .help("Retries of network requests that fail transiently, with exponential backoff (default: $CROISSANT_RETRIES or 3)")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u32))
+            .global(true)
+        )
+        .arg(clap::Arg::new("rate-limit")
+            .long("rate-limit")
+            .help("Maximum requests per second to each host (default: $CROISSANT_RATE_LIMIT or unlimited)")
+            .value_name("N")
+            .value_parser(clap::value_parser!(f64))
+            .global(true)
         );
 
     // Parse arguments and handle commands
     let matches = app.get_matches();
 
+    cli::log::init(
+        matches.get_count("verbose"),
+        matches.get_flag("quiet"),
+        cli::log::LogFormat::from_name(
+            matches
+                .get_one::<String>("log-format")
+                .expect("has default"),
+        )
+        .expect("validated by clap"),
+        matches.get_flag("timings"),
+    );
+    let config = load_project_config(&matches);
+    #[cfg(feature = "remote")]
+    rustcroissant::croissant::remote::auth::set_project_auth(config.auth.clone());
+    #[cfg(feature = "remote")]
+    configure_network(&matches);
+
     match matches.subcommand() {
         Some(("version", _)) => {
             // Print version information
@@ -52,64 +930,1637 @@ fn main() {
             println!("Git commit: {}", v.git_hash);
             println!("Built on: {}", v.build_time);
         }
-        Some(("generate", sub_m)) => {
-            let input = sub_m
-                .get_one::<String>("input")
-                .expect("Input CSV required");
-            let output = sub_m.get_one::<String>("output");
-            let input_path = std::path::Path::new(input);
-            let output_path = output.map(std::path::Path::new);
-
-            // Validate output path if provided
-            if let Some(out_path) = output_path {
-                if let Err(e) = rustcroissant::croissant::utils::validate_output_path(out_path) {
-                    eprintln!("Invalid output path: {e}");
-                    std::process::exit(1);
-                }
-            }
-
-            match rustcroissant::croissant::generate::generate_metadata_from_csv(
-                input_path,
-                output_path,
-            ) {
-                Ok(_) => {
-                    if let Some(o) = output {
-                        println!("Croissant metadata generated and saved to: {o}");
-                    } else {
-                        println!("Croissant metadata generated.");
+        Some(("check", sub_m)) => {
+            let files = sub_m
+                .get_many::<String>("files")
+                .map(|files| files.cloned().collect())
+                .unwrap_or_default();
+            let staged = sub_m.get_flag("staged");
+            match cli::check::collect_paths(files, staged) {
+                Ok(paths) => {
+                    if cli::check::run(&paths, staged) {
+                        exit(1);
                     }
                 }
                 Err(e) => {
-                    eprintln!("Error generating metadata: {e}");
-                    std::process::exit(1);
+                    cli::log::failure!("Error collecting files to check: {e}");
+                    exit(2);
                 }
             }
         }
+        Some(("diff", sub_m)) => run_diff(sub_m),
+        Some(("export", sub_m)) => run_export(sub_m),
+        Some(("inspect", sub_m)) => run_inspect(sub_m),
+        Some(("preview", sub_m)) => run_preview(sub_m),
+        Some(("records", sub_m)) => run_records(sub_m, &config),
+        Some(("stats", sub_m)) => run_stats(sub_m),
+        Some(("fingerprint", sub_m)) => run_fingerprint(sub_m),
+        Some(("generate", sub_m)) => run_generate(sub_m, &config),
+        #[cfg(feature = "remote")]
+        Some(("fetch", sub_m)) => run_fetch(sub_m),
+        Some(("import", sub_m)) => run_import(sub_m),
+        Some(("mcp", _)) => {
+            if let Err(e) = cli::mcp::serve() {
+                cli::log::failure!("MCP server error: {e}");
+                exit(1);
+            }
+        }
+        #[cfg(feature = "publish")]
+        Some(("publish", sub_m)) => run_publish(sub_m),
+        #[cfg(feature = "duckdb")]
+        Some(("query", sub_m)) => run_query(sub_m),
         Some(("validate", sub_m)) => {
             let input = sub_m
                 .get_one::<String>("input")
                 .expect("Input JSON-LD file required");
             let input_path = std::path::Path::new(input);
-            match rustcroissant::croissant::validate::validate_file(input_path) {
-                Ok(issues) => {
-                    if issues.is_empty() {
-                        println!("Validation passed with no issues.");
-                    } else {
-                        println!("{}", issues.report());
-                        if issues.has_errors() {
-                            std::process::exit(1);
-                        }
+            let spec = sub_m.get_flag("spec");
+            let (mut issues, parsed) =
+                match rustcroissant::croissant::validate::validate_file(input_path) {
+                    Ok(issues) => (issues, true),
+                    // Reported like any other issue, in the requested format;
+                    // the specification check can still tell what keeps the
+                    // document from being read
+                    Err(e) => (
+                        rustcroissant::croissant::validate::ValidationIssues::unparseable(&e),
+                        false,
+                    ),
+                };
+            if spec {
+                match rustcroissant::croissant::spec::validate_spec_file(input_path) {
+                    Ok(spec_issues) => issues.merge(spec_issues),
+                    // Not JSON at all, which is already reported
+                    Err(_) if !parsed => {}
+                    Err(e) => {
+                        cli::log::failure!("Error validating metadata: {e}");
+                        exit(1);
                     }
                 }
-                Err(e) => {
-                    eprintln!("Error validating metadata: {e}");
-                    std::process::exit(1);
-                }
+            }
+            if parsed && sub_m.get_flag("deep") {
+                check_dois(input_path, &mut issues);
+            }
+            if parsed && sub_m.get_flag("data") {
+                check_data(input_path, bad_row_policy(sub_m, &config), &mut issues);
+            }
+            if parsed && sub_m.get_flag("verify") {
+                check_content(input_path, &mut issues);
+            }
+            if !config.validate.rules.is_empty() {
+                issues = issues.with_levels(&config.validate.rules);
+            }
+            let format = rustcroissant::croissant::validate::ReportFormat::from_name(
+                sub_m.get_one::<String>("format").expect("has default"),
+            )
+            .expect("validated by clap");
+            println!("{}", issues.render(format, input));
+            if issues.has_errors() {
+                exit(1);
             }
         }
+        Some(("verify", sub_m)) => run_verify(sub_m),
         _ => {
             // This shouldn't happen with subcommand_required, but handle it anyway
-            println!("Unknown command. Use --help for usage information.");
+            cli::log::failure!("Unknown command. Use --help for usage information.");
+        }
+    }
+
+    cli::timings::print_summary();
+}
+
+/// Exit the process, printing the --timings summary first
+fn exit(code: i32) -> ! {
+    cli::timings::print_summary();
+    std::process::exit(code)
+}
+
+/// Handle the export subcommand
+fn run_export(sub_m: &clap::ArgMatches) {
+    use rustcroissant::croissant::export::{
+        ExportFormat, ExportOptions, canonical_nquads, export_metadata_bytes, sql::SqlDialect,
+    };
+    use rustcroissant::croissant::jsonld::JsonLdForm;
+
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input JSON-LD file required");
+    let format = match sub_m.get_one::<String>("format") {
+        Some(format) => format.as_str(),
+        None => "canonical N-Quads",
+    };
+    let input_path = std::path::Path::new(input);
+    let base_dir = input_path.parent().unwrap_or(std::path::Path::new(""));
+
+    let exported = if sub_m.get_flag("canonical") {
+        rustcroissant::croissant::validate::read_metadata(input_path)
+            .and_then(|metadata| canonical_nquads(&metadata))
+            .map(String::into_bytes)
+    } else {
+        ExportFormat::from_name(format).and_then(|format| {
+            if sub_m.get_flag("dictionary") && !format.is_dictionary() {
+                return Err(rustcroissant::croissant::Error::invalid_format(format!(
+                    "--dictionary applies to the csv and xlsx formats, not {}",
+                    format.name()
+                )));
+            }
+            let options = ExportOptions {
+                jsonld_form: JsonLdForm::from_name(
+                    sub_m.get_one::<String>("jsonld-form").expect("has default"),
+                )?,
+                sql_dialect: SqlDialect::from_name(
+                    sub_m.get_one::<String>("dialect").expect("has default"),
+                )?,
+                minified: sub_m.get_flag("compact"),
+            };
+            let metadata = rustcroissant::croissant::validate::read_metadata(input_path)?;
+            export_metadata_bytes(&metadata, format, Some(base_dir), &options)
+        })
+    };
+    let exported = match exported {
+        Ok(exported) => exported,
+        Err(e) => {
+            cli::log::failure!("Error exporting metadata: {e}");
+            exit(1);
+        }
+    };
+
+    match sub_m
+        .get_one::<String>("output")
+        .filter(|output| *output != "-")
+    {
+        Some(output) => {
+            if let Err(e) = std::fs::write(output, exported) {
+                cli::log::failure!("Error writing {output}: {e}");
+                exit(1);
+            }
+            cli::log::status!("Metadata exported as {format} to: {output}");
+        }
+        None => {
+            use std::io::Write;
+
+            if let Err(e) = std::io::stdout().write_all(&exported) {
+                cli::log::failure!("Error writing to standard output: {e}");
+                exit(1);
+            }
+        }
+    }
+}
+
+/// Handle the import subcommand
+fn run_import(sub_m: &clap::ArgMatches) {
+    use rustcroissant::croissant::import::{ImportFormat, import_metadata};
+
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input metadata file required");
+    let format = sub_m.get_one::<String>("format").expect("Format required");
+    let input_path = std::path::Path::new(input);
+    let base_dir = input_path.parent().unwrap_or(std::path::Path::new(""));
+
+    let imported = ImportFormat::from_name(format).and_then(|format| {
+        let content = std::fs::read_to_string(input_path)
+            .map_err(|_| rustcroissant::croissant::Error::file_not_found(input_path))?;
+        import_metadata(&content, format, base_dir)
+    });
+    let metadata = match imported {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            cli::log::failure!("Error importing metadata: {e}");
+            exit(1);
+        }
+    };
+
+    match sub_m
+        .get_one::<String>("output")
+        .filter(|output| *output != "-")
+    {
+        Some(output) => {
+            let output_path = std::path::Path::new(output);
+            if let Err(e) = rustcroissant::croissant::utils::validate_output_path(output_path)
+                .and_then(|_| {
+                    rustcroissant::croissant::generate::write_metadata(&metadata, output_path)
+                })
+            {
+                cli::log::failure!("Error writing {output}: {e}");
+                exit(1);
+            }
+            cli::log::status!("Croissant metadata imported from {format} and saved to: {output}");
+        }
+        None => match serde_json::to_string_pretty(&metadata) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                cli::log::failure!("Error serializing metadata: {e}");
+                exit(1);
+            }
+        },
+    }
+}
+
+/// Handle the generate subcommand
+fn run_generate(
+    sub_m: &clap::ArgMatches,
+    config: &rustcroissant::croissant::config::ProjectConfig,
+) {
+    let inputs = sub_m
+        .get_many::<String>("input")
+        .expect("Input CSV required")
+        .collect::<Vec<_>>();
+    let input = inputs[0];
+    let to_stdout =
+        sub_m.get_flag("print") || sub_m.get_one::<String>("output").is_some_and(|o| o == "-");
+    let output = sub_m
+        .get_one::<String>("output")
+        .filter(|output| *output != "-");
+    let input_path = std::path::Path::new(input);
+    let output_path = output.map(std::path::Path::new);
+    let is_object = rustcroissant::croissant::utils::is_object_store_url(input);
+    let is_file_set = input_path.is_dir()
+        && inputs.len() == 1
+        && rustcroissant::croissant::generate::is_file_set_directory(input_path);
+    let is_batch = (input_path.is_dir() && !is_file_set) || (is_object && input.ends_with('/'));
+
+    if inputs.len() > 1
+        && let Some(other) = inputs
+            .iter()
+            .find(|input| !std::path::Path::new(input).is_file())
+    {
+        cli::log::failure!("{other} is not a file; several inputs must all be local files");
+        exit(1);
+    }
+
+    if to_stdout && is_batch {
+        cli::log::failure!("Metadata for a directory cannot be written to standard output");
+        exit(1);
+    }
+
+    let force = sub_m.get_flag("force");
+    let dry_run = sub_m.get_flag("dry-run");
+    if !is_batch && let Some(out_path) = output_path {
+        if !force && out_path.exists() {
+            cli::log::failure!(
+                "{} already exists; use --force to overwrite it",
+                out_path.display()
+            );
+            exit(1);
+        }
+        // Validate output path if provided
+        if !dry_run && let Err(e) = rustcroissant::croissant::utils::validate_output_path(out_path)
+        {
+            cli::log::failure!("Invalid output path: {e}");
+            exit(1);
         }
     }
+
+    // Load the data dictionary if provided
+    let dictionary = match sub_m.get_one::<String>("dictionary") {
+        Some(path) => {
+            match rustcroissant::croissant::dictionary::DataDictionary::from_file(
+                std::path::Path::new(path),
+            ) {
+                Ok(dictionary) => Some(dictionary),
+                Err(e) => {
+                    cli::log::failure!("Error loading data dictionary: {e}");
+                    exit(1);
+                }
+            }
+        }
+        None => None,
+    };
+
+    let hash_cache = open_hash_cache(sub_m);
+
+    let mut dataset = dataset_properties(sub_m, config);
+    if !is_batch
+        && inputs.len() == 1
+        && !sub_m.get_flag("no-input")
+        && cli::prompt::is_interactive()
+    {
+        let file_name = input
+            .trim_end_matches('/')
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(input);
+        if let Err(e) = cli::prompt::complete_dataset_properties(&mut dataset, file_name) {
+            cli::log::failure!("Error reading answer: {e}");
+            exit(1);
+        }
+    }
+
+    let options = rustcroissant::croissant::generate::GenerateOptions {
+        descriptions: dictionary
+            .as_ref()
+            .map(|d| d as &dyn rustcroissant::croissant::dictionary::FieldDescriptionProvider),
+        inference: inference_options(sub_m, config),
+        hash_cache: hash_cache.as_ref(),
+        blake3: sub_m.get_flag("blake3"),
+        jsonld_form: rustcroissant::croissant::jsonld::JsonLdForm::from_name(
+            sub_m.get_one::<String>("jsonld-form").expect("has default"),
+        )
+        .expect("validated by clap"),
+        minified: sub_m.get_flag("compact"),
+        strip_unit_names: sub_m.get_flag("strip-unit-names"),
+        id_strategy: rustcroissant::croissant::generate::IdStrategy::from_name(
+            sub_m.get_one::<String>("id-strategy").expect("has default"),
+        )
+        .expect("validated by clap"),
+        record_encoding: sub_m.get_flag("record-encoding"),
+        statistics: sub_m.get_flag("statistics"),
+        content_urls: content_url_style(sub_m, config),
+        dataset,
+        keep_existing: !force,
+        dry_run,
+    };
+
+    if inputs.len() > 1 {
+        let jobs = sub_m.get_one::<u64>("jobs").map(|n| *n as usize);
+        match rustcroissant::croissant::generate::generate_metadata_from_files(
+            &inputs,
+            output_path,
+            &options,
+            jobs,
+        ) {
+            Ok(metadata) => {
+                if !dry_run {
+                    save_hash_cache(hash_cache.as_ref());
+                }
+                report_generated(&metadata, output_path, to_stdout, &options);
+            }
+            Err(e) => {
+                cli::log::failure!("Error generating metadata: {e}");
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    if rustcroissant::croissant::utils::is_ftp_url(input) {
+        generate_from_ftp(input, output_path, to_stdout, &options);
+        return;
+    }
+
+    if input.starts_with("http://") || input.starts_with("https://") {
+        generate_from_url(input, output_path, to_stdout, &options, sub_m);
+        return;
+    }
+
+    if is_object {
+        if let Err(e) = rustcroissant::croissant::utils::require_url_support(input) {
+            cli::log::failure!("Error generating metadata: {e}");
+            exit(1);
+        }
+        generate_from_object_storage(input, output_path, to_stdout, &options);
+        return;
+    }
+
+    if is_file_set {
+        match rustcroissant::croissant::generate::generate_metadata_for_file_set(
+            input_path,
+            output_path,
+            &options,
+        ) {
+            Ok(metadata) => report_generated(&metadata, output_path, to_stdout, &options),
+            Err(e) => {
+                cli::log::failure!("Error generating metadata: {e}");
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    if input_path.is_dir() {
+        let jobs = sub_m.get_one::<u64>("jobs").map(|n| *n as usize);
+        match rustcroissant::croissant::generate::generate_metadata_for_directory(
+            input_path,
+            output_path,
+            &options,
+            jobs,
+        ) {
+            Ok(batch) => {
+                if !dry_run {
+                    save_hash_cache(hash_cache.as_ref());
+                }
+                for (csv_path, written, metadata) in &batch.generated {
+                    match written {
+                        Some(o) if dry_run => cli::log::status!(
+                            "Dry run: would {} {} with the metadata for {}",
+                            dry_run_change(metadata, o, &options),
+                            o.display(),
+                            csv_path.display()
+                        ),
+                        Some(o) => cli::log::status!(
+                            "Croissant metadata for {} saved to: {}",
+                            csv_path.display(),
+                            o.display()
+                        ),
+                        None => {
+                            cli::log::status!(
+                                "Croissant metadata generated for {}.",
+                                csv_path.display()
+                            )
+                        }
+                    }
+                }
+                for (csv_path, e) in &batch.failed {
+                    cli::log::failure!("Error generating metadata for {}: {e}", csv_path.display());
+                }
+                cli::log::status!(
+                    "Generated metadata for {} file(s), {} failed.",
+                    batch.generated.len(),
+                    batch.failed.len()
+                );
+                if batch.has_failures() {
+                    exit(1);
+                }
+            }
+            Err(e) => {
+                cli::log::failure!("Error generating metadata: {e}");
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    match rustcroissant::croissant::generate::generate_metadata_from_file(
+        input_path,
+        output_path,
+        &options,
+    ) {
+        Ok(metadata) => {
+            if !dry_run {
+                save_hash_cache(hash_cache.as_ref());
+            }
+            report_generated(&metadata, output_path, to_stdout, &options);
+        }
+        Err(e) => {
+            cli::log::failure!("Error generating metadata: {e}");
+            exit(1);
+        }
+    }
+}
+
+/// Print generated metadata when it goes to standard output, and the
+/// generation status
+fn report_generated(
+    metadata: &rustcroissant::croissant::Metadata,
+    output_path: Option<&std::path::Path>,
+    to_stdout: bool,
+    options: &rustcroissant::croissant::generate::GenerateOptions,
+) {
+    if options.dry_run && !to_stdout {
+        let serialized = match output_path {
+            Some(o) => rustcroissant::croissant::generate::serialize_metadata(metadata, o, options),
+            None => rustcroissant::croissant::generate::metadata_to_json(metadata, options),
+        };
+        match serialized {
+            Ok(serialized) => print!("{serialized}"),
+            Err(e) => {
+                cli::log::failure!("Error serializing metadata: {e}");
+                exit(1);
+            }
+        }
+        match output_path {
+            Some(o) => cli::log::status!(
+                "Dry run: would {} {}",
+                dry_run_change(metadata, o, options),
+                o.display()
+            ),
+            None => cli::log::status!("Dry run: no output file given"),
+        }
+    } else if to_stdout {
+        match rustcroissant::croissant::generate::metadata_to_json(metadata, options) {
+            Ok(json) => print!("{json}"),
+            Err(e) => {
+                cli::log::failure!("Error serializing metadata: {e}");
+                exit(1);
+            }
+        }
+        cli::log::status!("Croissant metadata generated.");
+    } else if let Some(o) = output_path {
+        cli::log::status!("Croissant metadata generated and saved to: {}", o.display());
+    } else {
+        cli::log::status!("Croissant metadata generated.");
+    }
+}
+
+/// What writing the metadata to `output_path` would do: create the file,
+/// replace it, or leave it unchanged
+fn dry_run_change(
+    metadata: &rustcroissant::croissant::Metadata,
+    output_path: &std::path::Path,
+    options: &rustcroissant::croissant::generate::GenerateOptions,
+) -> &'static str {
+    match std::fs::read_to_string(output_path) {
+        Err(_) => "create",
+        Ok(existing) => {
+            let serialized = rustcroissant::croissant::generate::serialize_metadata(
+                metadata,
+                output_path,
+                options,
+            );
+            if serialized.is_ok_and(|serialized| serialized == existing) {
+                "leave unchanged"
+            } else {
+                "replace"
+            }
+        }
+    }
+}
+
+/// Generate metadata for a CSV file at an HTTP(S) URL, downloading it
+/// through the download cache
+#[cfg(feature = "remote")]
+fn generate_from_url(
+    url: &str,
+    output_path: Option<&std::path::Path>,
+    to_stdout: bool,
+    options: &rustcroissant::croissant::generate::GenerateOptions,
+    sub_m: &clap::ArgMatches,
+) {
+    let cache = open_download_cache(sub_m);
+    match async_runtime().block_on(
+        rustcroissant::croissant::generate::generate_metadata_from_url(
+            url,
+            output_path,
+            options,
+            &cache,
+        ),
+    ) {
+        Ok(metadata) => report_generated(&metadata, output_path, to_stdout, options),
+        Err(e) => {
+            cli::log::failure!("Error generating metadata: {e}");
+            exit(1);
+        }
+    }
+}
+
+/// Generate metadata for a URL; always fails, since rustcroissant was built
+/// without the `remote` feature
+#[cfg(not(feature = "remote"))]
+fn generate_from_url(
+    _url: &str,
+    _output_path: Option<&std::path::Path>,
+    _to_stdout: bool,
+    _options: &rustcroissant::croissant::generate::GenerateOptions,
+    _sub_m: &clap::ArgMatches,
+) {
+    cli::log::failure!("URL inputs require rustcroissant to be built with the remote feature");
+    exit(1);
+}
+
+/// Generate metadata for a CSV file on an SFTP or FTP server
+#[cfg(feature = "sftp")]
+fn generate_from_ftp(
+    url: &str,
+    output_path: Option<&std::path::Path>,
+    to_stdout: bool,
+    options: &rustcroissant::croissant::generate::GenerateOptions,
+) {
+    match rustcroissant::croissant::generate::generate_metadata_from_ftp(url, output_path, options)
+    {
+        Ok(metadata) => report_generated(&metadata, output_path, to_stdout, options),
+        Err(e) => {
+            cli::log::failure!("Error generating metadata: {e}");
+            exit(1);
+        }
+    }
+}
+
+/// Generate metadata for an SFTP or FTP URL; always fails, since
+/// rustcroissant was built without the `sftp` feature
+#[cfg(not(feature = "sftp"))]
+fn generate_from_ftp(
+    url: &str,
+    _output_path: Option<&std::path::Path>,
+    _to_stdout: bool,
+    _options: &rustcroissant::croissant::generate::GenerateOptions,
+) {
+    if let Err(e) = rustcroissant::croissant::utils::require_url_support(url) {
+        cli::log::failure!("Error generating metadata: {e}");
+    }
+    exit(1);
+}
+
+/// Generate metadata for an object in object storage, or for every CSV
+/// object under a prefix ending in `/`
+#[cfg(feature = "object-store")]
+fn generate_from_object_storage(
+    url: &str,
+    output_path: Option<&std::path::Path>,
+    to_stdout: bool,
+    options: &rustcroissant::croissant::generate::GenerateOptions,
+) {
+    use rustcroissant::croissant::generate::generate_metadata_from_object;
+    use rustcroissant::croissant::remote::cloud;
+
+    let runtime = async_runtime();
+
+    if !url.ends_with('/') {
+        match runtime.block_on(generate_metadata_from_object(url, output_path, options)) {
+            Ok(metadata) => report_generated(&metadata, output_path, to_stdout, options),
+            Err(e) => {
+                cli::log::failure!("Error generating metadata: {e}");
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    let objects = match runtime.block_on(cloud::list(url)) {
+        Ok(objects) => objects,
+        Err(e) => {
+            cli::log::failure!("Error listing {url}: {e}");
+            exit(1);
+        }
+    };
+    if let Some(dir) = output_path
+        && !options.dry_run
+        && let Err(e) = std::fs::create_dir_all(dir)
+    {
+        cli::log::failure!("Error creating {}: {e}", dir.display());
+        exit(1);
+    }
+
+    let (mut generated, mut failed) = (0, 0);
+    for object in objects.iter().filter(|o| o.url.ends_with(".csv")) {
+        let written = output_path.map(|dir| {
+            dir.join(cloud::object_name(&object.url))
+                .with_extension("jsonld")
+        });
+        match runtime.block_on(generate_metadata_from_object(
+            &object.url,
+            written.as_deref(),
+            options,
+        )) {
+            Ok(metadata) => {
+                generated += 1;
+                match written {
+                    Some(o) if options.dry_run => cli::log::status!(
+                        "Dry run: would {} {} with the metadata for {}",
+                        dry_run_change(&metadata, &o, options),
+                        o.display(),
+                        object.url
+                    ),
+                    Some(o) => cli::log::status!(
+                        "Croissant metadata for {} saved to: {}",
+                        object.url,
+                        o.display()
+                    ),
+                    None => cli::log::status!("Croissant metadata generated for {}.", object.url),
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                cli::log::failure!("Error generating metadata for {}: {e}", object.url);
+            }
+        }
+    }
+    cli::log::status!("Generated metadata for {generated} file(s), {failed} failed.");
+    if failed > 0 {
+        exit(1);
+    }
+}
+
+/// Generate metadata from object storage; never called, since every object
+/// storage URL is rejected when rustcroissant is built without support
+#[cfg(not(feature = "object-store"))]
+fn generate_from_object_storage(
+    _url: &str,
+    _output_path: Option<&std::path::Path>,
+    _to_stdout: bool,
+    _options: &rustcroissant::croissant::generate::GenerateOptions,
+) {
+    unreachable!("object storage URLs are rejected by require_url_support")
+}
+
+/// Handle the verify subcommand
+fn run_verify(sub_m: &clap::ArgMatches) {
+    use rustcroissant::croissant::verify::{VerifyOptions, VerifyOutcome, verify_metadata};
+
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input JSON-LD file required");
+    let input_path = std::path::Path::new(input);
+    let base_dir = input_path.parent().unwrap_or(std::path::Path::new(""));
+
+    let metadata = match rustcroissant::croissant::validate::read_metadata(input_path) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            cli::log::failure!("Error reading metadata: {e}");
+            exit(1);
+        }
+    };
+    let options = VerifyOptions {
+        remote: sub_m.get_flag("remote"),
+        max_threads: sub_m.get_one::<u64>("jobs").map(|n| *n as usize),
+        #[cfg(feature = "remote")]
+        download_cache: (sub_m.get_flag("cache")
+            || sub_m.get_flag("offline")
+            || sub_m.get_flag("refresh"))
+        .then(|| open_download_cache(sub_m)),
+        #[cfg(feature = "remote")]
+        progress: sub_m
+            .get_flag("remote")
+            .then(rustcroissant::croissant::remote::Progress::new),
+        fetcher: None,
+    };
+
+    #[cfg(feature = "remote")]
+    let reporter = options
+        .progress
+        .as_ref()
+        .and_then(|progress| cli::progress::start_if(sub_m.get_flag("progress"), progress));
+    let report = verify_metadata(&metadata, base_dir, &options);
+    #[cfg(feature = "remote")]
+    if let Some(reporter) = reporter {
+        reporter.finish();
+    }
+    let (mut verified, mut skipped) = (0, 0);
+    for check in &report.checks {
+        match &check.outcome {
+            VerifyOutcome::Verified => {
+                verified += 1;
+                println!("OK       {}", check.id);
+            }
+            VerifyOutcome::Mismatch(differences) => {
+                println!("MISMATCH {}: {}", check.id, differences.join("; "))
+            }
+            VerifyOutcome::Skipped(reason) => {
+                skipped += 1;
+                println!("SKIPPED  {}: {reason}", check.id);
+            }
+            VerifyOutcome::Failed(e) => println!("FAILED   {}: {e}", check.id),
+        }
+    }
+    println!(
+        "{verified} verified, {skipped} skipped, {} failed.",
+        report.checks.len() - verified - skipped
+    );
+    if report.has_failures() {
+        exit(1);
+    }
+}
+
+/// Handle the fetch subcommand
+#[cfg(feature = "remote")]
+fn run_fetch(sub_m: &clap::ArgMatches) {
+    use rustcroissant::croissant::remote::download::{is_cacheable, local_file_name};
+    use rustcroissant::croissant::remote::{DEFAULT_MAX_CONCURRENT_FETCHES, Progress};
+    use rustcroissant::croissant::verify::transfer_size;
+
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input JSON-LD file required");
+    let output_dir = sub_m.get_one::<String>("output").map(std::path::Path::new);
+    let force = sub_m.get_flag("force");
+
+    let metadata =
+        match rustcroissant::croissant::validate::read_metadata(std::path::Path::new(input)) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                cli::log::failure!("Error reading metadata: {e}");
+                exit(1);
+            }
+        };
+    if let Some(dir) = output_dir
+        && let Err(e) = std::fs::create_dir_all(dir)
+    {
+        cli::log::failure!("Error creating {}: {e}", dir.display());
+        exit(1);
+    }
+
+    let distributions = metadata
+        .distribution
+        .iter()
+        .filter(|distribution| is_cacheable(&distribution.content_url))
+        .collect::<Vec<_>>();
+    let urls = distributions
+        .iter()
+        .map(|distribution| distribution.content_url.clone())
+        .collect::<Vec<_>>();
+    let cache = open_download_cache(sub_m);
+    let progress = Progress::new();
+    let (files, bytes) = transfer_size(distributions.iter().copied());
+    progress.set_totals(files, bytes);
+    let reporter = cli::progress::start_if(sub_m.get_flag("progress"), &progress);
+    let downloads = async_runtime().block_on(cache.fetch_many(
+        &urls,
+        DEFAULT_MAX_CONCURRENT_FETCHES,
+        &progress,
+    ));
+    if let Some(reporter) = reporter {
+        reporter.finish();
+    }
+
+    let mut failed = 0;
+    for (distribution, download) in distributions.iter().zip(downloads) {
+        let fetched = download.and_then(|download| {
+            if !distribution.sha256.is_empty()
+                && !distribution.sha256.eq_ignore_ascii_case(&download.sha256)
+            {
+                return Err(rustcroissant::croissant::Error::remote(
+                    &download.url,
+                    format!(
+                        "sha256 is {}, expected {}",
+                        download.sha256, distribution.sha256
+                    ),
+                ));
+            }
+            match output_dir {
+                Some(dir) => {
+                    let name =
+                        local_file_name(&distribution.name, &distribution.id).ok_or_else(|| {
+                            rustcroissant::croissant::Error::new(
+                                "neither its name nor its @id is a plain file name",
+                            )
+                        })?;
+                    let copy = dir.join(name);
+                    if !force && copy.exists() {
+                        return Err(rustcroissant::croissant::Error::invalid_output_path(
+                            &copy,
+                            "already exists; use --force to overwrite it",
+                        ));
+                    }
+                    std::fs::copy(&download.path, &copy)?;
+                    Ok(copy)
+                }
+                None => Ok(download.path),
+            }
+        });
+        match fetched {
+            Ok(path) => println!("{} -> {}", distribution.id, path.display()),
+            Err(e) => {
+                failed += 1;
+                cli::log::failure!("Error fetching {}: {e}", distribution.id);
+            }
+        }
+    }
+    cli::log::status!(
+        "Fetched {} file(s), {failed} failed.",
+        distributions.len() - failed
+    );
+    if failed > 0 {
+        exit(1);
+    }
+}
+
+/// Handle the publish subcommand
+#[cfg(feature = "publish")]
+fn run_publish(sub_m: &clap::ArgMatches) {
+    use rustcroissant::croissant::publish::zenodo::{self, ZenodoOptions};
+    use rustcroissant::croissant::publish::{PublishOptions, huggingface};
+
+    // The repository is only given for the Hub, so a single positional
+    // argument is the input
+    let first = sub_m
+        .get_one::<String>("repository")
+        .expect("Input JSON-LD file required");
+    let (repository, input) = match sub_m.get_one::<String>("input") {
+        Some(input) => (Some(first), input),
+        None => (None, first),
+    };
+    let input_path = std::path::Path::new(input);
+    let options = PublishOptions {
+        include_data: sub_m.get_flag("data"),
+        private: sub_m.get_flag("private"),
+        message: sub_m.get_one::<String>("message").cloned(),
+    };
+
+    let published = match sub_m.get_one::<String>("target").map(String::as_str) {
+        Some("zenodo") => {
+            if let Some(repository) = repository {
+                cli::log::failure!("Error: unexpected argument {repository} with --target zenodo");
+                exit(2);
+            }
+            let zenodo_options = ZenodoOptions {
+                deposition: sub_m.get_one::<u64>("deposition").copied(),
+                creators: sub_m
+                    .get_many::<String>("creator")
+                    .map(|creators| creators.cloned().collect())
+                    .unwrap_or_default(),
+                submit: sub_m.get_flag("submit"),
+            };
+            async_runtime().block_on(zenodo::publish(input_path, &options, &zenodo_options))
+        }
+        _ => {
+            let Some(repository) = repository else {
+                cli::log::failure!("Error: a repository such as hf://user/dataset is required");
+                exit(2);
+            };
+            async_runtime().block_on(huggingface::publish(input_path, repository, &options))
+        }
+    };
+
+    match published {
+        Ok(published) => {
+            for file in &published.files {
+                cli::log::status!("Uploaded {file}");
+            }
+            if let Some(doi) = &published.doi {
+                println!("DOI: {doi} (written to {input})");
+            }
+            println!("Published to {}", published.url);
+        }
+        Err(e) => {
+            cli::log::failure!("Error publishing metadata: {e}");
+            exit(1);
+        }
+    }
+}
+
+/// Handle the records subcommand
+fn run_records(sub_m: &clap::ArgMatches, config: &rustcroissant::croissant::config::ProjectConfig) {
+    use rustcroissant::croissant::records::{
+        Condition, RecordFormat, RecordOptions, read_records_with_options, write_records,
+    };
+
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input JSON-LD file required");
+    let input_path = std::path::Path::new(input);
+    let base_dir = input_path.parent().unwrap_or(std::path::Path::new(""));
+    let format = RecordFormat::from_name(sub_m.get_one::<String>("to").expect("has default"))
+        .expect("validated by clap");
+    let conditions = sub_m
+        .get_many::<String>("where")
+        .unwrap_or_default()
+        .map(|condition| Condition::parse(condition))
+        .collect::<rustcroissant::croissant::Result<Vec<_>>>();
+    let conditions = match conditions {
+        Ok(conditions) => conditions,
+        Err(e) => {
+            cli::log::failure!("Error: {e}");
+            exit(1);
+        }
+    };
+    let options = RecordOptions {
+        resolve_references: sub_m.get_flag("resolve-references"),
+        split: sub_m.get_one::<String>("split").cloned(),
+        conditions,
+        offset: *sub_m.get_one::<usize>("offset").expect("has default"),
+        limit: sub_m.get_one::<usize>("limit").copied(),
+        fields: sub_m
+            .get_many::<String>("fields")
+            .map(|fields| fields.map(|field| field.trim().to_string()).collect()),
+        on_bad_row: bad_row_policy(sub_m, config),
+    };
+
+    let reader =
+        rustcroissant::croissant::validate::read_metadata(input_path).and_then(|metadata| {
+            let record_set = chosen_record_set(sub_m, &metadata, input)?;
+            read_records_with_options(&metadata, &record_set, base_dir, &options)
+        });
+    let reader = match reader {
+        Ok(reader) => reader,
+        Err(e) => {
+            cli::log::failure!("Error reading records: {e}");
+            exit(1);
+        }
+    };
+
+    let written = match sub_m
+        .get_one::<String>("output")
+        .filter(|output| *output != "-")
+    {
+        Some(output) => std::fs::File::create(output)
+            .map_err(rustcroissant::croissant::Error::from)
+            .and_then(|file| write_records(reader, format, std::io::BufWriter::new(file))),
+        None => write_records(reader, format, std::io::BufWriter::new(std::io::stdout())),
+    };
+    match written {
+        Ok(count) => tracing::info!(records = count, "wrote records"),
+        Err(e) => {
+            cli::log::failure!("Error writing records: {e}");
+            exit(1);
+        }
+    }
+}
+
+/// The record set named by --record-set, or else the only one
+/// Whether an option was given on the command line rather than defaulted
+fn given(sub_m: &clap::ArgMatches, id: &str) -> bool {
+    sub_m.value_source(id) == Some(clap::parser::ValueSource::CommandLine)
+}
+
+/// Inference options of `generate`: the defaults, then the project
+/// configuration, then the command line
+fn inference_options(
+    sub_m: &clap::ArgMatches,
+    config: &rustcroissant::croissant::config::ProjectConfig,
+) -> rustcroissant::croissant::InferenceOptions {
+    let mut options = rustcroissant::croissant::InferenceOptions::default();
+    config.inference.apply(&mut options);
+    if given(sub_m, "infer-rows") {
+        options.sample_rows = *sub_m.get_one::<u64>("infer-rows").expect("given") as usize;
+        options.full_scan = false;
+    }
+    if sub_m.get_flag("full-scan") {
+        options.full_scan = true;
+    }
+    if let Some(values) = sub_m.get_many::<String>("null-values") {
+        options.null_values = values
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect();
+    }
+    if let Some(locale) = sub_m.get_one::<String>("number-locale") {
+        options.number_locale = (locale != "auto").then(|| {
+            rustcroissant::croissant::NumberLocale::from_name(locale).expect("validated by clap")
+        });
+    }
+    if sub_m.get_flag("detect-enums")
+        && (given(sub_m, "enum-max-values") || options.enum_max_values.is_none())
+    {
+        options.enum_max_values = Some(
+            *sub_m
+                .get_one::<u64>("enum-max-values")
+                .expect("has default") as usize,
+        );
+    }
+    options.on_bad_row = bad_row_policy(sub_m, config);
+    if let Some(delimiter) = sub_m.get_one::<u8>("delimiter") {
+        options.delimiter = Some(*delimiter);
+    }
+    options
+}
+
+/// How `generate` writes the content URLs of local files: after
+/// --base-url or the project's base URL, or relative to the metadata file
+/// with --relative-paths
+fn content_url_style(
+    sub_m: &clap::ArgMatches,
+    config: &rustcroissant::croissant::config::ProjectConfig,
+) -> rustcroissant::croissant::generate::ContentUrlStyle {
+    use rustcroissant::croissant::generate::ContentUrlStyle;
+
+    if sub_m.get_flag("relative-paths") {
+        return ContentUrlStyle::RelativeToOutput;
+    }
+    match sub_m
+        .get_one::<String>("base-url")
+        .or(config.dataset.base_url.as_ref())
+    {
+        Some(base) => ContentUrlStyle::BaseUrl(base.clone()),
+        None => ContentUrlStyle::FileName,
+    }
+}
+
+/// Dataset properties given to `generate`, falling back to those of the
+/// project configuration
+fn dataset_properties(
+    sub_m: &clap::ArgMatches,
+    config: &rustcroissant::croissant::config::ProjectConfig,
+) -> rustcroissant::croissant::generate::DatasetProperties {
+    let or_configured = |given: Vec<String>, configured: &[String]| {
+        if given.is_empty() {
+            configured.to_vec()
+        } else {
+            given
+        }
+    };
+    let many = |id: &str| {
+        sub_m
+            .get_many::<String>(id)
+            .into_iter()
+            .flatten()
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    rustcroissant::croissant::generate::DatasetProperties {
+        name: sub_m.get_one::<String>("name").cloned(),
+        description: sub_m.get_one::<String>("description").cloned(),
+        version: sub_m.get_one::<String>("dataset-version").cloned(),
+        date_published: sub_m
+            .get_one::<chrono::NaiveDate>("date-published")
+            .map(|date| date.to_string()),
+        license: or_configured(many("license"), &config.dataset.license),
+        url: sub_m.get_one::<String>("url").cloned(),
+        creator: or_configured(many("creator"), &config.dataset.creator)
+            .into_iter()
+            .map(rustcroissant::croissant::Creator::person)
+            .collect(),
+        keywords: or_configured(many("keywords"), &config.dataset.keywords),
+        cite_as: sub_m.get_one::<String>("cite-as").cloned(),
+    }
+}
+
+/// The `--on-bad-row` policy of a command, unless given falling back to
+/// that of the project configuration
+fn bad_row_policy(
+    sub_m: &clap::ArgMatches,
+    config: &rustcroissant::croissant::config::ProjectConfig,
+) -> rustcroissant::croissant::BadRowPolicy {
+    match config.inference.on_bad_row {
+        Some(policy) if !given(sub_m, "on-bad-row") => policy,
+        _ => rustcroissant::croissant::BadRowPolicy::from_name(
+            sub_m.get_one::<String>("on-bad-row").expect("has default"),
+        )
+        .expect("validated by clap"),
+    }
+}
+
+/// Read the project configuration given with --config, or found in the
+/// working directory or one of its parents
+fn load_project_config(
+    matches: &clap::ArgMatches,
+) -> rustcroissant::croissant::config::ProjectConfig {
+    use rustcroissant::croissant::config::{ProjectConfig, find_config_file};
+
+    let explicit = matches.get_one::<String>("config");
+    let path = match explicit {
+        Some(path) => Some(std::path::PathBuf::from(path)),
+        None => std::env::current_dir()
+            .ok()
+            .and_then(|dir| find_config_file(&dir)),
+    };
+    let Some(path) = path else {
+        return ProjectConfig::default();
+    };
+    tracing::debug!(path = %path.display(), "reading project configuration");
+    #[cfg_attr(not(feature = "remote"), allow(unused_mut))]
+    let mut config = ProjectConfig::load(&path).unwrap_or_else(|e| {
+        cli::log::failure!("Error reading project configuration: {e}");
+        exit(1);
+    });
+    // A configuration found in the working directory may come with a
+    // cloned repository, whose credentials could send the user's secrets
+    // to any host
+    #[cfg(feature = "remote")]
+    if explicit.is_none() && !config.auth.hosts.is_empty() {
+        tracing::warn!(
+            path = %path.display(),
+            "ignoring credentials of a configuration not given with --config"
+        );
+        config.auth = Default::default();
+    }
+    config
+}
+
+fn chosen_record_set(
+    sub_m: &clap::ArgMatches,
+    metadata: &rustcroissant::croissant::Metadata,
+    input: &str,
+) -> rustcroissant::croissant::Result<String> {
+    match sub_m.get_one::<String>("record-set") {
+        Some(record_set) => Ok(record_set.clone()),
+        None => match metadata.record_set.as_slice() {
+            [record_set] => Ok(record_set.name.clone()),
+            _ => Err(rustcroissant::croissant::Error::new(format!(
+                "{} has {} record sets; choose one with --record-set",
+                input,
+                metadata.record_set.len()
+            ))),
+        },
+    }
+}
+
+fn run_diff(sub_m: &clap::ArgMatches) {
+    use rustcroissant::croissant::diff::diff_metadata;
+    use rustcroissant::croissant::validate::read_metadata;
+
+    let read = |name: &str| {
+        let input = sub_m.get_one::<String>(name).expect("required by clap");
+        read_metadata(std::path::Path::new(input)).unwrap_or_else(|e| {
+            cli::log::failure!("Error reading {input}: {e}");
+            exit(2);
+        })
+    };
+    let diff = diff_metadata(&read("old"), &read("new"));
+    if sub_m.get_one::<String>("format").map(String::as_str) == Some("json") {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&diff.to_json()).expect("serializable")
+        );
+    } else {
+        print!("{}", diff.report());
+    }
+    if !diff.is_empty() {
+        exit(1);
+    }
+}
+
+fn run_inspect(sub_m: &clap::ArgMatches) {
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input JSON-LD file required");
+
+    let metadata =
+        match rustcroissant::croissant::validate::read_metadata(std::path::Path::new(input)) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                cli::log::failure!("Error reading metadata: {e}");
+                exit(1);
+            }
+        };
+    if sub_m.get_one::<String>("format").map(String::as_str) == Some("json") {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&cli::inspect::describe_metadata(&metadata))
+                .expect("serializable")
+        );
+    } else {
+        print!("{}", cli::inspect::render_inspection(&metadata));
+    }
+}
+
+fn run_preview(sub_m: &clap::ArgMatches) {
+    use rustcroissant::croissant::records::{RecordOptions, read_records_with_options};
+
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input JSON-LD file required");
+    let input_path = std::path::Path::new(input);
+    let base_dir = input_path.parent().unwrap_or(std::path::Path::new(""));
+    let rows = *sub_m.get_one::<usize>("rows").expect("has default");
+    let max_width = *sub_m.get_one::<u64>("max-width").expect("has default") as usize;
+    let options = RecordOptions {
+        // One more record tells whether there are further ones
+        limit: Some(rows + 1),
+        ..RecordOptions::default()
+    };
+
+    let table = rustcroissant::croissant::validate::read_metadata(input_path)
+        .and_then(|metadata| {
+            let record_set = chosen_record_set(sub_m, &metadata, input)?;
+            read_records_with_options(&metadata, &record_set, base_dir, &options)
+        })
+        .and_then(|reader| cli::preview::render_preview(reader, rows, max_width));
+    match table {
+        Ok(table) => print!("{table}"),
+        Err(e) => {
+            cli::log::failure!("Error reading records: {e}");
+            exit(1);
+        }
+    }
+}
+
+fn run_fingerprint(sub_m: &clap::ArgMatches) {
+    use rustcroissant::croissant::fingerprint::record_set_fingerprint;
+    use rustcroissant::croissant::records::read_records;
+
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input JSON-LD file required");
+    let input_path = std::path::Path::new(input);
+    let base_dir = input_path.parent().unwrap_or(std::path::Path::new(""));
+
+    let fingerprints =
+        rustcroissant::croissant::validate::read_metadata(input_path).and_then(|metadata| {
+            record_set_names(sub_m, &metadata)
+                .into_iter()
+                .map(|record_set| {
+                    let fingerprint =
+                        record_set_fingerprint(read_records(&metadata, &record_set, base_dir)?)?;
+                    Ok((record_set, fingerprint))
+                })
+                .collect::<rustcroissant::croissant::Result<Vec<_>>>()
+        });
+    let fingerprints = match fingerprints {
+        Ok(fingerprints) => fingerprints,
+        Err(e) => {
+            cli::log::failure!("Error computing fingerprints: {e}");
+            exit(1);
+        }
+    };
+
+    if sub_m.get_one::<String>("format").map(String::as_str) == Some("json") {
+        let json = fingerprints
+            .iter()
+            .map(|(record_set, fingerprint)| {
+                serde_json::json!({
+                    "recordSet": record_set,
+                    "records": fingerprint.records,
+                    "sha256": fingerprint.sha256,
+                })
+            })
+            .collect::<Vec<_>>();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).expect("serializable")
+        );
+    } else {
+        for (record_set, fingerprint) in &fingerprints {
+            println!("{fingerprint}  {record_set}");
+        }
+    }
+}
+
+/// The record set named by --record-set, or else every record set
+fn record_set_names(
+    sub_m: &clap::ArgMatches,
+    metadata: &rustcroissant::croissant::Metadata,
+) -> Vec<String> {
+    match sub_m.get_one::<String>("record-set") {
+        Some(record_set) => vec![record_set.clone()],
+        None => metadata
+            .record_set
+            .iter()
+            .map(|record_set| record_set.name.clone())
+            .collect(),
+    }
+}
+
+fn run_stats(sub_m: &clap::ArgMatches) {
+    use cli::table::{Align, render};
+    use rustcroissant::croissant::records::read_records;
+    use rustcroissant::croissant::stats::{FieldStatistics, record_statistics};
+
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input JSON-LD file required");
+    let input_path = std::path::Path::new(input);
+    let base_dir = input_path.parent().unwrap_or(std::path::Path::new(""));
+
+    let statistics =
+        rustcroissant::croissant::validate::read_metadata(input_path).and_then(|metadata| {
+            record_set_names(sub_m, &metadata)
+                .into_iter()
+                .map(|record_set| {
+                    let statistics =
+                        record_statistics(read_records(&metadata, &record_set, base_dir)?)?;
+                    Ok((record_set, statistics))
+                })
+                .collect::<rustcroissant::croissant::Result<Vec<_>>>()
+        });
+    let statistics = match statistics {
+        Ok(statistics) => statistics,
+        Err(e) => {
+            cli::log::failure!("Error computing statistics: {e}");
+            exit(1);
+        }
+    };
+
+    if sub_m.get_one::<String>("format").map(String::as_str) == Some("json") {
+        let json = statistics
+            .iter()
+            .map(|(record_set, statistics)| {
+                let mut json = serde_json::json!({ "recordSet": record_set });
+                json.as_object_mut().expect("is an object").extend(
+                    serde_json::to_value(statistics)
+                        .expect("serializable")
+                        .as_object()
+                        .cloned()
+                        .unwrap_or_default(),
+                );
+                json
+            })
+            .collect::<Vec<_>>();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).expect("serializable")
+        );
+        return;
+    }
+
+    fn number(value: Option<&serde_json::Value>) -> String {
+        match value {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(value) => value.to_string(),
+            None => String::new(),
+        }
+    }
+    fn row(field: &FieldStatistics) -> Vec<String> {
+        let distinct = if field.distinct_estimated {
+            format!("~{}", field.distinct_count)
+        } else {
+            field.distinct_count.to_string()
+        };
+        vec![
+            field.name.clone(),
+            field
+                .data_type
+                .trim_start_matches("sc:")
+                .trim_start_matches("cr:")
+                .to_string(),
+            format!("{:.1}%", field.null_rate * 100.0),
+            distinct,
+            number(field.min.as_ref()),
+            number(field.max.as_ref()),
+            field
+                .mean
+                .map(|mean| format!("{mean:.4}"))
+                .unwrap_or_default(),
+            field
+                .length
+                .as_ref()
+                .map(|length| format!("{}..{} (mean {:.1})", length.min, length.max, length.mean))
+                .unwrap_or_default(),
+        ]
+    }
+    for (index, (record_set, statistics)) in statistics.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("{record_set}: {} records", statistics.records);
+        print!(
+            "{}",
+            render(
+                &[[
+                    "field", "type", "null", "distinct", "min", "max", "mean", "length"
+                ]
+                .map(String::from)
+                .to_vec()],
+                &[
+                    Align::Left,
+                    Align::Left,
+                    Align::Right,
+                    Align::Right,
+                    Align::Right,
+                    Align::Right,
+                    Align::Right,
+                    Align::Left,
+                ],
+                &statistics.fields.iter().map(row).collect::<Vec<_>>(),
+            )
+        );
+    }
+}
+
+/// Handle the query subcommand
+#[cfg(feature = "duckdb")]
+fn run_query(sub_m: &clap::ArgMatches) {
+    let input = sub_m
+        .get_one::<String>("input")
+        .expect("Input JSON-LD file required");
+    let sql = sub_m.get_one::<String>("sql").expect("SQL query required");
+    let input_path = std::path::Path::new(input);
+    let base_dir = input_path.parent().unwrap_or(std::path::Path::new(""));
+
+    let result =
+        rustcroissant::croissant::validate::read_metadata(input_path).and_then(|metadata| {
+            rustcroissant::croissant::query::query_metadata(&metadata, base_dir, sql)
+        });
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            cli::log::failure!("Error querying dataset: {e}");
+            exit(1);
+        }
+    };
+
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    let written = writer.write_record(&result.columns).and_then(|_| {
+        for row in &result.rows {
+            writer.write_record(row.iter().map(|v| v.as_deref().unwrap_or_default()))?;
+        }
+        writer.flush().map_err(csv::Error::from)
+    });
+    if let Err(e) = written {
+        cli::log::failure!("Error writing query results: {e}");
+        exit(1);
+    }
+}
+
+/// Open the hash cache selected by --cache-dir, unless --no-cache was given
+fn open_hash_cache(sub_m: &clap::ArgMatches) -> Option<rustcroissant::croissant::cache::HashCache> {
+    use rustcroissant::croissant::cache::HashCache;
+
+    if sub_m.get_flag("no-cache") {
+        return None;
+    }
+    match sub_m.get_one::<String>("cache-dir") {
+        Some(dir) => Some(HashCache::in_dir(std::path::Path::new(dir))),
+        None => HashCache::open_default(),
+    }
+}
+
+/// Add warnings for DOIs of the metadata that do not resolve or do not
+/// match it
+#[cfg(feature = "remote")]
+fn check_dois(
+    input_path: &std::path::Path,
+    issues: &mut rustcroissant::croissant::validate::ValidationIssues,
+) {
+    match rustcroissant::croissant::validate::read_metadata(input_path) {
+        Ok(metadata) => async_runtime().block_on(
+            rustcroissant::croissant::remote::doi::check_dois(&metadata, issues),
+        ),
+        Err(e) => {
+            cli::log::failure!("Error validating metadata: {e}");
+            exit(1);
+        }
+    }
+}
+
+/// Add warnings for DOIs of the metadata; always fails, since
+/// rustcroissant was built without the `remote` feature
+#[cfg(not(feature = "remote"))]
+fn check_dois(
+    _input_path: &std::path::Path,
+    _issues: &mut rustcroissant::croissant::validate::ValidationIssues,
+) {
+    cli::log::failure!("Error: --deep requires rustcroissant to be built with the remote feature");
+    exit(1);
+}
+
+/// Add issues for the records of the metadata that cannot be read
+fn check_data(
+    input_path: &std::path::Path,
+    on_bad_row: rustcroissant::croissant::BadRowPolicy,
+    issues: &mut rustcroissant::croissant::validate::ValidationIssues,
+) {
+    match rustcroissant::croissant::validate::read_metadata(input_path) {
+        Ok(metadata) => {
+            let base_dir = input_path.parent().unwrap_or(std::path::Path::new("."));
+            let data_issues =
+                rustcroissant::croissant::validate::validate_data(&metadata, base_dir, on_bad_row);
+            for issue in data_issues.issues() {
+                issues.push(issue.clone());
+            }
+        }
+        Err(e) => {
+            cli::log::failure!("Error validating metadata: {e}");
+            exit(1);
+        }
+    }
+}
+
+/// Add the distributions whose local files do not match them to `issues`
+fn check_content(
+    input_path: &std::path::Path,
+    issues: &mut rustcroissant::croissant::validate::ValidationIssues,
+) {
+    use rustcroissant::croissant::verify::{VerifyOptions, verify_metadata};
+
+    match rustcroissant::croissant::validate::read_metadata(input_path) {
+        Ok(metadata) => {
+            let base_dir = input_path.parent().unwrap_or(std::path::Path::new(""));
+            let report = verify_metadata(&metadata, base_dir, &VerifyOptions::default());
+            for issue in report.to_issues(&metadata).issues() {
+                issues.push(issue.clone());
+            }
+        }
+        Err(e) => {
+            cli::log::failure!("Error validating metadata: {e}");
+            exit(1);
+        }
+    }
+}
+
+/// Open the download cache selected by --cache-dir, in the mode selected by
+/// --offline and --refresh
+#[cfg(feature = "remote")]
+fn open_download_cache(
+    sub_m: &clap::ArgMatches,
+) -> rustcroissant::croissant::remote::download::DownloadCache {
+    use rustcroissant::croissant::remote::download::{CacheMode, DownloadCache};
+
+    let mode = if sub_m.get_flag("offline") {
+        CacheMode::Offline
+    } else if sub_m.get_flag("refresh") {
+        CacheMode::Refresh
+    } else {
+        CacheMode::Revalidate
+    };
+    let cache = match sub_m.get_one::<String>("cache-dir") {
+        Some(dir) => Some(DownloadCache::in_cache_dir(std::path::Path::new(dir), mode)),
+        None => DownloadCache::open_default(mode),
+    };
+    cache.unwrap_or_else(|| {
+        cli::log::failure!("No cache directory could be determined; pass --cache-dir");
+        exit(1);
+    })
+}
+
+/// Set the retries and rate limit of network requests from the environment
+/// and --retries and --rate-limit
+#[cfg(feature = "remote")]
+fn configure_network(matches: &clap::ArgMatches) {
+    use rustcroissant::croissant::remote::retry::{NetworkPolicy, configure};
+
+    let policy = NetworkPolicy::from_env().and_then(|mut policy| {
+        if let Some(retries) = matches.get_one::<u32>("retries") {
+            policy.retries = *retries;
+        }
+        if let Some(rate) = matches.get_one::<f64>("rate-limit") {
+            policy.rate_limit = Some(*rate);
+        }
+        configure(policy)
+    });
+    if let Err(e) = policy {
+        cli::log::failure!("{e}");
+        exit(1);
+    }
+}
+
+/// Single-threaded async runtime for the network operations of a command
+#[cfg(feature = "remote")]
+fn async_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap_or_else(|e| {
+            cli::log::failure!("Error starting async runtime: {e}");
+            exit(1);
+        })
+}
+
+/// Persist the hash cache, warning instead of failing the command on error
+fn save_hash_cache(cache: Option<&rustcroissant::croissant::cache::HashCache>) {
+    if let Some(cache) = cache
+        && let Err(e) = cache.save()
+    {
+        tracing::warn!(path = %cache.path().display(), error = %e, "could not save hash cache");
+    }
 }
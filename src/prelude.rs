@@ -0,0 +1,28 @@
+//! Commonly used rustcroissant types and functions
+//!
+//! ```no_run
+//! use rustcroissant::prelude::*;
+//! ```
+
+pub use crate::croissant::builder::{
+    DistributionBuilder, FieldBuilder, MetadataBuilder, RecordSetBuilder,
+};
+pub use crate::croissant::dictionary::{
+    DataDictionary, FieldDescription, FieldDescriptionProvider,
+};
+pub use crate::croissant::generate::{
+    GenerateOptions, generate_metadata_from_csv, generate_metadata_from_csv_with_descriptions,
+    generate_metadata_from_csv_with_options, generate_metadata_from_file,
+    generate_metadata_from_files,
+};
+pub use crate::croissant::loader::{JsonRecord, Loader};
+pub use crate::croissant::validate::{
+    IssueSeverity, ValidationIssue, ValidationIssues, validate_file, validate_metadata,
+    validate_str,
+};
+pub use crate::croissant::{DataType, Distribution, Error, Field, Metadata, RecordSet, Result};
+
+#[cfg(feature = "tokio")]
+pub use crate::croissant::generate::generate_metadata_from_csv_async;
+#[cfg(feature = "tokio")]
+pub use crate::croissant::validate::validate_file_async;
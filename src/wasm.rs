@@ -0,0 +1,17 @@
+//! WebAssembly bindings for in-browser validation
+//!
+//! Build with `cargo rustc --lib --target wasm32-unknown-unknown
+//! --no-default-features --features wasm --crate-type cdylib` and generate
+//! the JavaScript glue with `wasm-bindgen`.
+
+use crate::croissant::validate::validate_str_to_json;
+use wasm_bindgen::prelude::*;
+
+/// Validate a Croissant JSON-LD document and return the issues as JSON
+///
+/// The result has the shape `{"errors": n, "warnings": n, "issues": [...]}`.
+/// A document that cannot be parsed is reported as a single error issue.
+#[wasm_bindgen]
+pub fn validate_json(content: &str) -> String {
+    validate_str_to_json(content).to_string()
+}
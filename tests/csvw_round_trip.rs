@@ -0,0 +1,98 @@
+//! Metadata exported as CSVW must import back with the same fields, keys
+//! and delimiter, and read the same records
+
+use rustcroissant::croissant::export::csvw::table_group;
+use rustcroissant::croissant::generate::{
+    GenerateOptions, generate_metadata_from_csv_with_options,
+};
+use rustcroissant::croissant::import::csvw::{import_csvw, strftime_from_csvw};
+use rustcroissant::croissant::records::read_records;
+use rustcroissant::croissant::{InferenceOptions, Metadata, Result};
+
+/// Numbers with a decimal comma, and dates and times in other formats than
+/// ISO 8601, separated by semicolons
+const CSV: &str = "\
+id;amount;measured_on;taken_at;count
+1;1.234,5;31.01.2024;31/01/2024 10:00;3
+2;12,25;01.02.2024;13/02/2024 08:30;4
+3;7,5;02.02.2024;14/02/2024 17:45;5
+";
+
+fn records(metadata: &Metadata, dir: &std::path::Path) -> Vec<Vec<String>> {
+    read_records(metadata, &metadata.record_set[0].name, dir)
+        .expect("open records")
+        .map(|row| row.map(|row| row.iter().map(|value| format!("{value:?}")).collect()))
+        .collect::<Result<Vec<_>>>()
+        .expect("read records")
+}
+
+/// Name, dataType and transforms of each field
+fn fields(metadata: &Metadata) -> Vec<(String, String, serde_json::Value)> {
+    metadata.record_set[0]
+        .field
+        .iter()
+        .map(|field| {
+            let source = field.source.as_ref().expect("source");
+            (
+                field.name.clone(),
+                field.data_type.clone(),
+                serde_json::to_value(&source.transform).expect("serialize transforms"),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn generated_metadata_round_trips_through_csvw() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let csv_path = dir.path().join("readings.csv");
+    std::fs::write(&csv_path, CSV).expect("write CSV");
+
+    let options = GenerateOptions {
+        inference: InferenceOptions::default(),
+        dry_run: true,
+        ..Default::default()
+    };
+    let metadata =
+        generate_metadata_from_csv_with_options(&csv_path, None, &options).expect("generate");
+    let amount = &metadata.record_set[0].field[1];
+    assert_eq!(amount.require_source().expect("source").transform.len(), 2);
+
+    let document = table_group(&metadata);
+    let columns = &document["tables"][0]["tableSchema"]["columns"];
+    assert_eq!(
+        columns[1]["datatype"]["format"],
+        serde_json::json!({ "groupChar": ".", "decimalChar": "," })
+    );
+    assert_eq!(columns[2]["datatype"]["format"], "dd.MM.yyyy");
+    assert_eq!(columns[3]["datatype"]["format"], "dd/MM/yyyy HH:mm");
+    assert_eq!(columns[4]["datatype"], "integer");
+
+    let imported = import_csvw(&document.to_string(), dir.path()).expect("import");
+    assert_eq!(fields(&imported), fields(&metadata));
+    assert_eq!(
+        imported.distribution[0].delimiter,
+        metadata.distribution[0].delimiter
+    );
+    assert_eq!(
+        imported.distribution[0].sha256,
+        metadata.distribution[0].sha256
+    );
+    assert_eq!(
+        records(&imported, dir.path()),
+        records(&metadata, dir.path())
+    );
+}
+
+#[test]
+fn date_patterns_convert_to_strftime() {
+    assert_eq!(
+        strftime_from_csvw("yyyy-MM-dd'T'HH:mm:ss.SSSxxx").as_deref(),
+        Some("%Y-%m-%dT%H:%M:%S%.3f%:z")
+    );
+    assert_eq!(
+        strftime_from_csvw("M/d/yyyy h:mm a").as_deref(),
+        Some("%m/%d/%Y %I:%M %p")
+    );
+    assert_eq!(strftime_from_csvw("EEEE d MMMM"), None);
+}
@@ -0,0 +1,68 @@
+//! Metadata exported as DCAT-AP, in every JSON-LD form, must import back
+//! with the same dataset properties and distributions
+
+use rustcroissant::croissant::builder::{DistributionBuilder, MetadataBuilder};
+use rustcroissant::croissant::export::dcat::dcat_dataset;
+use rustcroissant::croissant::import::dcat::import_dcat;
+use rustcroissant::croissant::jsonld::{JsonLdForm, to_form};
+use rustcroissant::croissant::{Creator, Metadata};
+
+const SHA256: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+fn metadata() -> Metadata {
+    MetadataBuilder::new("stations")
+        .description("River gauge readings")
+        .version("1.2.0")
+        .date_published("2024-01-31")
+        .license("https://creativecommons.org/licenses/by/4.0/")
+        .url("https://example.com/stations")
+        .identifier("https://doi.org/10.1234/stations")
+        .keyword("hydrology")
+        .keyword("rivers")
+        .creator("Ada Lovelace")
+        .creator(Creator {
+            type_: "sc:Organization".to_string(),
+            name: "Gauging Agency".to_string(),
+            url: Some("https://example.com/agency".to_string()),
+        })
+        .distribution(
+            DistributionBuilder::new("readings.csv")
+                .content_url("https://example.com/data/readings.csv")
+                .content_size(2048)
+                .sha256(SHA256),
+        )
+        .build_unchecked()
+}
+
+#[test]
+fn metadata_round_trips_through_dcat_in_every_form() {
+    let metadata = metadata();
+    let dataset = dcat_dataset(&metadata);
+    for form in JsonLdForm::ALL {
+        let document = to_form(&dataset, *form, None).expect("rewrite DCAT");
+        let imported = import_dcat(&document.to_string()).expect("import DCAT");
+        let form = form.name();
+
+        assert_eq!(imported.name, metadata.name, "{form}");
+        assert_eq!(imported.description, metadata.description, "{form}");
+        assert_eq!(imported.version, metadata.version, "{form}");
+        assert_eq!(imported.date_published, metadata.date_published, "{form}");
+        assert_eq!(imported.license, metadata.license, "{form}");
+        assert_eq!(imported.url, metadata.url, "{form}");
+        assert_eq!(imported.identifier, metadata.identifier, "{form}");
+        let mut keywords = imported.keywords.clone();
+        keywords.sort();
+        assert_eq!(keywords, metadata.keywords, "{form}");
+        let mut creators = imported.creator.clone();
+        creators.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(creators, metadata.creator, "{form}");
+
+        let (old, new) = (&metadata.distribution[0], &imported.distribution[0]);
+        assert_eq!(imported.distribution.len(), 1, "{form}");
+        assert_eq!(new.name, old.name, "{form}");
+        assert_eq!(new.content_url, old.content_url, "{form}");
+        assert_eq!(new.encoding_format, old.encoding_format, "{form}");
+        assert_eq!(new.content_size, old.content_size, "{form}");
+        assert_eq!(new.sha256, old.sha256, "{form}");
+    }
+}
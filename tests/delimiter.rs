@@ -0,0 +1,43 @@
+//! Records must be read with the delimiter metadata was generated with,
+//! not one sniffed again from the file
+
+use rustcroissant::croissant::generate::{
+    GenerateOptions, generate_metadata_from_csv_with_options,
+};
+use rustcroissant::croissant::records::{Value, read_records};
+use rustcroissant::croissant::{InferenceOptions, Result};
+
+/// Semicolons outnumber commas in the header, so sniffing picks `;`
+const CSV: &str = "\
+id,note;kind;source
+1,a;b;c
+";
+
+#[test]
+fn records_are_read_with_the_generation_delimiter() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    // A .tsv file read with commas, not tabs
+    let csv_path = dir.path().join("notes.tsv");
+    std::fs::write(&csv_path, CSV).expect("write CSV");
+
+    let options = GenerateOptions {
+        inference: InferenceOptions {
+            delimiter: Some(b','),
+            ..Default::default()
+        },
+        dry_run: true,
+        ..Default::default()
+    };
+    let metadata =
+        generate_metadata_from_csv_with_options(&csv_path, None, &options).expect("generate");
+    assert_eq!(metadata.distribution[0].delimiter.as_deref(), Some(","));
+
+    let records = read_records(&metadata, "main", dir.path())
+        .expect("open records")
+        .collect::<Result<Vec<_>>>()
+        .expect("read records");
+    assert_eq!(
+        records,
+        [vec![Value::Integer(1), Value::Text("a;b;c".to_string())]]
+    );
+}
@@ -0,0 +1,57 @@
+//! Generated metadata must be byte-identical for identical inputs
+
+use rustcroissant::croissant::generate::{
+    GenerateOptions, generate_metadata_from_csv, metadata_to_json,
+};
+use rustcroissant::croissant::jsonld::JsonLdForm;
+use std::path::Path;
+
+const CSV: &str = "\
+station,reading_time,flow_rate,active,notes
+S-01,2024-06-01T08:00:00Z,12.5,true,clear
+S-02,2024-06-01T08:05:00Z,7,false,
+S-03,2024-06-01T08:10:00Z,0.25,true,\"silt, light\"
+";
+
+/// Serialize generated metadata with the publication date, which is the
+/// only input not taken from the file, pinned
+fn generate(csv_path: &Path, form: JsonLdForm) -> String {
+    let mut metadata = generate_metadata_from_csv(csv_path, None).expect("generate metadata");
+    metadata.date_published = "2024-06-01".to_string();
+    let options = GenerateOptions {
+        jsonld_form: form,
+        ..Default::default()
+    };
+    metadata_to_json(&metadata, &options).expect("serialize metadata")
+}
+
+#[test]
+fn generation_is_byte_identical_and_matches_snapshot() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let csv_path = dir.path().join("stations.csv");
+    std::fs::write(&csv_path, CSV).expect("write CSV");
+
+    for (form, snapshot) in [
+        (JsonLdForm::Compact, "stations.jsonld"),
+        (JsonLdForm::Expanded, "stations.expanded.jsonld"),
+    ] {
+        let first = generate(&csv_path, form);
+        let second = generate(&csv_path, form);
+        assert_eq!(first, second, "{} output differs between runs", form.name());
+
+        let snapshot_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(snapshot);
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&snapshot_path, &first).expect("write snapshot");
+        }
+        let expected = std::fs::read_to_string(&snapshot_path).expect("read snapshot");
+        assert_eq!(
+            first,
+            expected,
+            "{} output does not match {}; rerun with UPDATE_SNAPSHOTS=1 if the change is intended",
+            form.name(),
+            snapshot_path.display()
+        );
+    }
+}
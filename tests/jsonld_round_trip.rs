@@ -0,0 +1,80 @@
+//! Metadata must read back the same from every JSON-LD form it can be
+//! written in, and from the canonical Croissant samples
+
+use rustcroissant::croissant::generate::{
+    GenerateOptions, generate_metadata_from_csv_with_options,
+};
+use rustcroissant::croissant::jsonld::{JsonLdForm, to_form};
+use rustcroissant::croissant::validate::{metadata_from_json_ld, read_metadata};
+use rustcroissant::croissant::{InferenceOptions, Metadata};
+use std::path::Path;
+
+const CSV: &str = "\
+sku,price,sold_on,region,stock
+A-1,\"$1,299.00\",03/15/2024,north,12
+A-2,\"$12.50\",04/01/2024,south,
+A-3,\"$7.25\",04/02/2024,north,3
+";
+
+/// Metadata read back from `metadata` written in `form`
+fn round_trip(metadata: &Metadata, form: JsonLdForm) -> Metadata {
+    let document = to_form(
+        &serde_json::to_value(metadata).expect("serialize metadata"),
+        form,
+        None,
+    )
+    .expect("rewrite metadata");
+    metadata_from_json_ld(&document).expect("read metadata back")
+}
+
+fn assert_round_trips(metadata: &Metadata, form: JsonLdForm) {
+    let mut read = round_trip(metadata, form);
+    // The context is always rustcroissant's once read back
+    read.context = metadata.context.clone();
+    assert!(
+        read.equivalent(metadata),
+        "{} form differs at {:?}",
+        form.name(),
+        read.differences(metadata)
+    );
+}
+
+#[test]
+fn titanic_sample_reads_and_round_trips() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("samples_jsonld/titanic.jsonld");
+    let metadata = read_metadata(&path).expect("read titanic.jsonld");
+    let issues = metadata.validate();
+    assert!(!issues.has_errors(), "{}", issues.report());
+
+    let genders = metadata.find_record_set("genders").expect("genders");
+    // Of ["sc:URL", "wd:Q48277"], the data type is kept
+    assert_eq!(genders.field[1].data_type, "sc:URL");
+
+    for form in [JsonLdForm::Expanded, JsonLdForm::Flattened] {
+        assert_round_trips(&metadata, form);
+    }
+}
+
+#[test]
+fn generated_metadata_round_trips_through_every_form() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let csv_path = dir.path().join("sales.csv");
+    std::fs::write(&csv_path, CSV).expect("write CSV");
+
+    let options = GenerateOptions {
+        inference: InferenceOptions {
+            enum_max_values: Some(5),
+            ..Default::default()
+        },
+        statistics: true,
+        ..Default::default()
+    };
+    let metadata =
+        generate_metadata_from_csv_with_options(&csv_path, None, &options).expect("generate");
+    let price = &metadata.record_set[0].field[1];
+    assert!(!price.require_source().expect("source").transform.is_empty());
+
+    for form in JsonLdForm::ALL {
+        assert_round_trips(&metadata, *form);
+    }
+}
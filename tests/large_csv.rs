@@ -0,0 +1,83 @@
+//! Generation, inference and data validation must stream a CSV file, so
+//! that memory use does not grow with its size
+//!
+//! The file is several gigabytes, so the test is ignored by default; run it
+//! with `cargo test --release --test large_csv -- --ignored`. Its size in
+//! megabytes can be set with `RUSTCROISSANT_LARGE_CSV_MB`.
+
+use rustcroissant::croissant::BadRowPolicy;
+use rustcroissant::croissant::generate::generate_metadata_from_csv;
+use rustcroissant::croissant::validate::validate_data;
+use std::io::{BufWriter, Write};
+
+const DEFAULT_SIZE_MB: u64 = 3 * 1024;
+
+/// Largest resident memory the process may reach, well below the file size
+const PEAK_MEMORY_LIMIT_MB: u64 = 256;
+
+/// Peak resident memory of the process so far, in megabytes
+#[cfg(target_os = "linux")]
+fn peak_memory_mb() -> u64 {
+    let status = std::fs::read_to_string("/proc/self/status").expect("read process status");
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| {
+            value
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })
+        .expect("peak resident memory in process status")
+        / 1024
+}
+
+#[test]
+#[ignore = "writes and reads a multi-gigabyte CSV file"]
+#[cfg(target_os = "linux")]
+fn multi_gigabyte_csv_streams_in_bounded_memory() {
+    let size_mb = std::env::var("RUSTCROISSANT_LARGE_CSV_MB")
+        .ok()
+        .map(|size| {
+            size.parse::<u64>()
+                .expect("RUSTCROISSANT_LARGE_CSV_MB is a number")
+        })
+        .unwrap_or(DEFAULT_SIZE_MB);
+
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let csv_path = dir.path().join("readings.csv");
+    let mut writer = BufWriter::new(std::fs::File::create(&csv_path).expect("create CSV"));
+    writeln!(writer, "station,reading_time,flow_rate,active,notes").expect("write header");
+    let mut written = 0u64;
+    let mut row = 0u64;
+    while written < size_mb * 1024 * 1024 {
+        let line = format!(
+            "S-{:05},2024-06-01T{:02}:{:02}:00Z,{}.{},{},reading {row} of a long series\n",
+            row % 10_000,
+            row / 60 % 24,
+            row % 60,
+            row % 1_000,
+            row % 10,
+            row.is_multiple_of(2),
+        );
+        writer.write_all(line.as_bytes()).expect("write row");
+        written += line.len() as u64;
+        row += 1;
+    }
+    writer.flush().expect("flush CSV");
+    drop(writer);
+
+    let metadata = generate_metadata_from_csv(&csv_path, None).expect("generate metadata");
+    assert_eq!(metadata.record_set[0].field.len(), 5);
+
+    let issues = validate_data(&metadata, dir.path(), BadRowPolicy::Error);
+    assert!(!issues.has_errors(), "{}", issues.report());
+
+    let peak = peak_memory_mb();
+    assert!(
+        peak < PEAK_MEMORY_LIMIT_MB,
+        "peak memory {peak} MB reading a {size_mb} MB CSV exceeds {PEAK_MEMORY_LIMIT_MB} MB"
+    );
+}
@@ -0,0 +1,69 @@
+//! Files generated into one document must each keep a content URL of
+//! their own
+
+use rustcroissant::croissant::generate::{
+    ContentUrlStyle, GenerateOptions, generate_metadata_from_files,
+};
+use std::path::{Path, PathBuf};
+
+const CSV: &str = "\
+id,value
+1,2.5
+2,3
+";
+
+/// `a/data.csv` and `b/data.csv` in `dir`
+fn same_named_files(dir: &Path) -> [PathBuf; 2] {
+    ["a", "b"].map(|sub| {
+        let path = dir.join(sub).join("data.csv");
+        std::fs::create_dir(path.parent().expect("parent")).expect("create directory");
+        std::fs::write(&path, CSV).expect("write CSV");
+        path
+    })
+}
+
+#[test]
+fn files_sharing_a_name_get_relative_content_urls() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let paths = same_named_files(dir.path());
+    let output = dir.path().join("dataset.jsonld");
+
+    let metadata =
+        generate_metadata_from_files(&paths, Some(&output), &GenerateOptions::default(), None)
+            .expect("generate");
+    let files = metadata
+        .distribution
+        .iter()
+        .map(|distribution| {
+            (
+                distribution.content_url.as_str(),
+                distribution.name.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        files,
+        [("a/data.csv", "a/data.csv"), ("b/data.csv", "b/data.csv")]
+    );
+    assert_ne!(metadata.distribution[0].id, metadata.distribution[1].id);
+}
+
+#[test]
+fn files_sharing_a_name_under_a_base_url_are_an_error() {
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let paths = same_named_files(dir.path());
+    let options = GenerateOptions {
+        content_urls: ContentUrlStyle::BaseUrl("https://data.example.org/".to_string()),
+        dry_run: true,
+        ..Default::default()
+    };
+
+    let error = generate_metadata_from_files(&paths, None, &options, None)
+        .expect_err("clashing content URLs");
+    assert!(
+        error
+            .to_string()
+            .contains("https://data.example.org/data.csv"),
+        "{error}"
+    );
+}